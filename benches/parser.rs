@@ -0,0 +1,136 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use eta_parse::{
+    builder::{Buildable, Builder},
+    flat::fixed::{read::Reader, Parser, Record},
+};
+
+const WIDTHS: [usize; 3] = [80, 400, 2000];
+const LINES: usize = 1000;
+
+#[cfg(not(tarpaulin_include))]
+fn names(width: usize) -> Vec<String> {
+    (0..width / 10).map(|i| format!("field-{}", i)).collect()
+}
+
+/// A layout of 10 character fields, alternating text and zero padded numbers.
+#[cfg(not(tarpaulin_include))]
+fn layout(names: &[String]) -> Parser<'_> {
+    let mut builder = Parser::builder();
+    for (i, name) in names.iter().enumerate() {
+        builder = match i % 2 {
+            0 => builder.field(name).width(10).append(),
+            _ => builder
+                .field(name)
+                .width(10)
+                .align("right")
+                .padding('0')
+                .append(),
+        };
+    }
+    builder.build()
+}
+
+#[cfg(not(tarpaulin_include))]
+fn record(names: &[String]) -> Record {
+    names
+        .iter()
+        .enumerate()
+        .map(|(i, name)| {
+            let value = match i % 2 {
+                0 => format!("TEXT {}", i),
+                _ => (i * 37).to_string(),
+            };
+            (name.clone(), value)
+        })
+        .collect()
+}
+
+#[cfg(not(tarpaulin_include))]
+fn parse_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    for width in WIDTHS.iter() {
+        let names = names(*width);
+        let parser = layout(&names);
+        let line = parser.format(&record(&names));
+        group.throughput(Throughput::Bytes(line.len() as u64));
+        group.bench_with_input(BenchmarkId::new("parse", width), &line, |b, line| {
+            b.iter(|| parser.parse(line.as_str()).unwrap())
+        });
+        let mut reused = Record::new();
+        group.bench_with_input(BenchmarkId::new("parse_into", width), &line, |b, line| {
+            b.iter(|| parser.parse_into(line, &mut reused).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("parse_with", width), &line, |b, line| {
+            b.iter(|| {
+                let mut length = 0;
+                parser
+                    .parse_with(line, |_, value| length += value.len())
+                    .unwrap();
+                length
+            })
+        });
+        let projected = parser.project(&[names[1].as_str()]).unwrap();
+        group.bench_with_input(BenchmarkId::new("project", width), &line, |b, line| {
+            b.iter(|| projected.parse(line.as_str()).unwrap())
+        });
+        #[cfg(feature = "bench")]
+        {
+            let unplanned = parser.unplanned();
+            group.bench_with_input(BenchmarkId::new("unplanned", width), &line, |b, line| {
+                b.iter(|| unplanned.parse(line.as_str()).unwrap())
+            });
+        }
+    }
+    group.finish();
+}
+
+#[cfg(not(tarpaulin_include))]
+fn format_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("format");
+    for width in WIDTHS.iter() {
+        let names = names(*width);
+        let parser = layout(&names);
+        let record = record(&names);
+        group.throughput(Throughput::Bytes(*width as u64));
+        group.bench_with_input(BenchmarkId::new("format", width), &record, |b, record| {
+            b.iter(|| parser.format(record))
+        });
+    }
+    group.finish();
+}
+
+#[cfg(not(tarpaulin_include))]
+fn reader_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("reader");
+    group.sample_size(20);
+    for width in WIDTHS.iter() {
+        let names = names(*width);
+        let parser = layout(&names);
+        let mut input = parser.format(&record(&names));
+        input.push('\n');
+        let input = input.repeat(LINES);
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        group.bench_with_input(BenchmarkId::new("records", width), &input, |b, input| {
+            b.iter(|| {
+                Reader::from_reader(input.as_bytes(), &parser)
+                    .string_reader()
+                    .count()
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("read_into", width), &input, |b, input| {
+            b.iter(|| {
+                let mut reader = Reader::from_reader(input.as_bytes(), &parser);
+                let mut record = Record::new();
+                let mut records = 0;
+                while reader.read_into(&mut record).unwrap() {
+                    records += 1;
+                }
+                records
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, parse_benchmark, format_benchmark, reader_benchmark);
+criterion_main!(benches);
@@ -1,4 +1,7 @@
+#![allow(mismatched_lifetime_syntaxes)]
+
 use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use eta_parse::utilities::string::{fixed_width, strip_padding, Align};
 
 #[cfg(not(tarpaulin_include))]
 mod strip {
@@ -77,9 +80,36 @@ fn strip_left_benchmark(c: &mut Criterion) {
                 b.iter(|| assert_eq!(strip::strip_with_loop(input, **pad), ***output))
             },
         );
+        group.bench_with_input(
+            BenchmarkId::new("library", name),
+            &(input, pad, output),
+            |b, (input, pad, output)| {
+                b.iter(|| assert_eq!(strip_padding(input, Align::Left, **pad), ***output))
+            },
+        );
+    }
+    group.finish();
+}
+
+#[cfg(not(tarpaulin_include))]
+fn fixed_width_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("fixed-width");
+    for (name, input, width, align) in [
+        ("left-ascii", "ABCDEFGHIJ", 80, Align::Left),
+        ("right-ascii", "1234567890", 80, Align::Right),
+        ("left-unicode", "会げクABCDEFG", 80, Align::Left),
+        ("truncate", "ABCDEFGHIJKLMNOPQRSTUVWXYZ", 10, Align::Left),
+    ]
+    .iter()
+    {
+        group.bench_with_input(
+            BenchmarkId::new("library", name),
+            &(input, width, align),
+            |b, (input, width, align)| b.iter(|| fixed_width(input, **width, **align, ' ')),
+        );
     }
     group.finish();
 }
 
-criterion_group!(benches, strip_left_benchmark);
+criterion_group!(benches, strip_left_benchmark, fixed_width_benchmark);
 criterion_main!(benches);
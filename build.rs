@@ -0,0 +1,16 @@
+fn main() {
+    #[cfg(feature = "capi")]
+    {
+        let crate_dir = std::path::PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+        let out_dir = std::path::PathBuf::from(std::env::var("OUT_DIR").unwrap());
+        println!("cargo:rerun-if-changed=src/capi.rs");
+        println!("cargo:rerun-if-changed=cbindgen.toml");
+        // Generated into the output directory, the copy in `include` is checked by a test.
+        cbindgen::Builder::new()
+            .with_config(cbindgen::Config::from_root_or_default(&crate_dir))
+            .with_src(crate_dir.join("src/capi.rs"))
+            .generate()
+            .expect("Unable to generate the C header")
+            .write_to_file(out_dir.join("eta_parse.h"));
+    }
+}
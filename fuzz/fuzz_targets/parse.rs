@@ -0,0 +1,49 @@
+#![no_main]
+
+use eta_parse::{
+    builder::{Buildable, Builder},
+    flat::fixed::Parser,
+    utilities::string::{Align, WidthMode},
+};
+use libfuzzer_sys::{
+    arbitrary::{self, Arbitrary},
+    fuzz_target,
+};
+
+const NAMES: [&str; 8] = ["a", "b", "c", "d", "e", "f", "g", "h"];
+
+#[derive(Debug, Arbitrary)]
+struct Input<'a> {
+    /// The width, whether aligned right and padding of each field.
+    fields: Vec<(u8, bool, char)>,
+    width_mode: u8,
+    line: &'a str,
+}
+
+fuzz_target!(|input: Input| {
+    let width_mode = match input.width_mode % 4 {
+        0 => WidthMode::Chars,
+        1 => WidthMode::Bytes,
+        2 => WidthMode::DisplayWidth,
+        _ => WidthMode::Graphemes,
+    };
+    let mut builder = Parser::builder().width_mode(width_mode);
+    for (name, (width, right, padding)) in NAMES.iter().zip(&input.fields) {
+        let align = if *right { Align::Right } else { Align::Left };
+        builder = builder
+            .field(name)
+            .width(*width as usize % 32)
+            .align(align)
+            .padding(*padding)
+            .append();
+    }
+    let parser = builder.build();
+
+    if let Ok(record) = parser.parse(input.line) {
+        let _ = parser.parse(parser.format(&record));
+    }
+    if let Ok(row) = parser.parse_row(input.line) {
+        let _ = parser.parse_row(parser.format_row(&row));
+    }
+    let _ = parser.parse_with(input.line, |_, _| ());
+});
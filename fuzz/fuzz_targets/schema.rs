@@ -0,0 +1,29 @@
+#![no_main]
+
+use eta_parse::schema::{FieldSet, Schema};
+use libfuzzer_sys::fuzz_target;
+
+/// Lines longer than this are not parsed, so huge widths in a schema do not exhaust memory.
+const MAX_WIDTH: usize = 4096;
+
+fuzz_target!(|data: &[u8]| {
+    let s = match std::str::from_utf8(data) {
+        Ok(s) => s,
+        Err(_) => return,
+    };
+    let fields = FieldSet::from_toml(s).unwrap_or_default();
+    let schemas = [Schema::from_toml(s).ok(), serde_json::from_str(s).ok()];
+    for schema in schemas.iter().flatten() {
+        for parser in [schema.parser(), schema.parser_with(&fields)]
+            .into_iter()
+            .flatten()
+        {
+            if parser.width() <= MAX_WIDTH {
+                let line = " ".repeat(parser.width());
+                if let Ok(record) = parser.parse(line) {
+                    let _ = parser.format(&record);
+                }
+            }
+        }
+    }
+});
@@ -0,0 +1,62 @@
+#![no_main]
+
+use eta_parse::utilities::string::{
+    fixed_width, fixed_width_into, fixed_width_with, strip_padding, strip_padding_with, truncate,
+    Align, WidthMode,
+};
+use libfuzzer_sys::{
+    arbitrary::{self, Arbitrary},
+    fuzz_target,
+};
+
+#[derive(Debug, Arbitrary)]
+struct Input<'a> {
+    value: &'a str,
+    fill: &'a str,
+    width: u8,
+    right: bool,
+    padding: char,
+}
+
+fuzz_target!(|input: Input| {
+    let Input {
+        value,
+        fill,
+        padding,
+        ..
+    } = input;
+    let width = input.width as usize;
+    let align = if input.right {
+        Align::Right
+    } else {
+        Align::Left
+    };
+
+    let formatted = fixed_width(value, width, align, padding);
+    assert_eq!(formatted.chars().count(), width);
+    let mut buf = String::new();
+    fixed_width_into(value, width, align, padding, &mut buf);
+    assert_eq!(buf, formatted);
+    let _ = strip_padding(&formatted, align, padding);
+
+    let formatted = fixed_width_with(value, width, align, fill);
+    let _ = strip_padding_with(&formatted, align, fill);
+    let _ = strip_padding(value, align, padding);
+    let _ = strip_padding_with(value, align, fill);
+    let _ = truncate(value, width);
+
+    for mode in [
+        WidthMode::Chars,
+        WidthMode::Bytes,
+        WidthMode::DisplayWidth,
+        WidthMode::Graphemes,
+    ] {
+        let (head, tail) = mode.split(value, width);
+        assert_eq!(head.len() + tail.len(), value.len());
+        // The display width of a string depends on sequences such as emoji presentation
+        // selectors, which splitting char by char does not see.
+        if mode != WidthMode::DisplayWidth {
+            assert!(mode.measure(head) <= width);
+        }
+    }
+});
@@ -0,0 +1,95 @@
+#ifndef ETA_PARSE_H
+#define ETA_PARSE_H
+
+/* Generated by cbindgen from src/capi.rs, do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// A parser for a layout, created with [`eta_parser_new`] and freed with [`eta_parser_free`].
+typedef struct EtaParser EtaParser;
+
+// The values of the named fields of a parsed line, in layout order. A null field has a
+// `NULL` value. Freed with [`eta_fields_free`].
+typedef struct EtaFields {
+  char **values;
+  size_t len;
+} EtaFields;
+
+// The message of the last error on this thread, `NULL` if there was none. The string is
+// owned by the library and valid until the next call failing on this thread.
+const char *eta_last_error(void);
+
+// Creates a parser from a schema in JSON, with the fields of a TOML schema. Returns `NULL`
+// if the schema is invalid.
+//
+// # Safety
+//
+// `schema` must be a NUL terminated string.
+struct EtaParser *eta_parser_new(const char *schema);
+
+// Frees a parser, doing nothing if it is `NULL`.
+//
+// # Safety
+//
+// `parser` must come from [`eta_parser_new`] and not have been freed.
+void eta_parser_free(struct EtaParser *parser);
+
+// The total width of a record, in characters.
+//
+// # Safety
+//
+// `parser` must be a valid parser.
+size_t eta_parser_width(const struct EtaParser *parser);
+
+// The number of named fields, the length of the values of a parsed line.
+//
+// # Safety
+//
+// `parser` must be a valid parser.
+size_t eta_parser_field_count(const struct EtaParser *parser);
+
+// The name of the named field at `index`, `NULL` if out of bounds. The string is owned by
+// the parser.
+//
+// # Safety
+//
+// `parser` must be a valid parser.
+const char *eta_parser_field_name(const struct EtaParser *parser, size_t index);
+
+// Parses a line into the values of the named fields. Returns 0 on success and -1 on error,
+// leaving `out` untouched, including when a value contains a NUL character.
+//
+// # Safety
+//
+// `parser` must be a valid parser, `line` a NUL terminated string and `out` writable.
+int eta_parse(const struct EtaParser *parser, const char *line, struct EtaFields *out);
+
+// Frees the values of a parsed line and resets them to empty.
+//
+// # Safety
+//
+// `fields` must have been filled by [`eta_parse`] and not have been freed.
+void eta_fields_free(struct EtaFields *fields);
+
+// Formats the values of the named fields in layout order as a line, a `NULL` value being
+// formatted like a missing field. Returns `NULL` on error, the line is freed with
+// [`eta_string_free`].
+//
+// # Safety
+//
+// `parser` must be a valid parser and `values` point to `len` strings, each `NULL` or NUL
+// terminated.
+char *eta_format(const struct EtaParser *parser, const char *const *values, size_t len);
+
+// Frees a line returned by [`eta_format`], doing nothing if it is `NULL`.
+//
+// # Safety
+//
+// `s` must come from [`eta_format`] and not have been freed.
+void eta_string_free(char *s);
+
+#endif  /* ETA_PARSE_H */
@@ -0,0 +1,183 @@
+use crate::{
+    error::Error,
+    flat::{
+        fixed::{Parser, ResultRecord},
+        terminator::Terminator,
+    },
+};
+use futures_util::{
+    io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt},
+    stream::{self, IntoAsyncRead, Stream, TryStream, TryStreamExt},
+};
+use std::io;
+
+#[cfg(feature = "object_store")]
+pub mod store;
+
+/// Reads fixed width records from an asynchronous source, such as an object in a bucket, without
+/// copying it to local disk first.
+///
+/// The record terminators that end with a delimiter and [`Terminator::Fixed`] are supported,
+/// length prefixed records are not.
+pub struct Reader<'a, R> {
+    inner: R,
+    parser: &'a Parser<'a>,
+    terminator: Terminator,
+    buf: Vec<u8>,
+    line: u64,
+}
+
+#[allow(dead_code)]
+impl<'a, R> Reader<'a, R>
+where
+    R: AsyncBufRead + Unpin,
+{
+    pub fn from_reader(inner: R, parser: &'a Parser<'a>) -> Self {
+        Reader {
+            inner,
+            parser,
+            terminator: Terminator::default(),
+            buf: Vec::new(),
+            line: 0,
+        }
+    }
+
+    pub fn terminator(mut self, terminator: Terminator) -> Self {
+        self.terminator = terminator;
+        self
+    }
+
+    /// The number of the last line read, from 1.
+    pub fn line(&self) -> u64 {
+        self.line
+    }
+
+    pub fn parser(&self) -> &Parser<'a> {
+        self.parser
+    }
+
+    /// Reads and parses the next record, returning `None` at the end of the input.
+    pub async fn next_record(&mut self) -> Option<ResultRecord> {
+        match self.read_record().await {
+            Ok(false) => None,
+            Ok(true) => {
+                self.line += 1;
+                let result = match std::str::from_utf8(&self.buf) {
+                    Ok(s) => self.parser.parse(s),
+                    Err(source) => Err(Error::Encoding { line: None, source }),
+                };
+                Some(result.map_err(|e| e.at_line(self.line)))
+            }
+            Err(e) => Some(Err(e.into())),
+        }
+    }
+
+    /// Turns the reader into a stream of records.
+    pub fn into_stream(self) -> impl Stream<Item = ResultRecord> + 'a
+    where
+        R: 'a,
+    {
+        stream::unfold(self, |mut reader| async move {
+            let record = reader.next_record().await?;
+            Some((record, reader))
+        })
+    }
+
+    async fn read_record(&mut self) -> io::Result<bool> {
+        self.buf.clear();
+        let delimiter = match &self.terminator {
+            Terminator::Newline | Terminator::CrLf => b"\n".as_slice(),
+            Terminator::Nel => &[0x85],
+            Terminator::NelUtf8 => &[0xc2, 0x85],
+            Terminator::Bytes(seq) if !seq.is_empty() => seq,
+            Terminator::Bytes(_) | Terminator::Fixed => {
+                let width = self.parser.width() as u64;
+                let read = (&mut self.inner)
+                    .take(width)
+                    .read_to_end(&mut self.buf)
+                    .await?;
+                return Ok(read > 0);
+            }
+            Terminator::LengthPrefixed(_) | Terminator::Rdw => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    "Length prefixed records cannot be read asynchronously",
+                ))
+            }
+        };
+        let last = delimiter[delimiter.len() - 1];
+        loop {
+            if self.inner.read_until(last, &mut self.buf).await? == 0
+                || self.buf.ends_with(delimiter)
+            {
+                break;
+            }
+        }
+        if self.buf.is_empty() {
+            return Ok(false);
+        }
+        if self.buf.ends_with(delimiter) {
+            self.buf.truncate(self.buf.len() - delimiter.len());
+            if delimiter == b"\n" && self.buf.ends_with(b"\r") {
+                self.buf.pop();
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// Reads records from a stream of byte chunks, the generic bridge to object stores and HTTP
+/// bodies which yield their content as chunks.
+pub fn from_stream<'a, S>(stream: S, parser: &'a Parser<'a>) -> Reader<'a, IntoAsyncRead<S>>
+where
+    S: TryStream<Error = io::Error> + Unpin,
+    S::Ok: AsRef<[u8]>,
+{
+    Reader::from_reader(stream.into_async_read(), parser)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::{Buildable, Builder};
+    use futures_executor::block_on;
+    use futures_util::StreamExt;
+
+    fn parser() -> Parser<'static> {
+        Parser::builder()
+            .field("code")
+            .width(2)
+            .append()
+            .field("name")
+            .width(4)
+            .append()
+            .build()
+    }
+
+    #[test]
+    fn read_chunks() {
+        let parser = parser();
+        let chunks = vec![Ok("01AB"), Ok("CD\r\n02EF"), Ok("GH\n03"), Ok("\n")];
+        let rdr = from_stream(stream::iter(chunks), &parser);
+
+        let records = block_on(rdr.into_stream().collect::<Vec<_>>());
+
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].as_ref().unwrap()["name"], "ABCD");
+        assert_eq!(records[1].as_ref().unwrap()["code"], "02");
+        assert_eq!(records[2].as_ref().unwrap_err().line(), Some(3));
+    }
+
+    #[test]
+    fn read_fixed() {
+        let parser = parser();
+        let input: &[u8] = b"01ABCD02EFGH";
+        let mut rdr = Reader::from_reader(input, &parser).terminator(Terminator::Fixed);
+
+        let record = block_on(rdr.next_record()).unwrap().unwrap();
+
+        assert_eq!(record["name"], "ABCD");
+        assert_eq!(block_on(rdr.next_record()).unwrap().unwrap()["code"], "02");
+        assert!(block_on(rdr.next_record()).is_none());
+    }
+}
@@ -0,0 +1,52 @@
+use super::Reader;
+use crate::{error::Error, flat::fixed::Parser};
+use futures_util::{io::AsyncBufRead, TryStreamExt};
+use object_store::{path::Path, ObjectStore};
+use std::{io, pin::Pin};
+
+/// The content of an object being downloaded.
+pub type ObjectReader = Pin<Box<dyn AsyncBufRead + Send>>;
+
+/// Streams the records of an object from an object store, e.g. S3, GCS or Azure, as its content
+/// is downloaded.
+pub async fn open<'a>(
+    store: &dyn ObjectStore,
+    location: &Path,
+    parser: &'a Parser<'a>,
+) -> Result<Reader<'a, ObjectReader>, Error> {
+    let result = store
+        .get(location)
+        .await
+        .map_err(|e| Error::from(io::Error::other(e)))?;
+    let content = result
+        .into_stream()
+        .map_err(io::Error::other)
+        .into_async_read();
+    Ok(Reader::from_reader(Box::pin(content), parser))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::{Buildable, Builder};
+    use futures_executor::block_on;
+    use futures_util::StreamExt;
+    use object_store::{memory::InMemory, PutPayload};
+
+    #[test]
+    fn read_object() {
+        let parser = Parser::builder().field("code").width(4).append().build();
+        let store = InMemory::new();
+        let location = Path::from("spool/input.txt");
+        block_on(store.put(&location, PutPayload::from("AAAA\nBBBB\n"))).unwrap();
+
+        let records = block_on(async {
+            let rdr = open(&store, &location, &parser).await.unwrap();
+            rdr.into_stream().collect::<Vec<_>>().await
+        });
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1].as_ref().unwrap()["code"], "BBBB");
+        assert!(block_on(open(&store, &Path::from("missing"), &parser)).is_err());
+    }
+}
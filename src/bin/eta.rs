@@ -0,0 +1,321 @@
+use clap::{Parser as ClapParser, Subcommand, ValueEnum};
+use eta_parse::{
+    error::Error,
+    flat::fixed::{read::Reader, write::Writer, Parser, Record},
+    generate::{records, Spec},
+    job::run_job,
+    schema::{codegen, Schema},
+};
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, Read, Write},
+    path::PathBuf,
+    process,
+};
+
+/// Parse, format, validate and describe fixed width files.
+#[derive(Debug, ClapParser)]
+#[command(name = "eta", version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Converts a fixed width file to CSV or JSON lines.
+    Parse {
+        #[arg(short, long)]
+        schema: PathBuf,
+        #[arg(short = 't', long = "to", value_enum, default_value = "csv")]
+        format: DataFormat,
+        /// Input file, defaults to stdin.
+        input: Option<PathBuf>,
+        /// Output file, defaults to stdout.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Converts a CSV or JSON lines file to fixed width.
+    Format {
+        #[arg(short, long)]
+        schema: PathBuf,
+        #[arg(short = 'f', long = "from", value_enum, default_value = "csv")]
+        format: DataFormat,
+        /// Input file, defaults to stdin.
+        input: Option<PathBuf>,
+        /// Output file, defaults to stdout.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Checks that every line of a fixed width file can be parsed.
+    Validate {
+        #[arg(short, long)]
+        schema: PathBuf,
+        /// Stop after this many errors.
+        #[arg(long)]
+        max_errors: Option<u64>,
+        /// Input file, defaults to stdin.
+        input: Option<PathBuf>,
+    },
+    /// Prints the layout table and a ruler, using the first line of the input as a sample.
+    Describe {
+        #[arg(short, long)]
+        schema: PathBuf,
+        /// Optional input file providing a sample line.
+        input: Option<PathBuf>,
+    },
+    /// Generates a Rust struct and its parser from a schema.
+    Gen {
+        #[arg(short, long)]
+        schema: PathBuf,
+        /// Name of the struct.
+        #[arg(short, long, default_value = "Record")]
+        name: String,
+        /// Output file, defaults to stdout.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Writes a fixed width file of random records fitting a schema, as test data.
+    GenData {
+        #[arg(short, long)]
+        schema: PathBuf,
+        /// Number of records.
+        #[arg(short = 'n', long, default_value = "100")]
+        count: u64,
+        /// Seed of the random values, the same seed generates the same file.
+        #[arg(long, default_value = "0")]
+        seed: u64,
+        /// Output file, defaults to stdout.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Runs a TOML job definition.
+    Run { job: PathBuf },
+}
+
+/// Record oriented formats the fixed width data is converted from or to.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum DataFormat {
+    Csv,
+    /// One JSON object per line.
+    Json,
+}
+
+fn input(path: &Option<PathBuf>) -> Result<Box<dyn Read>, Error> {
+    Ok(match path {
+        Some(path) => Box::new(File::open(path)?),
+        None => Box::new(io::stdin()),
+    })
+}
+
+fn output(path: &Option<PathBuf>) -> Result<Box<dyn Write>, Error> {
+    Ok(match path {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(io::stdout()),
+    })
+}
+
+fn other<E: ToString>(e: E) -> Error {
+    Error::from(io::Error::other(e.to_string()))
+}
+
+fn names<'a>(parser: &'a Parser) -> Vec<&'a str> {
+    parser.fields().iter().filter_map(|f| f.name()).collect()
+}
+
+fn parse(
+    parser: &Parser,
+    format: DataFormat,
+    input: Box<dyn Read>,
+    output: Box<dyn Write>,
+) -> Result<(), Error> {
+    let names = names(parser);
+    let mut reader = Reader::from_reader(input, parser);
+    match format {
+        DataFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(output);
+            writer.write_record(&names).map_err(other)?;
+            for record in reader.string_reader() {
+                let record = record?;
+                writer
+                    .write_record(
+                        names
+                            .iter()
+                            .map(|n| record.get(*n).map_or("", |v| v.as_str())),
+                    )
+                    .map_err(other)?;
+            }
+            writer.flush()?;
+        }
+        DataFormat::Json => {
+            let mut output = io::BufWriter::new(output);
+            for record in reader.string_reader() {
+                let record = record?;
+                let object = names
+                    .iter()
+                    .map(|n| {
+                        let value = record
+                            .get(*n)
+                            .map_or(serde_json::Value::Null, |v| v.clone().into());
+                        (n.to_string(), value)
+                    })
+                    .collect::<serde_json::Map<String, serde_json::Value>>();
+                serde_json::to_writer(&mut output, &object).map_err(other)?;
+                output.write_all(b"\n")?;
+            }
+            output.flush()?;
+        }
+    }
+    Ok(())
+}
+
+fn format(
+    parser: &Parser,
+    format: DataFormat,
+    input: Box<dyn Read>,
+    output: Box<dyn Write>,
+) -> Result<(), Error> {
+    let mut writer = Writer::from_writer(output, parser);
+    match format {
+        DataFormat::Csv => {
+            let mut reader = csv::Reader::from_reader(input);
+            let headers = reader.headers().map_err(other)?.clone();
+            for row in reader.records() {
+                let row = row.map_err(other)?;
+                let record = headers
+                    .iter()
+                    .zip(row.iter())
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect::<Record>();
+                writer.write(&record)?;
+            }
+        }
+        DataFormat::Json => {
+            for line in BufReader::new(input).lines() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let object: serde_json::Map<String, serde_json::Value> =
+                    serde_json::from_str(&line).map_err(other)?;
+                let record = object
+                    .into_iter()
+                    .filter_map(|(k, v)| match v {
+                        serde_json::Value::String(s) => Some((k, s)),
+                        serde_json::Value::Null => None,
+                        v => Some((k, v.to_string())),
+                    })
+                    .collect::<Record>();
+                writer.write(&record)?;
+            }
+        }
+    }
+    writer.flush()
+}
+
+fn validate(parser: &Parser, input: Box<dyn Read>, max_errors: Option<u64>) -> Result<bool, Error> {
+    let mut reader = Reader::from_reader(input, parser);
+    if let Some(max) = max_errors {
+        reader = reader.max_errors(max);
+    }
+    let mut records = 0;
+    let mut errors = 0;
+    for record in reader.string_reader() {
+        records += 1;
+        if let Err(e) = record {
+            errors += 1;
+            println!("{}", e);
+        }
+    }
+    println!("{} records, {} errors", records, errors);
+    Ok(errors == 0)
+}
+
+fn describe(parser: &Parser, input: Option<Box<dyn Read>>) -> Result<(), Error> {
+    let sample = match input {
+        Some(input) => BufReader::new(input).lines().next().transpose()?,
+        None => None,
+    };
+    println!("{}", parser.describe());
+    println!();
+    println!("{}", parser.ruler(sample.as_deref()));
+    Ok(())
+}
+
+fn run(cli: Cli) -> Result<bool, Error> {
+    match cli.command {
+        Command::Parse {
+            schema,
+            format: data_format,
+            input: i,
+            output: o,
+        } => {
+            let schema = Schema::from_file(schema)?;
+            parse(&schema.parser()?, data_format, input(&i)?, output(&o)?)?;
+        }
+        Command::Format {
+            schema,
+            format: data_format,
+            input: i,
+            output: o,
+        } => {
+            let schema = Schema::from_file(schema)?;
+            format(&schema.parser()?, data_format, input(&i)?, output(&o)?)?;
+        }
+        Command::Validate {
+            schema,
+            max_errors,
+            input: i,
+        } => {
+            let schema = Schema::from_file(schema)?;
+            return validate(&schema.parser()?, input(&i)?, max_errors);
+        }
+        Command::Describe { schema, input: i } => {
+            let schema = Schema::from_file(schema)?;
+            let sample = match i {
+                Some(_) => Some(input(&i)?),
+                None => None,
+            };
+            describe(&schema.parser()?, sample)?;
+        }
+        Command::Gen {
+            schema,
+            name,
+            output: o,
+        } => {
+            let code = codegen::generate(&Schema::from_file(schema)?, &name)?;
+            output(&o)?.write_all(code.as_bytes())?;
+        }
+        Command::GenData {
+            schema,
+            count,
+            seed,
+            output: o,
+        } => {
+            let schema = Schema::from_file(schema)?;
+            let parser = schema.parser()?;
+            let mut writer = Writer::from_writer(output(&o)?, &parser);
+            for record in records(&parser, &Spec::new().seed(seed), count) {
+                writer.write(&record)?;
+            }
+            writer.flush()?;
+        }
+        Command::Run { job } => {
+            let summary = run_job(job)?;
+            println!("{}", serde_json::to_string_pretty(&summary).map_err(other)?);
+            return Ok(summary.rejected == 0);
+        }
+    }
+    Ok(true)
+}
+
+fn main() {
+    match run(Cli::parse()) {
+        Ok(true) => (),
+        Ok(false) => process::exit(1),
+        Err(e) => {
+            eprintln!("eta: {}", e);
+            process::exit(2);
+        }
+    }
+}
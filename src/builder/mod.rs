@@ -1,5 +1,6 @@
-use std::fmt::Debug;
+use core::fmt::Debug;
 
+#[allow(dead_code)]
 pub trait Buildable {
     type Builder: Builder<Target = Self>;
 
@@ -0,0 +1,393 @@
+//! C bindings, so batch programs in C or C++ delegate layout handling to this crate. The
+//! declarations are in `include/eta_parse.h`, generated by `cbindgen` into the build output
+//! directory when building with the `capi` feature and checked in. Link against the static or dynamic library built with
+//! `cargo rustc --release --features capi --crate-type staticlib` (or `cdylib`).
+//!
+//! Functions failing return `NULL` or a negative value, and the message of the error is then
+//! available from [`eta_last_error`] on the same thread.
+
+use crate::{
+    error::Error,
+    flat::fixed::{row::RowRecord, Parser},
+    schema::Schema,
+};
+use std::{
+    cell::RefCell,
+    ffi::{CStr, CString},
+    mem::ManuallyDrop,
+    os::raw::{c_char, c_int},
+    ptr::{self, NonNull},
+    slice,
+};
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_error(message: String) {
+    let message = CString::new(message.replace('\0', " ")).expect("NUL characters are replaced");
+    LAST_ERROR.with(|e| *e.borrow_mut() = Some(message));
+}
+
+/// A parser for a layout, created with [`eta_parser_new`] and freed with [`eta_parser_free`].
+pub struct EtaParser {
+    // Borrows from `schema`, so it is dropped first.
+    parser: ManuallyDrop<Parser<'static>>,
+    schema: NonNull<Schema>,
+    names: Vec<CString>,
+}
+
+impl EtaParser {
+    fn new(schema: Schema) -> Result<Self, Error> {
+        let schema = NonNull::from(Box::leak(Box::new(schema)));
+        // The schema is only freed on drop, after the parser.
+        let parser = match unsafe { schema.as_ref() }.parser() {
+            Ok(parser) => parser,
+            Err(e) => {
+                drop(unsafe { Box::from_raw(schema.as_ptr()) });
+                return Err(e);
+            }
+        };
+        let names = parser
+            .fields()
+            .iter()
+            .filter_map(|f| f.name())
+            .map(|name| CString::new(name).unwrap_or_default())
+            .collect();
+        Ok(EtaParser {
+            parser: ManuallyDrop::new(parser),
+            schema,
+            names,
+        })
+    }
+}
+
+impl Drop for EtaParser {
+    fn drop(&mut self) {
+        unsafe {
+            ManuallyDrop::drop(&mut self.parser);
+            drop(Box::from_raw(self.schema.as_ptr()));
+        }
+    }
+}
+
+/// The values of the named fields of a parsed line, in layout order. A null field has a
+/// `NULL` value. Freed with [`eta_fields_free`].
+#[repr(C)]
+pub struct EtaFields {
+    pub values: *mut *mut c_char,
+    pub len: usize,
+}
+
+unsafe fn to_str<'s>(s: *const c_char, what: &str) -> Option<&'s str> {
+    if s.is_null() {
+        set_error(format!("The {} is NULL", what));
+        return None;
+    }
+    match CStr::from_ptr(s).to_str() {
+        Ok(s) => Some(s),
+        Err(e) => {
+            set_error(format!("The {} is not UTF-8: {}", what, e));
+            None
+        }
+    }
+}
+
+/// The message of the last error on this thread, `NULL` if there was none. The string is
+/// owned by the library and valid until the next call failing on this thread.
+#[no_mangle]
+pub extern "C" fn eta_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ref().map_or(ptr::null(), |e| e.as_ptr()))
+}
+
+/// Creates a parser from a schema in JSON, with the fields of a TOML schema. Returns `NULL`
+/// if the schema is invalid.
+///
+/// # Safety
+///
+/// `schema` must be a NUL terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn eta_parser_new(schema: *const c_char) -> *mut EtaParser {
+    let schema = match to_str(schema, "schema") {
+        Some(schema) => schema,
+        None => return ptr::null_mut(),
+    };
+    let parser = serde_json::from_str::<Schema>(schema)
+        .map_err(|e| Error::Layout(e.to_string()))
+        .and_then(EtaParser::new);
+    match parser {
+        Ok(parser) => Box::into_raw(Box::new(parser)),
+        Err(e) => {
+            set_error(e.to_string());
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Frees a parser, doing nothing if it is `NULL`.
+///
+/// # Safety
+///
+/// `parser` must come from [`eta_parser_new`] and not have been freed.
+#[no_mangle]
+pub unsafe extern "C" fn eta_parser_free(parser: *mut EtaParser) {
+    if !parser.is_null() {
+        drop(Box::from_raw(parser));
+    }
+}
+
+/// The total width of a record, in characters.
+///
+/// # Safety
+///
+/// `parser` must be a valid parser.
+#[no_mangle]
+pub unsafe extern "C" fn eta_parser_width(parser: *const EtaParser) -> usize {
+    (&*parser).parser.width()
+}
+
+/// The number of named fields, the length of the values of a parsed line.
+///
+/// # Safety
+///
+/// `parser` must be a valid parser.
+#[no_mangle]
+pub unsafe extern "C" fn eta_parser_field_count(parser: *const EtaParser) -> usize {
+    (&*parser).names.len()
+}
+
+/// The name of the named field at `index`, `NULL` if out of bounds. The string is owned by
+/// the parser.
+///
+/// # Safety
+///
+/// `parser` must be a valid parser.
+#[no_mangle]
+pub unsafe extern "C" fn eta_parser_field_name(
+    parser: *const EtaParser,
+    index: usize,
+) -> *const c_char {
+    (&*parser)
+        .names
+        .get(index)
+        .map_or(ptr::null(), |name| name.as_ptr())
+}
+
+/// Parses a line into the values of the named fields. Returns 0 on success and -1 on error,
+/// leaving `out` untouched, including when a value contains a NUL character.
+///
+/// # Safety
+///
+/// `parser` must be a valid parser, `line` a NUL terminated string and `out` writable.
+#[no_mangle]
+pub unsafe extern "C" fn eta_parse(
+    parser: *const EtaParser,
+    line: *const c_char,
+    out: *mut EtaFields,
+) -> c_int {
+    let line = match to_str(line, "line") {
+        Some(line) => line,
+        None => return -1,
+    };
+    let row = match (&*parser).parser.parse_row(line) {
+        Ok(row) => row,
+        Err(e) => {
+            set_error(e.to_string());
+            return -1;
+        }
+    };
+    let values = match row
+        .iter()
+        .enumerate()
+        .map(|(i, value)| value.map(CString::new).transpose().map_err(|_| i))
+        .collect::<Result<Vec<_>, _>>()
+    {
+        Ok(values) => values,
+        Err(i) => {
+            let name = (&*parser).names[i].to_string_lossy();
+            set_error(format!(
+                "The value of field {} contains a NUL character",
+                name
+            ));
+            return -1;
+        }
+    };
+    let values = values
+        .into_iter()
+        .map(|value| value.map_or(ptr::null_mut(), CString::into_raw))
+        .collect::<Vec<_>>()
+        .into_boxed_slice();
+    let len = values.len();
+    *out = EtaFields {
+        values: Box::into_raw(values) as *mut *mut c_char,
+        len,
+    };
+    0
+}
+
+/// Frees the values of a parsed line and resets them to empty.
+///
+/// # Safety
+///
+/// `fields` must have been filled by [`eta_parse`] and not have been freed.
+#[no_mangle]
+pub unsafe extern "C" fn eta_fields_free(fields: *mut EtaFields) {
+    let fields = match fields.as_mut() {
+        Some(fields) if !fields.values.is_null() => fields,
+        _ => return,
+    };
+    let values = Box::from_raw(ptr::slice_from_raw_parts_mut(fields.values, fields.len));
+    for value in values.iter().filter(|v| !v.is_null()) {
+        drop(CString::from_raw(*value));
+    }
+    *fields = EtaFields {
+        values: ptr::null_mut(),
+        len: 0,
+    };
+}
+
+/// Formats the values of the named fields in layout order as a line, a `NULL` value being
+/// formatted like a missing field. Returns `NULL` on error, the line is freed with
+/// [`eta_string_free`].
+///
+/// # Safety
+///
+/// `parser` must be a valid parser and `values` point to `len` strings, each `NULL` or NUL
+/// terminated.
+#[no_mangle]
+pub unsafe extern "C" fn eta_format(
+    parser: *const EtaParser,
+    values: *const *const c_char,
+    len: usize,
+) -> *mut c_char {
+    let values = match (values.is_null(), len) {
+        (_, 0) => &[][..],
+        (true, _) => {
+            set_error("The values are NULL".to_string());
+            return ptr::null_mut();
+        }
+        (false, len) => slice::from_raw_parts(values, len),
+    };
+    let mut row = RowRecord::new();
+    for (i, value) in values.iter().enumerate() {
+        if value.is_null() {
+            row.set(i, None);
+            continue;
+        }
+        match to_str(*value, "value") {
+            Some(value) => row.set(i, Some(value)),
+            None => return ptr::null_mut(),
+        }
+    }
+    match CString::new((&*parser).parser.format_row(&row)) {
+        Ok(line) => line.into_raw(),
+        Err(e) => {
+            set_error(e.to_string());
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Frees a line returned by [`eta_format`], doing nothing if it is `NULL`.
+///
+/// # Safety
+///
+/// `s` must come from [`eta_format`] and not have been freed.
+#[no_mangle]
+pub unsafe extern "C" fn eta_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_format() {
+        let schema = CString::new(
+            r#"{"fields": [{"name": "id", "width": 3}, {"width": 2}, {"name": "name", "width": 5}]}"#,
+        )
+        .unwrap();
+        let line = CString::new("001  Alice").unwrap();
+
+        unsafe {
+            let parser = eta_parser_new(schema.as_ptr());
+            assert!(!parser.is_null());
+            assert_eq!(eta_parser_width(parser), 10);
+            assert_eq!(eta_parser_field_count(parser), 2);
+            assert_eq!(
+                CStr::from_ptr(eta_parser_field_name(parser, 1)).to_str(),
+                Ok("name")
+            );
+
+            let mut fields = EtaFields {
+                values: ptr::null_mut(),
+                len: 0,
+            };
+            assert_eq!(eta_parse(parser, line.as_ptr(), &mut fields), 0);
+            let values = slice::from_raw_parts(fields.values, fields.len);
+            assert_eq!(CStr::from_ptr(values[1]).to_str(), Ok("Alice"));
+
+            let values = values
+                .iter()
+                .map(|v| *v as *const c_char)
+                .collect::<Vec<_>>();
+            let formatted = eta_format(parser, values.as_ptr(), values.len());
+            assert_eq!(CStr::from_ptr(formatted).to_str(), Ok("001  Alice"));
+
+            eta_string_free(formatted);
+            eta_fields_free(&mut fields);
+            eta_parser_free(parser);
+        }
+    }
+
+    #[test]
+    fn parse_nul_value() {
+        let schema =
+            CString::new(r#"{"fields": [{"name": "id", "width": 3, "default_value": "\u0000"}]}"#)
+                .unwrap();
+        let line = CString::new("   ").unwrap();
+        let mut fields = EtaFields {
+            values: ptr::null_mut(),
+            len: 0,
+        };
+
+        unsafe {
+            let parser = eta_parser_new(schema.as_ptr());
+            assert!(!parser.is_null());
+            assert_eq!(eta_parse(parser, line.as_ptr(), &mut fields), -1);
+            assert!(fields.values.is_null());
+            assert_eq!(
+                CStr::from_ptr(eta_last_error()).to_str(),
+                Ok("The value of field id contains a NUL character")
+            );
+            eta_parser_free(parser);
+        }
+    }
+
+    #[test]
+    fn header_is_up_to_date() {
+        assert_eq!(
+            include_str!("../include/eta_parse.h"),
+            include_str!(concat!(env!("OUT_DIR"), "/eta_parse.h")),
+            "include/eta_parse.h is out of date, copy the one generated in OUT_DIR"
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let schema = CString::new("{\"fields\": [").unwrap();
+
+        unsafe {
+            assert!(eta_parser_new(schema.as_ptr()).is_null());
+            assert!(!eta_last_error().is_null());
+            assert!(eta_parser_new(ptr::null()).is_null());
+            assert_eq!(
+                CStr::from_ptr(eta_last_error()).to_str(),
+                Ok("The schema is NULL")
+            );
+        }
+    }
+}
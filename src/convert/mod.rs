@@ -0,0 +1,11 @@
+#[cfg(feature = "parquet")]
+mod parquet;
+#[cfg(feature = "polars")]
+mod polars;
+mod sql;
+
+#[cfg(feature = "parquet")]
+pub use self::parquet::{fixed_to_parquet, ParquetOptions};
+#[cfg(feature = "polars")]
+pub use self::polars::FromFixed;
+pub use self::sql::{to_sql, Inserts, Placeholder, Statement};
@@ -0,0 +1,174 @@
+use crate::{
+    error::Error,
+    flat::fixed::{read::Reader, Parser},
+};
+use arrow_array::{builder::StringBuilder, ArrayRef, RecordBatch};
+use arrow_schema::{DataType, Field, Schema};
+use parquet::{arrow::ArrowWriter, errors::ParquetError, file::properties::WriterProperties};
+use std::{fs::File, io, io::Read, path::Path, sync::Arc};
+
+/// How a Parquet file is written by [`fixed_to_parquet`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParquetOptions {
+    /// Maximum number of rows in a row group.
+    pub row_group_size: usize,
+    /// Number of records buffered before they are handed to the Parquet writer.
+    pub batch_size: usize,
+}
+
+impl Default for ParquetOptions {
+    fn default() -> Self {
+        ParquetOptions {
+            row_group_size: 1024 * 1024,
+            batch_size: 8192,
+        }
+    }
+}
+
+/// Streams the records of a reader into a Parquet file with a nullable string column per named
+/// field, returning the number of rows written. Stops at the first record that fails to parse.
+pub fn fixed_to_parquet<R, P>(
+    reader: &mut Reader<'_, R>,
+    path: P,
+    options: &ParquetOptions,
+) -> Result<u64, Error>
+where
+    R: Read,
+    P: AsRef<Path>,
+{
+    let names = names(reader.parser());
+    let schema = Arc::new(Schema::new(
+        names
+            .iter()
+            .map(|name| Field::new(*name, DataType::Utf8, true))
+            .collect::<Vec<Field>>(),
+    ));
+    let properties = WriterProperties::builder()
+        .set_max_row_group_size(options.row_group_size.max(1))
+        .build();
+    let mut writer = ArrowWriter::try_new(File::create(path)?, schema.clone(), Some(properties))
+        .map_err(other)?;
+    let batch_size = options.batch_size.max(1);
+    let mut columns = new_columns(names.len(), batch_size);
+    let mut buffered = 0;
+    let mut rows = 0;
+    for record in reader.string_reader() {
+        let record = record?;
+        for (column, name) in columns.iter_mut().zip(names.iter()) {
+            column.append_option(record.get(*name));
+        }
+        buffered += 1;
+        if buffered == batch_size {
+            write_batch(&mut writer, &schema, &mut columns)?;
+            rows += buffered as u64;
+            buffered = 0;
+        }
+    }
+    if buffered > 0 {
+        write_batch(&mut writer, &schema, &mut columns)?;
+        rows += buffered as u64;
+    }
+    writer.close().map_err(other)?;
+    Ok(rows)
+}
+
+/// The names of the columns, the first field of each name as when parsing.
+fn names<'p>(parser: &'p Parser) -> Vec<&'p str> {
+    let mut names = Vec::new();
+    for name in parser.fields().iter().filter_map(|f| f.name()) {
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names
+}
+
+fn new_columns(count: usize, capacity: usize) -> Vec<StringBuilder> {
+    (0..count)
+        .map(|_| StringBuilder::with_capacity(capacity, capacity * 8))
+        .collect()
+}
+
+fn write_batch(
+    writer: &mut ArrowWriter<File>,
+    schema: &Arc<Schema>,
+    columns: &mut [StringBuilder],
+) -> Result<(), Error> {
+    let arrays = columns
+        .iter_mut()
+        .map(|c| Arc::new(c.finish()) as ArrayRef)
+        .collect::<Vec<ArrayRef>>();
+    let batch = RecordBatch::try_new(schema.clone(), arrays).map_err(other)?;
+    writer.write(&batch).map_err(other)
+}
+
+/// Failures writing the file are I/O errors, other failures come from the layout of the
+/// columns.
+fn other<E: Into<ParquetError>>(e: E) -> Error {
+    match e.into() {
+        ParquetError::External(e) => match e.downcast::<io::Error>() {
+            Ok(e) => Error::Io(*e),
+            Err(e) => Error::Layout(e.to_string()),
+        },
+        e => Error::Layout(e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        builder::{Buildable, Builder},
+        flat::fixed::Null,
+    };
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+
+    #[test]
+    fn write_parquet() {
+        let parser = Parser::builder()
+            .field("id")
+            .width(2)
+            .append()
+            .field("name")
+            .width(4)
+            .null_when(Null::Blank)
+            .append()
+            .build();
+        let input = "01ABCD\n02    \n03EFGH\n";
+        let path = std::env::temp_dir().join("eta-parse-fixed-to-parquet.parquet");
+        let mut rdr = Reader::from_string(input, &parser);
+        let options = ParquetOptions {
+            row_group_size: 2,
+            batch_size: 1,
+        };
+
+        assert_eq!(fixed_to_parquet(&mut rdr, &path, &options).unwrap(), 3);
+
+        let file = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+        let metadata = file.metadata();
+        assert_eq!(metadata.file_metadata().num_rows(), 3);
+        assert_eq!(metadata.num_row_groups(), 2);
+        assert_eq!(
+            metadata.file_metadata().schema_descr().column(1).name(),
+            "name"
+        );
+        let rows = file
+            .get_row_iter(None)
+            .unwrap()
+            .map(|row| row.unwrap().to_string())
+            .collect::<Vec<String>>();
+        assert_eq!(rows[1], "{id: \"02\", name: null}");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn parquet_errors() {
+        let io = io::Error::new(io::ErrorKind::StorageFull, "full");
+
+        assert!(matches!(other(io), Error::Io(_)));
+        assert!(matches!(
+            other(ParquetError::General(String::from("schema"))),
+            Error::Layout(_)
+        ));
+    }
+}
@@ -0,0 +1,167 @@
+use crate::{
+    error::Error,
+    flat::fixed::{read::Reader, Parser},
+};
+use polars::prelude::{Column, DataFrame, NamedFrom, Series};
+use std::io::Read;
+
+/// Builds a value from the records of a fixed width reader.
+pub trait FromFixed: Sized {
+    fn from_fixed<R: Read>(reader: &mut Reader<'_, R>) -> Result<Self, Error>;
+}
+
+/// Builds a data frame with a column per field name, stopping at the first record that fails
+/// to parse.
+///
+/// Column types are inferred from the values: integers when every value is an integer without
+/// leading zeros, which are kept as strings as they are usually codes, floats when every value
+/// is a decimal number, strings otherwise. Null fields, and blank values of numeric columns,
+/// are null.
+impl FromFixed for DataFrame {
+    fn from_fixed<R: Read>(reader: &mut Reader<'_, R>) -> Result<Self, Error> {
+        let names = names(reader.parser());
+        let mut values = vec![Vec::new(); names.len()];
+        for record in reader.string_reader() {
+            let mut record = record?;
+            for (column, name) in values.iter_mut().zip(names.iter()) {
+                column.push(record.remove(*name));
+            }
+        }
+        let columns = names
+            .iter()
+            .zip(values)
+            .map(|(name, values)| column(name, values))
+            .collect::<Vec<Column>>();
+        DataFrame::new(columns).map_err(|e| Error::Layout(e.to_string()))
+    }
+}
+
+/// The names of the columns, the first field of each name as when parsing.
+fn names<'p>(parser: &'p Parser) -> Vec<&'p str> {
+    let mut names = Vec::new();
+    for name in parser.fields().iter().filter_map(|f| f.name()) {
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names
+}
+
+fn column(name: &str, values: Vec<Option<String>>) -> Column {
+    let present = || {
+        values
+            .iter()
+            .flatten()
+            .map(|v| v.trim())
+            .filter(|v| !v.is_empty())
+    };
+    let series = if present().all(is_integer) && present().next().is_some() {
+        Series::new(name.into(), parse::<i64>(&values))
+    } else if present().all(is_decimal) && present().next().is_some() {
+        Series::new(name.into(), parse::<f64>(&values))
+    } else {
+        Series::new(name.into(), values)
+    };
+    series.into()
+}
+
+fn parse<T: std::str::FromStr>(values: &[Option<String>]) -> Vec<Option<T>> {
+    values
+        .iter()
+        .map(|v| v.as_ref().and_then(|v| v.trim().parse().ok()))
+        .collect()
+}
+
+fn is_integer(value: &str) -> bool {
+    let digits = value.strip_prefix('-').unwrap_or(value);
+    digits.len() <= 18 && is_whole(digits)
+}
+
+fn is_decimal(value: &str) -> bool {
+    let digits = value.strip_prefix('-').unwrap_or(value);
+    match digits.split_once('.') {
+        Some(("", "")) => false,
+        Some((whole, fraction)) => {
+            (whole.is_empty() || is_whole(whole)) && fraction.chars().all(|c| c.is_ascii_digit())
+        }
+        None => is_whole(digits),
+    }
+}
+
+/// Digits without leading zeros.
+fn is_whole(digits: &str) -> bool {
+    !digits.is_empty()
+        && digits.chars().all(|c| c.is_ascii_digit())
+        && (digits == "0" || !digits.starts_with('0'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        builder::{Buildable, Builder},
+        flat::fixed::Null,
+    };
+    use polars::prelude::DataType;
+
+    #[test]
+    fn dataframe_from_fixed() {
+        let parser = Parser::builder()
+            .field("code")
+            .width(4)
+            .append()
+            .field("count")
+            .width(3)
+            .append()
+            .field("amount")
+            .width(6)
+            .append()
+            .field("name")
+            .width(4)
+            .null_when(Null::Blank)
+            .append()
+            .build();
+        let input = "0012 42  -1.5ABCD\n0034   100.25    \n";
+        let mut rdr = Reader::from_string(input, &parser);
+
+        let df = DataFrame::from_fixed(&mut rdr).unwrap();
+
+        assert_eq!(df.shape(), (2, 4));
+        let types = df.dtypes();
+        assert_eq!(
+            types,
+            vec![
+                DataType::String,
+                DataType::Int64,
+                DataType::Float64,
+                DataType::String
+            ]
+        );
+        let count = df.column("count").unwrap().i64().unwrap();
+        assert_eq!(count.get(0), Some(42));
+        assert_eq!(count.get(1), None);
+        assert_eq!(df.column("name").unwrap().null_count(), 1);
+        assert_eq!(
+            df.column("code").unwrap().str().unwrap().get(0),
+            Some("0012")
+        );
+    }
+
+    #[test]
+    fn dataframe_duplicate_names() {
+        let parser = Parser::builder()
+            .field("code")
+            .width(2)
+            .append()
+            .field("code")
+            .width(2)
+            .append()
+            .build();
+        let mut rdr = Reader::from_string("AABB\n", &parser);
+
+        let df = DataFrame::from_fixed(&mut rdr).unwrap();
+
+        assert_eq!(df.shape(), (1, 1));
+        assert_eq!(df.column("code").unwrap().str().unwrap().get(0), Some("AA"));
+    }
+}
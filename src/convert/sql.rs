@@ -0,0 +1,221 @@
+use crate::{
+    error::Error,
+    flat::fixed::{Parser, ResultRecord},
+};
+use std::collections::HashMap;
+
+/// How the parameters of a statement are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Placeholder {
+    /// `?`, as used by SQLite and MySQL.
+    Question,
+    /// `$1`, `$2`, ..., as used by PostgreSQL.
+    Dollar,
+}
+
+/// A parameterized statement and the values of its parameters, `None` for null.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Statement {
+    pub sql: String,
+    pub params: Vec<Option<String>>,
+}
+
+/// Batches records into parameterized `INSERT` statements, see [`to_sql`].
+pub struct Inserts<'p, I> {
+    records: I,
+    table: String,
+    fields: Vec<&'p str>,
+    columns: HashMap<&'p str, String>,
+    placeholder: Placeholder,
+    batch_size: usize,
+    error: Option<Error>,
+}
+
+/// Converts records into `INSERT` statements for a staging table, with a column per named
+/// field of the layout. By default each statement inserts up to 100 records, with `?`
+/// placeholders and columns named after the fields.
+///
+/// Table and column names are quoted, the values are only ever passed as parameters. A record
+/// that fails to parse is yielded as an error after the statement of the records before it.
+pub fn to_sql<'p, I>(records: I, parser: &'p Parser, table: &str) -> Inserts<'p, I>
+where
+    I: Iterator<Item = ResultRecord>,
+{
+    Inserts {
+        records,
+        table: table.to_string(),
+        fields: parser.fields().iter().filter_map(|f| f.name()).collect(),
+        columns: HashMap::new(),
+        placeholder: Placeholder::Question,
+        batch_size: 100,
+        error: None,
+    }
+}
+
+#[allow(dead_code)]
+impl<'p, I> Inserts<'p, I>
+where
+    I: Iterator<Item = ResultRecord>,
+{
+    /// Sets the maximum number of records inserted by a statement.
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    pub fn placeholder(mut self, placeholder: Placeholder) -> Self {
+        self.placeholder = placeholder;
+        self
+    }
+
+    /// Inserts a field into a column with another name.
+    pub fn column(mut self, field: &'p str, column: &str) -> Self {
+        self.columns.insert(field, column.to_string());
+        self
+    }
+
+    /// Leaves a field out of the statements.
+    pub fn skip(mut self, field: &str) -> Self {
+        self.fields.retain(|f| *f != field);
+        self
+    }
+
+    fn statement(&self, params: Vec<Option<String>>) -> Statement {
+        let columns = self
+            .fields
+            .iter()
+            .map(|f| quote(self.columns.get(f).map_or(f, |c| c.as_str())))
+            .collect::<Vec<String>>()
+            .join(", ");
+        let rows = (0..params.len() / self.fields.len().max(1))
+            .map(|row| {
+                let values = (0..self.fields.len())
+                    .map(|column| match self.placeholder {
+                        Placeholder::Question => String::from("?"),
+                        Placeholder::Dollar => {
+                            format!("${}", row * self.fields.len() + column + 1)
+                        }
+                    })
+                    .collect::<Vec<String>>();
+                format!("({})", values.join(", "))
+            })
+            .collect::<Vec<String>>();
+        Statement {
+            sql: format!(
+                "INSERT INTO {} ({}) VALUES {}",
+                quote(&self.table),
+                columns,
+                rows.join(", ")
+            ),
+            params,
+        }
+    }
+}
+
+impl<'p, I> Iterator for Inserts<'p, I>
+where
+    I: Iterator<Item = ResultRecord>,
+{
+    type Item = Result<Statement, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(e) = self.error.take() {
+            return Some(Err(e));
+        }
+        let mut params = Vec::with_capacity(self.batch_size * self.fields.len());
+        for _ in 0..self.batch_size {
+            let mut record = match self.records.next() {
+                Some(Ok(record)) => record,
+                Some(Err(e)) if params.is_empty() => return Some(Err(e)),
+                Some(Err(e)) => {
+                    // The error is yielded after the records read before it.
+                    self.error = Some(e);
+                    break;
+                }
+                None => break,
+            };
+            params.extend(self.fields.iter().map(|f| record.remove(*f)));
+        }
+        match params.is_empty() {
+            true => None,
+            false => Some(Ok(self.statement(params))),
+        }
+    }
+}
+
+fn quote(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        builder::{Buildable, Builder},
+        flat::fixed::{read::Reader, Null},
+    };
+
+    #[test]
+    fn insert_statements() {
+        let parser = Parser::builder()
+            .field("id")
+            .width(2)
+            .append()
+            .spacer(0..1)
+            .field("name")
+            .width(4)
+            .null_when(Null::Blank)
+            .append()
+            .build();
+        let input = "01 Ann \n02     \nX\n03 Bob \n";
+        let mut rdr = Reader::from_string(input, &parser);
+
+        let statements = to_sql(rdr.string_reader(), &parser, "stage")
+            .batch_size(2)
+            .placeholder(Placeholder::Dollar)
+            .column("name", "full\"name")
+            .collect::<Vec<_>>();
+
+        assert_eq!(statements.len(), 3);
+        let first = statements[0].as_ref().unwrap();
+        assert_eq!(
+            first.sql,
+            "INSERT INTO \"stage\" (\"id\", \"full\"\"name\") VALUES ($1, $2), ($3, $4)"
+        );
+        assert_eq!(
+            first.params,
+            vec![
+                Some(String::from("01")),
+                Some(String::from("Ann")),
+                Some(String::from("02")),
+                None
+            ]
+        );
+        assert!(statements[1].is_err());
+        let last = statements[2].as_ref().unwrap();
+        assert_eq!(
+            last.sql,
+            "INSERT INTO \"stage\" (\"id\", \"full\"\"name\") VALUES ($1, $2)"
+        );
+    }
+
+    #[test]
+    fn insert_skipping_fields() {
+        let parser = Parser::builder()
+            .field("id")
+            .width(2)
+            .append()
+            .field("name")
+            .width(3)
+            .append()
+            .build();
+
+        let statements = to_sql(vec![parser.parse("01Ann")].into_iter(), &parser, "t")
+            .skip("name")
+            .collect::<Vec<_>>();
+
+        let statement = statements[0].as_ref().unwrap();
+        assert_eq!(statement.sql, "INSERT INTO \"t\" (\"id\") VALUES (?)");
+        assert_eq!(statement.params, vec![Some(String::from("01"))]);
+    }
+}
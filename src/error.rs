@@ -1,95 +1,288 @@
-use std::{
-    error::Error as StdError,
-    fmt::{Display, Formatter, Result},
-    io,
+use crate::utilities::charset::Charset;
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
 };
+use core::{
+    fmt::{Display, Formatter, Result as FmtResult},
+    str::Utf8Error,
+};
+#[cfg(feature = "std")]
+use std::{io, path::PathBuf};
+use thiserror::Error as ThisError;
 
-#[derive(Debug, Clone)]
-pub enum ParseError {
-    ImsufficentBuffer(usize, Option<usize>),
+/// Why a line could not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq, ThisError)]
+pub enum ParseErrorKind {
+    /// The line is shorter than the layout, `available` is `None` when its length is unknown.
+    #[error("{}", insufficient_buffer(*required, *available))]
+    InsufficientBuffer {
+        required: usize,
+        available: Option<usize>,
+    },
+    /// The record type of the line has no layout.
+    #[error("Unknown record type {0}")]
+    UnknownRecordType(String),
+    /// The input ends inside a quoted field of a delimited record.
+    #[error("Unterminated quoted field")]
+    UnterminatedQuote,
+    /// The value of a field is not in its code table.
+    #[error("Unknown code {0}")]
+    UnknownCode(String),
+    /// The check digit of a field does not match its value.
+    #[error("Invalid check digit in {0}")]
+    CheckDigit(String),
+    /// The line is longer than the declared record width.
+    #[error("Record is {actual} long, expected {expected}")]
+    RecordLength { expected: usize, actual: usize },
+    /// The value of a binary field is not an integer it can hold.
+    #[error("{0} does not fit the binary field")]
+    BinaryOverflow(String),
+    /// A value used as a number is not one, or does not fit the total it is added to.
+    #[error("{0} is not a number")]
+    NotNumeric(String),
 }
 
-impl Display for ParseError {
-    fn fmt(&self, f: &mut Formatter) -> Result {
-        match self {
-            ParseError::ImsufficentBuffer(width, None) => {
-                write!(f, "Undefined buffer size, required {}", width)
-            }
-            ParseError::ImsufficentBuffer(width, Some(max)) => write!(
-                f,
-                "Insufficient buffer size, required {} only {} available",
-                width, max
-            ),
-        }
+fn insufficient_buffer(required: usize, available: Option<usize>) -> String {
+    match available {
+        Some(available) => format!(
+            "Insufficient buffer size, required {} only {} available",
+            required, available
+        ),
+        None => format!("Undefined buffer size, required {}", required),
     }
 }
 
-#[allow(dead_code)]
-#[derive(Debug)]
-/// An error produced while parsing fixed width data.
-pub enum Error {
-    /// An IO error occured while reading the data.
-    IOError(io::Error),
-    /// An error occured while parsing the data.
-    ParserError(ParseError),
+#[derive(Debug, Clone, PartialEq, Eq, ThisError)]
+pub enum IntakeError {
+    #[error("File content already processed ({0})")]
+    DuplicateContent(String),
+    #[error("File header already processed ({0})")]
+    DuplicateHeader(String),
 }
 
-impl Display for Error {
-    fn fmt(&self, f: &mut Formatter) -> Result {
-        match self {
-            Error::IOError(ref e) => write!(f, "{}", e),
-            Error::ParserError(ref e) => e.fmt(f),
-        }
+/// A field value that failed a validation check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl Display for FieldError {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(f, "{}: {}", self.field, self.message)
     }
 }
 
-impl From<io::Error> for Error {
-    fn from(e: io::Error) -> Self {
-        Error::IOError(e)
+/// An error produced while reading, parsing, validating or writing fixed width data.
+#[derive(Debug, ThisError)]
+pub enum Error {
+    /// Reading or writing the underlying data failed.
+    #[cfg(feature = "std")]
+    #[error("{0}")]
+    Io(#[from] io::Error),
+    /// A layout or schema is invalid.
+    #[error("Invalid layout: {0}")]
+    Layout(String),
+    /// A job or other configuration is invalid.
+    #[error("Configuration error: {0}")]
+    Config(String),
+    /// An expression failed to parse or evaluate.
+    #[error("Expression error: {0}")]
+    Expression(String),
+    /// A line could not be parsed. The line number is known when the error comes from a reader,
+    /// the field when the error is specific to one.
+    #[error("{}{kind}", position(*line, field.as_deref()))]
+    Parse {
+        line: Option<u64>,
+        field: Option<String>,
+        kind: ParseErrorKind,
+    },
+    /// The fields of a record failed validation.
+    #[error("{}{}", position(Some(*line), None), join(fields))]
+    Validation { line: u64, fields: Vec<FieldError> },
+    /// A line is not valid UTF-8.
+    #[error("{}Invalid UTF-8 after {} bytes", position(*line, None), source.valid_up_to())]
+    Encoding {
+        line: Option<u64>,
+        #[source]
+        source: Utf8Error,
+    },
+    /// A line contains a byte outside of ASCII, the column counts bytes from 1.
+    #[error("{}Non-ASCII byte 0x{byte:02X} at column {column}", position(*line, None))]
+    NonAscii {
+        line: Option<u64>,
+        column: usize,
+        byte: u8,
+    },
+    /// A character cannot be written in the character set of the output.
+    #[error("{}Character {character:?} cannot be encoded in {charset:?}", position(*line, None))]
+    Unmappable {
+        line: Option<u64>,
+        character: char,
+        charset: Charset,
+    },
+    /// A count or total does not match the value it is checked against.
+    #[error("{name} does not reconcile, expected {expected} but found {actual}")]
+    Reconciliation {
+        name: String,
+        expected: String,
+        actual: String,
+    },
+    /// Records expected to be sorted by a key are out of order.
+    #[error("Key {key} is out of sequence after {previous}")]
+    Sequence { key: String, previous: String },
+    /// A record has the same key as an earlier record.
+    #[error("Duplicate key {key}")]
+    Duplicate { key: String },
+    /// An error reading one of several files, with the path of the file.
+    #[cfg(feature = "std")]
+    #[error("{}: {source}", path.display())]
+    InFile {
+        path: PathBuf,
+        #[source]
+        source: Box<Error>,
+    },
+    /// The file was refused by an intake guard.
+    #[error(transparent)]
+    Intake(#[from] IntakeError),
+    /// The operation was cancelled through a cancellation token.
+    #[error("Operation cancelled")]
+    Cancelled,
+    /// Reading was aborted as more than the maximum number of errors were found.
+    #[error("Aborted after {errors} errors at line {line}")]
+    TooManyErrors { errors: u64, line: u64 },
+}
+
+fn position(line: Option<u64>, field: Option<&str>) -> String {
+    match (line, field) {
+        (Some(line), Some(field)) => format!("Line {}, field {}: ", line, field),
+        (Some(line), None) => format!("Line {}: ", line),
+        (None, Some(field)) => format!("Field {}: ", field),
+        (None, None) => String::new(),
     }
 }
 
-impl From<ParseError> for Error {
-    fn from(kind: ParseError) -> Self {
-        Error::ParserError(kind)
+fn join(fields: &[FieldError]) -> String {
+    fields
+        .iter()
+        .map(|f| f.to_string())
+        .collect::<Vec<String>>()
+        .join("; ")
+}
+
+impl From<ParseErrorKind> for Error {
+    fn from(kind: ParseErrorKind) -> Self {
+        Error::Parse {
+            line: None,
+            field: None,
+            kind,
+        }
     }
 }
 
-impl StdError for Error {
-    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+impl Error {
+    /// The line the error occured on, if known.
+    pub fn line(&self) -> Option<u64> {
         match self {
-            Error::IOError(ref e) => Some(e),
-            Error::ParserError(ref _e) => None,
+            Error::Parse { line, .. }
+            | Error::Encoding { line, .. }
+            | Error::NonAscii { line, .. }
+            | Error::Unmappable { line, .. } => *line,
+            Error::Validation { line, .. } | Error::TooManyErrors { line, .. } => Some(*line),
+            #[cfg(feature = "std")]
+            Error::InFile { source, .. } => source.line(),
+            _ => None,
         }
     }
 
-    #[allow(deprecated)]
-    fn cause(&self) -> Option<&(dyn StdError)> {
-        self.source()
+    /// Sets the line of a parse or encoding error that does not have one yet.
+    pub(crate) fn at_line(mut self, at: u64) -> Self {
+        if let Error::Parse { line, .. }
+        | Error::Encoding { line, .. }
+        | Error::NonAscii { line, .. } = &mut self
+        {
+            line.get_or_insert(at);
+        }
+        self
+    }
+}
+
+/// Everything wrong with a single record, either it could not be parsed or some of its fields
+/// failed validation.
+#[derive(Debug)]
+pub struct RecordError {
+    /// The line the record was read from.
+    pub line: u64,
+    /// The error parsing the line, if it could not be parsed.
+    pub error: Option<Error>,
+    /// The fields that failed validation.
+    pub fields: Vec<FieldError>,
+}
+
+impl Display for RecordError {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self.error {
+            Some(ref e) if e.line().is_some() => e.fmt(f),
+            Some(ref e) => write!(f, "{}{}", position(Some(self.line), None), e),
+            None => write!(
+                f,
+                "{}{}",
+                position(Some(self.line), None),
+                join(&self.fields)
+            ),
+        }
+    }
+}
+
+impl core::error::Error for RecordError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        self.error
+            .as_ref()
+            .map(|e| e as &(dyn core::error::Error + 'static))
+    }
+}
+
+impl From<RecordError> for Error {
+    fn from(e: RecordError) -> Self {
+        match e.error {
+            Some(error) => error.at_line(e.line),
+            None => Error::Validation {
+                line: e.line,
+                fields: e.fields,
+            },
+        }
     }
 }
 
 #[cfg(test)]
-#[allow(deprecated)]
+#[allow(clippy::redundant_pattern_matching, clippy::io_other_error)]
 mod test {
     use super::*;
+    use std::error::Error as StdError;
 
     #[test]
     fn check_buffer_insufficient() {
-        let error = Error::from(ParseError::ImsufficentBuffer(10, Some(5)));
+        let error = Error::from(ParseErrorKind::InsufficientBuffer {
+            required: 10,
+            available: Some(5),
+        });
 
-        assert!(matches!(error, Error::ParserError(_)));
+        assert!(matches!(error, Error::Parse { line: None, .. }));
         assert_eq!(
             error.to_string(),
             String::from("Insufficient buffer size, required 10 only 5 available")
         );
         assert!(matches!(error.source(), None));
-        assert!(matches!(error.cause(), None));
     }
 
     #[test]
     fn check_buffer_undefined() {
-        let error = Error::from(ParseError::ImsufficentBuffer(10, None));
+        let error = Error::from(ParseErrorKind::InsufficientBuffer {
+            required: 10,
+            available: None,
+        });
 
         assert_eq!(
             error.to_string(),
@@ -97,14 +290,120 @@ mod test {
         );
     }
 
+    #[test]
+    fn check_parse_position() {
+        let error = Error::Parse {
+            line: None,
+            field: Some(String::from("amount")),
+            kind: ParseErrorKind::InsufficientBuffer {
+                required: 10,
+                available: Some(5),
+            },
+        };
+
+        assert_eq!(
+            error.to_string(),
+            "Field amount: Insufficient buffer size, required 10 only 5 available"
+        );
+        let error = error.at_line(3).at_line(4);
+        assert_eq!(error.line(), Some(3));
+        assert_eq!(
+            error.to_string(),
+            "Line 3, field amount: Insufficient buffer size, required 10 only 5 available"
+        );
+    }
+
+    #[test]
+    fn check_intake_duplicate() {
+        let error = Error::from(IntakeError::DuplicateContent(String::from("content:1")));
+
+        assert!(matches!(error, Error::Intake(_)));
+        assert_eq!(
+            error.to_string(),
+            String::from("File content already processed (content:1)")
+        );
+        assert!(error.source().is_none());
+    }
+
     #[test]
     fn check_io_error() {
         let io_error = io::Error::new(io::ErrorKind::Other, "test");
         let error = Error::from(io_error);
 
-        assert!(matches!(error, Error::IOError(_)));
+        assert!(matches!(error, Error::Io(_)));
         assert_eq!(error.to_string(), String::from("test"));
         assert!(matches!(error.source(), Some(_)));
-        assert!(matches!(error.cause(), Some(_)));
+    }
+
+    #[test]
+    #[allow(invalid_from_utf8)]
+    fn check_encoding_error() {
+        let error = Error::Encoding {
+            line: Some(2),
+            source: std::str::from_utf8(b"AB\xFFCD").unwrap_err(),
+        };
+
+        assert_eq!(error.to_string(), "Line 2: Invalid UTF-8 after 2 bytes");
+        assert!(error.source().is_some());
+    }
+
+    #[test]
+    fn check_reconciliation_error() {
+        let error = Error::Reconciliation {
+            name: String::from("Record count"),
+            expected: String::from("10"),
+            actual: String::from("9"),
+        };
+
+        assert_eq!(
+            error.to_string(),
+            "Record count does not reconcile, expected 10 but found 9"
+        );
+    }
+
+    #[test]
+    fn check_record_error() {
+        let error = RecordError {
+            line: 3,
+            error: None,
+            fields: vec![
+                FieldError {
+                    field: String::from("amount"),
+                    message: String::from("not numeric"),
+                },
+                FieldError {
+                    field: String::from("name"),
+                    message: String::from("required"),
+                },
+            ],
+        };
+
+        assert_eq!(
+            error.to_string(),
+            String::from("Line 3: amount: not numeric; name: required")
+        );
+        assert!(error.source().is_none());
+        let error = Error::from(error);
+        assert!(matches!(error, Error::Validation { line: 3, .. }));
+        assert_eq!(
+            error.to_string(),
+            String::from("Line 3: amount: not numeric; name: required")
+        );
+
+        let error = RecordError {
+            line: 4,
+            error: Some(Error::from(ParseErrorKind::InsufficientBuffer {
+                required: 10,
+                available: Some(5),
+            })),
+            fields: Vec::new(),
+        };
+
+        assert_eq!(
+            error.to_string(),
+            String::from("Line 4: Insufficient buffer size, required 10 only 5 available")
+        );
+        assert!(error.source().is_some());
+        assert_eq!(Error::from(error).line(), Some(4));
     }
 }
@@ -0,0 +1,179 @@
+use crate::{
+    error::{Error, FieldError},
+    flat::fixed::{Record, ResultRecord},
+};
+use alloc::vec;
+
+/// Composable adapters over records numbered with their line, as yielded by
+/// [`Reader::enumerated_records`](super::read::Reader::enumerated_records), so that a chain of
+/// transformations reads as a sequence of steps. Errors are passed through unchanged and keep
+/// their line.
+pub trait RecordAdapters: Iterator<Item = (u64, ResultRecord)> + Sized {
+    /// Transforms every record.
+    fn map_records<F>(self, f: F) -> MapRecords<Self, F>
+    where
+        F: FnMut(Record) -> Record,
+    {
+        MapRecords { records: self, f }
+    }
+
+    /// Keeps the records matching the predicate.
+    fn filter_records<P>(self, predicate: P) -> FilterRecords<Self, P>
+    where
+        P: FnMut(&Record) -> bool,
+    {
+        FilterRecords {
+            records: self,
+            predicate,
+        }
+    }
+
+    /// Checks every record, a failing record is replaced by an `Error::Validation` on its line.
+    fn validate<V>(self, check: V) -> Validate<Self, V>
+    where
+        V: FnMut(&Record) -> Result<(), FieldError>,
+    {
+        Validate {
+            records: self,
+            check,
+        }
+    }
+
+    /// Drops the line numbers, yielding the records alone.
+    fn records(self) -> Records<Self> {
+        Records { records: self }
+    }
+}
+
+impl<I> RecordAdapters for I where I: Iterator<Item = (u64, ResultRecord)> {}
+
+pub struct MapRecords<I, F> {
+    records: I,
+    f: F,
+}
+
+impl<I, F> Iterator for MapRecords<I, F>
+where
+    I: Iterator<Item = (u64, ResultRecord)>,
+    F: FnMut(Record) -> Record,
+{
+    type Item = (u64, ResultRecord);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (line, result) = self.records.next()?;
+        Some((line, result.map(&mut self.f)))
+    }
+}
+
+pub struct FilterRecords<I, P> {
+    records: I,
+    predicate: P,
+}
+
+impl<I, P> Iterator for FilterRecords<I, P>
+where
+    I: Iterator<Item = (u64, ResultRecord)>,
+    P: FnMut(&Record) -> bool,
+{
+    type Item = (u64, ResultRecord);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (line, result) = self.records.next()?;
+            match &result {
+                Ok(record) if !(self.predicate)(record) => continue,
+                _ => return Some((line, result)),
+            }
+        }
+    }
+}
+
+pub struct Validate<I, V> {
+    records: I,
+    check: V,
+}
+
+impl<I, V> Iterator for Validate<I, V>
+where
+    I: Iterator<Item = (u64, ResultRecord)>,
+    V: FnMut(&Record) -> Result<(), FieldError>,
+{
+    type Item = (u64, ResultRecord);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (line, result) = self.records.next()?;
+        let result = result.and_then(|record| match (self.check)(&record) {
+            Ok(()) => Ok(record),
+            Err(e) => Err(Error::Validation {
+                line,
+                fields: vec![e],
+            }),
+        });
+        Some((line, result))
+    }
+}
+
+pub struct Records<I> {
+    records: I,
+}
+
+impl<I> Iterator for Records<I>
+where
+    I: Iterator<Item = (u64, ResultRecord)>,
+{
+    type Item = ResultRecord;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.records.next().map(|(_, result)| result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        builder::{Buildable, Builder},
+        flat::fixed::{read::Reader, Parser},
+    };
+
+    #[test]
+    fn chain_adapters() {
+        let parser = Parser::builder()
+            .field("code")
+            .width(2)
+            .append()
+            .field("amount")
+            .width(3)
+            .append()
+            .build();
+        let mut rdr = Reader::from_string("A 001\nB 002\nsho\nA 0X3\nA 004", &parser);
+
+        let results = rdr
+            .enumerated_records()
+            .filter_records(|r| r["code"] == "A")
+            .map_records(|mut r| {
+                r.insert(String::from("source"), String::from("feed"));
+                r
+            })
+            .validate(|r| match r["amount"].parse::<u32>() {
+                Ok(_) => Ok(()),
+                Err(_) => Err(FieldError {
+                    field: String::from("amount"),
+                    message: String::from("is not numeric"),
+                }),
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            results.iter().map(|(line, _)| *line).collect::<Vec<u64>>(),
+            vec![1, 3, 4, 5]
+        );
+        assert_eq!(results[0].1.as_ref().unwrap()["source"], "feed");
+        assert_eq!(results[1].1.as_ref().unwrap_err().line(), Some(3));
+        assert_eq!(
+            results[2].1.as_ref().unwrap_err().to_string(),
+            "Line 4: amount: is not numeric"
+        );
+        assert_eq!(rdr.enumerated_records().records().count(), 0);
+    }
+}
@@ -1,9 +1,20 @@
 use crate::{
     builder::{Buildable, Builder},
-    flat::fixed::{Field, Parser},
-    utilities::string::Align,
+    flat::fixed::{
+        bytes::{Endian, FieldKind},
+        check::CheckDigit,
+        codes::CodeTable,
+        flags::Flags,
+        numeric::Sign,
+        Field, Null, Parser, Sequence,
+    },
+    utilities::string::{Align, Case, StripMode, Trim, TruncateSide, WidthMode},
+};
+use alloc::vec::Vec;
+use core::{
+    convert::TryInto,
+    ops::{Range, RangeInclusive},
 };
-use std::{convert::TryInto, ops::Range};
 
 impl<'a> Buildable for Parser<'a> {
     type Builder = ParserBuilder<'a>;
@@ -13,11 +24,24 @@ impl<'a> Buildable for Parser<'a> {
     }
 }
 
+/// How the start positions given to [`FieldBuilder::at`] are numbered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Columns {
+    /// The first column is 0, as in Rust ranges.
+    #[default]
+    ZeroBased,
+    /// The first column is 1, as in most specification documents.
+    OneBased,
+}
+
 #[derive(Debug)]
 pub struct ParserBuilder<'a> {
     fields: Vec<Field<'a>>,
     align: Align,
     padding: char,
+    width_mode: WidthMode,
+    record_width: Option<usize>,
+    columns: Columns,
 }
 
 #[allow(dead_code)]
@@ -27,6 +51,9 @@ impl<'a> ParserBuilder<'a> {
             fields: Vec::new(),
             align: Align::Left,
             padding: ' ',
+            width_mode: WidthMode::Chars,
+            record_width: None,
+            columns: Columns::ZeroBased,
         }
     }
 
@@ -43,7 +70,11 @@ impl<'a> ParserBuilder<'a> {
     pub fn default_align<T: TryInto<Align>>(mut self, align: T) -> Self {
         match align.try_into() {
             Ok(align) => self.align = align,
-            Err(_) => eprintln!("Unable to parse argument as Align"),
+            Err(_) => {
+                // Without `std` an invalid alignment is ignored silently.
+                #[cfg(feature = "std")]
+                eprintln!("Unable to parse argument as Align");
+            }
         }
         self
     }
@@ -53,12 +84,49 @@ impl<'a> ParserBuilder<'a> {
         self
     }
 
+    /// Sets how the widths of all the fields are counted.
+    pub fn width_mode(mut self, width_mode: WidthMode) -> Self {
+        self.width_mode = width_mode;
+        self
+    }
+
+    /// Declares the total width of a record, the fields are followed by a filler with the
+    /// default alignment and padding up to that width. Parsing then accepts lines missing some
+    /// of the filler, as when trailing spaces were trimmed, and fails lines longer than the
+    /// record width.
+    ///
+    /// Building panics if the fields are wider than the record.
+    pub fn record_width(mut self, width: usize) -> Self {
+        self.record_width = Some(width);
+        self
+    }
+
+    /// Sets how the start positions of anchored fields are numbered, so they are declared as
+    /// in the specification.
+    pub fn columns(mut self, columns: Columns) -> Self {
+        self.columns = columns;
+        self
+    }
+
+    /// The total width of the fields added so far.
+    pub fn width(&self) -> usize {
+        self.fields.iter().map(|f| f.width).sum()
+    }
+
     pub fn field(self, name: &'a str) -> FieldBuilder<'a> {
         let align = self.align;
         let padding = self.padding;
         FieldBuilder::new(self, Some(name), align, padding)
     }
 
+    /// Appends the fields of a reusable layout, such as an address block shared by several
+    /// layouts, including its filler. Field names are kept, prefixing them needs owned names,
+    /// see [`Schema::include`](crate::schema::Schema::include).
+    pub fn include(mut self, layout: &Parser<'a>) -> Self {
+        self.fields.extend_from_slice(layout.fields());
+        self
+    }
+
     pub fn spacer(self, range: Range<usize>) -> Self {
         let align = self.align;
         let padding = self.padding;
@@ -66,20 +134,37 @@ impl<'a> ParserBuilder<'a> {
     }
 }
 
+impl<'a> Default for ParserBuilder<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<'a> Builder for ParserBuilder<'a> {
     type Target = Parser<'a>;
 
     fn build(&mut self) -> Self::Target {
-        let mut width = 0;
-        Parser {
-            fields: self
-                .fields
-                .iter()
-                .copied()
-                .inspect(|f| width += f.width)
-                .collect(),
-            width: width as usize,
-        }
+        let mut fields = self
+            .fields
+            .iter()
+            .map(|f| f.with_width_mode(self.width_mode))
+            .collect::<Vec<_>>();
+        let filler = self.record_width.map(|record_width| {
+            let width = fields.iter().map(|f| f.width).sum::<usize>();
+            let filler = record_width
+                .checked_sub(width)
+                .expect("The fields are wider than the record width");
+            if filler > 0 {
+                fields.push(
+                    Field::new(None, filler, self.align, self.padding)
+                        .with_width_mode(self.width_mode),
+                );
+            }
+            filler
+        });
+        let mut parser = Parser::new(fields);
+        parser.filler = filler;
+        parser
     }
 }
 
@@ -87,8 +172,22 @@ pub struct FieldBuilder<'a> {
     parser: ParserBuilder<'a>,
     name: Option<&'a str>,
     width: Option<usize>,
+    start: Option<usize>,
     align: Align,
     padding: char,
+    default_value: Option<&'a str>,
+    null: Option<Null<'a>>,
+    fill: Option<&'a str>,
+    strip: StripMode,
+    trim: Trim,
+    case: Option<Case>,
+    truncate: TruncateSide,
+    numeric: Option<Sign>,
+    codes: Option<&'a CodeTable>,
+    description_field: Option<&'a str>,
+    sequence: Option<Sequence>,
+    check_digit: Option<&'a dyn CheckDigit>,
+    kind: FieldKind<'a>,
 }
 
 #[allow(dead_code)]
@@ -98,8 +197,22 @@ impl<'a> FieldBuilder<'a> {
             parser,
             name,
             width: None,
+            start: None,
             align,
             padding,
+            default_value: None,
+            null: None,
+            fill: None,
+            strip: StripMode::AlignedSide,
+            trim: Trim::None,
+            case: None,
+            truncate: TruncateSide::Left,
+            numeric: None,
+            codes: None,
+            description_field: None,
+            sequence: None,
+            check_digit: None,
+            kind: FieldKind::Text,
         }
     }
 
@@ -113,10 +226,45 @@ impl<'a> FieldBuilder<'a> {
         self
     }
 
+    /// Declares the field at columns `first` to `last` inclusive, numbered from 1, as written
+    /// in most specifications, e.g. `12..=20` for a field of width 9 at 0-based position 11.
+    /// The field is anchored as with [`at`](Self::at).
+    pub fn range_1based(mut self, range: RangeInclusive<usize>) -> Self {
+        let (first, last) = range.into_inner();
+        assert!(
+            first >= 1 && last + 1 >= first,
+            "Invalid 1-based range {}..={}",
+            first,
+            last
+        );
+        self.start = Some(first - 1);
+        self.width = Some(last + 1 - first);
+        self
+    }
+
+    /// Anchors the field at an absolute start position, numbered as set by
+    /// [`ParserBuilder::columns`], 0-based by default, as given by specifications listing start
+    /// and length. The gap from the end of the previous field is filled with a spacer.
+    ///
+    /// Adding the field panics if it starts before the end of the previous field.
+    pub fn at(mut self, start: usize) -> Self {
+        self.start = Some(match self.parser.columns {
+            Columns::ZeroBased => start,
+            Columns::OneBased => start
+                .checked_sub(1)
+                .expect("Column 0 does not exist in 1-based columns"),
+        });
+        self
+    }
+
     pub fn align<T: TryInto<Align>>(mut self, align: T) -> Self {
         match align.try_into() {
             Ok(align) => self.align = align,
-            Err(_) => eprintln!("Unable to parse argument as Align"),
+            Err(_) => {
+                // Without `std` an invalid alignment is ignored silently.
+                #[cfg(feature = "std")]
+                eprintln!("Unable to parse argument as Align");
+            }
         }
         self
     }
@@ -126,33 +274,213 @@ impl<'a> FieldBuilder<'a> {
         self
     }
 
+    /// Pads with a repeated string instead of the padding character, e.g. `**`.
+    pub fn fill(mut self, fill: &'a str) -> Self {
+        self.fill = Some(fill);
+        self
+    }
+
+    /// Sets the value used when the field is entirely padding on parse, or missing from the
+    /// record on format.
+    pub fn default_value(mut self, value: &'a str) -> Self {
+        self.default_value = Some(value);
+        self
+    }
+
+    /// Sets when the field is null, see [`Null`].
+    pub fn null_when(mut self, null: Null<'a>) -> Self {
+        self.null = Some(null);
+        self
+    }
+
+    /// Sets which sides of the value the padding is stripped from on parse.
+    pub fn strip(mut self, strip: StripMode) -> Self {
+        self.strip = strip;
+        self
+    }
+
+    /// Sets the whitespace trimmed from the value on parse, after the padding is stripped.
+    pub fn trim(mut self, trim: Trim) -> Self {
+        self.trim = trim;
+        self
+    }
+
+    /// Sets which characters are kept when a value is too long on format, see
+    /// [`TruncateSide`].
+    pub fn truncate_side(mut self, truncate: TruncateSide) -> Self {
+        self.truncate = truncate;
+        self
+    }
+
+    /// Makes the field numeric with the sign written in the given position, see
+    /// [`Field::with_numeric`].
+    pub fn numeric(mut self, sign: Sign) -> Self {
+        self.numeric = Some(sign);
+        self
+    }
+
+    /// Restricts the values of the field to the codes of the table, see
+    /// [`Field::with_codes`].
+    pub fn codes(mut self, codes: &'a CodeTable) -> Self {
+        self.codes = Some(codes);
+        self
+    }
+
+    /// Adds the description of the code to parsed records under the given name.
+    pub fn description_field(mut self, name: &'a str) -> Self {
+        self.description_field = Some(name);
+        self
+    }
+
+    /// Makes the field a record sequence number of the given width, aligned right and padded
+    /// with zeros. The writer fills it with numbers from `start` increasing by `step`.
+    pub fn auto_sequence(mut self, start: u64, step: u64, width: usize) -> Self {
+        self.sequence = Some(Sequence { start, step });
+        self.width = Some(width);
+        self.align = Align::Right;
+        self.padding = '0';
+        self
+    }
+
+    /// Follows the value with a check digit, see [`Field::with_check_digit`].
+    pub fn check_digit(mut self, check_digit: &'a dyn CheckDigit) -> Self {
+        self.check_digit = Some(check_digit);
+        self
+    }
+
+    /// Makes the field a binary integer of the given number of bytes, such as a COBOL `COMP`
+    /// field, decoded by the bytes pipeline. Sets the width to the number of bytes.
+    pub fn binary_int(mut self, bytes: usize, endian: Endian, signed: bool) -> Self {
+        self.kind = FieldKind::BinaryInt {
+            bytes,
+            endian,
+            signed,
+        };
+        self.width = Some(bytes);
+        self
+    }
+
+    /// Makes the field a set of named boolean flags, see [`Flags`]. The width is one column per
+    /// character in lines, and one byte per 8 flags in records of raw bytes.
+    pub fn flags(mut self, flags: &'a Flags) -> Self {
+        self.kind = FieldKind::Flags(flags);
+        self
+    }
+
+    /// Converts the value to upper case on parse.
+    pub fn uppercase(mut self) -> Self {
+        self.case = Some(Case::Upper);
+        self
+    }
+
+    /// Converts the value to lower case on parse.
+    pub fn lowercase(mut self) -> Self {
+        self.case = Some(Case::Lower);
+        self
+    }
+
     pub fn append(mut self) -> ParserBuilder<'a> {
         let field = self.build();
-        self.parser.append(field)
+        let index = self.parser.fields.len();
+        let index = self.anchor(index);
+        self.parser.insert(index, field)
     }
 
     pub fn insert(mut self, index: usize) -> ParserBuilder<'a> {
         let field = self.build();
+        let index = self.anchor(index);
         self.parser.insert(index, field)
     }
+
+    /// Inserts a spacer before `index` up to the start of an anchored field, returning the
+    /// index of the field.
+    fn anchor(&mut self, index: usize) -> usize {
+        let start = match self.start {
+            Some(start) => start,
+            None => return index,
+        };
+        let position = self.parser.fields[..index]
+            .iter()
+            .map(|f| f.width)
+            .sum::<usize>();
+        if start < position {
+            panic!(
+                "The field at {} overlaps the previous field, ending at {}",
+                start, position
+            );
+        }
+        if start > position {
+            let spacer = Field::new(
+                None,
+                start - position,
+                self.parser.align,
+                self.parser.padding,
+            );
+            self.parser.fields.insert(index, spacer);
+            return index + 1;
+        }
+        index
+    }
 }
 
 impl<'a> Builder for FieldBuilder<'a> {
     type Target = Field<'a>;
 
     fn build(&mut self) -> Self::Target {
-        Field::new(
+        let field = Field::new(
             self.name,
             self.width.expect("Width must be specified"),
             self.align,
             self.padding,
         )
+        .with_strip(self.strip)
+        .with_trim(self.trim)
+        .with_truncate_side(self.truncate)
+        .with_kind(self.kind);
+        let field = match self.fill {
+            Some(fill) => field.with_fill(fill),
+            None => field,
+        };
+        let field = match self.numeric {
+            Some(sign) => field.with_numeric(sign),
+            None => field,
+        };
+        let field = match self.sequence {
+            Some(sequence) => field.with_sequence(sequence),
+            None => field,
+        };
+        let field = match self.check_digit {
+            Some(check_digit) => field.with_check_digit(check_digit),
+            None => field,
+        };
+        let field = match self.codes {
+            Some(codes) => field.with_codes(codes),
+            None => field,
+        };
+        let field = match self.description_field {
+            Some(name) => field.with_description_field(name),
+            None => field,
+        };
+        let field = match self.case {
+            Some(case) => field.with_case(case),
+            None => field,
+        };
+        let field = match self.default_value {
+            Some(value) => field.with_default_value(value),
+            None => field,
+        };
+        match self.null {
+            Some(null) => field.with_null_when(null),
+            None => field,
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error::{Error, ParseErrorKind};
+    use std::collections::HashMap;
 
     #[test]
     fn check_builder() {
@@ -169,6 +497,18 @@ mod tests {
         assert_eq!(builder.padding, 'X');
     }
 
+    #[test]
+    fn check_builder_width_mode() {
+        let parser = Parser::builder()
+            .field("first")
+            .width(4)
+            .append()
+            .width_mode(WidthMode::DisplayWidth)
+            .build();
+
+        assert_eq!(parser.fields()[0].width_mode(), WidthMode::DisplayWidth);
+    }
+
     #[test]
     fn check_builder_align() {
         let builder = Parser::builder().default_align(Align::Right);
@@ -293,6 +633,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn check_field_default_value() {
+        let parser = Parser::builder()
+            .field("first")
+            .width(4)
+            .align("right")
+            .padding('0')
+            .default_value("0")
+            .append()
+            .build();
+
+        assert_eq!(
+            parser.fields[0],
+            Field::new(Some("first"), 4, Align::Right, '0').with_default_value("0")
+        );
+        assert_eq!(
+            parser.parse("0000").unwrap().get("first"),
+            Some(&String::from("0"))
+        );
+        assert_eq!(parser.format(&HashMap::new()), "0000");
+    }
+
     #[test]
     fn check_field_two() {
         let parser = Parser::builder()
@@ -378,4 +740,127 @@ mod tests {
         assert_eq!(parser.fields.len(), 1);
         assert_eq!(parser.fields[0], Field::new(None, 10, Align::Left, ' '));
     }
+
+    #[test]
+    fn check_record_width() {
+        let parser = Parser::builder()
+            .record_width(10)
+            .field("id")
+            .width(3)
+            .append()
+            .field("name")
+            .width(4)
+            .append()
+            .build();
+        let mut record = HashMap::new();
+        record.insert(String::from("id"), String::from("001"));
+
+        assert_eq!(parser.width(), 10);
+        assert_eq!(parser.fields[2], Field::new(None, 3, Align::Left, ' '));
+        assert_eq!(parser.format(&record), "001       ");
+        assert_eq!(parser.parse("001Ann ").unwrap()["name"], "Ann");
+        assert_eq!(parser.parse("001Anne   ").unwrap()["name"], "Anne");
+        assert!(parser.parse("001A").is_err());
+        assert!(matches!(
+            parser.parse("001Anne    X"),
+            Err(Error::Parse {
+                kind: ParseErrorKind::RecordLength {
+                    expected: 10,
+                    actual: 12
+                },
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "The fields are wider than the record width")]
+    fn check_record_width_too_narrow() {
+        Parser::builder()
+            .record_width(2)
+            .field("first")
+            .width(3)
+            .append()
+            .build();
+    }
+
+    #[test]
+    fn check_field_at() {
+        let parser = Parser::builder()
+            .field("id")
+            .at(2)
+            .width(3)
+            .append()
+            .field("name")
+            .at(5)
+            .width(4)
+            .append()
+            .field("code")
+            .at(12)
+            .width(2)
+            .append()
+            .build();
+
+        assert_eq!(parser.width(), 14);
+        assert_eq!(parser.fields().len(), 5);
+        let record = parser.parse("  001Ann    XY").unwrap();
+        assert_eq!(record["id"], "001");
+        assert_eq!(record["name"], "Ann");
+        assert_eq!(record["code"], "XY");
+    }
+
+    #[test]
+    #[should_panic(expected = "The field at 2 overlaps the previous field, ending at 3")]
+    fn check_field_at_overlap() {
+        Parser::builder()
+            .field("first")
+            .width(3)
+            .append()
+            .field("second")
+            .at(2)
+            .width(3)
+            .append();
+    }
+
+    #[test]
+    fn check_one_based_columns() {
+        let parser = Parser::builder()
+            .field("id")
+            .range_1based(3..=5)
+            .append()
+            .columns(Columns::OneBased)
+            .field("name")
+            .at(7)
+            .width(3)
+            .append()
+            .build();
+
+        assert_eq!(parser.width(), 9);
+        let record = parser.parse("  001 Ann").unwrap();
+        assert_eq!(record["id"], "001");
+        assert_eq!(record["name"], "Ann");
+    }
+
+    #[test]
+    fn check_include() {
+        let address = Parser::builder()
+            .field("street")
+            .width(4)
+            .append()
+            .field("city")
+            .width(3)
+            .append()
+            .build();
+        let parser = Parser::builder()
+            .field("id")
+            .width(2)
+            .append()
+            .include(&address)
+            .build();
+
+        assert_eq!(parser.width(), 9);
+        let record = parser.parse("01MainRio").unwrap();
+        assert_eq!(record["street"], "Main");
+        assert_eq!(record["city"], "Rio");
+    }
 }
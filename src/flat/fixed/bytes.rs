@@ -0,0 +1,434 @@
+use crate::{
+    error::{Error, ParseErrorKind},
+    flat::fixed::{flags::Flags, Field, Parser, Record, ResultRecord},
+    utilities::string::Align,
+    HashMap,
+};
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::convert::TryFrom;
+#[cfg(feature = "std")]
+use {
+    crate::flat::terminator::Terminator,
+    std::io::{BufReader, Read},
+};
+
+/// A record of raw field values, for binary or non UTF-8 data.
+pub type BytesRecord = HashMap<String, Vec<u8>>;
+
+/// The order of the bytes of a binary integer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endian {
+    /// Most significant byte first, as on mainframes.
+    Big,
+    /// Least significant byte first.
+    Little,
+}
+
+impl TryFrom<&str> for Endian {
+    type Error = String;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        match s.to_lowercase().trim() {
+            "big" => Ok(Endian::Big),
+            "little" => Ok(Endian::Little),
+            _ => Err(String::from("Unknown endian argument")),
+        }
+    }
+}
+
+/// How a field is decoded. Flags apply to lines as well, the other kinds only to the bytes
+/// pipeline, see [`Parser::decode_bytes`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FieldKind<'a> {
+    /// UTF-8 text, handled like the field of a line.
+    #[default]
+    Text,
+    /// A binary integer of up to 8 bytes, such as a COBOL `COMP` field of 2, 4 or 8 bytes
+    /// big endian, decoded to its decimal value.
+    BinaryInt {
+        bytes: usize,
+        endian: Endian,
+        signed: bool,
+    },
+    /// Named boolean flags, one per bit. In lines the flags are one per character.
+    Flags(&'a Flags),
+}
+
+#[allow(dead_code)]
+impl<'a> Parser<'a> {
+    /// The fields with their start and end offsets in a record of raw bytes, one byte per
+    /// column whatever the width mode.
+    fn byte_ranges(&self) -> impl Iterator<Item = (&Field<'a>, usize, usize)> + '_ {
+        let mut offset = 0;
+        self.fields.iter().map(move |field| {
+            let start = offset;
+            offset += field.width;
+            (field, start, offset)
+        })
+    }
+
+    /// Parses a record of raw bytes, one byte per column, into the raw values of the named
+    /// fields. Values are not decoded nor stripped of their padding, as the bytes of a binary
+    /// field may equal the padding. As when parsing lines, only the first of the fields with
+    /// the same name is kept.
+    pub fn parse_bytes(&self, bytes: &[u8]) -> Result<BytesRecord, Error> {
+        let mut record = BytesRecord::with_capacity(self.fields.len());
+        self.parse_bytes_with(bytes, |name, value| {
+            if !record.contains_key(name) {
+                record.insert(String::from(name), value.to_vec());
+            }
+        })?;
+        Ok(record)
+    }
+
+    /// Parses a record of raw bytes, calling `visit` with the name and value of each named
+    /// field in layout order, borrowing the values from the record.
+    pub fn parse_bytes_with<F>(&self, bytes: &[u8], mut visit: F) -> Result<(), Error>
+    where
+        F: FnMut(&str, &[u8]),
+    {
+        if bytes.len() < self.width {
+            return Err(Error::from(ParseErrorKind::InsufficientBuffer {
+                required: self.width,
+                available: Some(bytes.len()),
+            }));
+        }
+        for (field, start, end) in self.byte_ranges() {
+            if let Some(name) = field.name {
+                visit(name, &bytes[start..end]);
+            }
+        }
+        Ok(())
+    }
+
+    /// Parses a record of raw bytes into a record of text values, decoding each field by its
+    /// [`FieldKind`]. Text fields are stripped, checked and converted as the fields of a line.
+    pub fn decode_bytes(&self, bytes: &[u8]) -> ResultRecord {
+        if bytes.len() < self.width {
+            return Err(Error::from(ParseErrorKind::InsufficientBuffer {
+                required: self.width,
+                available: Some(bytes.len()),
+            }));
+        }
+        let mut record = Record::with_capacity(self.fields.len());
+        for (field, start, end) in self.byte_ranges() {
+            let name = match field.name {
+                Some(name) if !record.contains_key(name) => name,
+                _ => continue,
+            };
+            let raw = &bytes[start..end];
+            let value = match field.kind {
+                FieldKind::Text => {
+                    let text = core::str::from_utf8(raw)
+                        .map_err(|source| Error::Encoding { line: None, source })?;
+                    match field.value(text) {
+                        Some(value) => value.into_owned(),
+                        None => continue,
+                    }
+                }
+                FieldKind::BinaryInt { endian, signed, .. } => {
+                    decode_int(field, raw, endian, signed)?
+                }
+                FieldKind::Flags(flags) => {
+                    flags.expand_bits(raw, |flag, value| {
+                        record.insert(flag.to_string(), value.to_string());
+                    });
+                    continue;
+                }
+            };
+            record.insert(name.to_string(), value);
+        }
+        Ok(record)
+    }
+
+    /// Formats a record of text values as raw bytes, encoding each field by its
+    /// [`FieldKind`]. Text fields are formatted as the fields of a line and should be ASCII,
+    /// as the layout counts one byte per column. A binary field missing from the record is
+    /// zero.
+    pub fn encode_bytes(&self, record: &Record) -> Result<Vec<u8>, Error> {
+        let mut buf = Vec::with_capacity(self.width);
+        let mut text = String::new();
+        for field in &self.fields {
+            let (endian, signed) = match field.kind {
+                FieldKind::Text => {
+                    text.clear();
+                    field.format_into(record, &mut text);
+                    buf.extend_from_slice(text.as_bytes());
+                    continue;
+                }
+                FieldKind::BinaryInt { endian, signed, .. } => (endian, signed),
+                FieldKind::Flags(flags) => {
+                    buf.extend(flags.combine_bits(record, field.width));
+                    continue;
+                }
+            };
+            let value = field.name.and_then(|name| record.get(name));
+            let mut bytes = encode_int(field, value.map_or("0", |v| v.as_str()), signed)?;
+            if endian == Endian::Little {
+                bytes.reverse();
+            }
+            buf.extend_from_slice(&bytes);
+        }
+        Ok(buf)
+    }
+
+    /// Formats a record of raw values, one byte per column. Values are padded with the
+    /// padding of their field when it is ASCII, and with spaces otherwise, and truncated to
+    /// the width of their field keeping the leftmost bytes.
+    pub fn format_bytes(&self, record: &BytesRecord) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(self.width);
+        self.format_bytes_into(record, &mut buf);
+        buf
+    }
+
+    /// Formats a record of raw values, appending it to `buf`.
+    pub fn format_bytes_into(&self, record: &BytesRecord, buf: &mut Vec<u8>) {
+        buf.reserve(self.width);
+        for (field, start, end) in self.byte_ranges() {
+            let width = end - start;
+            let value = field
+                .name
+                .and_then(|name| record.get(name))
+                .map_or(&[][..], |v| &v[..v.len().min(width)]);
+            let padding = match field.padding {
+                p if p.is_ascii() => p as u8,
+                _ => b' ',
+            };
+            let fill = core::iter::repeat_n(padding, width - value.len());
+            match field.align {
+                Align::Left => {
+                    buf.extend_from_slice(value);
+                    buf.extend(fill);
+                }
+                Align::Right => {
+                    buf.extend(fill);
+                    buf.extend_from_slice(value);
+                }
+            }
+        }
+    }
+}
+
+/// Fails if a binary field is wider than the 8 bytes of the integers it is decoded to.
+fn check_binary_width(field: &Field) -> Result<(), Error> {
+    match field.width {
+        1..=8 => Ok(()),
+        width => Err(Error::Layout(format!(
+            "Binary field {} is {} bytes wide, at most 8 are supported",
+            field.name.unwrap_or_default(),
+            width
+        ))),
+    }
+}
+
+/// Decodes a binary integer to its decimal value.
+fn decode_int(field: &Field, raw: &[u8], endian: Endian, signed: bool) -> Result<String, Error> {
+    check_binary_width(field)?;
+    let fold = |n: u64, b: &u8| (n << 8) | u64::from(*b);
+    let n = match endian {
+        Endian::Big => raw.iter().fold(0, fold),
+        Endian::Little => raw.iter().rev().fold(0, fold),
+    };
+    let shift = 64 - raw.len() * 8;
+    Ok(match signed {
+        // Sign extends from the top bit of the field.
+        true => (((n << shift) as i64) >> shift).to_string(),
+        false => n.to_string(),
+    })
+}
+
+/// Encodes a decimal value as a big endian binary integer of the width of the field.
+fn encode_int(field: &Field, value: &str, signed: bool) -> Result<Vec<u8>, Error> {
+    check_binary_width(field)?;
+    let bits = field.width as u32 * 8;
+    let n = value.trim().parse::<i128>().ok().filter(|n| match signed {
+        true => (-(1i128 << (bits - 1))..1i128 << (bits - 1)).contains(n),
+        false => (0..1i128 << bits).contains(n),
+    });
+    match n {
+        Some(n) => Ok((n as u64).to_be_bytes()[8 - field.width..].to_vec()),
+        None => Err(Error::Parse {
+            line: None,
+            field: field.name.map(String::from),
+            kind: ParseErrorKind::BinaryOverflow(value.to_string()),
+        }),
+    }
+}
+
+/// Reads records of raw bytes, for binary or non UTF-8 data. Records are split by the
+/// terminator, by default they have none and are exactly the width of the layout.
+#[cfg(feature = "std")]
+pub struct BytesReader<'a, R> {
+    inner: BufReader<R>,
+    parser: &'a Parser<'a>,
+    terminator: Terminator,
+    buf: Vec<u8>,
+    line: u64,
+}
+
+#[cfg(feature = "std")]
+#[allow(dead_code)]
+impl<'a, R> BytesReader<'a, R>
+where
+    R: Read,
+{
+    pub fn from_reader(reader: R, parser: &'a Parser) -> Self {
+        BytesReader {
+            inner: BufReader::new(reader),
+            parser,
+            terminator: Terminator::Fixed,
+            buf: Vec::new(),
+            line: 0,
+        }
+    }
+
+    /// Sets how records are separated.
+    pub fn terminator(mut self, terminator: Terminator) -> Self {
+        self.terminator = terminator;
+        self
+    }
+
+    /// The number of the last record read.
+    pub fn line(&self) -> u64 {
+        self.line
+    }
+
+    /// Reads the next record, calling `visit` with the name and raw value of each of its
+    /// fields without allocating a record. Returns `Ok(false)` once there are no more records.
+    pub fn read_with<F>(&mut self, visit: F) -> Result<bool, Error>
+    where
+        F: FnMut(&str, &[u8]),
+    {
+        if !self
+            .terminator
+            .read_record(&mut self.inner, self.parser.width(), &mut self.buf)?
+        {
+            return Ok(false);
+        }
+        self.line += 1;
+        self.parser
+            .parse_bytes_with(&self.buf, visit)
+            .map(|_| true)
+            .map_err(|e| e.at_line(self.line))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a, R> Iterator for BytesReader<'a, R>
+where
+    R: Read,
+{
+    type Item = Result<BytesRecord, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self
+            .terminator
+            .read_record(&mut self.inner, self.parser.width(), &mut self.buf)
+        {
+            Ok(true) => {
+                self.line += 1;
+                Some(
+                    self.parser
+                        .parse_bytes(&self.buf)
+                        .map_err(|e| e.at_line(self.line)),
+                )
+            }
+            Ok(false) => None,
+            Err(e) => Some(Err(Error::from(e))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::{Buildable, Builder};
+
+    fn parser() -> Parser<'static> {
+        Parser::builder()
+            .field("id")
+            .width(3)
+            .align("right")
+            .padding('0')
+            .append()
+            .spacer(0..1)
+            .field("amount")
+            .width(4)
+            .append()
+            .build()
+    }
+
+    #[test]
+    fn parse_and_format_bytes() {
+        let parser = parser();
+        let bytes = b"042 \x00\x01\xFF\x7F";
+
+        let record = parser.parse_bytes(bytes).unwrap();
+        assert_eq!(record["id"], b"042");
+        assert_eq!(record["amount"], b"\x00\x01\xFF\x7F");
+        assert_eq!(parser.format_bytes(&record), bytes);
+
+        let mut short = BytesRecord::new();
+        short.insert(String::from("id"), b"7".to_vec());
+        short.insert(String::from("amount"), b"\xFFabcdef".to_vec());
+        assert_eq!(parser.format_bytes(&short), b"007 \xFFabc");
+
+        assert!(parser.parse_bytes(b"042 ").is_err());
+    }
+
+    #[test]
+    fn binary_ints() {
+        let parser = Parser::builder()
+            .field("count")
+            .binary_int(2, Endian::Big, false)
+            .append()
+            .field("delta")
+            .binary_int(4, Endian::Big, true)
+            .append()
+            .field("total")
+            .binary_int(8, Endian::Little, true)
+            .append()
+            .field("code")
+            .width(3)
+            .append()
+            .build();
+        let bytes = b"\x01\x02\xFF\xFF\xFF\xFE\x10\x00\x00\x00\x00\x00\x00\x80AB ";
+
+        let record = parser.decode_bytes(bytes).unwrap();
+        assert_eq!(record["count"], "258");
+        assert_eq!(record["delta"], "-2");
+        assert_eq!(record["total"], i64::MIN.wrapping_add(16).to_string());
+        assert_eq!(record["code"], "AB");
+        assert_eq!(parser.encode_bytes(&record).unwrap(), bytes);
+
+        let mut overflow = record.clone();
+        overflow.insert(String::from("count"), String::from("65536"));
+        assert!(matches!(
+            parser.encode_bytes(&overflow),
+            Err(Error::Parse {
+                kind: ParseErrorKind::BinaryOverflow(_),
+                ..
+            })
+        ));
+        overflow.insert(String::from("count"), String::from("-1"));
+        assert!(parser.encode_bytes(&overflow).is_err());
+    }
+
+    #[test]
+    fn read_bytes() {
+        let parser = parser();
+        let input = b"001 \x00\x00\x00\x01002 \xFF\xFF\xFF\xFE003";
+
+        let mut rdr = BytesReader::from_reader(&input[..], &parser);
+        let records = rdr.by_ref().collect::<Vec<_>>();
+
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[1].as_ref().unwrap()["amount"], b"\xFF\xFF\xFF\xFE");
+        assert_eq!(records[2].as_ref().unwrap_err().line(), Some(3));
+        assert_eq!(rdr.line(), 3);
+    }
+}
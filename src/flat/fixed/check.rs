@@ -0,0 +1,150 @@
+use alloc::vec::Vec;
+use core::{fmt::Debug, ptr};
+
+/// An algorithm computing the check digit of a value, such as an account or routing number.
+/// A field with a check digit holds the value followed by its digit, which is computed and
+/// appended on format and verified and removed on parse, see
+/// [`Field::with_check_digit`](super::Field::with_check_digit).
+pub trait CheckDigit: Debug {
+    /// The check digit of the payload, `None` if the payload has characters the algorithm
+    /// does not accept.
+    fn compute(&self, payload: &str) -> Option<char>;
+
+    /// Whether the last character of the value is the check digit of the rest.
+    fn verify(&self, value: &str) -> bool {
+        match split(value) {
+            Some((payload, digit)) => self.compute(payload) == Some(digit),
+            None => false,
+        }
+    }
+}
+
+/// Check digits are equal when they are the same instance.
+impl PartialEq for dyn CheckDigit + '_ {
+    fn eq(&self, other: &Self) -> bool {
+        ptr::eq(
+            self as *const Self as *const (),
+            other as *const Self as *const (),
+        )
+    }
+}
+
+/// Splits the value into its payload and check digit.
+pub(crate) fn split(value: &str) -> Option<(&str, char)> {
+    let digit = value.chars().next_back()?;
+    Some((&value[..value.len() - digit.len_utf8()], digit))
+}
+
+/// The digits of the payload from the right, `None` if it has a character that is not a digit.
+fn digits(payload: &str) -> Option<Vec<u32>> {
+    payload.chars().rev().map(|c| c.to_digit(10)).collect()
+}
+
+/// The Luhn algorithm used by card and many account numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Luhn;
+
+impl CheckDigit for Luhn {
+    fn compute(&self, payload: &str) -> Option<char> {
+        let sum: u32 = digits(payload)?
+            .into_iter()
+            .enumerate()
+            .map(|(i, d)| match (i % 2 == 0, d * 2) {
+                (true, doubled) if doubled > 9 => doubled - 9,
+                (true, doubled) => doubled,
+                (false, _) => d,
+            })
+            .sum();
+        core::char::from_digit((10 - sum % 10) % 10, 10)
+    }
+}
+
+/// A weighted modulus 10 check digit, with the weights applied to the payload from the left
+/// and repeated as needed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mod10 {
+    pub weights: &'static [u32],
+}
+
+#[allow(dead_code)]
+impl Mod10 {
+    /// The ABA routing number check digit, weighted 3, 7, 1.
+    pub const ABA: Mod10 = Mod10 {
+        weights: &[3, 7, 1],
+    };
+}
+
+impl CheckDigit for Mod10 {
+    fn compute(&self, payload: &str) -> Option<char> {
+        let mut digits = digits(payload)?;
+        digits.reverse();
+        let sum: u32 = digits
+            .into_iter()
+            .zip(self.weights.iter().cycle())
+            .map(|(d, w)| d * w)
+            .sum();
+        core::char::from_digit((10 - sum % 10) % 10, 10)
+    }
+}
+
+/// A modulus 11 check digit, with the payload weighted from the right by 2 up to
+/// `max_weight` and repeated as needed. A check digit of 10 is written `X`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mod11 {
+    pub max_weight: u32,
+}
+
+#[allow(dead_code)]
+impl Mod11 {
+    /// Weights 2 to 7, used by many bank account numbers.
+    pub const STANDARD: Mod11 = Mod11 { max_weight: 7 };
+    /// Weights 2 to 10, used by ISBN-10.
+    pub const ISBN: Mod11 = Mod11 { max_weight: 10 };
+}
+
+impl Default for Mod11 {
+    fn default() -> Self {
+        Mod11::STANDARD
+    }
+}
+
+impl CheckDigit for Mod11 {
+    fn compute(&self, payload: &str) -> Option<char> {
+        let weights = 2..=self.max_weight.max(2);
+        let sum: u32 = digits(payload)?
+            .into_iter()
+            .zip(weights.cycle())
+            .map(|(d, w)| d * w)
+            .sum();
+        match (11 - sum % 11) % 11 {
+            10 => Some('X'),
+            digit => core::char::from_digit(digit, 10),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn luhn() {
+        assert_eq!(Luhn.compute("7992739871"), Some('3'));
+        assert!(Luhn.verify("79927398713"));
+        assert!(!Luhn.verify("79927398710"));
+        assert_eq!(Luhn.compute("79A"), None);
+    }
+
+    #[test]
+    fn mod10_aba() {
+        assert_eq!(Mod10::ABA.compute("01100001"), Some('5'));
+        assert!(Mod10::ABA.verify("011000015"));
+    }
+
+    #[test]
+    fn mod11() {
+        assert_eq!(Mod11::ISBN.compute("030640615"), Some('2'));
+        assert_eq!(Mod11::ISBN.compute("080442957"), Some('X'));
+        assert!(!Mod11::STANDARD.verify(""));
+    }
+}
@@ -0,0 +1,64 @@
+use alloc::{collections::BTreeMap, string::String};
+use core::iter::FromIterator;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The codes a field may hold and their descriptions, e.g. `A` for `Active`. A field with a
+/// code table fails to parse values that are not in the table, and can add the description of
+/// its code to the record, see [`Field::with_codes`](super::Field::with_codes).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct CodeTable {
+    codes: BTreeMap<String, String>,
+}
+
+#[allow(dead_code)]
+impl CodeTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a code and its description.
+    pub fn code<C: Into<String>, D: Into<String>>(mut self, code: C, description: D) -> Self {
+        self.codes.insert(code.into(), description.into());
+        self
+    }
+
+    /// The description of a code, `None` if the code is not in the table.
+    pub fn describe(&self, code: &str) -> Option<&str> {
+        self.codes.get(code).map(|d| d.as_str())
+    }
+
+    pub fn contains(&self, code: &str) -> bool {
+        self.codes.contains_key(code)
+    }
+
+    /// The codes and their descriptions, ordered by code.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.codes.iter().map(|(c, d)| (c.as_str(), d.as_str()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.codes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.codes.is_empty()
+    }
+}
+
+impl<C, D> FromIterator<(C, D)> for CodeTable
+where
+    C: Into<String>,
+    D: Into<String>,
+{
+    fn from_iter<I: IntoIterator<Item = (C, D)>>(iter: I) -> Self {
+        CodeTable {
+            codes: iter
+                .into_iter()
+                .map(|(c, d)| (c.into(), d.into()))
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,249 @@
+//! Comparison of two files of the same layout by key, producing the records added, removed
+//! and changed between them, as when generating a delta file from yesterday's and today's
+//! extracts.
+
+use crate::{
+    error::Error,
+    flat::fixed::{read::Reader, Parser, Record, ResultRecord},
+};
+use std::{
+    collections::{HashMap, HashSet},
+    fs::File,
+    path::Path,
+};
+
+/// Options of a comparison.
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    ignore: Vec<String>,
+}
+
+#[allow(dead_code)]
+impl Options {
+    pub fn new() -> Self {
+        Options::default()
+    }
+
+    /// Ignores changes of a field, such as a timestamp of the extract.
+    pub fn ignore<T: Into<String>>(mut self, field: T) -> Self {
+        self.ignore.push(field.into());
+        self
+    }
+}
+
+/// A record whose key is in both files with different values.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Changed {
+    pub old: Record,
+    pub new: Record,
+    /// The names of the fields that differ, in layout order.
+    pub fields: Vec<String>,
+}
+
+/// The differences between two files. Added and changed records are in the order of the new
+/// file, removed records in the order of the old file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Delta {
+    pub added: Vec<Record>,
+    pub removed: Vec<Record>,
+    pub changed: Vec<Changed>,
+}
+
+impl Delta {
+    /// Whether the files hold the same records.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Compares two files of the layout, matching their records by the values of the key fields.
+/// Values are compared as by [`Parser::diff`]. The old file is held in memory while the new one
+/// is read, and a key repeated in either file fails with `Error::Duplicate`. Errors are wrapped
+/// in `Error::InFile` with the path of their file.
+pub fn files<P, Q>(
+    parser: &Parser,
+    old: P,
+    new: Q,
+    key: &[&str],
+    options: &Options,
+) -> Result<Delta, Error>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    let (old, new) = (old.as_ref(), new.as_ref());
+    let open = |path: &Path| {
+        File::open(path)
+            .map(|file| Reader::from_file(file, parser))
+            .map_err(|e| in_file(path, Error::from(e)))
+    };
+    let mut old_reader = open(old)?;
+    let old_records = index(old_reader.string_reader(), key).map_err(|e| in_file(old, e))?;
+    let mut new_reader = open(new)?;
+    compare(
+        parser,
+        old_records,
+        new_reader.string_reader(),
+        key,
+        options,
+    )
+    .map_err(|e| in_file(new, e))
+}
+
+/// Compares two streams of records of the layout, as [`files`] does.
+pub fn records<A, B>(
+    parser: &Parser,
+    old: A,
+    new: B,
+    key: &[&str],
+    options: &Options,
+) -> Result<Delta, Error>
+where
+    A: IntoIterator<Item = ResultRecord>,
+    B: IntoIterator<Item = ResultRecord>,
+{
+    compare(parser, index(old, key)?, new, key, options)
+}
+
+/// The records by key, with their position.
+fn index<I>(records: I, key: &[&str]) -> Result<HashMap<String, (usize, Record)>, Error>
+where
+    I: IntoIterator<Item = ResultRecord>,
+{
+    let mut index = HashMap::new();
+    for (i, record) in records.into_iter().enumerate() {
+        let record = record?;
+        let k = key_of(&record, key);
+        if index.contains_key(&k) {
+            return Err(duplicate(&k));
+        }
+        index.insert(k, (i, record));
+    }
+    Ok(index)
+}
+
+fn compare<I>(
+    parser: &Parser,
+    mut old: HashMap<String, (usize, Record)>,
+    new: I,
+    key: &[&str],
+    options: &Options,
+) -> Result<Delta, Error>
+where
+    I: IntoIterator<Item = ResultRecord>,
+{
+    let mut delta = Delta::default();
+    let mut seen = HashSet::new();
+    for record in new {
+        let record = record?;
+        let k = key_of(&record, key);
+        if !seen.insert(k.clone()) {
+            return Err(duplicate(&k));
+        }
+        let previous = match old.remove(&k) {
+            Some((_, previous)) => previous,
+            None => {
+                delta.added.push(record);
+                continue;
+            }
+        };
+        let fields = parser
+            .diff(&previous, &record)
+            .into_iter()
+            .filter(|change| !options.ignore.iter().any(|f| f == change.name))
+            .map(|change| change.name.to_string())
+            .collect::<Vec<_>>();
+        if !fields.is_empty() {
+            delta.changed.push(Changed {
+                old: previous,
+                new: record,
+                fields,
+            });
+        }
+    }
+    let mut removed = old.into_values().collect::<Vec<_>>();
+    removed.sort_by_key(|(i, _)| *i);
+    delta.removed = removed.into_iter().map(|(_, record)| record).collect();
+    Ok(delta)
+}
+
+fn key_of(record: &Record, key: &[&str]) -> String {
+    let mut k = String::new();
+    for (i, field) in key.iter().enumerate() {
+        if i > 0 {
+            k.push('\u{1f}');
+        }
+        k.push_str(record.get(*field).map_or("", |v| v.as_str()));
+    }
+    k
+}
+
+fn duplicate(key: &str) -> Error {
+    Error::Duplicate {
+        key: key.replace('\u{1f}', ", "),
+    }
+}
+
+fn in_file(path: &Path, error: Error) -> Error {
+    Error::InFile {
+        path: path.to_path_buf(),
+        source: Box::new(error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::{Buildable, Builder};
+    use std::fs;
+
+    fn parser() -> Parser<'static> {
+        Parser::builder()
+            .field("id")
+            .width(3)
+            .append()
+            .field("name")
+            .width(5)
+            .append()
+            .field("stamp")
+            .width(2)
+            .append()
+            .build()
+    }
+
+    #[test]
+    fn compare_files() {
+        let dir = std::env::temp_dir().join(format!("eta-parse-compare-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let (old, new) = (dir.join("old.txt"), dir.join("new.txt"));
+        fs::write(&old, "001Ann  01\n002Bob  01\n003Cid  01\n").unwrap();
+        fs::write(&new, "003Cyd  02\n001Ann  02\n004Dee  02\n").unwrap();
+        let parser = parser();
+
+        let delta = files(
+            &parser,
+            &old,
+            &new,
+            &["id"],
+            &Options::new().ignore("stamp"),
+        )
+        .unwrap();
+        assert_eq!(delta.added.len(), 1);
+        assert_eq!(delta.added[0]["id"], "004");
+        assert_eq!(delta.removed.len(), 1);
+        assert_eq!(delta.removed[0]["id"], "002");
+        assert_eq!(delta.changed.len(), 1);
+        assert_eq!(delta.changed[0].new["name"], "Cyd");
+        assert_eq!(delta.changed[0].fields, vec!["name"]);
+
+        let delta = files(&parser, &old, &old, &["id"], &Options::new()).unwrap();
+        assert!(delta.is_empty());
+
+        fs::write(&new, "001Ann  02\n001Ann  03\n").unwrap();
+        let error = files(&parser, &old, &new, &["id"], &Options::new()).unwrap_err();
+        assert!(
+            matches!(error, Error::InFile { ref source, .. } if matches!(**source, Error::Duplicate { .. }))
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
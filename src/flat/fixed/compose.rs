@@ -0,0 +1,116 @@
+use crate::{error::Error, flat::fixed::Parser};
+use alloc::{format, vec::Vec};
+use core::ops::{Bound, RangeBounds};
+
+#[allow(dead_code)]
+impl<'a> Parser<'a> {
+    /// A layout made of the fields of this layout followed by those of `other`, e.g. a prefix
+    /// block followed by a record. A record width declared on this layout becomes a spacer.
+    pub fn concat(&self, other: &Parser<'a>) -> Parser<'a> {
+        let mut fields = Vec::with_capacity(self.fields.len() + other.fields.len());
+        fields.extend_from_slice(&self.fields);
+        fields.extend_from_slice(&other.fields);
+        Parser {
+            filler: other.filler,
+            ..Parser::new(fields)
+        }
+    }
+
+    /// A layout made of a range of the fields of this layout, spacers included, e.g.
+    /// `..parser.fields().len() - 3` for the same layout without its last three fields.
+    pub fn slice<R: RangeBounds<usize>>(&self, range: R) -> Result<Parser<'a>, Error> {
+        let bounds = (range.start_bound().cloned(), range.end_bound().cloned());
+        let fields = self.fields.get(bounds).ok_or_else(|| {
+            Error::Layout(format!(
+                "Invalid range of fields {:?} for a layout of {} fields",
+                bounds,
+                self.fields.len()
+            ))
+        })?;
+        // The filler is only kept with the last field.
+        let filler = match bounds.1 {
+            Bound::Unbounded => self.filler,
+            Bound::Included(end) if end + 1 == self.fields.len() => self.filler,
+            Bound::Excluded(end) if end == self.fields.len() => self.filler,
+            _ => None,
+        };
+        Ok(Parser {
+            filler: filler.filter(|_| !fields.is_empty()),
+            ..Parser::new(fields.to_vec())
+        })
+    }
+
+    /// The same layout with fields renamed, from pairs of the current and the new name. Fails
+    /// if a field to rename is not in the layout.
+    pub fn rename<'n, I>(&self, names: I) -> Result<Parser<'a>, Error>
+    where
+        I: IntoIterator<Item = (&'n str, &'a str)>,
+    {
+        let mut fields = self.fields.clone();
+        for (from, to) in names {
+            let field = fields
+                .iter_mut()
+                .find(|f| f.name == Some(from))
+                .ok_or_else(|| Error::Layout(format!("Unknown field {}", from)))?;
+            field.name = Some(to);
+        }
+        Ok(Parser {
+            filler: self.filler,
+            ..Parser::new(fields)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::{Buildable, Builder};
+
+    fn parser() -> Parser<'static> {
+        Parser::builder()
+            .field("id")
+            .width(2)
+            .append()
+            .spacer(0..1)
+            .field("name")
+            .width(3)
+            .append()
+            .field("code")
+            .width(2)
+            .append()
+            .build()
+    }
+
+    #[test]
+    fn concat_and_slice() {
+        let parser = parser();
+        let header = Parser::builder().field("type").width(1).append().build();
+
+        let concat = header.concat(&parser);
+        assert_eq!(concat.width(), 9);
+        assert_eq!(concat.parse("H01 AnnXY").unwrap()["type"], "H");
+
+        let slice = parser.slice(..parser.fields().len() - 1).unwrap();
+        assert_eq!(slice.width(), 6);
+        let record = slice.parse("01 Ann").unwrap();
+        assert_eq!(record.len(), 2);
+        assert_eq!(record["name"], "Ann");
+
+        assert!(matches!(parser.slice(2..5), Err(Error::Layout(_))));
+    }
+
+    #[test]
+    fn rename() {
+        let parser = parser();
+
+        let renamed = parser.rename([("name", "customer")]).unwrap();
+        let record = renamed.parse("01 AnnXY").unwrap();
+        assert_eq!(record["customer"], "Ann");
+        assert!(!record.contains_key("name"));
+
+        assert!(matches!(
+            parser.rename([("missing", "other")]),
+            Err(Error::Layout(_))
+        ));
+    }
+}
@@ -0,0 +1,284 @@
+use crate::{
+    error::Error,
+    flat::fixed::{Record, ResultRecord},
+};
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap, HashSet, VecDeque},
+    hash::{Hash, Hasher},
+};
+
+/// Which of the records with the same key is kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Keep {
+    /// The first record is yielded as it is read, later ones are duplicates.
+    First,
+    /// The last record is kept, in the position of the first. Every unique record is held in
+    /// memory until the input is exhausted.
+    Last,
+}
+
+/// Drops, or reports, records whose key fields repeat those of an earlier record.
+///
+/// Duplicates are dropped unless [`report`](Dedupe::report) is set, in which case each one is
+/// yielded as an [`Error::Duplicate`]. Errors from the records iterator are passed through.
+pub struct Dedupe<'k, I> {
+    records: I,
+    fields: &'k [&'k str],
+    keep: Keep,
+    report: bool,
+    seen: Seen,
+    last: HashMap<String, usize>,
+    held: VecDeque<ResultRecord>,
+    done: bool,
+}
+
+#[allow(dead_code)]
+impl<'k, I> Dedupe<'k, I>
+where
+    I: Iterator<Item = ResultRecord>,
+{
+    pub fn new(records: I, fields: &'k [&'k str]) -> Self {
+        Dedupe {
+            records,
+            fields,
+            keep: Keep::First,
+            report: false,
+            seen: Seen::Keys(HashSet::new()),
+            last: HashMap::new(),
+            held: VecDeque::new(),
+            done: false,
+        }
+    }
+
+    /// Sets which record of a key is kept, the first by default.
+    pub fn keep(mut self, keep: Keep) -> Self {
+        self.keep = keep;
+        self
+    }
+
+    /// Yields duplicates as errors instead of dropping them.
+    pub fn report(mut self) -> Self {
+        self.report = true;
+        self
+    }
+
+    /// Remembers the keys in a bloom filter sized for the expected number of keys and false
+    /// positive rate, bounding the memory used. A false positive drops, or reports, a record
+    /// that is not a duplicate. Only used when keeping the first record.
+    pub fn bloom(mut self, expected: usize, false_positive_rate: f64) -> Self {
+        self.seen = Seen::Bloom(Bloom::new(expected, false_positive_rate));
+        self
+    }
+
+    fn key(&self, record: &Record) -> String {
+        let mut key = String::new();
+        for (i, field) in self.fields.iter().enumerate() {
+            if i > 0 {
+                key.push('\u{1f}');
+            }
+            key.push_str(record.get(*field).map_or("", |v| v.as_str()));
+        }
+        key
+    }
+
+    fn duplicate(&self, key: &str) -> Option<ResultRecord> {
+        match self.report {
+            true => Some(Err(Error::Duplicate {
+                key: key.replace('\u{1f}', ", "),
+            })),
+            false => None,
+        }
+    }
+
+    fn next_first(&mut self) -> Option<ResultRecord> {
+        loop {
+            let record = match self.records.next()? {
+                Ok(record) => record,
+                Err(e) => return Some(Err(e)),
+            };
+            let key = self.key(&record);
+            if self.seen.insert(&key) {
+                return Some(Ok(record));
+            }
+            if let Some(duplicate) = self.duplicate(&key) {
+                return Some(duplicate);
+            }
+        }
+    }
+
+    fn next_last(&mut self) -> Option<ResultRecord> {
+        while !self.done {
+            let record = match self.records.next() {
+                Some(Ok(record)) => record,
+                Some(Err(e)) => {
+                    self.held.push_back(Err(e));
+                    continue;
+                }
+                None => {
+                    self.done = true;
+                    break;
+                }
+            };
+            let key = self.key(&record);
+            match self.last.get(&key) {
+                Some(&index) => {
+                    self.held[index] = Ok(record);
+                    if let Some(duplicate) = self.duplicate(&key) {
+                        self.held.push_back(duplicate);
+                    }
+                }
+                None => {
+                    self.last.insert(key, self.held.len());
+                    self.held.push_back(Ok(record));
+                }
+            }
+        }
+        self.held.pop_front()
+    }
+}
+
+impl<'k, I> Iterator for Dedupe<'k, I>
+where
+    I: Iterator<Item = ResultRecord>,
+{
+    type Item = ResultRecord;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.keep {
+            Keep::First => self.next_first(),
+            Keep::Last => self.next_last(),
+        }
+    }
+}
+
+/// The keys seen so far, exactly or approximately.
+enum Seen {
+    Keys(HashSet<String>),
+    Bloom(Bloom),
+}
+
+impl Seen {
+    /// Adds the key, returning whether it was not seen before.
+    fn insert(&mut self, key: &str) -> bool {
+        match self {
+            Seen::Keys(keys) => !keys.contains(key) && keys.insert(key.to_string()),
+            Seen::Bloom(bloom) => bloom.insert(key),
+        }
+    }
+}
+
+/// A bloom filter using double hashing.
+struct Bloom {
+    bits: Vec<u64>,
+    hashes: u64,
+}
+
+impl Bloom {
+    fn new(expected: usize, false_positive_rate: f64) -> Self {
+        let expected = expected.max(1) as f64;
+        let rate = false_positive_rate.clamp(f64::MIN_POSITIVE, 0.5);
+        let ln2 = std::f64::consts::LN_2;
+        let bits = (-expected * rate.ln() / (ln2 * ln2)).ceil().max(64.0);
+        Bloom {
+            bits: vec![0; (bits as usize).div_ceil(64)],
+            hashes: ((bits / expected * ln2).round() as u64).max(1),
+        }
+    }
+
+    fn insert(&mut self, key: &str) -> bool {
+        let (first, second) = (hash(key, 0), hash(key, 1) | 1);
+        let size = self.bits.len() as u64 * 64;
+        let mut new = false;
+        for i in 0..self.hashes {
+            let bit = first.wrapping_add(i.wrapping_mul(second)) % size;
+            let (word, mask) = ((bit / 64) as usize, 1 << (bit % 64));
+            new |= self.bits[word] & mask == 0;
+            self.bits[word] |= mask;
+        }
+        new
+    }
+}
+
+fn hash(key: &str, seed: u8) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    seed.hash(&mut hasher);
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        builder::{Buildable, Builder},
+        flat::fixed::{read::Reader, Parser},
+    };
+
+    fn parser() -> Parser<'static> {
+        Parser::builder()
+            .field("key")
+            .width(2)
+            .append()
+            .field("value")
+            .width(2)
+            .append()
+            .build()
+    }
+
+    const INPUT: &str = "01A1\n02B1\n01A2\nX\n03C1\n02B2\n";
+
+    fn summary(result: ResultRecord) -> String {
+        match result {
+            Ok(record) => record["value"].clone(),
+            Err(e) => e.to_string(),
+        }
+    }
+
+    #[test]
+    fn dedupe_keep_first() {
+        let parser = parser();
+        let mut rdr = Reader::from_string(INPUT, &parser);
+
+        let values = rdr.dedupe_by(&["key"]).map(summary).collect::<Vec<_>>();
+
+        assert_eq!(values.len(), 4);
+        assert_eq!(values[..2], ["A1", "B1"]);
+        assert_eq!(values[3], "C1");
+    }
+
+    #[test]
+    fn dedupe_keep_last_and_report() {
+        let parser = parser();
+        let mut rdr = Reader::from_string(INPUT, &parser);
+
+        let values = rdr
+            .dedupe_by(&["key"])
+            .keep(Keep::Last)
+            .report()
+            .map(summary)
+            .collect::<Vec<_>>();
+
+        assert_eq!(values.len(), 6);
+        assert_eq!(values[..3], ["A2", "B2", "Duplicate key 01"]);
+        assert_eq!(values[4..], ["C1", "Duplicate key 02"]);
+    }
+
+    #[test]
+    fn dedupe_with_bloom_filter() {
+        let parser = parser();
+        let mut rdr = Reader::from_string(INPUT, &parser);
+
+        let values = rdr
+            .dedupe_by(&["key", "value"])
+            .bloom(100, 0.001)
+            .map(summary)
+            .collect::<Vec<_>>();
+
+        assert_eq!(values.len(), 6);
+
+        let mut bloom = Bloom::new(1000, 0.01);
+        let new = (0..1000).filter(|i| bloom.insert(&i.to_string())).count();
+        assert!(new > 980);
+        assert!(!bloom.insert("10"));
+    }
+}
@@ -0,0 +1,259 @@
+use crate::flat::fixed::{Parser, Record};
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::fmt::{self, Display, Formatter, Write};
+
+const SPACER: &str = "<spacer>";
+
+#[allow(dead_code)]
+impl<'a> Parser<'a> {
+    /// Produces a table listing every field with its offset (0-based), its positions (1-based,
+    /// inclusive, as found in most specification documents), width, alignment and padding.
+    pub fn describe(&self) -> String {
+        let name_width = self
+            .fields
+            .iter()
+            .map(|f| f.name().unwrap_or(SPACER).chars().count())
+            .chain(core::iter::once(4))
+            .max()
+            .unwrap_or(4);
+
+        let mut s = String::new();
+        let _ = writeln!(
+            s,
+            "{:>3}  {:<name_width$}  {:>6}  {:>11}  {:>5}  {:<5}  Padding",
+            "#",
+            "Name",
+            "Offset",
+            "Positions",
+            "Width",
+            "Align",
+            name_width = name_width
+        );
+        let mut offset = 0;
+        for (i, field) in self.fields.iter().enumerate() {
+            let positions = match field.width() {
+                0 => String::from("-"),
+                w => format!("{}-{}", offset + 1, offset + w),
+            };
+            let _ = writeln!(
+                s,
+                "{:>3}  {:<name_width$}  {:>6}  {:>11}  {:>5}  {:<5}  {:?}",
+                i + 1,
+                field.name().unwrap_or(SPACER),
+                offset,
+                positions,
+                field.width(),
+                format!("{:?}", field.align()),
+                field.padding(),
+                name_width = name_width
+            );
+            offset += field.width();
+        }
+        let _ = write!(s, "Total width: {}", self.width);
+        s
+    }
+
+    /// Produces a column ruler with the field boundaries marked underneath, optionally followed
+    /// by a sample line so misaligned fields stand out.
+    ///
+    /// Each field is drawn as `[name---]`, spacers as blanks and single column fields as `^`.
+    pub fn ruler(&self, sample: Option<&str>) -> String {
+        let width = sample
+            .map(|s| s.chars().count())
+            .unwrap_or(0)
+            .max(self.width);
+
+        let tens = (1..=width)
+            .map(|i| match i % 10 {
+                0 => core::char::from_digit(((i / 10) % 10) as u32, 10).unwrap_or(' '),
+                _ => ' ',
+            })
+            .collect::<String>();
+        let units = (1..=width)
+            .map(|i| core::char::from_digit((i % 10) as u32, 10).unwrap_or(' '))
+            .collect::<String>();
+
+        let mut fields = String::with_capacity(self.width);
+        for field in &self.fields {
+            let w = field.width();
+            match (field.name(), w) {
+                (_, 0) => (),
+                (None, _) => fields.extend(core::iter::repeat_n(' ', w)),
+                (Some(_), 1) => fields.push('^'),
+                (Some(name), _) => {
+                    fields.push('[');
+                    let name = name.chars().take(w - 2).collect::<String>();
+                    let len = name.chars().count();
+                    fields.push_str(&name);
+                    fields.extend(core::iter::repeat_n('-', w - 2 - len));
+                    fields.push(']');
+                }
+            }
+        }
+
+        let mut lines = vec![
+            tens.trim_end().to_string(),
+            units,
+            fields.trim_end().to_string(),
+        ];
+        if let Some(sample) = sample {
+            lines.push(sample.to_string());
+        }
+        lines.join("\n")
+    }
+
+    /// Renders a record as one `name: "value"` line per field in layout order, with the names
+    /// aligned, see [`display`](Self::display).
+    pub fn render(&self, record: &Record) -> String {
+        self.display(record).to_string()
+    }
+
+    /// A view of a record for logs, listing its fields in layout order instead of the order of
+    /// the map. Values are quoted so padding stands out, fields missing from the record are
+    /// shown as `<missing>` and entries outside the layout follow in name order. The alternate
+    /// form, `{:#}`, adds the 1-based positions of the fields.
+    pub fn display<'r>(&'r self, record: &'r Record) -> impl Display + 'r {
+        RecordDisplay {
+            parser: self,
+            record,
+        }
+    }
+}
+
+struct RecordDisplay<'r, 'a> {
+    parser: &'r Parser<'a>,
+    record: &'r Record,
+}
+
+impl Display for RecordDisplay<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut offset = 0;
+        let mut lines = Vec::with_capacity(self.record.len());
+        for field in &self.parser.fields {
+            let start = offset;
+            offset += field.width();
+            if let Some(name) = field.name() {
+                let positions = format!("{}-{}", start + 1, offset);
+                lines.push((name, Some(positions), self.record.get(name)));
+            }
+        }
+        let mut extra = self
+            .record
+            .iter()
+            .filter(|(name, _)| !lines.iter().any(|(n, _, _)| n == name))
+            .map(|(name, value)| (name.as_str(), None, Some(value)))
+            .collect::<Vec<_>>();
+        extra.sort_by_key(|(name, _, _)| *name);
+        lines.extend(extra);
+
+        let name_width = lines
+            .iter()
+            .map(|(name, _, _)| name.chars().count())
+            .max()
+            .unwrap_or(0);
+        for (i, (name, positions, value)) in lines.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{:<width$}: ", name, width = name_width)?;
+            match value {
+                Some(value) => write!(f, "{:?}", value)?,
+                None => write!(f, "<missing>")?,
+            }
+            if let (true, Some(positions)) = (f.alternate(), positions) {
+                write!(f, " ({})", positions)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        builder::{Buildable, Builder},
+        flat::fixed::Parser,
+    };
+
+    fn parser() -> Parser<'static> {
+        Parser::builder()
+            .field("id")
+            .width(4)
+            .align("right")
+            .padding('0')
+            .append()
+            .spacer(0..2)
+            .field("flag")
+            .width(1)
+            .append()
+            .field("description")
+            .width(8)
+            .append()
+            .build()
+    }
+
+    #[test]
+    fn describe_layout() {
+        assert_eq!(
+            parser().describe(),
+            [
+                "  #  Name         Offset    Positions  Width  Align  Padding",
+                "  1  id                0          1-4      4  Right  '0'",
+                "  2  <spacer>          4          5-6      2  Left   ' '",
+                "  3  flag              6          7-7      1  Left   ' '",
+                "  4  description       7         8-15      8  Left   ' '",
+                "Total width: 15",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn ruler_with_sample() {
+        assert_eq!(
+            parser().ruler(Some("0042  YHello   !!")),
+            [
+                "         1",
+                "12345678901234567",
+                "[id]  ^[descri]",
+                "0042  YHello   !!",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn ruler_without_sample() {
+        let parser = Parser::builder().field("a").width(2).append().build();
+
+        assert_eq!(parser.ruler(None), "\n12\n[]");
+    }
+
+    #[test]
+    fn render_record() {
+        let parser = parser();
+        let mut record = parser.parse("0042  YHello   ").unwrap();
+        record.remove("flag");
+        record.insert(String::from("extra"), String::from("x"));
+
+        assert_eq!(
+            parser.render(&record),
+            [
+                "id         : \"42\"",
+                "flag       : <missing>",
+                "description: \"Hello\"",
+                "extra      : \"x\"",
+            ]
+            .join("\n")
+        );
+        assert_eq!(
+            format!("{:#}", parser.display(&record)).lines().next(),
+            Some("id         : \"42\" (1-4)")
+        );
+    }
+}
@@ -0,0 +1,123 @@
+//! Comparison of two records under a layout, for reconciliation jobs comparing the same records
+//! across two files.
+
+use crate::{
+    flat::fixed::{bytes::FieldKind, numeric, Field, Parser, Record},
+    utilities::string::Align,
+};
+use alloc::{string::String, vec::Vec};
+use core::cmp::Ordering;
+
+/// A field whose value differs between two records, `None` where it is missing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldChange<'r> {
+    pub name: &'r str,
+    pub old: Option<&'r str>,
+    pub new: Option<&'r str>,
+}
+
+#[allow(dead_code)]
+impl<'a> Parser<'a> {
+    /// The fields of the layout whose values differ between two records, in layout order.
+    /// Values are compared without surrounding whitespace, and numbers in numeric fields or
+    /// fields filled with zeros on the left by value, so `0010`, `10` and `10.0` are equal.
+    /// Flags are compared one by one and entries outside the layout are ignored.
+    pub fn diff<'r>(&'r self, old: &'r Record, new: &'r Record) -> Vec<FieldChange<'r>> {
+        let mut changes = Vec::new();
+        for field in &self.fields {
+            let mut compare = |name: &'r str| {
+                let (o, n) = (old.get(name), new.get(name));
+                if !equivalent(field, o.map(String::as_str), n.map(String::as_str)) {
+                    changes.push(FieldChange {
+                        name,
+                        old: o.map(String::as_str),
+                        new: n.map(String::as_str),
+                    });
+                }
+            };
+            match (field.kind, field.name) {
+                (FieldKind::Flags(flags), _) => flags.names().for_each(compare),
+                (_, Some(name)) => compare(name),
+                (_, None) => (),
+            }
+        }
+        changes
+    }
+}
+
+fn equivalent(field: &Field, old: Option<&str>, new: Option<&str>) -> bool {
+    let (old, new) = match (old, new) {
+        (Some(old), Some(new)) => (old.trim(), new.trim()),
+        (old, new) => return old == new,
+    };
+    if old == new {
+        return true;
+    }
+    let numeric = field.numeric.is_some() || (field.align == Align::Right && field.padding == '0');
+    match (numeric::number(old), numeric::number(new)) {
+        (Some(old), Some(new)) => numeric && numeric::compare(&old, &new) == Ordering::Equal,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        builder::{Buildable, Builder},
+        flat::fixed::{flags::Flags, numeric::Sign},
+    };
+
+    #[test]
+    fn diff_records() {
+        let flags = Flags::new(["active", "vip"]);
+        let parser = Parser::builder()
+            .field("id")
+            .width(4)
+            .align("right")
+            .padding('0')
+            .append()
+            .field("code")
+            .width(4)
+            .append()
+            .field("amount")
+            .width(8)
+            .numeric(Sign::Leading)
+            .append()
+            .field("status")
+            .width(2)
+            .flags(&flags)
+            .append()
+            .build();
+        let old = parser.parse("00100010   12.50YN").unwrap();
+        let mut new = old.clone();
+        new.insert(String::from("id"), String::from("0010"));
+        new.insert(String::from("amount"), String::from("12.5 "));
+        new.insert(String::from("other"), String::from("x"));
+        assert_eq!(parser.diff(&old, &new), vec![]);
+
+        new.insert(String::from("code"), String::from("10"));
+        new.insert(String::from("vip"), String::from("true"));
+        new.remove("amount");
+        assert_eq!(
+            parser.diff(&old, &new),
+            vec![
+                FieldChange {
+                    name: "code",
+                    old: Some("0010"),
+                    new: Some("10"),
+                },
+                FieldChange {
+                    name: "amount",
+                    old: Some("12.50"),
+                    new: None,
+                },
+                FieldChange {
+                    name: "vip",
+                    old: Some("false"),
+                    new: Some("true"),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,90 @@
+//! Common record transforms for [`Writer::transform`](super::write::Writer::transform).
+
+use crate::flat::fixed::Record;
+use alloc::string::{String, ToString};
+
+/// Sets a field to the same value in every record, e.g. a source system id.
+pub fn constant<F, V>(field: F, value: V) -> impl FnMut(&mut Record)
+where
+    F: Into<String>,
+    V: Into<String>,
+{
+    let field = field.into();
+    let value = value.into();
+    move |record| {
+        record.insert(field.clone(), value.clone());
+    }
+}
+
+/// Numbers the records in a field, from `start` increasing by `step`, replacing any value. The
+/// numbers wrap around to 0 past `u64::MAX`.
+pub fn sequence<F>(field: F, start: u64, step: u64) -> impl FnMut(&mut Record)
+where
+    F: Into<String>,
+{
+    let field = field.into();
+    let mut next = start;
+    move |record| {
+        record.insert(field.clone(), next.to_string());
+        next = next.wrapping_add(step);
+    }
+}
+
+/// Sets a field to a value computed from the record, e.g. a check digit recomputed from the
+/// account number.
+pub fn computed<F, C>(field: F, mut compute: C) -> impl FnMut(&mut Record)
+where
+    F: Into<String>,
+    C: FnMut(&Record) -> String,
+{
+    let field = field.into();
+    move |record| {
+        let value = compute(record);
+        record.insert(field.clone(), value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        builder::{Buildable, Builder},
+        flat::fixed::{write::Writer, Parser},
+    };
+
+    #[test]
+    fn writer_transforms() {
+        let parser = Parser::builder()
+            .field("seq")
+            .width(3)
+            .align("right")
+            .padding('0')
+            .append()
+            .field("source")
+            .width(2)
+            .append()
+            .field("code")
+            .width(3)
+            .append()
+            .field("check")
+            .width(1)
+            .append()
+            .build();
+        let mut wtr = Writer::from_writer(Vec::new(), &parser)
+            .transform(sequence("seq", 10, 5))
+            .transform(constant("source", "XY"))
+            .transform(computed("check", |r| {
+                let sum = r["code"].bytes().map(|b| b as u32).sum::<u32>();
+                (sum % 10).to_string()
+            }))
+            .hash_total("seq");
+        let record = |code: &str| [(String::from("code"), code.to_string())].into();
+
+        wtr.write(&record("ABC")).unwrap();
+        wtr.write_all_from_iter(vec![record("XYZ")]).unwrap();
+
+        assert_eq!(wtr.totals().sum("seq"), Some(25));
+        let out = String::from_utf8(wtr.into_inner().unwrap()).unwrap();
+        assert_eq!(out, "010XYABC8\n015XYXYZ7\n");
+    }
+}
@@ -0,0 +1,177 @@
+use crate::flat::fixed::record::RecordSource;
+use alloc::{
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+const TRUE: &str = "true";
+const FALSE: &str = "false";
+
+/// Named boolean flags packed in a field, such as a status flag column. In a line each flag is
+/// a character, in a record of raw bytes each flag is a bit, from the most significant bit of
+/// the first byte. The flags replace the field in parsed records, each as `true` or `false`,
+/// and are combined back into the field on format, see
+/// [`FieldKind::Flags`](super::bytes::FieldKind::Flags).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Flags {
+    names: Vec<Option<String>>,
+    set: char,
+    unset: char,
+}
+
+#[allow(dead_code)]
+impl Flags {
+    /// Flags named after their position, an empty name leaves the position unused. A flag is
+    /// set by `Y` and unset by `N`.
+    pub fn new<I, S>(names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Flags {
+            names: names
+                .into_iter()
+                .map(Into::into)
+                .map(|name: String| Some(name).filter(|n| !n.is_empty()))
+                .collect(),
+            set: 'Y',
+            unset: 'N',
+        }
+    }
+
+    /// Sets the characters of set and unset flags in a line, e.g. `1` and `0`.
+    pub fn chars(mut self, set: char, unset: char) -> Self {
+        self.set = set;
+        self.unset = unset;
+        self
+    }
+
+    /// The names of the flags, in position order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.names.iter().flatten().map(String::as_str)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.names().any(|n| n == name)
+    }
+
+    /// Visits each flag with whether its character in the raw value of the field is set.
+    pub(crate) fn expand<F: FnMut(&str, &str)>(&self, raw: &str, mut visit: F) {
+        let mut chars = raw.chars();
+        for name in &self.names {
+            let set = chars.next() == Some(self.set);
+            if let Some(name) = name {
+                visit(name, if set { TRUE } else { FALSE });
+            }
+        }
+    }
+
+    /// Visits each flag with whether its bit in the raw bytes of the field is set.
+    pub(crate) fn expand_bits<F: FnMut(&str, &str)>(&self, raw: &[u8], mut visit: F) {
+        for (i, name) in self.names.iter().enumerate() {
+            let set = raw.get(i / 8).is_some_and(|b| b & (0x80 >> (i % 8)) != 0);
+            if let Some(name) = name {
+                visit(name, if set { TRUE } else { FALSE });
+            }
+        }
+    }
+
+    /// The characters of the flags in the record, missing flags are unset.
+    pub(crate) fn combine<S: RecordSource + ?Sized>(&self, data: &S) -> String {
+        self.names
+            .iter()
+            .map(|name| match self.is_set(data, name) {
+                true => self.set,
+                false => self.unset,
+            })
+            .collect()
+    }
+
+    /// The bits of the flags in the record, in `width` bytes.
+    pub(crate) fn combine_bits<S: RecordSource + ?Sized>(&self, data: &S, width: usize) -> Vec<u8> {
+        let mut bytes = vec![0; width];
+        for (i, name) in self.names.iter().enumerate().take(width * 8) {
+            if self.is_set(data, name) {
+                bytes[i / 8] |= 0x80 >> (i % 8);
+            }
+        }
+        bytes
+    }
+
+    /// Whether the flag is set in the record, by `true`, `yes`, `y`, `1` or its set character.
+    fn is_set<S: RecordSource + ?Sized>(&self, data: &S, name: &Option<String>) -> bool {
+        let value = match name.as_deref().and_then(|name| data.get(name)) {
+            Some(value) => value.trim(),
+            None => return false,
+        };
+        ["true", "yes", "y", "1"]
+            .iter()
+            .any(|t| value.eq_ignore_ascii_case(t))
+            || value == self.set.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        builder::{Buildable, Builder},
+        flat::fixed::{Parser, Record},
+    };
+
+    #[test]
+    fn expand_and_combine() {
+        let flags = Flags::new(["active", "", "vip"]);
+        let mut record = Record::new();
+
+        flags.expand("YYN", |name, value| {
+            record.insert(name.to_string(), value.to_string());
+        });
+        assert_eq!(record.len(), 2);
+        assert_eq!(record["active"], "true");
+        assert_eq!(record["vip"], "false");
+
+        record.insert(String::from("vip"), String::from("1"));
+        assert_eq!(flags.combine(&record), "YNY");
+        assert_eq!(flags.combine_bits(&record, 1), vec![0b1010_0000]);
+    }
+
+    #[test]
+    fn parse_and_format_flags() {
+        let flags = Flags::new(["active", "vip", "closed"]).chars('1', '0');
+        let parser = Parser::builder()
+            .field("id")
+            .width(2)
+            .append()
+            .field("status")
+            .width(3)
+            .flags(&flags)
+            .append()
+            .build();
+
+        let record = parser.parse("01101").unwrap();
+        assert_eq!(record.len(), 4);
+        assert!(!record.contains_key("status"));
+        assert_eq!(record["vip"], "false");
+        assert_eq!(record["closed"], "true");
+        assert_eq!(parser.format(&record), "01101");
+
+        let mut reused = Record::new();
+        reused.insert(String::from("other"), String::from("x"));
+        parser.parse_into("02110", &mut reused).unwrap();
+        assert_eq!(reused.len(), 4);
+        assert_eq!(reused["vip"], "true");
+
+        let parser = Parser::builder()
+            .field("status")
+            .width(1)
+            .flags(&flags)
+            .append()
+            .build();
+        let record = parser.decode_bytes(b"\x60").unwrap();
+        assert_eq!(record["active"], "false");
+        assert_eq!(record["closed"], "true");
+        assert_eq!(parser.encode_bytes(&record).unwrap(), b"\x60");
+    }
+}
@@ -1,19 +1,73 @@
 use crate::{
-    error::{Error, ParseError},
-    utilities::string::{fixed_width, strip_padding, Align},
+    error::{Error, ParseErrorKind},
+    utilities::string::{
+        convert_case, fixed_width_into, fixed_width_with, strip, strip_with, trim, Align, Case,
+        StripMode, Trim, TruncateSide, WidthMode,
+    },
+    HashMap,
 };
-use std::{
-    collections::HashMap,
+use alloc::{
+    borrow::Cow,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{
+    cmp::Ordering,
     convert::{From, Into, TryInto},
     fmt::Debug,
     ops::Range,
     result::Result,
     str::Chars,
 };
-
-mod builder;
-mod read;
-mod write;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+pub mod adapters;
+pub mod builder;
+pub mod bytes;
+pub mod check;
+pub mod codes;
+#[cfg(feature = "std")]
+pub mod compare;
+mod compose;
+#[cfg(feature = "std")]
+pub mod dedupe;
+mod describe;
+pub mod diff;
+pub mod enrich;
+pub mod flags;
+#[cfg(feature = "std")]
+pub mod multi;
+pub mod numeric;
+mod plan;
+#[cfg(feature = "std")]
+pub mod progress;
+#[cfg(feature = "std")]
+pub mod read;
+pub mod record;
+#[cfg(feature = "std")]
+pub mod redact;
+#[cfg(feature = "std")]
+pub mod reject;
+pub mod row;
+#[cfg(feature = "std")]
+pub mod segment;
+#[cfg(feature = "std")]
+pub mod split;
+pub mod validation;
+#[cfg(feature = "std")]
+pub mod write;
+
+use self::{
+    bytes::FieldKind,
+    check::CheckDigit,
+    codes::CodeTable,
+    numeric::Sign,
+    plan::Plan,
+    record::{RecordSink, RecordSource},
+    row::RowRecord,
+};
 
 pub type Record = HashMap<String, String>;
 pub type ResultRecord = Result<Record, Error>;
@@ -22,40 +76,337 @@ pub type ResultRecord = Result<Record, Error>;
 pub struct Parser<'a> {
     fields: Vec<Field<'a>>,
     width: usize,
+    plan: Plan,
+    /// The width of the trailing filler added to reach the declared record width, if any.
+    filler: Option<usize>,
 }
 
 #[allow(dead_code)]
 impl<'a> Parser<'a> {
-    fn parse<T: Into<String>>(&self, s: T) -> ResultRecord {
-        let s: String = s.into();
-        let mut iter = s.chars();
-        let c = &mut iter.by_ref();
-        match c.size_hint() {
-            (_, Some(max)) if max < self.width => {
-                return Err(Error::from(ParseError::ImsufficentBuffer(
-                    self.width,
-                    Some(max),
-                )))
+    /// Creates a parser for the fields, compiling the plan used to slice lines.
+    pub(crate) fn new(fields: Vec<Field<'a>>) -> Self {
+        Parser {
+            width: fields.iter().map(|f| f.width).sum(),
+            plan: Plan::compile(&fields),
+            fields,
+            filler: None,
+        }
+    }
+
+    /// A copy of the parser without its compiled plan, walking the chars of every line as
+    /// lines that are not ASCII are parsed. Only for benchmarking the plan.
+    #[cfg(feature = "bench")]
+    #[doc(hidden)]
+    pub fn unplanned(&self) -> Parser<'a> {
+        Parser {
+            fields: self.fields.clone(),
+            width: self.width,
+            plan: self.plan.unsliced(),
+            filler: self.filler,
+        }
+    }
+
+    /// The fields of the layout, in order, including spacers.
+    pub fn fields(&self) -> &[Field<'a>] {
+        &self.fields
+    }
+
+    /// The total width of a record, in characters.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Checks the length of the line before it is sliced. With a declared record width, a line
+    /// missing some of the trailing filler is padded and a longer line fails, otherwise a line
+    /// only fails if it is too short.
+    fn fit<'s>(&self, s: &'s str) -> Result<Cow<'s, str>, Error> {
+        let required = self.width - self.filler.unwrap_or(0);
+        if s.len() < required {
+            return Err(Error::from(ParseErrorKind::InsufficientBuffer {
+                required,
+                available: Some(s.len()),
+            }));
+        }
+        let last = match (self.filler, self.fields.last()) {
+            (Some(_), Some(last)) => last,
+            _ => return Ok(Cow::Borrowed(s)),
+        };
+        let length = last.width_mode.measure(s);
+        match length.cmp(&self.width) {
+            Ordering::Less => {
+                let mut padded = String::with_capacity(s.len() + self.width - length);
+                padded.push_str(s);
+                padded.extend(core::iter::repeat_n(last.padding, self.width - length));
+                Ok(Cow::Owned(padded))
             }
-            (_, None) => return Err(Error::from(ParseError::ImsufficentBuffer(self.width, None))),
-            _ => (),
+            Ordering::Equal => Ok(Cow::Borrowed(s)),
+            Ordering::Greater => Err(Error::from(ParseErrorKind::RecordLength {
+                expected: self.width,
+                actual: length,
+            })),
         }
+    }
 
-        let mut map = HashMap::new();
+    /// A parser for the same layout that only extracts the selected fields, in layout order.
+    /// The other fields become spacers and are skipped over without being extracted, which
+    /// speeds up parsing wide layouts when only a few fields are needed. Records formatted with
+    /// the projection have the other fields blank.
+    pub fn project(&self, fields: &[&str]) -> Result<Parser<'a>, Error> {
+        if let Some(unknown) = fields
+            .iter()
+            .find(|name| !self.fields.iter().any(|f| f.name == Some(**name)))
+        {
+            return Err(Error::Layout(format!("Unknown field {}", unknown)));
+        }
+        let mut projected: Vec<Field<'a>> = Vec::with_capacity(fields.len() * 2 + 1);
         for field in &self.fields {
-            field.parse(&mut map, c);
+            if field.name.is_some_and(|name| fields.contains(&name)) {
+                projected.push(*field);
+                continue;
+            }
+            match projected.last_mut() {
+                // Adjacent spacers counting chars are skipped as one.
+                Some(last)
+                    if last.name.is_none()
+                        && last.width_mode == WidthMode::Chars
+                        && field.width_mode == WidthMode::Chars =>
+                {
+                    last.width += field.width;
+                }
+                _ => projected.push(Field {
+                    name: None,
+                    ..*field
+                }),
+            }
         }
+        Ok(Parser {
+            filler: self.filler,
+            ..Parser::new(projected)
+        })
+    }
+
+    pub fn parse<T: Into<String>>(&self, s: T) -> ResultRecord {
+        let mut map = HashMap::with_capacity(self.fields.len());
+        self.parse_into(&s.into(), &mut map)?;
         Ok(map)
     }
 
-    fn format(&self, data: Record) -> String {
-        self.fields
-            .iter()
-            .fold(String::with_capacity(self.width), |mut s, f| {
-                s.push_str(&*f.format(&data));
-                s
-            })
+    /// Parses the line into an existing record, reusing its allocations. Values of fields in
+    /// the layout are overwritten in place and any other entries are removed, so the same
+    /// record can be passed for every line of a file.
+    pub fn parse_into(&self, s: &str, record: &mut Record) -> Result<(), Error> {
+        let s = self.fit(s)?;
+        let mut slicer = self.plan.slicer(&s);
+        let mut written = 0;
+        for (i, field) in self.fields.iter().enumerate() {
+            let raw = slicer.take(field);
+            let name = match field.name {
+                // The first field with a name wins, as when parsing into a new record.
+                Some(name) if self.plan.is_first(i) => name,
+                _ => continue,
+            };
+            if let FieldKind::Flags(flags) = field.kind {
+                flags.expand(raw, |flag, value| {
+                    match record.get_mut(flag) {
+                        Some(v) => {
+                            v.clear();
+                            v.push_str(value);
+                        }
+                        None => {
+                            record.insert(flag.to_string(), value.to_string());
+                        }
+                    }
+                    written += 1;
+                });
+                continue;
+            }
+            let value = field.value(raw).map(|v| field.verify(v)).transpose()?;
+            let description = match &value {
+                Some(value) => field.decode(value)?,
+                None => None,
+            };
+            match (field.description_field, description) {
+                (Some(to), Some(description)) => {
+                    match record.get_mut(to) {
+                        Some(v) => {
+                            v.clear();
+                            v.push_str(description);
+                        }
+                        None => {
+                            record.insert(to.to_string(), description.to_string());
+                        }
+                    }
+                    written += 1;
+                }
+                (Some(to), None) => {
+                    record.remove(to);
+                }
+                (None, _) => (),
+            }
+            match (record.get_mut(name), value) {
+                (Some(v), Some(value)) => {
+                    v.clear();
+                    v.push_str(&value);
+                }
+                (None, Some(value)) => {
+                    record.insert(name.to_string(), value.into_owned());
+                }
+                (_, None) => {
+                    record.remove(name);
+                    continue;
+                }
+            }
+            written += 1;
+        }
+        if record.len() > written {
+            record.retain(|k, _| self.plan.produces(k));
+        }
+        Ok(())
+    }
+
+    /// Parses the line, calling `visit` with the name and value of each named field in layout
+    /// order instead of building a record. Values are borrowed from the line unless a fill, case
+    /// or default changes them, and null fields are not visited. As when parsing into a record,
+    /// only the first of the fields with the same name is visited.
+    pub fn parse_with<F>(&self, s: &str, mut visit: F) -> Result<(), Error>
+    where
+        F: FnMut(&str, &str),
+    {
+        let s = self.fit(s)?;
+        let mut slicer = self.plan.slicer(&s);
+        for (i, field) in self.fields.iter().enumerate() {
+            let raw = slicer.take(field);
+            let name = match field.name {
+                Some(name) if self.plan.is_first(i) => name,
+                _ => continue,
+            };
+            if let FieldKind::Flags(flags) = field.kind {
+                flags.expand(raw, &mut visit);
+                continue;
+            }
+            if let Some(value) = field.value(raw).map(|v| field.verify(v)).transpose()? {
+                let description = field.decode(&value)?;
+                visit(name, &value);
+                if let (Some(to), Some(description)) = (field.description_field, description) {
+                    visit(to, description);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Parses the line into any container implementing [`RecordSink`], such as a map or a
+    /// user struct, setting the value of each named field that is not null.
+    pub fn parse_to<S>(&self, s: &str, sink: &mut S) -> Result<(), Error>
+    where
+        S: RecordSink + ?Sized,
+    {
+        self.parse_with(s, |name, value| sink.set(name, value))
+    }
+
+    /// Parses the line into a record holding the values of the named fields in layout order.
+    pub fn parse_row<T: Into<String>>(&self, s: T) -> Result<RowRecord, Error> {
+        let mut row = RowRecord::new();
+        self.parse_row_into(&s.into(), &mut row)?;
+        Ok(row)
     }
+
+    /// Parses the line into an existing row, reusing its allocations.
+    pub fn parse_row_into(&self, s: &str, row: &mut RowRecord) -> Result<(), Error> {
+        let s = self.fit(s)?;
+        let mut slicer = self.plan.slicer(&s);
+        let mut index = 0;
+        for field in &self.fields {
+            let raw = slicer.take(field);
+            if field.name.is_some() {
+                let value = field.value(raw).map(|v| field.verify(v)).transpose()?;
+                if let Some(value) = &value {
+                    field.decode(value)?;
+                }
+                row.set(index, value.as_deref());
+                index += 1;
+            }
+        }
+        row.truncate(index);
+        Ok(())
+    }
+
+    pub fn format(&self, data: &Record) -> String {
+        self.format_from(data)
+    }
+
+    /// Formats the record, appending it to `buf`. Reusing the buffer across records avoids
+    /// allocating a line per record.
+    pub fn format_into(&self, data: &Record, buf: &mut String) {
+        self.format_from_into(data, buf)
+    }
+
+    /// Formats any container implementing [`RecordSource`], such as a map or a user struct.
+    pub fn format_from<S>(&self, data: &S) -> String
+    where
+        S: RecordSource + ?Sized,
+    {
+        let mut s = String::with_capacity(self.width);
+        self.format_from_into(data, &mut s);
+        s
+    }
+
+    /// Formats any container implementing [`RecordSource`], appending it to `buf`.
+    pub fn format_from_into<S>(&self, data: &S, buf: &mut String)
+    where
+        S: RecordSource + ?Sized,
+    {
+        buf.reserve(self.width);
+        for field in &self.fields {
+            field.format_from_into(data, buf);
+        }
+    }
+
+    /// Formats a row, taking the values of the named fields in layout order. Null and missing
+    /// values are formatted like fields missing from a record.
+    pub fn format_row(&self, row: &RowRecord) -> String {
+        let mut values = row.iter();
+        let mut s = String::with_capacity(self.width);
+        for field in &self.fields {
+            let value = match field.name {
+                Some(_) => values.next().flatten(),
+                None => Some(""),
+            };
+            field.format_value_into(value, &mut s);
+        }
+        s
+    }
+}
+
+/// When the value of a field is null, as opposed to empty. Null fields are left out of parsed
+/// records, and fields missing from a record are formatted with the null representation.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Null<'a> {
+    /// The field only contains whitespace, formatted as spaces.
+    Blank,
+    /// Every character of the field is the given character, e.g. all 9s.
+    Filled(char),
+    /// The field, without padding, is the given value, e.g. `NULL`.
+    Value(&'a str),
+}
+
+impl<'a> Null<'a> {
+    fn matches(&self, raw: &str, value: &str) -> bool {
+        match self {
+            Null::Blank => raw.chars().all(char::is_whitespace),
+            Null::Filled(c) => !raw.is_empty() && raw.chars().all(|r| r == *c),
+            Null::Value(v) => value == *v,
+        }
+    }
+}
+
+/// A record sequence number, filled by the writer from `start` increasing by `step` and
+/// checked by the reader to increase.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Sequence {
+    pub start: u64,
+    pub step: u64,
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -64,6 +415,20 @@ pub struct Field<'a> {
     width: usize,
     align: Align,
     padding: char,
+    default_value: Option<&'a str>,
+    null: Option<Null<'a>>,
+    fill: Option<&'a str>,
+    strip: StripMode,
+    trim: Trim,
+    case: Option<Case>,
+    width_mode: WidthMode,
+    truncate: TruncateSide,
+    numeric: Option<Sign>,
+    codes: Option<&'a CodeTable>,
+    description_field: Option<&'a str>,
+    sequence: Option<Sequence>,
+    check_digit: Option<&'a dyn CheckDigit>,
+    kind: FieldKind<'a>,
 }
 
 #[allow(dead_code)]
@@ -74,6 +439,20 @@ impl<'a> Field<'a> {
             width,
             align,
             padding,
+            default_value: None,
+            null: None,
+            fill: None,
+            strip: StripMode::AlignedSide,
+            trim: Trim::None,
+            case: None,
+            width_mode: WidthMode::Chars,
+            truncate: TruncateSide::Left,
+            numeric: None,
+            codes: None,
+            description_field: None,
+            sequence: None,
+            check_digit: None,
+            kind: FieldKind::Text,
         }
     }
 
@@ -100,7 +479,11 @@ impl<'a> Field<'a> {
     pub fn with_align<T: TryInto<Align>>(mut self, align: T) -> Self {
         match align.try_into() {
             Ok(align) => self.align = align,
-            Err(_) => eprintln!("Unable to parse argument as Align"),
+            Err(_) => {
+                // Without `std` an invalid alignment is ignored silently.
+                #[cfg(feature = "std")]
+                eprintln!("Unable to parse argument as Align");
+            }
         }
         self
     }
@@ -110,6 +493,98 @@ impl<'a> Field<'a> {
         self
     }
 
+    /// Pads with a repeated string instead of the padding character, e.g. `**`.
+    pub fn with_fill(mut self, fill: &'a str) -> Self {
+        self.fill = Some(fill);
+        self
+    }
+
+    /// Sets how the width of the field is counted.
+    pub fn with_width_mode(mut self, width_mode: WidthMode) -> Self {
+        self.width_mode = width_mode;
+        self
+    }
+
+    /// Sets the value used when the field is entirely padding on parse, or missing from the
+    /// record on format.
+    pub fn with_default_value(mut self, value: &'a str) -> Self {
+        self.default_value = Some(value);
+        self
+    }
+
+    /// Sets when the field is null.
+    pub fn with_null_when(mut self, null: Null<'a>) -> Self {
+        self.null = Some(null);
+        self
+    }
+
+    /// Sets which sides of the value the padding is stripped from on parse.
+    pub fn with_strip(mut self, strip: StripMode) -> Self {
+        self.strip = strip;
+        self
+    }
+
+    /// Sets the whitespace trimmed from the value on parse, after the padding is stripped.
+    pub fn with_trim(mut self, trim: Trim) -> Self {
+        self.trim = trim;
+        self
+    }
+
+    /// Sets the case the value is converted to on parse.
+    pub fn with_case(mut self, case: Case) -> Self {
+        self.case = Some(case);
+        self
+    }
+
+    /// Sets which characters are kept when a value is too long on format.
+    pub fn with_truncate_side(mut self, truncate: TruncateSide) -> Self {
+        self.truncate = truncate;
+        self
+    }
+
+    /// Makes the field numeric: thousands separators and currency symbols are removed and the
+    /// sign moved to the front on parse, and the value is zero-filled with the sign in its
+    /// position on format. Values that are not numbers are handled like text.
+    pub fn with_numeric(mut self, sign: Sign) -> Self {
+        self.numeric = Some(sign);
+        self
+    }
+
+    /// Restricts the values of the field to the codes of the table, parsing any other value
+    /// fails. Null values are not checked.
+    pub fn with_codes(mut self, codes: &'a CodeTable) -> Self {
+        self.codes = Some(codes);
+        self
+    }
+
+    /// Adds the description of the code to parsed records under the given name. Rows only hold
+    /// the fields of the layout and do not get the description.
+    pub fn with_description_field(mut self, name: &'a str) -> Self {
+        self.description_field = Some(name);
+        self
+    }
+
+    /// Makes the field a record sequence number, see [`Sequence`]. The writer replaces any
+    /// value of the field with the next number.
+    pub fn with_sequence(mut self, sequence: Sequence) -> Self {
+        self.sequence = Some(sequence);
+        self
+    }
+
+    /// Follows the value with a check digit, computed and appended on format, and verified and
+    /// removed on parse.
+    pub fn with_check_digit(mut self, check_digit: &'a dyn CheckDigit) -> Self {
+        self.check_digit = Some(check_digit);
+        self
+    }
+
+    /// Sets how the field is decoded, see [`FieldKind`]. Only flags apply to lines, other
+    /// kinds are decoded by the bytes pipeline, see [`Parser::decode_bytes`].
+    pub fn with_kind(mut self, kind: FieldKind<'a>) -> Self {
+        self.kind = kind;
+        self
+    }
+
     pub fn name(&self) -> Option<&str> {
         self.name
     }
@@ -126,33 +601,269 @@ impl<'a> Field<'a> {
         self.padding
     }
 
+    pub fn default_value(&self) -> Option<&str> {
+        self.default_value
+    }
+
+    pub fn null_when(&self) -> Option<Null<'a>> {
+        self.null
+    }
+
+    pub fn fill(&self) -> Option<&str> {
+        self.fill
+    }
+
+    pub fn width_mode(&self) -> WidthMode {
+        self.width_mode
+    }
+
+    pub fn strip(&self) -> StripMode {
+        self.strip
+    }
+
+    pub fn trim(&self) -> Trim {
+        self.trim
+    }
+
+    pub fn case(&self) -> Option<Case> {
+        self.case
+    }
+
+    pub fn truncate_side(&self) -> TruncateSide {
+        self.truncate
+    }
+
+    pub fn numeric(&self) -> Option<Sign> {
+        self.numeric
+    }
+
+    pub fn sequence(&self) -> Option<Sequence> {
+        self.sequence
+    }
+
+    pub fn check_digit(&self) -> Option<&'a dyn CheckDigit> {
+        self.check_digit
+    }
+
+    pub fn kind(&self) -> FieldKind<'a> {
+        self.kind
+    }
+
+    pub fn codes(&self) -> Option<&'a CodeTable> {
+        self.codes
+    }
+
+    pub fn description_field(&self) -> Option<&'a str> {
+        self.description_field
+    }
+
+    /// The keys parsing the field adds to records besides the name of the field, the
+    /// description of its code and its flags.
+    fn produces(&self) -> impl Iterator<Item = &'a str> {
+        let flags = match self.kind {
+            FieldKind::Flags(flags) => Some(flags.names()),
+            _ => None,
+        };
+        self.description_field
+            .into_iter()
+            .chain(flags.into_iter().flatten())
+    }
+
+    /// Verifies the check digit of the value, returning the value without it.
+    fn verify<'v>(&self, value: Cow<'v, str>) -> Result<Cow<'v, str>, Error> {
+        let check_digit = match self.check_digit {
+            Some(check_digit) => check_digit,
+            None => return Ok(value),
+        };
+        match check::split(&value) {
+            Some((payload, _)) if check_digit.verify(&value) => Ok(match value {
+                Cow::Borrowed(s) => Cow::Borrowed(&s[..payload.len()]),
+                Cow::Owned(ref s) => Cow::Owned(s[..payload.len()].to_string()),
+            }),
+            _ => Err(Error::Parse {
+                line: None,
+                field: self.name.map(String::from),
+                kind: ParseErrorKind::CheckDigit(value.into_owned()),
+            }),
+        }
+    }
+
+    /// Checks the value against the code table, returning the description of the code.
+    fn decode(&self, value: &str) -> Result<Option<&'a str>, Error> {
+        match self.codes {
+            Some(codes) => match codes.describe(value) {
+                Some(description) => Ok(Some(description)),
+                None => Err(Error::Parse {
+                    line: None,
+                    field: self.name.map(String::from),
+                    kind: ParseErrorKind::UnknownCode(value.to_string()),
+                }),
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// Whether the value is longer than the field and would be truncated on format.
+    pub fn overflows(&self, value: &str) -> bool {
+        let width = self
+            .numeric
+            .and_then(|sign| numeric::width(value, sign))
+            .unwrap_or_else(|| self.width_mode.measure(value));
+        width + usize::from(self.check_digit.is_some()) > self.width()
+    }
+
     fn parse(&self, map: &mut HashMap<String, String>, chars: &mut Chars) {
-        let width = self.width() as usize;
-        if let Some(name) = self.name {
-            map.entry(name.to_string()).or_insert_with(|| {
-                strip_padding(
-                    &chars.take(width).collect::<String>(),
-                    self.align(),
-                    self.padding(),
-                )
-                .to_string()
+        let raw = self.take(chars);
+        if let (Some(_), FieldKind::Flags(flags)) = (self.name, self.kind) {
+            flags.expand(raw, |flag, value| {
+                map.entry(flag.to_string())
+                    .or_insert_with(|| value.to_string());
             });
-        } else {
-            chars.take(width).for_each(|_| {});
+            return;
+        }
+        let value = self.value(raw);
+        if let (Some(name), Some(value)) = (self.name, value) {
+            map.entry(name.to_string())
+                .or_insert_with(|| value.into_owned());
+        }
+    }
+
+    /// Consumes the characters of the field, returning them unchanged.
+    fn take<'s>(&self, chars: &mut Chars<'s>) -> &'s str {
+        if self.width_mode != WidthMode::Chars {
+            let (value, rest) = self.width_mode.split(chars.as_str(), self.width());
+            *chars = rest.chars();
+            return value;
+        }
+        let rest = chars.as_str();
+        chars.take(self.width()).for_each(|_| {});
+        &rest[..rest.len() - chars.as_str().len()]
+    }
+
+    /// Converts the characters taken from a line into the value of the field, `None` if null.
+    fn value<'v>(&'v self, raw: &'v str) -> Option<Cow<'v, str>> {
+        let value = match self.fill {
+            Some(fill) => strip_with(raw, self.align(), fill, self.strip),
+            None => strip(raw, self.align(), self.padding(), self.strip),
+        };
+        if self.null.is_some_and(|n| n.matches(raw, &value)) {
+            return None;
+        }
+        let value = match self.numeric {
+            Some(_) => numeric::parse(raw),
+            None => self.normalize(value),
+        };
+        match self.default_value {
+            Some(default) if value.is_empty() => Some(Cow::Borrowed(default)),
+            _ => Some(value),
         }
     }
 
-    fn format(&self, data: &Record) -> String {
-        let mut s = String::with_capacity(self.width() as usize);
-        if let Some(name) = self.name() {
-            if let Some(data) = data.get(name) {
-                s.push_str(data);
+    /// Applies the trim and case options to a value stripped of its padding.
+    fn normalize<'v>(&self, value: Cow<'v, str>) -> Cow<'v, str> {
+        let value = match value {
+            Cow::Borrowed(s) => Cow::Borrowed(trim(s, self.trim)),
+            Cow::Owned(s) if self.trim != Trim::None => Cow::Owned(trim(&s, self.trim).to_string()),
+            value => value,
+        };
+        match (self.case, value) {
+            (Some(case), Cow::Borrowed(s)) => convert_case(s, case),
+            (Some(case), Cow::Owned(s)) => Cow::Owned(convert_case(&s, case).into_owned()),
+            (None, value) => value,
+        }
+    }
+
+    pub fn format(&self, data: &Record) -> String {
+        let mut s = String::with_capacity(self.width());
+        self.format_into(data, &mut s);
+        s
+    }
+
+    /// Formats the value of the field in the record, appending it to `buf`.
+    pub fn format_into(&self, data: &Record, buf: &mut String) {
+        self.format_from_into(data, buf)
+    }
+
+    /// Formats the value of the field in any [`RecordSource`], appending it to `buf`.
+    pub fn format_from_into<S>(&self, data: &S, buf: &mut String)
+    where
+        S: RecordSource + ?Sized,
+    {
+        let value = match (self.name(), self.kind) {
+            (Some(_), FieldKind::Flags(flags)) => {
+                return self.format_value_into(Some(&flags.combine(data)), buf)
             }
+            (Some(name), _) => data.get(name),
+            (None, _) => Some(""),
+        };
+        self.format_value_into(value, buf)
+    }
+
+    /// Formats a value, `None` if it is missing, appending it to `buf`.
+    fn format_value_into(&self, value: Option<&str>, buf: &mut String) {
+        let checked;
+        let value = match (value, self.null) {
+            (Some(value), _) => match self.check_digit.and_then(|c| c.compute(value)) {
+                Some(digit) => {
+                    checked = format!("{}{}", value, digit);
+                    checked.as_str()
+                }
+                None => value,
+            },
+            (None, Some(Null::Blank)) => {
+                buf.extend(core::iter::repeat_n(' ', self.width()));
+                return;
+            }
+            (None, Some(Null::Filled(c))) => {
+                buf.extend(core::iter::repeat_n(c, self.width()));
+                return;
+            }
+            (None, Some(Null::Value(v))) => v,
+            (None, None) => self.default_value.unwrap_or(""),
+        };
+        if let Some(sign) = self.numeric {
+            let keep_right = self.truncate == TruncateSide::Right;
+            if numeric::format_into(value, self.width(), sign, keep_right, buf) {
+                return;
+            }
+        }
+        let value = match self.truncate {
+            TruncateSide::Right => keep_right(value, self.width(), self.width_mode),
+            _ => value,
+        };
+        let (value, width) = match self.width_mode {
+            WidthMode::Chars => (value, self.width()),
+            // Converts the columns into chars for this value, assuming the padding takes one
+            // column per char.
+            mode => {
+                let value = mode.split(value, self.width()).0;
+                let width = value.chars().count() + self.width() - mode.measure(value);
+                (value, width)
+            }
+        };
+        match self.fill {
+            Some(fill) => buf.push_str(&fixed_width_with(value, width, self.align(), fill)),
+            None => fixed_width_into(value, width, self.align(), self.padding(), buf),
         }
-        fixed_width(&*s, self.width(), self.align(), self.padding()).to_string()
     }
 }
 
+/// The longest suffix of the value that fits in the number of columns.
+fn keep_right(value: &str, width: usize, mode: WidthMode) -> &str {
+    if mode == WidthMode::Chars {
+        let len = value.chars().count();
+        return match value.char_indices().nth(len.saturating_sub(width)) {
+            Some((i, _)) if len > width => &value[i..],
+            _ => value,
+        };
+    }
+    let mut chars = value.chars();
+    while mode.measure(chars.as_str()) > width {
+        chars.next();
+    }
+    chars.as_str()
+}
+
 impl<'a> Default for Field<'a> {
     fn default() -> Self {
         Self {
@@ -160,6 +871,20 @@ impl<'a> Default for Field<'a> {
             width: 0,
             align: Align::Left,
             padding: ' ',
+            default_value: None,
+            null: None,
+            fill: None,
+            strip: StripMode::AlignedSide,
+            trim: Trim::None,
+            case: None,
+            width_mode: WidthMode::Chars,
+            truncate: TruncateSide::Left,
+            numeric: None,
+            codes: None,
+            description_field: None,
+            sequence: None,
+            check_digit: None,
+            kind: FieldKind::Text,
         }
     }
 }
@@ -170,10 +895,7 @@ mod tests {
 
     #[test]
     fn check_parser() {
-        let parser = Parser {
-            fields: Vec::new(),
-            width: 0,
-        };
+        let parser = Parser::new(Vec::new());
 
         assert_eq!(parser.fields.len(), 0);
     }
@@ -181,13 +903,13 @@ mod tests {
     #[test]
     fn check_format() {
         let fields = vec![Field::default().with_name("test").with_range(0..10)];
-        let parser = Parser { fields, width: 10 };
+        let parser = Parser::new(fields);
         let data: HashMap<String, String> = [(String::from("test"), String::from("ABCD"))]
             .iter()
             .cloned()
             .collect();
 
-        assert_eq!(parser.format(data), "ABCD      ");
+        assert_eq!(parser.format(&data), "ABCD      ");
     }
 
     #[test]
@@ -200,7 +922,7 @@ mod tests {
                 .with_align(Align::Right)
                 .with_padding('0'),
         ];
-        let parser = Parser { fields, width: 10 };
+        let parser = Parser::new(fields);
         let data: HashMap<String, String> = [
             (String::from("test-1"), String::from("ABCD")),
             (String::from("test-2"), String::from("1234")),
@@ -209,7 +931,7 @@ mod tests {
         .cloned()
         .collect();
 
-        assert_eq!(parser.format(data), "ABCD 01234");
+        assert_eq!(parser.format(&data), "ABCD 01234");
     }
 
     #[test]
@@ -222,19 +944,145 @@ mod tests {
                 .with_align(Align::Right)
                 .with_padding('0'),
         ];
-        let parser = Parser { fields, width: 10 };
+        let parser = Parser::new(fields);
         let data: HashMap<String, String> = [(String::from("test-1"), String::from("ABCD"))]
             .iter()
             .cloned()
             .collect();
 
-        assert_eq!(parser.format(data), "ABCD 00000");
+        assert_eq!(parser.format(&data), "ABCD 00000");
+    }
+
+    #[test]
+    fn check_field_truncate_side() {
+        let field = Field::default()
+            .with_name("test")
+            .with_width(3)
+            .with_truncate_side(TruncateSide::Right);
+        let format = |field: Field, value: &str| {
+            let data = [(String::from("test"), value.to_string())].into();
+            field.format(&data)
+        };
+
+        assert_eq!(format(field, "12345"), "345");
+        assert_eq!(format(field, "日本語AB"), "語AB");
+        assert_eq!(
+            format(field.with_width_mode(WidthMode::DisplayWidth), "日本語AB"),
+            "AB "
+        );
+        assert_eq!(
+            format(field.with_truncate_side(TruncateSide::Error), "12345"),
+            "123"
+        );
+        assert!(field.overflows("1234"));
+    }
+
+    #[test]
+    fn check_field_numeric() {
+        let field = Field::default()
+            .with_name("amount")
+            .with_width(8)
+            .with_numeric(Sign::Trailing);
+        let mut record = HashMap::new();
+
+        field.parse(&mut record, &mut "$1,234CR".chars());
+        assert_eq!(record["amount"], "-1234");
+        assert_eq!(field.format(&record), "0001234-");
+        record.insert(String::from("amount"), String::from("n/a"));
+        assert_eq!(field.format(&record), "n/a     ");
+        assert!(field.with_width(4).overflows("-1234"));
+    }
+
+    #[test]
+    fn check_field_codes() {
+        let codes = CodeTable::new().code("A", "Active").code("I", "Inactive");
+        let fields = vec![
+            Field::default()
+                .with_name("status")
+                .with_width(1)
+                .with_codes(&codes)
+                .with_description_field("status_name")
+                .with_null_when(Null::Blank),
+            Field::default().with_name("id").with_width(3),
+        ];
+        let parser = Parser::new(fields);
+
+        let record = parser.parse("A001").unwrap();
+        assert_eq!(record["status_name"], "Active");
+        let mut record = record;
+        parser.parse_into("I002", &mut record).unwrap();
+        assert_eq!(record["status_name"], "Inactive");
+        assert_eq!(record.len(), 3);
+        parser.parse_into(" 003", &mut record).unwrap();
+        assert_eq!(record, parser.parse(" 003").unwrap());
+        assert_eq!(record.len(), 1);
+        assert!(matches!(
+            parser.parse("X003"),
+            Err(Error::Parse {
+                kind: ParseErrorKind::UnknownCode(_),
+                ..
+            })
+        ));
+        assert!(parser.parse_row("X003").is_err());
+    }
+
+    #[test]
+    fn check_field_check_digit() {
+        let fields = vec![
+            Field::default()
+                .with_name("routing")
+                .with_width(9)
+                .with_check_digit(&check::Mod10::ABA),
+            Field::default()
+                .with_name("account")
+                .with_width(12)
+                .with_align(Align::Right)
+                .with_padding('0')
+                .with_check_digit(&check::Luhn),
+        ];
+        let parser = Parser::new(fields);
+
+        let record = parser.parse("011000015079927398713").unwrap();
+        assert_eq!(record["routing"], "01100001");
+        assert_eq!(record["account"], "7992739871");
+        assert_eq!(parser.format(&record), "011000015079927398713");
+        assert!(matches!(
+            parser.parse("011000016079927398713"),
+            Err(Error::Parse {
+                kind: ParseErrorKind::CheckDigit(_),
+                ..
+            })
+        ));
+        assert!(parser.parse_row("011000015079927398710").is_err());
+    }
+
+    #[test]
+    fn check_format_into_reuses_buffer() {
+        let fields = vec![
+            Field::default().with_name("test-1").with_range(0..3),
+            Field::default()
+                .with_name("test-2")
+                .with_range(3..6)
+                .with_null_when(Null::Filled('*')),
+        ];
+        let parser = Parser::new(fields);
+        let data: HashMap<String, String> = [(String::from("test-1"), String::from("AB"))]
+            .iter()
+            .cloned()
+            .collect();
+        let mut buf = String::new();
+
+        parser.format_into(&data, &mut buf);
+        assert_eq!(buf, "AB ***");
+        buf.clear();
+        parser.fields()[0].format_into(&data, &mut buf);
+        assert_eq!(buf, "AB ");
     }
 
     #[test]
     fn check_parsing() {
         let fields = vec![Field::default().with_name("test").with_range(0..10)];
-        let parser = Parser { fields, width: 10 };
+        let parser = Parser::new(fields);
 
         if let Ok(map) = parser.parse("1234567890") {
             assert!(map.contains_key("test"));
@@ -248,7 +1096,7 @@ mod tests {
             Field::default().with_name("test-1").with_range(0..5),
             Field::default().with_name("test-2").with_range(5..10),
         ];
-        let parser = Parser { fields, width: 10 };
+        let parser = Parser::new(fields);
 
         if let Ok(map) = parser.parse("1234567890") {
             assert_eq!(map.len(), 2);
@@ -265,7 +1113,7 @@ mod tests {
             Field::default().with_range(0..5),
             Field::default().with_name("test").with_range(5..10),
         ];
-        let parser = Parser { fields, width: 10 };
+        let parser = Parser::new(fields);
 
         if let Ok(map) = parser.parse("1234567890") {
             assert_eq!(map.len(), 1);
@@ -280,9 +1128,9 @@ mod tests {
             Field::default().with_range(0..5),
             Field::default().with_name("test").with_range(5..10),
         ];
-        let parser = Parser { fields, width: 10 };
+        let parser = Parser::new(fields);
         if let Err(e) = parser.parse("1234567") {
-            assert!(matches!(e, Error::ParserError(_)));
+            assert!(matches!(e, Error::Parse { .. }));
             assert_eq!(
                 e.to_string(),
                 "Insufficient buffer size, required 10 only 7 available"
@@ -418,6 +1266,197 @@ mod tests {
         assert_eq!(field.padding(), 'X');
     }
 
+    #[test]
+    fn check_field_default_value() {
+        let field = Field::default()
+            .with_name("test")
+            .with_range(0..4)
+            .with_align(Align::Right)
+            .with_padding('0')
+            .with_default_value("0");
+        let mut map = HashMap::new();
+        field.parse(&mut map, &mut "0000".chars());
+
+        assert_eq!(map.get("test"), Some(&String::from("0")));
+        assert_eq!(field.format(&HashMap::new()), "0000");
+        assert_eq!(
+            field.with_default_value("7").format(
+                &[(String::from("test"), String::new())]
+                    .iter()
+                    .cloned()
+                    .collect()
+            ),
+            "0000"
+        );
+        assert_eq!(
+            field.with_default_value("7").format(&HashMap::new()),
+            "0007"
+        );
+    }
+
+    #[test]
+    fn check_field_null_when() {
+        let blank = Field::default()
+            .with_name("test")
+            .with_range(0..4)
+            .with_align(Align::Right)
+            .with_padding('0')
+            .with_null_when(Null::Blank);
+        let filled = blank.with_null_when(Null::Filled('9'));
+        let value = blank.with_null_when(Null::Value("NULL"));
+
+        let parse = |field: Field, s: &str| {
+            let mut map = HashMap::new();
+            field.parse(&mut map, &mut s.chars());
+            map.remove("test")
+        };
+        assert_eq!(parse(blank, "    "), None);
+        assert_eq!(parse(blank, "0000"), Some(String::new()));
+        assert_eq!(parse(filled, "9999"), None);
+        assert_eq!(parse(filled, "0999"), Some(String::from("999")));
+        assert_eq!(parse(value, "NULL"), None);
+
+        let empty = HashMap::new();
+        assert_eq!(blank.format(&empty), "    ");
+        assert_eq!(filled.format(&empty), "9999");
+        assert_eq!(value.format(&empty), "NULL");
+        assert_eq!(
+            blank.format(
+                &[(String::from("test"), String::new())]
+                    .iter()
+                    .cloned()
+                    .collect()
+            ),
+            "0000"
+        );
+    }
+
+    #[test]
+    fn check_field_trim_and_case() {
+        let field = Field::default()
+            .with_name("test")
+            .with_range(0..8)
+            .with_padding('*');
+        let parse = |field: Field, s: &str| {
+            let mut map = HashMap::new();
+            field.parse(&mut map, &mut s.chars());
+            map.remove("test").unwrap()
+        };
+
+        assert_eq!(parse(field, " a  b ***"), " a  b ");
+        assert_eq!(parse(field.with_trim(Trim::Left), " a  b ***"), "a  b ");
+        assert_eq!(parse(field.with_trim(Trim::Right), " a  b ***"), " a  b");
+        assert_eq!(
+            parse(
+                field.with_trim(Trim::Both).with_case(Case::Upper),
+                " a  b ***"
+            ),
+            "A  B"
+        );
+        assert_eq!(parse(field.with_case(Case::Lower), "AbC*****"), "abc");
+    }
+
+    #[test]
+    fn check_field_fill() {
+        let field = Field::default()
+            .with_name("test")
+            .with_range(0..6)
+            .with_align(Align::Right)
+            .with_fill("*-");
+        let mut map = HashMap::new();
+        field.parse(&mut map, &mut "*-*123".chars());
+
+        assert_eq!(map.get("test"), Some(&String::from("123")));
+        assert_eq!(field.format(&map), "*-*123");
+    }
+
+    #[test]
+    fn check_field_width_mode() {
+        let field = Field::default().with_name("test").with_range(0..5);
+        let parser = Parser::new(vec![
+            field.with_width_mode(WidthMode::DisplayWidth),
+            Field::default()
+                .with_name("next")
+                .with_range(0..2)
+                .with_width_mode(WidthMode::DisplayWidth),
+        ]);
+        let record = parser.parse("日本AB ").unwrap();
+
+        assert_eq!(record.get("test"), Some(&String::from("日本A")));
+        assert_eq!(record.get("next"), Some(&String::from("B")));
+        assert_eq!(parser.format(&record), "日本AB ");
+
+        let field = field.with_width_mode(WidthMode::DisplayWidth);
+        let map = [(String::from("test"), String::from("日本語"))]
+            .iter()
+            .cloned()
+            .collect();
+        assert_eq!(field.format(&map), "日本 ");
+        let field = field.with_width_mode(WidthMode::Bytes);
+        assert_eq!(field.format(&map), "日  ");
+    }
+
+    #[cfg(feature = "unicode-segmentation")]
+    #[test]
+    fn check_field_width_mode_graphemes() {
+        let fields = vec![
+            Field::default()
+                .with_name("first")
+                .with_range(0..2)
+                .with_width_mode(WidthMode::Graphemes),
+            Field::default()
+                .with_name("second")
+                .with_range(0..2)
+                .with_width_mode(WidthMode::Graphemes),
+        ];
+        let parser = Parser::new(fields);
+        let record = parser.parse("e\u{301}\u{1F44D}\u{1F3FD}AB").unwrap();
+
+        assert_eq!(record.get("first").unwrap(), "e\u{301}\u{1F44D}\u{1F3FD}");
+        assert_eq!(record.get("second").unwrap(), "AB");
+        let map = [(String::from("first"), String::from("e\u{301}e\u{301}e"))]
+            .iter()
+            .cloned()
+            .collect();
+        assert_eq!(parser.fields()[0].format(&map), "e\u{301}e\u{301}");
+    }
+
+    #[test]
+    fn check_field_strip() {
+        let field = Field::default()
+            .with_name("test")
+            .with_range(0..7)
+            .with_align(Align::Right)
+            .with_padding('0');
+        let parse = |field: Field, s: &str| {
+            let mut map = HashMap::new();
+            field.parse(&mut map, &mut s.chars());
+            map.remove("test").unwrap()
+        };
+
+        assert_eq!(parse(field, "00123  "), "123  ");
+        assert_eq!(parse(field.with_strip(StripMode::Both), "00123  "), "123");
+        assert_eq!(
+            parse(field.with_strip(StripMode::None), "00123  "),
+            "00123  "
+        );
+    }
+
+    #[test]
+    fn check_parse_into_null() {
+        let fields = vec![Field::default()
+            .with_name("test")
+            .with_range(0..4)
+            .with_null_when(Null::Blank)];
+        let parser = Parser::new(fields);
+        let mut record = Record::new();
+
+        parser.parse_into("ABCD", &mut record).unwrap();
+        assert_eq!(record.len(), 1);
+        parser.parse_into("    ", &mut record).unwrap();
+        assert!(record.is_empty());
+    }
+
     #[test]
     fn check_field_new() {
         let field = Field::new(Some("foo"), 20, Align::Right, 'X');
@@ -427,4 +1466,117 @@ mod tests {
         assert_eq!(field.align(), Align::Right);
         assert_eq!(field.padding(), 'X');
     }
+
+    #[test]
+    fn check_parse_into_reuses_record() {
+        let fields = vec![
+            Field::default().with_name("test-1").with_range(0..5),
+            Field::default().with_range(5..6),
+            Field::default()
+                .with_name("test-2")
+                .with_range(6..10)
+                .with_align(Align::Right)
+                .with_padding('0'),
+        ];
+        let parser = Parser::new(fields);
+        let mut record: Record = [(String::from("stale"), String::from("value"))]
+            .iter()
+            .cloned()
+            .collect();
+
+        parser.parse_into("ABC  X0012", &mut record).unwrap();
+        assert_eq!(record.len(), 2);
+        assert_eq!(record.get("test-1"), Some(&String::from("ABC")));
+        assert_eq!(record.get("test-2"), Some(&String::from("12")));
+
+        let capacity = record["test-1"].capacity();
+        parser.parse_into("DE   X0345", &mut record).unwrap();
+        assert_eq!(record.len(), 2);
+        assert_eq!(record.get("test-1"), Some(&String::from("DE")));
+        assert_eq!(record.get("test-2"), Some(&String::from("345")));
+        assert_eq!(record["test-1"].capacity(), capacity);
+    }
+
+    #[test]
+    fn check_parse_with_visitor() {
+        let fields = vec![
+            Field::default().with_name("test-1").with_range(0..5),
+            Field::default().with_range(5..6),
+            Field::default()
+                .with_name("test-2")
+                .with_range(6..10)
+                .with_align(Align::Right)
+                .with_padding('0')
+                .with_null_when(Null::Filled('9')),
+            Field::default().with_name("test-1").with_range(10..12),
+        ];
+        let parser = Parser::new(fields);
+        let mut visited = Vec::new();
+
+        parser
+            .parse_with("ABC  X0012YZ", |field, value| {
+                visited.push(format!("{}={}", field, value))
+            })
+            .unwrap();
+        assert_eq!(visited, ["test-1=ABC", "test-2=12"]);
+
+        visited.clear();
+        parser
+            .parse_with("ABC  X9999YZ", |field, value| {
+                visited.push(format!("{}={}", field, value))
+            })
+            .unwrap();
+        assert_eq!(visited, ["test-1=ABC"]);
+        assert!(parser.parse_with("ABC", |_, _| ()).is_err());
+    }
+
+    #[test]
+    fn check_project() {
+        let fields = vec![
+            Field::default().with_name("test-1").with_range(0..2),
+            Field::default().with_name("test-2").with_range(2..4),
+            Field::default().with_range(4..5),
+            Field::default().with_name("test-3").with_range(5..7),
+            Field::default().with_name("test-4").with_range(7..9),
+        ];
+        let parser = Parser::new(fields);
+
+        let projected = parser.project(&["test-4", "test-1"]).unwrap();
+
+        assert_eq!(projected.fields().len(), 3);
+        assert_eq!(projected.fields()[1].width(), 5);
+        assert_eq!(projected.width(), 9);
+        let record = projected.parse("ABCD-EFGH").unwrap();
+        assert_eq!(record.len(), 2);
+        assert_eq!(record.get("test-1"), Some(&String::from("AB")));
+        assert_eq!(record.get("test-4"), Some(&String::from("GH")));
+        assert!(matches!(
+            parser.project(&["missing"]),
+            Err(Error::Layout(_))
+        ));
+    }
+
+    #[test]
+    fn check_parse_into_small_buffer() {
+        let fields = vec![Field::default().with_name("test").with_range(0..10)];
+        let parser = Parser::new(fields);
+        let mut record = Record::new();
+
+        assert!(parser.parse_into("1234", &mut record).is_err());
+        assert!(record.is_empty());
+    }
+
+    #[test]
+    fn check_parse_duplicate_names() {
+        let fields = vec![
+            Field::default().with_name("test").with_range(0..2),
+            Field::default().with_name("test").with_range(2..4),
+        ];
+        let parser = Parser::new(fields);
+        let mut record = Record::new();
+
+        parser.parse_into("ABCD", &mut record).unwrap();
+        assert_eq!(record.get("test"), Some(&String::from("AB")));
+        assert_eq!(parser.parse("ABCD").unwrap(), record);
+    }
 }
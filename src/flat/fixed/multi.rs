@@ -0,0 +1,212 @@
+use crate::{
+    error::Error,
+    flat::fixed::{read::Reader, Parser, ResultRecord},
+};
+use std::{
+    collections::VecDeque,
+    fs::{self, File},
+    path::{Path, PathBuf},
+};
+
+/// Sets the options of the reader of each file.
+pub type Configure<'a> = Box<dyn Fn(Reader<'a, File>) -> Reader<'a, File> + 'a>;
+
+/// Reads several files in turn as a single stream of records, see [`Reader::from_paths`].
+///
+/// Each file is read by its own [`Reader`], so terminators, byte order marks and final lines
+/// are handled per file. Errors are wrapped in `Error::InFile` with the path of their file and
+/// keep the line number within it.
+pub struct MultiReader<'a> {
+    paths: VecDeque<PathBuf>,
+    parser: &'a Parser<'a>,
+    configure: Option<Configure<'a>>,
+    current: Option<(PathBuf, Reader<'a, File>)>,
+}
+
+#[allow(dead_code)]
+impl<'a> MultiReader<'a> {
+    pub(crate) fn new(paths: VecDeque<PathBuf>, parser: &'a Parser) -> Self {
+        MultiReader {
+            paths,
+            parser,
+            configure: None,
+            current: None,
+        }
+    }
+
+    /// Sets the options of the reader of each file, e.g. `|r| r.skip_records(1)` to skip a
+    /// header line in every file.
+    pub fn configure<F>(mut self, configure: F) -> Self
+    where
+        F: Fn(Reader<'a, File>) -> Reader<'a, File> + 'a,
+    {
+        self.configure = Some(Box::new(configure));
+        self
+    }
+
+    /// The file and line number of the last record read.
+    pub fn location(&self) -> Option<(&Path, u64)> {
+        self.current
+            .as_ref()
+            .map(|(path, reader)| (path.as_path(), reader.line()))
+    }
+
+    /// The paths of the files not opened yet.
+    pub fn remaining(&self) -> impl Iterator<Item = &Path> {
+        self.paths.iter().map(PathBuf::as_path)
+    }
+
+    fn open(&mut self, path: PathBuf) -> Result<(), Error> {
+        let file = File::open(&path).map_err(|e| in_file(&path, Error::from(e)))?;
+        let reader = Reader::from_file(file, self.parser);
+        let reader = match &self.configure {
+            Some(configure) => configure(reader),
+            None => reader,
+        };
+        self.current = Some((path, reader));
+        Ok(())
+    }
+}
+
+impl<'a> Iterator for MultiReader<'a> {
+    type Item = ResultRecord;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((path, reader)) = self.current.as_mut() {
+                match reader.string_reader().next() {
+                    Some(result) => return Some(result.map_err(|e| in_file(path, e))),
+                    None if reader.is_cancelled() => return None,
+                    None => (),
+                }
+            }
+            let path = self.paths.pop_front()?;
+            if let Err(e) = self.open(path) {
+                return Some(Err(e));
+            }
+        }
+    }
+}
+
+fn in_file(path: &Path, error: Error) -> Error {
+    Error::InFile {
+        path: path.to_path_buf(),
+        source: Box::new(error),
+    }
+}
+
+/// The files matching a pattern, sorted by path. The last component of the pattern may contain
+/// `*`, matching any characters, and `?`, matching a single character, e.g.
+/// `feeds/2024-01-31/part-*.txt`.
+pub fn glob<P: AsRef<Path>>(pattern: P) -> Result<Vec<PathBuf>, Error> {
+    let pattern = pattern.as_ref();
+    let name = pattern
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| Error::Config(format!("Invalid pattern {}", pattern.display())))?;
+    let dir = match pattern.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let matched = entry
+            .file_name()
+            .to_str()
+            .is_some_and(|n| matches(name.as_bytes(), n.as_bytes()));
+        if matched && entry.file_type()?.is_file() {
+            paths.push(entry.path());
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+/// Expands directories to the files they contain, sorted by path.
+pub(crate) fn expand<I, P>(paths: I) -> Result<VecDeque<PathBuf>, Error>
+where
+    I: IntoIterator<Item = P>,
+    P: AsRef<Path>,
+{
+    let mut expanded = VecDeque::new();
+    for path in paths {
+        let path = path.as_ref();
+        if !path.is_dir() {
+            expanded.push_back(path.to_path_buf());
+            continue;
+        }
+        let mut files = Vec::new();
+        for entry in fs::read_dir(path)? {
+            let entry = entry?;
+            if entry.file_type()?.is_file() {
+                files.push(entry.path());
+            }
+        }
+        files.sort();
+        expanded.extend(files);
+    }
+    Ok(expanded)
+}
+
+fn matches(pattern: &[u8], name: &[u8]) -> bool {
+    match (pattern.first(), name.first()) {
+        (None, None) => true,
+        (Some(b'*'), _) => {
+            matches(&pattern[1..], name) || (!name.is_empty() && matches(pattern, &name[1..]))
+        }
+        (Some(b'?'), Some(_)) => matches(&pattern[1..], &name[1..]),
+        (Some(p), Some(n)) if p == n => matches(&pattern[1..], &name[1..]),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::{Buildable, Builder};
+
+    #[test]
+    fn read_multiple_files() {
+        let dir = std::env::temp_dir().join(format!("eta-parse-multi-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("part-1.txt"), "H\n1111\n2222").unwrap();
+        fs::write(dir.join("part-2.txt"), "H\n33\n4444\n").unwrap();
+        fs::write(dir.join("other.txt"), "9999\n").unwrap();
+        let parser = Parser::builder().field("test").width(4).append().build();
+
+        let paths = glob(dir.join("part-?.txt")).unwrap();
+        let mut rdr = Reader::from_paths(&paths, &parser)
+            .unwrap()
+            .configure(|r| r.skip_records(1));
+        let mut values = Vec::new();
+        let mut error = None;
+        for result in rdr.by_ref() {
+            match result {
+                Ok(record) => values.push(record["test"].clone()),
+                Err(e) => error = Some(e),
+            }
+        }
+        let all = Reader::from_paths([&dir], &parser).unwrap().count();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(paths.len(), 2);
+        assert_eq!(values, ["1111", "2222", "4444"]);
+        let error = error.unwrap();
+        assert_eq!(error.line(), Some(2));
+        assert!(error.to_string().ends_with(
+            "part-2.txt: Line 2: Insufficient buffer size, required 4 only 2 available"
+        ));
+        assert_eq!(rdr.location().map(|(_, line)| line), Some(3));
+        assert_eq!(all, 7);
+    }
+
+    #[test]
+    fn glob_patterns() {
+        assert!(matches(b"part-*.txt", b"part-0001.txt"));
+        assert!(matches(b"part-?.txt", b"part-1.txt"));
+        assert!(!matches(b"part-?.txt", b"part-12.txt"));
+        assert!(matches(b"*", b""));
+        assert!(!matches(b"*.gz", b"part.txt"));
+    }
+}
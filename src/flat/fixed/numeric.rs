@@ -0,0 +1,214 @@
+use alloc::{
+    borrow::Cow,
+    format,
+    string::{String, ToString},
+};
+use core::{cmp::Ordering, convert::TryFrom};
+
+/// Currency symbols removed from numeric values.
+const CURRENCY: &[char] = &['$', '€', '£', '¥'];
+
+/// Where the sign of a numeric field is written. Numeric fields are zero-filled between the
+/// sign and the digits.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Sign {
+    /// A leading `-` for negative values, nothing for others, e.g. `-0001234` and `00001234`.
+    #[default]
+    Leading,
+    /// A leading `-` or `+`, e.g. `+0001234`.
+    LeadingPlus,
+    /// A trailing `-` or `+`, e.g. `0001234-`.
+    Trailing,
+    /// A `CR` suffix for negative values and `DB` for others, e.g. `001234CR`.
+    CrDb,
+}
+
+impl Sign {
+    fn symbol(&self, negative: bool) -> &'static str {
+        match (self, negative) {
+            (Sign::Leading, false) => "",
+            (Sign::Leading | Sign::LeadingPlus | Sign::Trailing, true) => "-",
+            (Sign::LeadingPlus | Sign::Trailing, false) => "+",
+            (Sign::CrDb, true) => "CR",
+            (Sign::CrDb, false) => "DB",
+        }
+    }
+}
+
+impl TryFrom<&str> for Sign {
+    type Error = String;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        match s.to_lowercase().trim() {
+            "leading" => Ok(Sign::Leading),
+            "leading_plus" => Ok(Sign::LeadingPlus),
+            "trailing" => Ok(Sign::Trailing),
+            "cr_db" => Ok(Sign::CrDb),
+            _ => Err(String::from("Unknown sign argument")),
+        }
+    }
+}
+
+/// Splits a numeric value written with any of the sign conventions, thousands separators or
+/// currency symbols into whether it is negative and its digits without leading zeros. Returns
+/// `None` if the value is not a number.
+fn split(value: &str) -> Option<(bool, String)> {
+    let mut s = value.trim();
+    let mut negative = false;
+    let suffix = s.len().checked_sub(2).and_then(|i| s.get(i..));
+    if let Some(suffix) = suffix.filter(|x| x.eq_ignore_ascii_case("cr")) {
+        negative = true;
+        s = s[..s.len() - suffix.len()].trim_end();
+    } else if let Some(suffix) = suffix.filter(|x| x.eq_ignore_ascii_case("db")) {
+        s = s[..s.len() - suffix.len()].trim_end();
+    } else if let Some(rest) = s.strip_suffix('-') {
+        negative = true;
+        s = rest;
+    } else if let Some(rest) = s.strip_suffix('+') {
+        s = rest;
+    }
+    if let Some(rest) = s.strip_prefix('-') {
+        negative = !negative;
+        s = rest;
+    } else if let Some(rest) = s.strip_prefix('+') {
+        s = rest;
+    }
+    let digits = s
+        .chars()
+        .filter(|c| *c != ',' && !c.is_whitespace() && !CURRENCY.contains(c))
+        .collect::<String>();
+    let number = digits.chars().any(|c| c.is_ascii_digit())
+        && digits.chars().all(|c| c.is_ascii_digit() || c == '.')
+        && digits.matches('.').count() <= 1;
+    if !number {
+        return None;
+    }
+    let digits = match digits.trim_start_matches('0') {
+        "" => String::from("0"),
+        d if d.starts_with('.') => format!("0{}", d),
+        d => d.to_string(),
+    };
+    let zero = digits.chars().all(|c| c == '0' || c == '.');
+    Some((negative && !zero, digits))
+}
+
+/// Parses a numeric value into a plain number with a leading `-`, e.g. `$1,234.50CR` into
+/// `-1234.50`. Values that are not numbers are only trimmed.
+pub(crate) fn parse(value: &str) -> Cow<'_, str> {
+    match number(value) {
+        Some(number) => Cow::Owned(number),
+        None => Cow::Borrowed(value.trim()),
+    }
+}
+
+/// The value as a plain number with a leading `-`, `None` if it is not a number.
+pub(crate) fn number(value: &str) -> Option<String> {
+    split(value).map(|(negative, digits)| match negative {
+        true => format!("-{}", digits),
+        false => digits,
+    })
+}
+
+/// Compares plain numbers with a leading `-`, as made by [`number`], the empty string of values
+/// that are not numbers first.
+pub(crate) fn compare(a: &str, b: &str) -> Ordering {
+    let (a_negative, b_negative) = (a.starts_with('-'), b.starts_with('-'));
+    match (a.is_empty(), b.is_empty()) {
+        (true, true) => return Ordering::Equal,
+        (true, false) => return Ordering::Less,
+        (false, true) => return Ordering::Greater,
+        (false, false) => (),
+    }
+    if a_negative != b_negative {
+        return b_negative.cmp(&a_negative);
+    }
+    fn magnitude(n: &str) -> (usize, &str, &str) {
+        let n = n.trim_start_matches('-');
+        let (integer, fraction) = n.split_once('.').unwrap_or((n, ""));
+        (integer.len(), integer, fraction.trim_end_matches('0'))
+    }
+    let ordering = magnitude(a).cmp(&magnitude(b));
+    match a_negative {
+        true => ordering.reverse(),
+        false => ordering,
+    }
+}
+
+/// The number of columns a value takes once formatted, `None` if it is not a number.
+pub(crate) fn width(value: &str, sign: Sign) -> Option<usize> {
+    split(value).map(|(negative, digits)| digits.len() + sign.symbol(negative).len())
+}
+
+/// Formats a numeric value zero-filled to the width with the sign in its position, appending
+/// it to `buf`. Digits that do not fit are cut from the left when `keep_right` is set, from the
+/// right otherwise. Returns `false`, leaving `buf` untouched, if the value is not a number.
+pub(crate) fn format_into(
+    value: &str,
+    width: usize,
+    sign: Sign,
+    keep_right: bool,
+    buf: &mut String,
+) -> bool {
+    let (negative, digits) = match split(value) {
+        Some(split) => split,
+        None => return false,
+    };
+    let symbol = sign.symbol(negative);
+    let columns = width.saturating_sub(symbol.len());
+    let digits = match digits.len().checked_sub(columns) {
+        Some(extra) if keep_right => &digits[extra..],
+        Some(_) => &digits[..columns],
+        None => &digits,
+    };
+    let leading = matches!(sign, Sign::Leading | Sign::LeadingPlus);
+    if leading {
+        buf.push_str(symbol);
+    }
+    buf.extend(core::iter::repeat_n('0', columns - digits.len()));
+    buf.push_str(digits);
+    if !leading {
+        buf.push_str(symbol);
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(value: &str, width: usize, sign: Sign) -> String {
+        let mut buf = String::new();
+        format_into(value, width, sign, false, &mut buf);
+        buf
+    }
+
+    #[test]
+    fn parse_numbers() {
+        assert_eq!(parse(" $1,234.50 "), "1234.50");
+        assert_eq!(parse("0001234-"), "-1234");
+        assert_eq!(parse("001234CR"), "-1234");
+        assert_eq!(parse("001234DB"), "1234");
+        assert_eq!(parse("+0000.5"), "0.5");
+        assert_eq!(parse("-000"), "0");
+        assert_eq!(parse("N/A "), "N/A");
+    }
+
+    #[test]
+    fn compare_numbers() {
+        assert_eq!(compare("10", "9"), Ordering::Greater);
+        assert_eq!(compare("-10", "-9"), Ordering::Less);
+        assert_eq!(compare("2.50", "2.5"), Ordering::Equal);
+        assert_eq!(compare("", "-1"), Ordering::Less);
+    }
+
+    #[test]
+    fn format_signs() {
+        assert_eq!(format("-1,234", 8, Sign::Leading), "-0001234");
+        assert_eq!(format("1234", 8, Sign::Leading), "00001234");
+        assert_eq!(format("1234", 8, Sign::LeadingPlus), "+0001234");
+        assert_eq!(format("-12.5", 8, Sign::Trailing), "00012.5-");
+        assert_eq!(format("-1234", 8, Sign::CrDb), "001234CR");
+        assert_eq!(format("ABC", 8, Sign::Leading), "");
+        assert_eq!(width("-1234", Sign::CrDb), Some(6));
+    }
+}
@@ -0,0 +1,171 @@
+use crate::{flat::fixed::Field, utilities::string::WidthMode};
+use alloc::{collections::BTreeSet, string::String, vec::Vec};
+use core::{ops::Range, str::Chars};
+
+/// The byte ranges of the fields of a layout, compiled when the parser is built.
+///
+/// For layouts counting chars, the range of a field is known in advance on lines where every
+/// char is a single byte, so fields are sliced directly out of ASCII lines instead of walking
+/// the chars of every field before them. The plan also holds which fields are extracted and
+/// which keys records can have, looked up for every line.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct Plan {
+    ranges: Option<Vec<Range<usize>>>,
+    width: usize,
+    /// Whether each field is the first with its name, the one extracted.
+    first: Vec<bool>,
+    /// The keys of parsed records: the names of fields, the descriptions of codes and flags.
+    keys: BTreeSet<String>,
+}
+
+impl Plan {
+    pub(crate) fn compile(fields: &[Field]) -> Self {
+        let mut offset = 0;
+        let ranges = fields
+            .iter()
+            .map(|f| match f.width_mode {
+                WidthMode::Chars => {
+                    offset += f.width;
+                    Some(offset - f.width..offset)
+                }
+                _ => None,
+            })
+            .collect();
+        let mut names = BTreeSet::new();
+        let first = fields
+            .iter()
+            .map(|f| f.name.is_some_and(|name| names.insert(name)))
+            .collect();
+        let keys = fields
+            .iter()
+            .flat_map(|f| f.name.into_iter().chain(f.produces()))
+            .map(String::from)
+            .collect();
+        Plan {
+            ranges,
+            width: offset,
+            first,
+            keys,
+        }
+    }
+
+    /// The same plan walking the chars of every line, for benchmarking the ranges.
+    #[cfg(feature = "bench")]
+    pub(crate) fn unsliced(&self) -> Self {
+        Plan {
+            ranges: None,
+            ..self.clone()
+        }
+    }
+
+    /// Whether the field at the index is the first with its name.
+    pub(crate) fn is_first(&self, index: usize) -> bool {
+        self.first[index]
+    }
+
+    /// Whether parsing a line can add the key to records.
+    pub(crate) fn produces(&self, key: &str) -> bool {
+        self.keys.contains(key)
+    }
+
+    /// Extracts the fields of a line, at least as long in bytes as the layout.
+    pub(crate) fn slicer<'p, 's>(&'p self, line: &'s str) -> Slicer<'p, 's> {
+        match &self.ranges {
+            Some(ranges)
+                if line
+                    .as_bytes()
+                    .get(..self.width)
+                    .is_some_and(<[u8]>::is_ascii) =>
+            {
+                Slicer::Direct {
+                    ranges,
+                    line,
+                    index: 0,
+                }
+            }
+            _ => Slicer::Chars(line.chars()),
+        }
+    }
+}
+
+/// Takes the raw text of each field of a line in turn.
+pub(crate) enum Slicer<'p, 's> {
+    Direct {
+        ranges: &'p [Range<usize>],
+        line: &'s str,
+        index: usize,
+    },
+    Chars(Chars<'s>),
+}
+
+impl<'p, 's> Slicer<'p, 's> {
+    /// The raw text of the next field, which must be the field following the previous one.
+    pub(crate) fn take(&mut self, field: &Field) -> &'s str {
+        match self {
+            Slicer::Direct {
+                ranges,
+                line,
+                index,
+            } => {
+                let range = ranges[*index].clone();
+                *index += 1;
+                &line[range]
+            }
+            Slicer::Chars(chars) => field.take(chars),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plan_slices_ascii_lines() {
+        let fields = vec![
+            Field::default().with_name("first").with_range(0..2),
+            Field::default().with_range(2..3),
+            Field::default().with_name("second").with_range(3..6),
+        ];
+        let plan = Plan::compile(&fields);
+
+        let mut slicer = plan.slicer("AB-CDE and more");
+        assert!(matches!(slicer, Slicer::Direct { .. }));
+        let raw = fields.iter().map(|f| slicer.take(f)).collect::<Vec<_>>();
+        assert_eq!(raw, ["AB", "-", "CDE"]);
+
+        let mut slicer = plan.slicer("ÄB-CDE");
+        assert!(matches!(slicer, Slicer::Chars(_)));
+        let raw = fields.iter().map(|f| slicer.take(f)).collect::<Vec<_>>();
+        assert_eq!(raw, ["ÄB", "-", "CDE"]);
+
+        let fields = vec![Field::default()
+            .with_name("first")
+            .with_range(0..2)
+            .with_width_mode(WidthMode::Bytes)];
+        assert!(matches!(
+            Plan::compile(&fields).slicer("AB"),
+            Slicer::Chars(_)
+        ));
+    }
+
+    #[test]
+    fn plan_first_fields_and_keys() {
+        let fields = vec![
+            Field::default().with_name("code").with_range(0..2),
+            Field::default().with_range(2..3),
+            Field::default()
+                .with_name("code")
+                .with_range(3..5)
+                .with_description_field("name"),
+        ];
+        let plan = Plan::compile(&fields);
+
+        assert_eq!(
+            (0..3).map(|i| plan.is_first(i)).collect::<Vec<_>>(),
+            [true, false, false]
+        );
+        assert!(plan.produces("code") && plan.produces("name"));
+        assert!(!plan.produces("other"));
+    }
+}
@@ -0,0 +1,268 @@
+use std::io::{self, BufRead, Read};
+#[cfg(feature = "metrics")]
+use std::time::Instant;
+
+/// Counts of what a reader has done so far.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Progress {
+    /// Lines read from the input, including skipped ones.
+    pub lines: u64,
+    /// Records parsed successfully.
+    pub records: u64,
+    /// Lines skipped as blank, comments, by the filter, by `skip_records` or out of the sample.
+    pub skipped: u64,
+    /// Records that failed to parse.
+    pub errors: u64,
+    /// Records that parsed but failed a field check of
+    /// [`read_all_validated`](super::read::Reader::read_all_validated).
+    pub invalid: u64,
+    /// Bytes consumed from the input, after any byte order mark handling.
+    pub bytes: u64,
+}
+
+/// How often the progress hook of a reader is invoked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interval {
+    /// Every N lines.
+    Lines(u64),
+    /// Every time another N bytes have been consumed.
+    Bytes(u64),
+}
+
+/// A callback receiving the progress of a reader.
+pub type ProgressHook<'a> = Box<dyn FnMut(&Progress) + 'a>;
+
+/// A progress hook publishing the counts through the `metrics` facade, named after the prefix,
+/// e.g. `ingest_records_read`. The counters are set to the totals of a single reader, and the
+/// `elapsed_seconds` gauge to the time since the hook was created.
+#[cfg(feature = "metrics")]
+pub fn metrics_hook(prefix: &str) -> impl FnMut(&Progress) {
+    let counters = [
+        "lines_read",
+        "records_read",
+        "lines_skipped",
+        "parse_failures",
+        "validation_failures",
+        "bytes_read",
+    ]
+    .map(|name| format!("{}_{}", prefix, name));
+    let elapsed = format!("{}_elapsed_seconds", prefix);
+    let started = Instant::now();
+    move |progress| {
+        let values = [
+            progress.lines,
+            progress.records,
+            progress.skipped,
+            progress.errors,
+            progress.invalid,
+            progress.bytes,
+        ];
+        for (name, value) in counters.iter().zip(values) {
+            metrics::counter!(name.clone()).absolute(value);
+        }
+        metrics::gauge!(elapsed.clone()).set(started.elapsed().as_secs_f64());
+    }
+}
+
+pub(crate) struct Tracker<'a> {
+    interval: Interval,
+    hook: ProgressHook<'a>,
+    next: u64,
+    finished: bool,
+}
+
+impl<'a> Tracker<'a> {
+    pub(crate) fn new(interval: Interval, hook: ProgressHook<'a>) -> Self {
+        let mut tracker = Tracker {
+            interval,
+            hook,
+            next: 0,
+            finished: false,
+        };
+        tracker.next = tracker.step();
+        tracker
+    }
+
+    /// Invokes the hook if the interval has been reached since the last report.
+    pub(crate) fn update(&mut self, progress: &Progress) {
+        let value = match self.interval {
+            Interval::Lines(_) => progress.lines,
+            Interval::Bytes(_) => progress.bytes,
+        };
+        if value >= self.next {
+            (self.hook)(progress);
+            self.next = (value / self.step() + 1) * self.step();
+        }
+    }
+
+    /// Invokes the hook a final time at the end of the input.
+    pub(crate) fn finish(&mut self, progress: &Progress) {
+        if !self.finished {
+            self.finished = true;
+            (self.hook)(progress);
+        }
+    }
+
+    fn step(&self) -> u64 {
+        match self.interval {
+            Interval::Lines(n) | Interval::Bytes(n) => n.max(1),
+        }
+    }
+}
+
+/// Counts the bytes consumed from a buffered reader.
+#[derive(Debug)]
+pub(crate) struct CountingReader<R> {
+    inner: R,
+    count: u64,
+}
+
+impl<R> CountingReader<R> {
+    pub(crate) fn new(inner: R) -> Self {
+        CountingReader { inner, count: 0 }
+    }
+
+    pub(crate) fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Starts counting from `count`, for input that was positioned past its start.
+    pub(crate) fn set_count(&mut self, count: u64) {
+        self.count = count;
+    }
+
+    pub(crate) fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    pub(crate) fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.count += n as u64;
+        Ok(n)
+    }
+}
+
+impl<R: BufRead> BufRead for CountingReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.count += amt as u64;
+        self.inner.consume(amt);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{cell::RefCell, io::Cursor};
+
+    #[test]
+    fn counting_reader() {
+        let mut reader = CountingReader::new(Cursor::new(b"AB\nCDE\n".to_vec()));
+        let mut buf = Vec::new();
+
+        reader.read_until(b'\n', &mut buf).unwrap();
+        assert_eq!(reader.count(), 3);
+        reader.read_to_end(&mut buf).unwrap();
+        assert_eq!(reader.count(), 7);
+    }
+
+    #[test]
+    fn tracker_intervals() {
+        let calls = RefCell::new(Vec::new());
+        let mut tracker = Tracker::new(
+            Interval::Bytes(10),
+            Box::new(|p: &Progress| calls.borrow_mut().push(p.bytes)),
+        );
+
+        for bytes in [4, 9, 12, 15, 31, 35] {
+            tracker.update(&Progress {
+                bytes,
+                ..Progress::default()
+            });
+        }
+        tracker.finish(&Progress {
+            bytes: 36,
+            ..Progress::default()
+        });
+        tracker.finish(&Progress::default());
+
+        drop(tracker);
+        assert_eq!(calls.into_inner(), vec![12, 31, 36]);
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn publish_metrics() {
+        use metrics::{
+            Counter, CounterFn, Gauge, Histogram, Key, KeyName, Metadata, Recorder, SharedString,
+            Unit,
+        };
+        use std::{
+            collections::HashMap,
+            sync::{Arc, Mutex},
+        };
+
+        /// Records the last absolute value of every counter.
+        #[derive(Default)]
+        struct Capture(Arc<Mutex<HashMap<String, u64>>>);
+
+        struct Handle(String, Arc<Mutex<HashMap<String, u64>>>);
+
+        impl CounterFn for Handle {
+            fn increment(&self, value: u64) {
+                *self.1.lock().unwrap().entry(self.0.clone()).or_insert(0) += value;
+            }
+
+            fn absolute(&self, value: u64) {
+                self.1.lock().unwrap().insert(self.0.clone(), value);
+            }
+        }
+
+        impl Recorder for Capture {
+            fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+            fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+            fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+            fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+                let handle = Handle(key.name().to_string(), self.0.clone());
+                Counter::from_arc(Arc::new(handle))
+            }
+
+            fn register_gauge(&self, _: &Key, _: &Metadata<'_>) -> Gauge {
+                Gauge::noop()
+            }
+
+            fn register_histogram(&self, _: &Key, _: &Metadata<'_>) -> Histogram {
+                Histogram::noop()
+            }
+        }
+
+        let recorder = Capture::default();
+        let mut hook = metrics_hook("ingest");
+        metrics::with_local_recorder(&recorder, || {
+            hook(&Progress {
+                lines: 5,
+                records: 3,
+                errors: 1,
+                invalid: 1,
+                bytes: 40,
+                ..Progress::default()
+            })
+        });
+
+        let values = recorder.0.lock().unwrap();
+        assert_eq!(values["ingest_records_read"], 3);
+        assert_eq!(values["ingest_parse_failures"], 1);
+        assert_eq!(values["ingest_validation_failures"], 1);
+        assert_eq!(values["ingest_bytes_read"], 40);
+    }
+}
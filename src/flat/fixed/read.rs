@@ -1,23 +1,177 @@
-use crate::flat::fixed::{Parser, ResultRecord};
+use crate::{
+    error::{Error, FieldError, RecordError},
+    flat::{
+        fixed::{
+            dedupe::Dedupe,
+            multi::{expand, MultiReader},
+            progress::{CountingReader, Interval, Progress, Tracker},
+            redact::{Redact, Redactor},
+            reject::{self, RejectWriter},
+            row::RowRecord,
+            Parser, Record, ResultRecord,
+        },
+        terminator::Terminator,
+    },
+    utilities::{
+        bom::{Bom, BomReader, Encoding},
+        cancel::CancellationToken,
+        digest::{Algorithm, DigestReader},
+    },
+};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+use std::path::Path;
 use std::{
+    collections::HashMap,
     fs::File,
-    io::{BufRead, BufReader, Cursor, Lines, Read},
+    io::{self, BufRead, BufReader, Cursor, Read, Seek, SeekFrom},
+    str::Utf8Error,
+    time::{Duration, Instant},
 };
 
-pub struct StringReader<'a, R: 'a> {
-    r: &'a mut Reader<'a, R>,
+pub struct StringReader<'r, 'a, R: 'r> {
+    r: &'r mut Reader<'a, R>,
+}
+
+impl<'r, 'a, R> StringReader<'r, 'a, R>
+where
+    R: Read,
+{
+    fn parse(&mut self) -> ResultRecord {
+        let result = self
+            .r
+            .check_encoding()
+            .and_then(|_| self.r.parser.parse(self.r.buf.as_str()));
+        self.r.track(result)
+    }
+}
+
+/// Iterates over the records of a reader paired with the line number each one was read from.
+pub struct EnumeratedReader<'r, 'a, R: 'r> {
+    r: &'r mut Reader<'a, R>,
+}
+
+/// Iterates over the records of a reader paired with the line each one was parsed from.
+pub struct RawReader<'r, 'a, R: 'r> {
+    r: &'r mut Reader<'a, R>,
+}
+
+/// Iterates over the records of a reader as rows addressed by field index.
+pub struct RowReader<'r, 'a, R: 'r> {
+    r: &'r mut Reader<'a, R>,
+}
+
+/// A position in the input to resume reading from, see [`Reader::checkpoint`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Checkpoint {
+    /// Offset in bytes of the next line from the start of the input.
+    pub offset: u64,
+    /// The number of the last line read.
+    pub line: u64,
+}
+
+/// What happens to a final line that is shorter than the layout, typically a file truncated
+/// in transfer or a last record missing its trailing spaces.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FinalLine {
+    /// The line is parsed like any other and fails as too short.
+    #[default]
+    Error,
+    /// The line is skipped.
+    Ignore,
+    /// The line is padded with spaces to the width of the layout before being parsed.
+    PadAndParse,
+}
+
+/// What happens to a line that is not valid UTF-8.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Utf8Mode {
+    /// Reading stops at the line, as if the input ended before it.
+    #[default]
+    Stop,
+    /// The invalid bytes are replaced with `U+FFFD` and the line is parsed, which shifts the
+    /// following fields if a replaced sequence is not one byte long.
+    Lossy,
+    /// The line fails with `Error::Encoding`, giving the offset of the first invalid byte, and
+    /// reading continues with the next line.
+    Strict,
+}
+
+/// A predicate deciding whether a raw line should be parsed.
+pub type LineFilter<'a> = Box<dyn FnMut(&str) -> bool + 'a>;
+
+/// A check on the value of a single field, an error describes why the value is invalid.
+pub type FieldCheck<'a> = Box<dyn FnMut(&str) -> Result<(), String> + 'a>;
+
+/// The key fields that must be unique across a file and the line each key was first seen on.
+struct UniqueKey {
+    fields: Vec<String>,
+    seen: HashMap<String, u64>,
 }
 
-impl<'a, R> StringReader<'a, R> {
-    fn parse(&self, s: String) -> ResultRecord {
-        self.r.parser.parse(s)
+impl UniqueKey {
+    /// Records the key of the record, failing if an earlier record has the same key.
+    fn check(&mut self, line: u64, record: &Record) -> Result<(), FieldError> {
+        let values = self
+            .fields
+            .iter()
+            .map(|f| record.get(f).map_or("", |v| v.as_str()))
+            .collect::<Vec<&str>>();
+        let key = values.join("\u{1f}");
+        match self.seen.get(&key) {
+            Some(first) => Err(FieldError {
+                field: self.fields.join("+"),
+                message: format!(
+                    "duplicate key {} first seen on line {}",
+                    values.join(", "),
+                    first
+                ),
+            }),
+            None => {
+                self.seen.insert(key, line);
+                Ok(())
+            }
+        }
     }
 }
 
 #[allow(dead_code)]
 pub struct Reader<'a, R> {
-    lines: Lines<BufReader<R>>,
+    inner: CountingReader<BufReader<BomReader<DigestReader<R>>>>,
     parser: &'a Parser<'a>,
+    terminator: Terminator,
+    buf: String,
+    /// Whether the byte order mark policy was set, rather than following the terminator.
+    bom_set: bool,
+    progress: Progress,
+    tracker: Option<Tracker<'a>>,
+    started: Option<(Instant, Option<Duration>)>,
+    skip_blank: bool,
+    comments: Vec<String>,
+    filter: Option<LineFilter<'a>>,
+    checks: Vec<(String, FieldCheck<'a>)>,
+    unique: Option<UniqueKey>,
+    sequences: Vec<(&'a str, Option<u64>)>,
+    max_errors: Option<u64>,
+    aborted: bool,
+    cancel: Option<CancellationToken>,
+    digest: Option<Algorithm>,
+    record_digest: String,
+    skip_records: u64,
+    take_records: Option<u64>,
+    sample: Option<Sample>,
+    strict_ascii: bool,
+    non_ascii: Option<(usize, u8)>,
+    utf8_mode: Utf8Mode,
+    invalid_utf8: Option<Utf8Error>,
+    /// The error reading the current record, yielded in its place.
+    io_error: Option<io::Error>,
+    final_line: FinalLine,
+    tab_stop: Option<usize>,
+    raw: Option<String>,
+    rejects: Option<RejectWriter<'a>>,
 }
 
 #[allow(dead_code)]
@@ -27,30 +181,851 @@ where
 {
     pub fn from_reader(reader: R, parser: &'a Parser) -> Self {
         Reader {
-            lines: BufReader::new(reader).lines(),
+            inner: CountingReader::new(BufReader::new(BomReader::new(DigestReader::new(reader)))),
             parser,
+            terminator: Terminator::default(),
+            buf: String::new(),
+            bom_set: false,
+            progress: Progress::default(),
+            tracker: None,
+            started: None,
+            skip_blank: false,
+            comments: Vec::new(),
+            filter: None,
+            checks: Vec::new(),
+            unique: None,
+            sequences: Vec::new(),
+            max_errors: None,
+            aborted: false,
+            cancel: None,
+            digest: None,
+            record_digest: String::new(),
+            skip_records: 0,
+            take_records: None,
+            sample: None,
+            strict_ascii: false,
+            non_ascii: None,
+            utf8_mode: Utf8Mode::default(),
+            invalid_utf8: None,
+            io_error: None,
+            final_line: FinalLine::default(),
+            tab_stop: None,
+            raw: None,
+            rejects: None,
+        }
+    }
+
+    /// Creates a new reader for data without line terminators, where every record is exactly
+    /// `parser.width()` bytes long.
+    pub fn from_reader_fixed(reader: R, parser: &'a Parser) -> Self {
+        Self::from_reader(reader, parser).terminator(Terminator::Fixed)
+    }
+
+    /// Creates a new reader for variable length records preceded by an IBM record descriptor
+    /// word, as in VB datasets transferred from a mainframe in binary mode.
+    pub fn from_reader_rdw(reader: R, parser: &'a Parser) -> Self {
+        Self::from_reader(reader, parser).terminator(Terminator::Rdw)
+    }
+
+    /// Sets how records are separated, defaults to newlines.
+    pub fn terminator(mut self, terminator: Terminator) -> Self {
+        if !self.bom_set {
+            let bom = match terminator {
+                Terminator::Fixed | Terminator::Rdw | Terminator::LengthPrefixed(_) => Bom::Ignore,
+                _ => Bom::Strip,
+            };
+            self.inner.get_mut().get_mut().set_policy(bom);
         }
+        self.terminator = terminator;
+        self
     }
 
-    pub fn string_reader(&'a mut self) -> StringReader<'a, R> {
+    /// Sets how a byte order mark at the start of the input is handled. By default it is
+    /// stripped from text, and left in binary data read with the `Fixed`, `Rdw` and
+    /// `LengthPrefixed` terminators, where the same bytes may be data. Must be set before
+    /// reading.
+    pub fn bom(mut self, bom: Bom) -> Self {
+        self.inner.get_mut().get_mut().set_policy(bom);
+        self.bom_set = true;
+        self
+    }
+
+    /// The encoding announced by the byte order mark of the input, if any.
+    pub fn encoding(&self) -> Option<Encoding> {
+        self.inner.get_ref().get_ref().encoding()
+    }
+
+    /// Computes the digest of the raw bytes of each record, without its terminator, and of the
+    /// whole input including any byte order mark. Must be set before reading.
+    pub fn digest(mut self, algorithm: Algorithm) -> Self {
+        self.digest = Some(algorithm);
+        self.inner
+            .get_mut()
+            .get_mut()
+            .get_mut()
+            .set_algorithm(algorithm);
+        self
+    }
+
+    /// The digest of the last record read, skipped lines included, if a digest is computed.
+    pub fn record_digest(&self) -> Option<&str> {
+        self.digest.map(|_| self.record_digest.as_str())
+    }
+
+    /// The digest of the input read so far, which is the digest of the file once all the
+    /// records have been read.
+    pub fn file_digest(&self) -> Option<String> {
+        self.inner.get_ref().get_ref().get_ref().digest()
+    }
+
+    /// Skips lines that are empty or only contain whitespace.
+    pub fn skip_blank_lines(mut self) -> Self {
+        self.skip_blank = true;
+        self
+    }
+
+    /// Skips lines starting with the given prefix, can be called multiple times.
+    pub fn skip_comments<T: Into<String>>(mut self, prefix: T) -> Self {
+        self.comments.push(prefix.into());
+        self
+    }
+
+    /// Skips the first `n` records without parsing them. Lines skipped as blank, comments or by
+    /// the filter are not counted.
+    pub fn skip_records(mut self, n: u64) -> Self {
+        self.skip_records = n;
+        self
+    }
+
+    /// Stops after `n` records, without reading the rest of the input.
+    pub fn take_records(mut self, n: u64) -> Self {
+        self.take_records = Some(n);
+        self
+    }
+
+    /// Only parses a random sample of about `fraction` of the records, the same seed always
+    /// selects the same records of the same input.
+    pub fn sample(mut self, fraction: f64, seed: u64) -> Self {
+        self.sample = Some(Sample {
+            fraction,
+            state: seed,
+        });
+        self
+    }
+
+    /// Sets what happens to a final line shorter than the layout, by default it fails to parse.
+    /// A line is final when the input ends after it, whether or not it is terminated.
+    pub fn final_line(mut self, policy: FinalLine) -> Self {
+        self.final_line = policy;
+        self
+    }
+
+    /// Expands tabs to spaces up to the next multiple of `tab_stop` columns before slicing the
+    /// fields, for reports whose columns were aligned with tabs.
+    pub fn expand_tabs(mut self, tab_stop: usize) -> Self {
+        self.tab_stop = Some(tab_stop.max(1));
+        self
+    }
+
+    /// Writes the lines that fail to parse unchanged to the reject writer instead of returning
+    /// them as errors, so only valid records are read. Lines failing the field checks of
+    /// [`read_all_validated`](Reader::read_all_validated) are written as well, and still
+    /// returned with the errors.
+    pub fn reject_to(mut self, rejects: RejectWriter<'a>) -> Self {
+        self.raw.get_or_insert_with(String::new);
+        self.rejects = Some(rejects);
+        self
+    }
+
+    /// The number of lines written to the reject writer.
+    pub fn rejected(&self) -> u64 {
+        self.rejects.as_ref().map_or(0, RejectWriter::rejected)
+    }
+
+    /// Fails every line containing a byte outside of ASCII with `Error::NonAscii`, giving the
+    /// line and column of the first such byte. Reading continues with the next line, set
+    /// [`max_errors`](Reader::max_errors) to stop at the first one.
+    pub fn strict_ascii(mut self) -> Self {
+        self.strict_ascii = true;
+        self
+    }
+
+    /// Sets what happens to a line that is not valid UTF-8, by default reading stops. Lines
+    /// skipped as blank, comments or by the filter are checked on their replaced text.
+    pub fn invalid_utf8(mut self, mode: Utf8Mode) -> Self {
+        self.utf8_mode = mode;
+        self
+    }
+
+    /// Only parses lines for which the predicate returns `true`.
+    pub fn filter<F>(mut self, predicate: F) -> Self
+    where
+        F: FnMut(&str) -> bool + 'a,
+    {
+        self.filter = Some(Box::new(predicate));
+        self
+    }
+
+    /// Adds a check on the value of a field, applied by
+    /// [`read_all_validated`](Reader::read_all_validated). A field missing from the record is
+    /// checked as empty.
+    pub fn validate_field<T, F>(mut self, field: T, check: F) -> Self
+    where
+        T: Into<String>,
+        F: FnMut(&str) -> Result<(), String> + 'a,
+    {
+        self.checks.push((field.into(), Box::new(check)));
+        self
+    }
+
+    /// Checks that the key fields, together, are unique across the file, applied by
+    /// [`read_all_validated`](Reader::read_all_validated). A record repeating the key of an
+    /// earlier record fails with the line of the first one. Every key is held in memory, and
+    /// records failing to parse or failing other checks do not take part.
+    pub fn unique_key<T: AsRef<str>>(mut self, fields: &[T]) -> Self {
+        self.unique = Some(UniqueKey {
+            fields: fields.iter().map(|f| f.as_ref().to_string()).collect(),
+            seen: HashMap::new(),
+        });
+        self
+    }
+
+    /// Checks that the sequence number fields of the layout increase from record to record,
+    /// applied by [`read_all_validated`](Reader::read_all_validated).
+    pub fn check_sequences(mut self) -> Self {
+        self.sequences = self
+            .parser
+            .fields()
+            .iter()
+            .filter(|f| f.sequence().is_some())
+            .filter_map(|f| Some((f.name()?, None)))
+            .collect();
+        self
+    }
+
+    /// Aborts reading once more than `n` records have failed, the record exceeding the limit is
+    /// returned as `Error::TooManyErrors` and no further records are read. For
+    /// [`read_all_validated`](Reader::read_all_validated) records failing a field check count as
+    /// well.
+    pub fn max_errors(mut self, n: u64) -> Self {
+        self.max_errors = Some(n);
+        self
+    }
+
+    /// Stops reading further records once the token is cancelled.
+    pub fn cancel_on(mut self, token: CancellationToken) -> Self {
+        self.cancel = Some(token);
+        self
+    }
+
+    /// Invokes the hook with the progress of the reader at every interval, and once more at the
+    /// end of the input.
+    pub fn on_progress<F>(mut self, interval: Interval, hook: F) -> Self
+    where
+        F: FnMut(&Progress) + 'a,
+    {
+        self.tracker = Some(Tracker::new(interval, Box::new(hook)));
+        self
+    }
+
+    /// Publishes the progress of the reader through the `metrics` facade at every interval, see
+    /// [`metrics_hook`](super::progress::metrics_hook). Replaces any other progress hook.
+    #[cfg(feature = "metrics")]
+    pub fn publish_metrics(self, prefix: &str, interval: Interval) -> Self {
+        self.on_progress(interval, super::progress::metrics_hook(prefix))
+    }
+
+    /// The layout the records are parsed with.
+    pub fn parser(&self) -> &'a Parser<'a> {
+        self.parser
+    }
+
+    /// Counts of the lines read, skipped, parsed and failed so far, and of the bytes consumed.
+    pub fn progress(&self) -> Progress {
+        self.progress
+    }
+
+    /// The time spent since the first line was read, up to the end of the input.
+    pub fn elapsed(&self) -> Duration {
+        match self.started {
+            Some((_, Some(elapsed))) => elapsed,
+            Some((started, None)) => started.elapsed(),
+            None => Duration::ZERO,
+        }
+    }
+
+    /// Whether reading stopped, or will stop, because the cancellation token was triggered.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.as_ref().is_some_and(|c| c.is_cancelled())
+    }
+
+    pub fn string_reader(&mut self) -> StringReader<'_, 'a, R> {
         StringReader { r: self }
     }
+
+    /// Returns an iterator dropping records whose key fields repeat those of an earlier record,
+    /// see [`Dedupe`].
+    pub fn dedupe_by<'k>(&mut self, fields: &'k [&'k str]) -> Dedupe<'k, StringReader<'_, 'a, R>> {
+        Dedupe::new(self.string_reader(), fields)
+    }
+
+    /// Returns an iterator redacting the fields of the records, see [`Redactor`].
+    pub fn redact<'r>(&mut self, redactor: &'r Redactor) -> Redact<'r, StringReader<'_, 'a, R>> {
+        Redact::new(self.string_reader(), redactor)
+    }
+
+    /// Returns an iterator yielding each record together with the line it was parsed from,
+    /// without its terminator, as it was before tabs were expanded or a final line was padded.
+    pub fn with_raw(&mut self) -> RawReader<'_, 'a, R> {
+        self.raw.get_or_insert_with(String::new);
+        RawReader { r: self }
+    }
+
+    /// The line the current record was parsed from, once line retention is enabled by
+    /// [`with_raw`](Reader::with_raw).
+    pub fn raw_line(&self) -> Option<&str> {
+        self.raw.as_deref()
+    }
+
+    /// Returns an iterator yielding each record together with its 1-based line number.
+    pub fn enumerated_records(&mut self) -> EnumeratedReader<'_, 'a, R> {
+        EnumeratedReader { r: self }
+    }
+
+    /// Returns an iterator yielding each record as a [`RowRecord`].
+    pub fn row_reader(&mut self) -> RowReader<'_, 'a, R> {
+        RowReader { r: self }
+    }
+
+    /// The offset in bytes of the next line from the start of the input, byte order mark
+    /// included. Unknown for UTF-16 input transcoded to UTF-8.
+    pub fn offset(&self) -> Option<u64> {
+        let bom = self.inner.get_ref().get_ref();
+        match bom.is_transcoding() {
+            true => None,
+            false => Some(self.inner.count() + bom.skipped() as u64),
+        }
+    }
+
+    /// A checkpoint to resume reading after the last line read with
+    /// [`resume`](Reader::resume), e.g. saved every so many records while ingesting a large
+    /// file so a restart does not read it again. Take it once the records read so far have been
+    /// processed. Unknown for UTF-16 input transcoded to UTF-8.
+    pub fn checkpoint(&self) -> Option<Checkpoint> {
+        Some(Checkpoint {
+            offset: self.offset()?,
+            line: self.progress.lines,
+        })
+    }
+
+    /// The number of the last line read from the underlying reader, 0 before any line is read.
+    /// For fixed length records this is the record number.
+    pub fn line(&self) -> u64 {
+        self.progress.lines
+    }
+
+    /// Reads the next record into `record`, reusing the allocations of both the record and the
+    /// line buffer of the reader. Returns `Ok(false)` once there are no more records.
+    pub fn read_into(&mut self, record: &mut Record) -> Result<bool, Error> {
+        while self.next_line().is_some() {
+            let result = self
+                .check_encoding()
+                .and_then(|_| self.parser.parse_into(&self.buf, record));
+            let result = self.track(result);
+            if let Some(result) = self.reject(result) {
+                return result.map(|_| true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Reads the next record, calling `visit` with the name and value of each of its fields as
+    /// [`Parser::parse_with`] does, without allocating a record. Returns `Ok(false)` once there
+    /// are no more records.
+    pub fn read_with<F>(&mut self, mut visit: F) -> Result<bool, Error>
+    where
+        F: FnMut(&str, &str),
+    {
+        while self.next_line().is_some() {
+            let result = self
+                .check_encoding()
+                .and_then(|_| self.parser.parse_with(&self.buf, &mut visit));
+            let result = self.track(result);
+            if let Some(result) = self.reject(result) {
+                return result.map(|_| true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Reads the next record into `row`, reusing the allocations of both the row and the line
+    /// buffer of the reader. Returns `Ok(false)` once there are no more records.
+    pub fn read_row_into(&mut self, row: &mut RowRecord) -> Result<bool, Error> {
+        while self.next_line().is_some() {
+            let result = self
+                .check_encoding()
+                .and_then(|_| self.parser.parse_row_into(&self.buf, row));
+            let result = self.track(result);
+            if let Some(result) = self.reject(result) {
+                return result.map(|_| true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Reads every remaining record, continuing past errors. Returns the records that parsed
+    /// and passed every field check, and an error for each record that did not, with its line
+    /// number and all of its failed fields.
+    ///
+    /// Fails with `Error::TooManyErrors` if more than the [`max_errors`](Reader::max_errors) are
+    /// found, counting lines that fail to parse and records failing their checks together.
+    pub fn read_all_validated(&mut self) -> Result<(Vec<Record>, Vec<RecordError>), Error> {
+        let mut records = Vec::new();
+        let mut errors = Vec::new();
+        while let Some(line) = self.next_line() {
+            let result = self
+                .check_encoding()
+                .and_then(|_| self.parser.parse(self.buf.as_str()));
+            // Parse errors and field failures count together against the limit.
+            self.count(&result);
+            let record = match result {
+                Ok(record) => record,
+                Err(e @ Error::Io(_)) => return Err(e),
+                Err(e) => {
+                    let e = e.at_line(line);
+                    self.write_reject(line, &reject::reason(&e))?;
+                    errors.push(RecordError {
+                        line,
+                        error: Some(e),
+                        fields: Vec::new(),
+                    });
+                    self.limit_errors(errors.len(), line)?;
+                    continue;
+                }
+            };
+            let fields = self.check(line, &record);
+            if fields.is_empty() {
+                records.push(record);
+                continue;
+            }
+            self.progress.invalid += 1;
+            self.report();
+            let reason = fields
+                .iter()
+                .map(|f| f.to_string())
+                .collect::<Vec<String>>()
+                .join("; ");
+            self.write_reject(line, &reason)?;
+            errors.push(RecordError {
+                line,
+                error: None,
+                fields,
+            });
+            self.limit_errors(errors.len(), line)?;
+        }
+        Ok((records, errors))
+    }
+
+    /// Aborts reading once there are more errors than allowed.
+    fn limit_errors(&mut self, errors: usize, line: u64) -> Result<(), Error> {
+        match self.max_errors {
+            Some(max) if errors as u64 > max => {
+                self.aborted = true;
+                Err(Error::TooManyErrors {
+                    errors: errors as u64,
+                    line,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Applies the field checks, then the unique key, to the record, returning the failures.
+    fn check(&mut self, line: u64, record: &Record) -> Vec<FieldError> {
+        let mut failures = Vec::new();
+        for (field, check) in self.checks.iter_mut() {
+            let value = record.get(field.as_str()).map_or("", |v| v.as_str());
+            if let Err(message) = check(value) {
+                failures.push(FieldError {
+                    field: field.clone(),
+                    message,
+                });
+            }
+        }
+        if let (Some(unique), true) = (self.unique.as_mut(), failures.is_empty()) {
+            failures.extend(unique.check(line, record).err());
+        }
+        for (field, last) in self.sequences.iter_mut() {
+            let value = record.get(*field).map_or("", |v| v.as_str());
+            let message = match (value.parse::<u64>(), *last) {
+                (Ok(n), Some(previous)) if n <= previous => {
+                    format!("{} does not increase from {}", n, previous)
+                }
+                (Ok(n), _) => {
+                    *last = Some(n);
+                    continue;
+                }
+                (Err(_), _) => format!("{} is not a sequence number", value),
+            };
+            failures.push(FieldError {
+                field: field.to_string(),
+                message,
+            });
+        }
+        failures
+    }
+
+    /// Reads the next line that is not skipped into the line buffer, returning its number.
+    fn next_line(&mut self) -> Option<u64> {
+        loop {
+            if self.is_cancelled() || self.aborted {
+                return None;
+            }
+            self.started.get_or_insert_with(|| (Instant::now(), None));
+            if self.take_records == Some(0) || !self.read_record() {
+                if let Some((started, elapsed)) = self.started.as_mut() {
+                    elapsed.get_or_insert_with(|| started.elapsed());
+                }
+                if let Some(tracker) = self.tracker.as_mut() {
+                    tracker.finish(&self.progress);
+                }
+                return None;
+            }
+            self.progress.lines += 1;
+            self.progress.bytes = self.inner.count();
+            if self.io_error.is_some() {
+                // The input cannot be read past the error, which is yielded as the last line.
+                self.aborted = true;
+                return Some(self.progress.lines);
+            }
+            if let Some(raw) = self.raw.as_mut() {
+                raw.clear();
+                raw.push_str(&self.buf);
+            }
+            if let Some(tab_stop) = self.tab_stop {
+                expand_tabs(&mut self.buf, tab_stop);
+            }
+            let skip = self.short_final_line();
+            let buf = std::mem::take(&mut self.buf);
+            let skip = skip || self.skip(&buf);
+            self.buf = buf;
+            if !skip {
+                if let Some(n) = self.take_records.as_mut() {
+                    *n -= 1;
+                }
+                return Some(self.progress.lines);
+            }
+            self.progress.skipped += 1;
+            self.report();
+        }
+    }
+
+    /// Applies the final line policy to the current line if it is short and the input ends
+    /// after it, padding the line or returning whether it is skipped.
+    fn short_final_line(&mut self) -> bool {
+        let width = self.parser.width();
+        if self.final_line == FinalLine::Error || self.buf.len() >= width {
+            return false;
+        }
+        if !self.inner.fill_buf().is_ok_and(|rest| rest.is_empty()) {
+            return false;
+        }
+        match self.final_line {
+            FinalLine::Ignore => true,
+            _ => {
+                let padding = width - self.buf.chars().count();
+                self.buf.extend(std::iter::repeat_n(' ', padding));
+                false
+            }
+        }
+    }
+
+    /// Fails the current line if it could not be read, if it is not valid UTF-8 in strict mode,
+    /// or not ASCII in strict ASCII mode.
+    fn check_encoding(&mut self) -> Result<(), Error> {
+        if let Some(e) = self.io_error.take() {
+            return Err(Error::Io(e));
+        }
+        if let Some(source) = self.invalid_utf8 {
+            return Err(Error::Encoding { line: None, source });
+        }
+        match self.non_ascii {
+            Some((column, byte)) => Err(Error::NonAscii {
+                line: None,
+                column,
+                byte,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Counts the outcome of parsing the current line, adding the line number to errors.
+    fn track<T>(&mut self, result: Result<T, Error>) -> Result<T, Error> {
+        if let Err(Error::Io(_)) = result {
+            return result;
+        }
+        self.count(&result);
+        match self.max_errors {
+            Some(max) if result.is_err() && self.progress.errors > max => {
+                self.aborted = true;
+                Err(Error::TooManyErrors {
+                    errors: self.progress.errors,
+                    line: self.progress.lines,
+                })
+            }
+            _ => result.map_err(|e| e.at_line(self.progress.lines)),
+        }
+    }
+
+    /// Counts the result of a line in the progress, except I/O errors.
+    fn count<T>(&mut self, result: &Result<T, Error>) {
+        match result {
+            Ok(_) => self.progress.records += 1,
+            Err(Error::Io(_)) => return,
+            Err(_) => self.progress.errors += 1,
+        }
+        self.report();
+    }
+
+    /// Writes the current line to the reject writer if the result is an error that can be
+    /// rejected, returning `None` as the line is skipped, otherwise returns the result.
+    fn reject<T>(&mut self, result: Result<T, Error>) -> Option<Result<T, Error>> {
+        match result {
+            Err(ref e)
+                if self.rejects.is_some()
+                    && !matches!(e, Error::TooManyErrors { .. } | Error::Io(_)) =>
+            {
+                let line = e.line().unwrap_or(self.progress.lines);
+                match self.write_reject(line, &reject::reason(e)) {
+                    Ok(()) => None,
+                    Err(e) => Some(Err(e)),
+                }
+            }
+            result => Some(result),
+        }
+    }
+
+    fn write_reject(&mut self, line: u64, reason: &str) -> Result<(), Error> {
+        match self.rejects.as_mut() {
+            Some(rejects) => rejects.write(line, self.raw.as_deref().unwrap_or_default(), reason),
+            None => Ok(()),
+        }
+    }
+
+    fn report(&mut self) {
+        if let Some(tracker) = self.tracker.as_mut() {
+            tracker.update(&self.progress);
+        }
+    }
+
+    fn read_record(&mut self) -> bool {
+        let mut bytes = std::mem::take(&mut self.buf).into_bytes();
+        let read = self
+            .terminator
+            .read_record(&mut self.inner, self.parser.width(), &mut bytes);
+        if let Some(algorithm) = self.digest {
+            self.record_digest = algorithm.digest(&bytes);
+        }
+        if self.strict_ascii {
+            self.non_ascii = bytes
+                .iter()
+                .position(|b| !b.is_ascii())
+                .map(|i| (i + 1, bytes[i]));
+        }
+        let more = match read {
+            Ok(more) => more,
+            Err(e) => {
+                self.io_error = Some(e);
+                true
+            }
+        };
+        self.invalid_utf8 = None;
+        match String::from_utf8(bytes) {
+            Ok(s) => {
+                self.buf = s;
+                more
+            }
+            Err(e) if self.utf8_mode == Utf8Mode::Stop => {
+                let mut bytes = e.into_bytes();
+                bytes.clear();
+                self.buf = String::from_utf8(bytes).unwrap_or_default();
+                false
+            }
+            Err(e) => {
+                if self.utf8_mode == Utf8Mode::Strict {
+                    self.invalid_utf8 = Some(e.utf8_error());
+                }
+                self.buf = String::from_utf8_lossy(e.as_bytes()).into_owned();
+                more
+            }
+        }
+    }
+
+    fn skip(&mut self, s: &str) -> bool {
+        if self.skip_blank && s.trim().is_empty() {
+            return true;
+        }
+        if self
+            .comments
+            .iter()
+            .any(|prefix| s.starts_with(prefix.as_str()))
+        {
+            return true;
+        }
+        if self.filter.as_mut().is_some_and(|filter| !filter(s)) {
+            return true;
+        }
+        if self.skip_records > 0 {
+            self.skip_records -= 1;
+            return true;
+        }
+        self.sample.as_mut().is_some_and(|sample| !sample.next())
+    }
+}
+
+/// Replaces the tabs of a line with spaces up to the next tab stop, counting chars.
+fn expand_tabs(line: &mut String, tab_stop: usize) {
+    if !line.contains('\t') {
+        return;
+    }
+    let mut expanded = String::with_capacity(line.len() + tab_stop * 2);
+    let mut column = 0;
+    for c in line.chars() {
+        match c {
+            '\t' => {
+                let spaces = tab_stop - column % tab_stop;
+                expanded.extend(std::iter::repeat_n(' ', spaces));
+                column += spaces;
+            }
+            c => {
+                expanded.push(c);
+                column += 1;
+            }
+        }
+    }
+    *line = expanded;
 }
 
-impl<'a, R> Iterator for StringReader<'a, R>
+/// Selects records at random with a fixed seed, using SplitMix64.
+struct Sample {
+    fraction: f64,
+    state: u64,
+}
+
+impl Sample {
+    /// Whether the next record is part of the sample.
+    fn next(&mut self) -> bool {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        ((z >> 11) as f64 / (1u64 << 53) as f64) < self.fraction
+    }
+}
+
+impl<'r, 'a, R> Iterator for StringReader<'r, 'a, R>
 where
     R: Read,
 {
     type Item = ResultRecord;
 
     fn next(&mut self) -> Option<Self::Item> {
-        match self.r.lines.next() {
-            Some(Ok(s)) => Some(self.parse(s)),
-            _ => None,
+        loop {
+            self.r.next_line()?;
+            let result = self.parse();
+            if let Some(result) = self.r.reject(result) {
+                return Some(result);
+            }
+        }
+    }
+}
+
+impl<'r, 'a, R> Iterator for EnumeratedReader<'r, 'a, R>
+where
+    R: Read,
+{
+    type Item = (u64, ResultRecord);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = self.r.next_line()?;
+            let result = self
+                .r
+                .check_encoding()
+                .and_then(|_| self.r.parser.parse(self.r.buf.as_str()));
+            let result = self.r.track(result);
+            if let Some(result) = self.r.reject(result) {
+                return Some((line, result));
+            }
+        }
+    }
+}
+
+impl<'r, 'a, R> Iterator for RawReader<'r, 'a, R>
+where
+    R: Read,
+{
+    type Item = (String, ResultRecord);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.r.next_line()?;
+            let result = self
+                .r
+                .check_encoding()
+                .and_then(|_| self.r.parser.parse(self.r.buf.as_str()));
+            let result = self.r.track(result);
+            if let Some(result) = self.r.reject(result) {
+                return Some((self.r.raw.clone().unwrap_or_default(), result));
+            }
+        }
+    }
+}
+
+impl<'r, 'a, R> Iterator for RowReader<'r, 'a, R>
+where
+    R: Read,
+{
+    type Item = Result<RowRecord, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.r.next_line()?;
+            let result = self
+                .r
+                .check_encoding()
+                .and_then(|_| self.r.parser.parse_row(self.r.buf.as_str()));
+            let result = self.r.track(result);
+            if let Some(result) = self.r.reject(result) {
+                return Some(result);
+            }
         }
     }
 }
 
+#[allow(dead_code)]
+impl<'a, R> Reader<'a, R>
+where
+    R: Read + Seek,
+{
+    /// Creates a reader continuing from a checkpoint, seeking the input to its offset. Line
+    /// numbers continue from the checkpoint, and the progress counts bytes from the start of
+    /// the input. Other options, such as skipping records, apply from the checkpoint on.
+    pub fn resume(
+        mut reader: R,
+        parser: &'a Parser,
+        checkpoint: Checkpoint,
+    ) -> Result<Self, Error> {
+        reader.seek(SeekFrom::Start(checkpoint.offset))?;
+        let mut rdr = Self::from_reader(reader, parser).bom(Bom::Ignore);
+        rdr.inner.set_count(checkpoint.offset);
+        rdr.progress.lines = checkpoint.line;
+        rdr.progress.bytes = checkpoint.offset;
+        Ok(rdr)
+    }
+}
+
 #[allow(dead_code)]
 impl<'a> Reader<'a, File> {
     /// Creates a new reader from a filepath. Will return an io::Error if there are any issues
@@ -58,6 +1033,44 @@ impl<'a> Reader<'a, File> {
     pub fn from_file(file: File, parser: &'a Parser) -> Self {
         Self::from_reader(file, parser)
     }
+
+    /// Creates a reader chaining the records of several files, read in the given order, with
+    /// directories expanded to the files they contain sorted by path. Use
+    /// [`glob`](super::multi::glob) to list the files matching a pattern.
+    pub fn from_paths<I, P>(paths: I, parser: &'a Parser) -> Result<MultiReader<'a>, Error>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<std::path::Path>,
+    {
+        Ok(MultiReader::new(expand(paths)?, parser))
+    }
+}
+
+#[cfg(feature = "gzip")]
+#[allow(dead_code)]
+impl<'a> Reader<'a, flate2::read::MultiGzDecoder<File>> {
+    /// Creates a new reader decompressing a gzip file, including files made of several
+    /// concatenated gzip members.
+    pub fn from_gzip<P: AsRef<Path>>(path: P, parser: &'a Parser) -> Result<Self, Error> {
+        let file = File::open(path)?;
+        Ok(Self::from_reader(
+            flate2::read::MultiGzDecoder::new(file),
+            parser,
+        ))
+    }
+}
+
+#[cfg(feature = "zstd")]
+#[allow(dead_code)]
+impl<'a> Reader<'a, zstd::stream::read::Decoder<'static, std::io::BufReader<File>>> {
+    /// Creates a new reader decompressing a zstd file.
+    pub fn from_zstd<P: AsRef<Path>>(path: P, parser: &'a Parser) -> Result<Self, Error> {
+        let file = File::open(path)?;
+        Ok(Self::from_reader(
+            zstd::stream::read::Decoder::new(file)?,
+            parser,
+        ))
+    }
 }
 
 #[allow(dead_code)]
@@ -80,6 +1093,7 @@ impl<'a> Reader<'a, Cursor<Vec<u8>>> {
 }
 
 #[cfg(test)]
+#[allow(clippy::needless_lifetimes, clippy::manual_flatten)]
 mod test {
     use super::*;
     use crate::builder::{Buildable, Builder};
@@ -161,4 +1175,703 @@ mod test {
             }
         }
     }
+
+    #[test]
+    fn read_enumerated_records() {
+        let s = "1111222233334444\n12\n1111222233334444";
+
+        let parser = Parser::builder().field("test").range(0..4).append().build();
+        let mut rdr = Reader::from_string(s, &parser);
+
+        let rows = rdr
+            .enumerated_records()
+            .collect::<Vec<(u64, ResultRecord)>>();
+
+        assert_eq!(rows.len(), 3);
+        assert_eq!(
+            rows.iter().map(|(line, _)| *line).collect::<Vec<u64>>(),
+            vec![1, 2, 3]
+        );
+        assert!(rows[0].1.is_ok());
+        assert!(rows[1].1.is_err());
+        assert!(rows[2].1.is_ok());
+    }
+
+    #[test]
+    fn read_with_raw_lines() {
+        let parser = Parser::builder().field("test").range(0..4).append().build();
+        let mut rdr = Reader::from_string("A\tBC\r\n12\n", &parser).expand_tabs(2);
+
+        let rows = rdr.with_raw().collect::<Vec<(String, ResultRecord)>>();
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].0, "A\tBC");
+        assert_eq!(rows[0].1.as_ref().unwrap()["test"], "A BC");
+        assert_eq!(rows[1].0, "12");
+        assert!(rows[1].1.is_err());
+        assert_eq!(rdr.raw_line(), Some("12"));
+    }
+
+    #[test]
+    fn read_line_number() {
+        let parser = Parser::builder().field("test").range(0..4).append().build();
+        let mut rdr = Reader::from_string("1111\n2222\n3333", &parser);
+
+        assert_eq!(rdr.line(), 0);
+        assert_eq!(rdr.string_reader().count(), 3);
+        assert_eq!(rdr.line(), 3);
+    }
+
+    #[test]
+    fn read_skip_blank_and_comments() {
+        let s = "# banner\n1111\n\n   \n* note\n2222";
+
+        let parser = Parser::builder().field("test").range(0..4).append().build();
+        let mut rdr = Reader::from_string(s, &parser)
+            .skip_blank_lines()
+            .skip_comments("#")
+            .skip_comments("*");
+
+        let rows = rdr
+            .enumerated_records()
+            .map(|(line, row)| (line, row.expect("Unexpected parse error")))
+            .collect::<Vec<_>>();
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].0, 2);
+        assert_eq!(rows[0].1.get("test"), Some(&String::from("1111")));
+        assert_eq!(rows[1].0, 6);
+        assert_eq!(rows[1].1.get("test"), Some(&String::from("2222")));
+    }
+
+    #[test]
+    fn read_with_filter() {
+        let s = "A111\nB222\nA333";
+
+        let parser = Parser::builder().field("test").range(0..4).append().build();
+        let mut rdr = Reader::from_string(s, &parser).filter(|line| line.starts_with('A'));
+
+        let rows = rdr.string_reader().flatten().collect::<Vec<_>>();
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1].get("test"), Some(&String::from("A333")));
+    }
+
+    #[test]
+    fn read_fixed_length_records() {
+        let s = "1111222233334444AAAABBBB";
+
+        let parser = Parser::builder()
+            .field("first")
+            .width(4)
+            .append()
+            .field("second")
+            .width(4)
+            .append()
+            .build();
+        let mut rdr = Reader::from_reader_fixed(Cursor::new(s), &parser);
+
+        let rows = rdr.string_reader().collect::<Vec<ResultRecord>>();
+
+        assert_eq!(rows.len(), 3);
+        let rows = rows.into_iter().flatten().collect::<Vec<_>>();
+        assert_eq!(rows[0].get("first"), Some(&String::from("1111")));
+        assert_eq!(rows[1].get("second"), Some(&String::from("4444")));
+        assert_eq!(rows[2].get("first"), Some(&String::from("AAAA")));
+        assert_eq!(rdr.line(), 3);
+    }
+
+    #[test]
+    fn read_fixed_length_short_final_record() {
+        let parser = Parser::builder().field("test").width(4).append().build();
+        let mut rdr = Reader::from_reader_fixed(Cursor::new("1111222233"), &parser);
+
+        let rows = rdr.string_reader().collect::<Vec<ResultRecord>>();
+
+        assert_eq!(rows.len(), 3);
+        assert!(rows[1].is_ok());
+        assert!(rows[2].is_err());
+    }
+
+    #[test]
+    fn read_short_final_line() {
+        let parser = Parser::builder()
+            .field("first")
+            .width(2)
+            .append()
+            .field("second")
+            .width(4)
+            .append()
+            .build();
+        let input = "AB  CD\nEF\nGH";
+        let read = |policy| {
+            Reader::from_string(input, &parser)
+                .final_line(policy)
+                .string_reader()
+                .collect::<Vec<ResultRecord>>()
+        };
+
+        let rows = read(FinalLine::Error);
+        assert_eq!(rows.len(), 3);
+        assert!(rows[1].is_err() && rows[2].is_err());
+
+        let rows = read(FinalLine::Ignore);
+        assert_eq!(rows.len(), 2);
+        assert!(rows[0].is_ok() && rows[1].is_err());
+
+        let rows = read(FinalLine::PadAndParse);
+        assert_eq!(rows.len(), 3);
+        assert!(rows[1].is_err());
+        let last = rows[2].as_ref().unwrap();
+        assert_eq!(last["first"], "GH");
+        assert_eq!(last["second"], "");
+
+        let mut rdr = Reader::from_string("AB  CD\nEF\n", &parser).final_line(FinalLine::Ignore);
+        assert_eq!(rdr.string_reader().count(), 1);
+        assert_eq!(rdr.progress().skipped, 1);
+    }
+
+    #[test]
+    fn read_expanding_tabs() {
+        let parser = Parser::builder()
+            .field("first")
+            .width(4)
+            .append()
+            .field("second")
+            .width(4)
+            .append()
+            .field("third")
+            .width(2)
+            .append()
+            .build();
+        let mut rdr = Reader::from_string("AB\tCDE\tFG\n\t\tHI\n", &parser).expand_tabs(4);
+
+        let rows = rdr
+            .string_reader()
+            .collect::<Result<Vec<Record>, Error>>()
+            .unwrap();
+
+        assert_eq!(rows[0]["first"], "AB");
+        assert_eq!(rows[0]["second"], "CDE");
+        assert_eq!(rows[0]["third"], "FG");
+        assert_eq!(rows[1]["first"], "");
+        assert_eq!(rows[1]["second"], "");
+        assert_eq!(rows[1]["third"], "HI");
+    }
+
+    #[test]
+    fn read_resume_from_checkpoint() {
+        let parser = Parser::builder().field("test").width(4).append().build();
+        let input = "\u{feff}1111\n2222\n3333\n4444\n";
+        let mut rdr = Reader::from_reader(Cursor::new(input), &parser);
+
+        let first = rdr.string_reader().take(2).count();
+        let checkpoint = rdr.checkpoint().unwrap();
+        assert_eq!(first, 2);
+        assert_eq!(
+            checkpoint,
+            Checkpoint {
+                offset: 13,
+                line: 2
+            }
+        );
+
+        let mut rdr = Reader::resume(Cursor::new(input), &parser, checkpoint).unwrap();
+        let rows = rdr
+            .enumerated_records()
+            .map(|(line, record)| (line, record.unwrap()["test"].clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rows,
+            vec![(3, String::from("3333")), (4, String::from("4444"))]
+        );
+        assert_eq!(rdr.offset(), Some(input.len() as u64));
+    }
+
+    #[test]
+    fn read_rdw_records() {
+        let parser = Parser::builder()
+            .field("first")
+            .width(2)
+            .append()
+            .field("second")
+            .width(2)
+            .append()
+            .build();
+        let input = b"\x00\x08\x00\x00AB12\x00\x08\x00\x00CD34".to_vec();
+        let mut rdr = Reader::from_reader_rdw(Cursor::new(input), &parser);
+
+        let rows = rdr.string_reader().flatten().collect::<Vec<_>>();
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1]["first"], "CD");
+        assert_eq!(rows[1]["second"], "34");
+    }
+
+    #[test]
+    fn read_truncated_rdw_record() {
+        let parser = Parser::builder().field("first").width(4).append().build();
+        let input = b"\x00\x08\x00\x00AB12\x00\x08\x00\x00CD".to_vec();
+        let mut rdr = Reader::from_reader_rdw(Cursor::new(input), &parser);
+
+        let results = rdr.string_reader().collect::<Vec<_>>();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].as_ref().unwrap()["first"], "AB12");
+        assert!(matches!(
+            &results[1],
+            Err(Error::Io(e)) if e.kind() == io::ErrorKind::UnexpectedEof
+        ));
+    }
+
+    #[cfg(feature = "crc32")]
+    #[test]
+    fn read_with_digests() {
+        let parser = Parser::builder().field("test").width(4).append().build();
+        let input = "\u{feff}1111\n2222\n";
+        let mut rdr = Reader::from_string(input, &parser).digest(Algorithm::Crc32);
+
+        assert_eq!(rdr.record_digest(), Some(""));
+        rdr.string_reader().next().unwrap().unwrap();
+        assert_eq!(
+            rdr.record_digest(),
+            Some(Algorithm::Crc32.digest(b"1111").as_str())
+        );
+        assert_eq!(rdr.string_reader().count(), 1);
+        assert_eq!(
+            rdr.file_digest(),
+            Some(Algorithm::Crc32.digest(input.as_bytes()))
+        );
+        assert_eq!(Reader::from_string(input, &parser).file_digest(), None);
+    }
+
+    #[test]
+    fn read_skip_take_and_sample() {
+        let parser = Parser::builder().field("test").width(4).append().build();
+        let input = (0..1000).map(|i| format!("{:04}\n", i)).collect::<String>();
+
+        let mut rdr = Reader::from_string(input.as_str(), &parser)
+            .skip_comments("0000")
+            .skip_records(2)
+            .take_records(3);
+        let rows = rdr.string_reader().flatten().collect::<Vec<_>>();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0]["test"], "0003");
+        assert_eq!(rdr.line(), 6);
+        assert_eq!(rdr.progress().skipped, 3);
+
+        let sample = |seed| {
+            Reader::from_string(input.as_str(), &parser)
+                .sample(0.1, seed)
+                .string_reader()
+                .map(|r| r.unwrap()["test"].clone())
+                .collect::<Vec<_>>()
+        };
+        let first = sample(42);
+        assert!(first.len() > 50 && first.len() < 150);
+        assert_eq!(first, sample(42));
+        assert_ne!(first, sample(7));
+    }
+
+    #[test]
+    fn read_strict_ascii() {
+        let parser = Parser::builder().field("name").width(5).append().build();
+        let mut rdr = Reader::from_string("Alice\nZoë  \nBob  \n", &parser).strict_ascii();
+
+        let results = rdr.string_reader().collect::<Vec<_>>();
+
+        assert_eq!(results.len(), 3);
+        assert!(results[0].is_ok() && results[2].is_ok());
+        let error = results[1].as_ref().unwrap_err();
+        assert!(matches!(
+            error,
+            Error::NonAscii {
+                line: Some(2),
+                column: 3,
+                byte: 0xC3
+            }
+        ));
+        assert_eq!(error.to_string(), "Line 2: Non-ASCII byte 0xC3 at column 3");
+    }
+
+    #[test]
+    fn read_invalid_utf8() {
+        let parser = Parser::builder().field("name").width(5).append().build();
+        let input = b"Alice\nZo\xFF  \nBob  \n";
+        let read = |mode| {
+            Reader::from_reader(&input[..], &parser)
+                .invalid_utf8(mode)
+                .string_reader()
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(read(Utf8Mode::Stop).len(), 1);
+
+        let lossy = read(Utf8Mode::Lossy);
+        assert_eq!(lossy.len(), 3);
+        assert_eq!(lossy[1].as_ref().unwrap()["name"], "Zo\u{FFFD}");
+
+        let strict = read(Utf8Mode::Strict);
+        assert_eq!(strict.len(), 3);
+        assert!(strict[0].is_ok() && strict[2].is_ok());
+        let error = strict[1].as_ref().unwrap_err();
+        assert!(matches!(error, Error::Encoding { line: Some(2), .. }));
+        assert_eq!(error.to_string(), "Line 2: Invalid UTF-8 after 2 bytes");
+    }
+
+    #[test]
+    fn read_crlf_lines() {
+        let parser = Parser::builder().field("test").width(4).append().build();
+        let mut rdr = Reader::from_string("1111\r\n2222\r\n", &parser);
+
+        let rows = rdr.string_reader().flatten().collect::<Vec<_>>();
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1].get("test"), Some(&String::from("2222")));
+    }
+
+    #[test]
+    fn read_cancelled() {
+        let parser = Parser::builder().field("test").width(4).append().build();
+        let token = CancellationToken::new();
+        let mut rdr = Reader::from_string("1111\n2222\n3333", &parser).cancel_on(token.clone());
+
+        let mut rows = rdr.string_reader();
+        assert!(rows.next().is_some());
+        token.cancel();
+        assert!(rows.next().is_none());
+
+        assert!(rdr.is_cancelled());
+        assert_eq!(rdr.line(), 1);
+    }
+
+    #[test]
+    fn read_with_terminator() {
+        let parser = Parser::builder().field("test").width(4).append().build();
+        let mut rdr =
+            Reader::from_bytes(b"1111\x852222\x85".to_vec(), &parser).terminator(Terminator::Nel);
+
+        let rows = rdr.string_reader().flatten().collect::<Vec<_>>();
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1].get("test"), Some(&String::from("2222")));
+    }
+
+    #[test]
+    fn read_strips_bom() {
+        let parser = Parser::builder().field("test").width(4).append().build();
+        let mut rdr = Reader::from_string("\u{feff}1111\n2222", &parser);
+
+        let rows = rdr.string_reader().flatten().collect::<Vec<_>>();
+
+        assert_eq!(rows[0].get("test"), Some(&String::from("1111")));
+        assert_eq!(rdr.encoding(), Some(Encoding::Utf8));
+    }
+
+    #[test]
+    fn read_fixed_keeps_bom() {
+        let parser = Parser::builder().field("test").width(3).append().build();
+        let bytes = b"\xef\xbb\xbfABC".to_vec();
+
+        let mut rdr = Reader::from_bytes(bytes.clone(), &parser).terminator(Terminator::Fixed);
+        let rows = rdr.string_reader().flatten().collect::<Vec<_>>();
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1].get("test"), Some(&String::from("ABC")));
+
+        let mut rdr = Reader::from_bytes(bytes, &parser)
+            .bom(Bom::Strip)
+            .terminator(Terminator::Fixed);
+        let rows = rdr.string_reader().flatten().collect::<Vec<_>>();
+
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].get("test"), Some(&String::from("ABC")));
+    }
+
+    #[test]
+    fn read_transcodes_utf16() {
+        let mut bytes = vec![0xFF, 0xFE];
+        "1111\n2222\n"
+            .encode_utf16()
+            .for_each(|u| bytes.extend_from_slice(&u.to_le_bytes()));
+
+        let parser = Parser::builder().field("test").width(4).append().build();
+        let mut rdr = Reader::from_bytes(bytes, &parser).bom(Bom::Transcode);
+
+        let rows = rdr.string_reader().flatten().collect::<Vec<_>>();
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1].get("test"), Some(&String::from("2222")));
+        assert_eq!(rdr.encoding(), Some(Encoding::Utf16Le));
+    }
+
+    #[test]
+    fn read_into_record() {
+        let parser = Parser::builder()
+            .field("first")
+            .width(4)
+            .append()
+            .field("second")
+            .width(4)
+            .align("right")
+            .padding('0')
+            .append()
+            .build();
+        let mut rdr =
+            Reader::from_string("AB  0012\n# skip\nABCD1234\nshort", &parser).skip_comments("#");
+        let mut record = Record::new();
+
+        assert!(rdr.read_into(&mut record).unwrap());
+        assert_eq!(record.get("first"), Some(&String::from("AB")));
+        assert_eq!(record.get("second"), Some(&String::from("12")));
+
+        assert!(rdr.read_into(&mut record).unwrap());
+        assert_eq!(record.get("first"), Some(&String::from("ABCD")));
+        assert_eq!(record.get("second"), Some(&String::from("1234")));
+        assert_eq!(rdr.line(), 3);
+
+        assert!(rdr.read_into(&mut record).is_err());
+        assert!(!rdr.read_into(&mut record).unwrap());
+    }
+
+    #[test]
+    fn read_with_visitor() {
+        let parser = Parser::builder()
+            .field("id")
+            .width(2)
+            .append()
+            .field("amount")
+            .width(4)
+            .align("right")
+            .padding('0')
+            .append()
+            .build();
+        let mut rdr = Reader::from_string("010012\nX\n020030\n", &parser);
+        let mut total = 0;
+        let mut visit = |field: &str, value: &str| {
+            if field == "amount" {
+                total += value.parse::<u32>().unwrap();
+            }
+        };
+
+        assert!(rdr.read_with(&mut visit).unwrap());
+        assert!(matches!(
+            rdr.read_with(&mut visit),
+            Err(Error::Parse { line: Some(2), .. })
+        ));
+        assert!(rdr.read_with(&mut visit).unwrap());
+        assert!(!rdr.read_with(&mut visit).unwrap());
+        assert_eq!(rdr.progress().records, 2);
+        assert_eq!(total, 42);
+    }
+
+    #[test]
+    fn read_rows() {
+        let parser = Parser::builder()
+            .field("first")
+            .width(4)
+            .append()
+            .spacer(4..8)
+            .field("second")
+            .width(4)
+            .append()
+            .build();
+        let mut rdr = Reader::from_file(
+            File::open(test_file("file-001.txt")).expect("Error reading test file"),
+            &parser,
+        );
+
+        let rows = rdr.row_reader().flatten().collect::<Vec<RowRecord>>();
+
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0], RowRecord::from(vec!["1111", "3333"]));
+
+        let mut rdr = Reader::from_string("AAAA....BBBB", &parser);
+        let mut row = RowRecord::new();
+        assert!(rdr.read_row_into(&mut row).unwrap());
+        assert_eq!(row, RowRecord::from(vec!["AAAA", "BBBB"]));
+        assert!(!rdr.read_row_into(&mut row).unwrap());
+    }
+
+    #[test]
+    fn read_with_progress() {
+        let s = "1111\n# note\n22\n3333\n4444\n5555";
+
+        let parser = Parser::builder().field("test").width(4).append().build();
+        let mut reports = Vec::new();
+        let mut rdr = Reader::from_string(s, &parser)
+            .skip_comments("#")
+            .on_progress(Interval::Lines(2), |p| reports.push(*p));
+
+        assert_eq!(rdr.string_reader().count(), 5);
+        assert_eq!(
+            rdr.progress(),
+            Progress {
+                lines: 6,
+                records: 4,
+                skipped: 1,
+                errors: 1,
+                invalid: 0,
+                bytes: 29,
+            }
+        );
+        drop(rdr);
+
+        assert_eq!(
+            reports.iter().map(|p| p.lines).collect::<Vec<u64>>(),
+            vec![2, 4, 6, 6]
+        );
+        assert_eq!(reports[1].errors, 1);
+        assert_eq!(reports[1].bytes, 20);
+    }
+
+    #[test]
+    fn read_all_validated() {
+        let s = "AB  0012\nshort\n    0034\nCD  00X6\n    00AB\nEF  0056";
+
+        let parser = Parser::builder()
+            .field("name")
+            .width(4)
+            .append()
+            .field("amount")
+            .width(4)
+            .align("right")
+            .padding('0')
+            .append()
+            .build();
+        let mut rdr = Reader::from_string(s, &parser)
+            .validate_field("name", |v| match v.is_empty() {
+                true => Err(String::from("is required")),
+                false => Ok(()),
+            })
+            .validate_field("amount", |v| match v.chars().all(|c| c.is_ascii_digit()) {
+                true => Ok(()),
+                false => Err(String::from("is not numeric")),
+            });
+
+        let (records, errors) = rdr.read_all_validated().unwrap();
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1].get("name"), Some(&String::from("EF")));
+        assert_eq!(
+            errors.iter().map(|e| e.line).collect::<Vec<u64>>(),
+            vec![2, 3, 4, 5]
+        );
+        assert!(errors[0].error.is_some());
+        assert_eq!(errors[1].to_string(), "Line 3: name: is required");
+        assert_eq!(errors[2].to_string(), "Line 4: amount: is not numeric");
+        assert_eq!(errors[3].fields.len(), 2);
+    }
+
+    #[test]
+    fn read_all_validated_unique_key() {
+        let parser = Parser::builder()
+            .field("branch")
+            .width(2)
+            .append()
+            .field("account")
+            .width(4)
+            .append()
+            .build();
+        let s = "010001\n010002\n020001\n010002\n010001";
+        let mut rdr = Reader::from_string(s, &parser).unique_key(&["branch", "account"]);
+
+        let (records, errors) = rdr.read_all_validated().unwrap();
+
+        assert_eq!(records.len(), 3);
+        assert_eq!(
+            errors.iter().map(|e| e.line).collect::<Vec<u64>>(),
+            vec![4, 5]
+        );
+        assert_eq!(
+            errors[0].to_string(),
+            "Line 4: branch+account: duplicate key 01, 0002 first seen on line 2"
+        );
+    }
+
+    #[test]
+    fn read_all_validated_progress() {
+        let parser = Parser::builder().field("id").width(2).append().build();
+        let mut rdr = Reader::from_string("01\nAB\n0", &parser).validate_field("id", |v| {
+            v.parse::<u8>().map(|_| ()).map_err(|e| e.to_string())
+        });
+
+        rdr.read_all_validated().unwrap();
+
+        assert_eq!(rdr.progress().records, 2);
+        assert_eq!(rdr.progress().errors, 1);
+        assert_eq!(rdr.progress().invalid, 1);
+        let elapsed = rdr.elapsed();
+        assert_eq!(rdr.elapsed(), elapsed);
+    }
+
+    #[test]
+    fn read_all_validated_sequences() {
+        let parser = Parser::builder()
+            .field("seq")
+            .auto_sequence(1, 1, 3)
+            .append()
+            .build();
+        let mut rdr = Reader::from_string("001\n002\n002\nABC\n005", &parser).check_sequences();
+
+        let (records, errors) = rdr.read_all_validated().unwrap();
+
+        assert_eq!(records.len(), 3);
+        assert_eq!(
+            errors[0].to_string(),
+            "Line 3: seq: 2 does not increase from 2"
+        );
+        assert_eq!(
+            errors[1].to_string(),
+            "Line 4: seq: ABC is not a sequence number"
+        );
+    }
+
+    #[test]
+    fn read_max_errors() {
+        let parser = Parser::builder().field("test").width(4).append().build();
+        let mut rdr = Reader::from_string("1111\n22\n3333\n44\n55\n6666", &parser).max_errors(2);
+
+        let rows = rdr.string_reader().collect::<Vec<ResultRecord>>();
+
+        assert_eq!(rows.len(), 5);
+        assert!(matches!(rows[3], Err(Error::Parse { line: Some(4), .. })));
+        assert!(matches!(
+            rows[4],
+            Err(Error::TooManyErrors { errors: 3, line: 5 })
+        ));
+        assert_eq!(rdr.line(), 5);
+    }
+
+    #[test]
+    fn read_all_validated_max_errors() {
+        let parser = Parser::builder().field("test").width(4).append().build();
+        let mut rdr = Reader::from_string("1111\n22\n    \n4444", &parser)
+            .validate_field("test", |v| match v.is_empty() {
+                true => Err(String::from("is required")),
+                false => Ok(()),
+            })
+            .max_errors(1);
+
+        let e = rdr.read_all_validated().unwrap_err();
+
+        assert_eq!(e.to_string(), "Aborted after 2 errors at line 3");
+    }
+
+    #[test]
+    fn read_all_validated_max_errors_in_any_order() {
+        let parser = Parser::builder().field("test").width(4).append().build();
+        for input in ["1111\n    \n22\n3333", "1111\n22\n    \n3333"] {
+            let mut rdr = Reader::from_string(input, &parser)
+                .validate_field("test", |v| match v.is_empty() {
+                    true => Err(String::from("is required")),
+                    false => Ok(()),
+                })
+                .max_errors(1);
+
+            let e = rdr.read_all_validated().unwrap_err();
+
+            assert!(matches!(e, Error::TooManyErrors { errors: 2, line: 3 }));
+        }
+    }
 }
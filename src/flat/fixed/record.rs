@@ -0,0 +1,178 @@
+use crate::HashMap;
+use alloc::{
+    collections::BTreeMap,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// A container parsed values are written to, see
+/// [`Parser::parse_to`](super::Parser::parse_to). Implemented for maps, vectors of pairs and
+/// vectors of values, and by user types such as structs with a field per column.
+pub trait RecordSink {
+    /// Sets the value of a named field. Null fields are not set.
+    fn set(&mut self, name: &str, value: &str);
+}
+
+/// A container records are formatted from, see
+/// [`Parser::format_from`](super::Parser::format_from).
+pub trait RecordSource {
+    /// The value of a field, `None` if it is missing or null.
+    fn get(&self, name: &str) -> Option<&str>;
+}
+
+impl RecordSink for HashMap<String, String> {
+    fn set(&mut self, name: &str, value: &str) {
+        self.insert(name.to_string(), value.to_string());
+    }
+}
+
+impl RecordSink for BTreeMap<String, String> {
+    fn set(&mut self, name: &str, value: &str) {
+        self.insert(name.to_string(), value.to_string());
+    }
+}
+
+/// Pairs are pushed in layout order.
+impl RecordSink for Vec<(String, String)> {
+    fn set(&mut self, name: &str, value: &str) {
+        self.push((name.to_string(), value.to_string()));
+    }
+}
+
+/// Values are pushed in layout order, null fields are left out.
+impl RecordSink for Vec<String> {
+    fn set(&mut self, _name: &str, value: &str) {
+        self.push(value.to_string());
+    }
+}
+
+impl<S: RecordSink + ?Sized> RecordSink for &mut S {
+    fn set(&mut self, name: &str, value: &str) {
+        (**self).set(name, value)
+    }
+}
+
+impl RecordSource for HashMap<String, String> {
+    fn get(&self, name: &str) -> Option<&str> {
+        HashMap::get(self, name).map(|v| v.as_str())
+    }
+}
+
+impl RecordSource for BTreeMap<String, String> {
+    fn get(&self, name: &str) -> Option<&str> {
+        BTreeMap::get(self, name).map(|v| v.as_str())
+    }
+}
+
+/// The first pair with the name is used.
+impl<K, V> RecordSource for [(K, V)]
+where
+    K: AsRef<str>,
+    V: AsRef<str>,
+{
+    fn get(&self, name: &str) -> Option<&str> {
+        self.iter()
+            .find(|(k, _)| k.as_ref() == name)
+            .map(|(_, v)| v.as_ref())
+    }
+}
+
+impl<K, V> RecordSource for Vec<(K, V)>
+where
+    K: AsRef<str>,
+    V: AsRef<str>,
+{
+    fn get(&self, name: &str) -> Option<&str> {
+        RecordSource::get(self.as_slice(), name)
+    }
+}
+
+impl<S: RecordSource + ?Sized> RecordSource for &S {
+    fn get(&self, name: &str) -> Option<&str> {
+        (**self).get(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        builder::{Buildable, Builder},
+        flat::fixed::{write::Writer, Parser},
+    };
+
+    #[derive(Debug, Default, PartialEq)]
+    struct Account {
+        id: String,
+        name: String,
+    }
+
+    impl RecordSink for Account {
+        fn set(&mut self, name: &str, value: &str) {
+            match name {
+                "id" => self.id = value.to_string(),
+                "name" => self.name = value.to_string(),
+                _ => (),
+            }
+        }
+    }
+
+    impl RecordSource for Account {
+        fn get(&self, name: &str) -> Option<&str> {
+            match name {
+                "id" => Some(&self.id),
+                "name" => Some(&self.name),
+                _ => None,
+            }
+        }
+    }
+
+    fn parser() -> Parser<'static> {
+        Parser::builder()
+            .field("id")
+            .width(4)
+            .align("right")
+            .padding('0')
+            .append()
+            .field("name")
+            .width(5)
+            .append()
+            .build()
+    }
+
+    #[test]
+    fn parse_to_containers() {
+        let parser = parser();
+        let mut account = Account::default();
+        let mut values = Vec::<String>::new();
+        let mut sorted = BTreeMap::new();
+
+        parser.parse_to("0042ALICE", &mut account).unwrap();
+        parser.parse_to("0007BOB  ", &mut values).unwrap();
+        parser.parse_to("0001EVE  ", &mut sorted).unwrap();
+
+        assert_eq!(account.id, "42");
+        assert_eq!(account.name, "ALICE");
+        assert_eq!(values, ["7", "BOB"]);
+        assert_eq!(sorted.keys().collect::<Vec<_>>(), ["id", "name"]);
+    }
+
+    #[test]
+    fn format_from_containers() {
+        let parser = parser();
+        let account = Account {
+            id: String::from("42"),
+            name: String::from("ALICE"),
+        };
+        let pairs = vec![("name", "BOB"), ("id", "7")];
+
+        assert_eq!(parser.format_from(&account), "0042ALICE");
+        assert_eq!(parser.format_from(&pairs), "0007BOB  ");
+
+        let mut wtr = Writer::from_writer(Vec::new(), &parser);
+        wtr.write_from(&account).unwrap();
+        wtr.write_from(&pairs[..]).unwrap();
+        let out = String::from_utf8(wtr.into_inner().unwrap()).unwrap();
+        assert_eq!(out, "0042ALICE\n0007BOB  \n");
+    }
+}
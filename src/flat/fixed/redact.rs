@@ -0,0 +1,174 @@
+use crate::flat::fixed::{Record, ResultRecord};
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+/// How the value of a field is scrubbed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Redaction {
+    /// Replaces every character but the last `keep` with the mask character, e.g. a card
+    /// number showing only its last four digits.
+    Mask { keep: usize, mask: char },
+    /// Replaces the value with a hex hash of the salt and the value, so equal values stay equal
+    /// across records and files redacted with the same salt. Not a cryptographic hash, and the
+    /// 16 characters of the hash are truncated to the width of the field on format.
+    Hash,
+    /// Replaces the value with an empty string, formatted as padding.
+    Blank,
+}
+
+impl Redaction {
+    /// Masks all but the last four characters with `*`.
+    pub fn mask_all_but_last4() -> Self {
+        Redaction::Mask { keep: 4, mask: '*' }
+    }
+
+    fn apply(&self, value: &str, salt: &str) -> String {
+        match self {
+            Redaction::Mask { keep, mask } => {
+                let masked = value.chars().count().saturating_sub(*keep);
+                value
+                    .chars()
+                    .enumerate()
+                    .map(|(i, c)| if i < masked { *mask } else { c })
+                    .collect()
+            }
+            Redaction::Hash => {
+                let mut hasher = DefaultHasher::new();
+                salt.hash(&mut hasher);
+                value.hash(&mut hasher);
+                format!("{:016x}", hasher.finish())
+            }
+            Redaction::Blank => String::new(),
+        }
+    }
+}
+
+/// The redactions applied to the fields of a record, for producing test copies of files with
+/// personal data scrubbed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Redactor {
+    fields: Vec<(String, Redaction)>,
+    salt: String,
+}
+
+#[allow(dead_code)]
+impl Redactor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Redacts a field, fields missing from a record are left missing.
+    pub fn field<T: Into<String>>(mut self, field: T, redaction: Redaction) -> Self {
+        self.fields.push((field.into(), redaction));
+        self
+    }
+
+    /// Sets the salt of hashed fields, keep it secret so hashes cannot be reversed by hashing
+    /// likely values.
+    pub fn salt<T: Into<String>>(mut self, salt: T) -> Self {
+        self.salt = salt.into();
+        self
+    }
+
+    /// Redacts the fields of a record in place.
+    pub fn apply(&self, record: &mut Record) {
+        for (field, redaction) in &self.fields {
+            if let Some(value) = record.get_mut(field) {
+                *value = redaction.apply(value, &self.salt);
+            }
+        }
+    }
+}
+
+/// Redacts the records of an iterator, errors are passed through.
+pub struct Redact<'r, I> {
+    records: I,
+    redactor: &'r Redactor,
+}
+
+impl<'r, I> Redact<'r, I>
+where
+    I: Iterator<Item = ResultRecord>,
+{
+    pub fn new(records: I, redactor: &'r Redactor) -> Self {
+        Redact { records, redactor }
+    }
+}
+
+impl<'r, I> Iterator for Redact<'r, I>
+where
+    I: Iterator<Item = ResultRecord>,
+{
+    type Item = ResultRecord;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.records.next().map(|result| {
+            result.map(|mut record| {
+                self.redactor.apply(&mut record);
+                record
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        builder::{Buildable, Builder},
+        flat::fixed::{read::Reader, write::Writer, Parser},
+    };
+
+    #[test]
+    fn redact_reader() {
+        let parser = Parser::builder()
+            .field("card")
+            .width(8)
+            .append()
+            .field("name")
+            .width(4)
+            .append()
+            .field("email")
+            .width(16)
+            .append()
+            .build();
+        let input = "12345678Ann ann@example.com \n87654321Bob ann@example.com \n";
+        let redactor = Redactor::new()
+            .field("card", Redaction::mask_all_but_last4())
+            .field("name", Redaction::Blank)
+            .field("email", Redaction::Hash)
+            .salt("secret");
+        let mut rdr = Reader::from_string(input, &parser);
+
+        let records = rdr
+            .redact(&redactor)
+            .collect::<Result<Vec<Record>, _>>()
+            .unwrap();
+
+        assert_eq!(records[0]["card"], "****5678");
+        assert_eq!(records[0]["name"], "");
+        assert_eq!(records[0]["email"].len(), 16);
+        assert_ne!(records[0]["email"], "ann@example.com");
+        assert_eq!(records[0]["email"], records[1]["email"]);
+        assert_ne!(
+            Redaction::Hash.apply("ann@example.com", "other"),
+            records[0]["email"]
+        );
+    }
+
+    #[test]
+    fn redact_writer() {
+        let parser = Parser::builder().field("card").width(6).append().build();
+        let redactor = Redactor::new().field("card", Redaction::Mask { keep: 2, mask: 'X' });
+        let mut wtr = Writer::from_writer(Vec::new(), &parser).redact(redactor);
+        let mut record = Record::new();
+        record.insert(String::from("card"), String::from("123456"));
+
+        wtr.write(&record).unwrap();
+
+        assert_eq!(record["card"], "123456");
+        assert_eq!(wtr.into_inner().unwrap(), b"XXXX56\n");
+    }
+}
@@ -0,0 +1,187 @@
+use crate::{error::Error, flat::terminator::Terminator};
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+/// Writes the lines a reader rejects unchanged to a reject file, and the line number and reason
+/// of each one to a tab separated sidecar report, so they can be audited, fixed and replayed.
+///
+/// Configured on a reader with [`Reader::reject_to`](super::read::Reader::reject_to).
+pub struct RejectWriter<'w> {
+    lines: BufWriter<Box<dyn Write + 'w>>,
+    report: Option<BufWriter<Box<dyn Write + 'w>>>,
+    terminator: Terminator,
+    rejected: u64,
+}
+
+#[allow(dead_code)]
+impl<'w> RejectWriter<'w> {
+    /// Creates a reject writer without a report.
+    pub fn new<W: Write + 'w>(lines: W) -> Self {
+        RejectWriter {
+            lines: BufWriter::new(Box::new(lines)),
+            report: None,
+            terminator: Terminator::default(),
+            rejected: 0,
+        }
+    }
+
+    /// Creates the reject file and its report, named after the reject file with a `.report`
+    /// extension appended.
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let mut report = path.as_os_str().to_owned();
+        report.push(".report");
+        Ok(Self::new(File::create(path)?).report(File::create(report)?))
+    }
+
+    /// Writes a report line per rejected line.
+    pub fn report<W: Write + 'w>(mut self, report: W) -> Self {
+        self.report = Some(BufWriter::new(Box::new(report)));
+        self
+    }
+
+    /// Sets how the rejected lines are separated, newlines by default.
+    pub fn terminator(mut self, terminator: Terminator) -> Self {
+        self.terminator = terminator;
+        self
+    }
+
+    /// The number of lines rejected so far.
+    pub fn rejected(&self) -> u64 {
+        self.rejected
+    }
+
+    /// Writes a rejected line and its report entry.
+    pub fn write(&mut self, line: u64, raw: &str, reason: &str) -> Result<(), Error> {
+        self.terminator
+            .write_record(&mut self.lines, raw.as_bytes())?;
+        if let Some(report) = self.report.as_mut() {
+            writeln!(report, "{}\t{}", line, reason.replace(['\t', '\n'], " "))?;
+        }
+        self.rejected += 1;
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> Result<(), Error> {
+        self.lines.flush()?;
+        if let Some(report) = self.report.as_mut() {
+            report.flush()?;
+        }
+        Ok(())
+    }
+}
+
+impl<'w> Drop for RejectWriter<'w> {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+/// Why a line was rejected, without the line number the report already has.
+pub(crate) fn reason(error: &Error) -> String {
+    match error {
+        Error::Parse {
+            field: Some(field),
+            kind,
+            ..
+        } => format!("Field {}: {}", field, kind),
+        Error::Parse { kind, .. } => kind.to_string(),
+        Error::Validation { fields, .. } => fields
+            .iter()
+            .map(|f| f.to_string())
+            .collect::<Vec<String>>()
+            .join("; "),
+        e => e.to_string(),
+    }
+}
+
+/// A shared buffer the tests can read after the writer is dropped.
+#[cfg(test)]
+#[derive(Clone, Default)]
+pub(crate) struct SharedBuffer(std::rc::Rc<std::cell::RefCell<Vec<u8>>>);
+
+#[cfg(test)]
+impl SharedBuffer {
+    pub(crate) fn contents(&self) -> String {
+        String::from_utf8(self.0.borrow().clone()).unwrap()
+    }
+}
+
+#[cfg(test)]
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        builder::{Buildable, Builder},
+        flat::fixed::{read::Reader, Parser, Record},
+    };
+
+    #[test]
+    fn reject_failed_lines() {
+        let parser = Parser::builder()
+            .field("id")
+            .width(2)
+            .append()
+            .field("amount")
+            .width(3)
+            .append()
+            .build();
+        let (lines, report) = (SharedBuffer::default(), SharedBuffer::default());
+        let mut rdr = Reader::from_string("01100\nX\n02ABC\n03300\n", &parser)
+            .validate_field("amount", |v| match v.parse::<u32>() {
+                Ok(_) => Ok(()),
+                Err(_) => Err(String::from("not numeric")),
+            })
+            .reject_to(RejectWriter::new(lines.clone()).report(report.clone()));
+
+        let (records, errors) = rdr.read_all_validated().unwrap();
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(errors.len(), 2);
+        drop(rdr);
+        assert_eq!(lines.contents(), "X\n02ABC\n");
+        assert_eq!(
+            report.contents(),
+            "2\tInsufficient buffer size, required 5 only 1 available\n3\tamount: not numeric\n"
+        );
+    }
+
+    #[test]
+    fn reject_while_iterating() {
+        let parser = Parser::builder().field("id").width(2).append().build();
+        let lines = SharedBuffer::default();
+        let mut rdr =
+            Reader::from_string("01\nX\n02\n", &parser).reject_to(RejectWriter::new(lines.clone()));
+
+        let records = rdr
+            .string_reader()
+            .collect::<Result<Vec<Record>, Error>>()
+            .unwrap();
+        assert_eq!(rdr.rejected(), 1);
+        drop(rdr);
+        let mut record = Record::new();
+        let mut rdr =
+            Reader::from_string("X\n03\n", &parser).reject_to(RejectWriter::new(lines.clone()));
+        assert!(rdr.read_into(&mut record).unwrap());
+        assert!(!rdr.read_into(&mut record).unwrap());
+        drop(rdr);
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(record["id"], "03");
+        assert_eq!(lines.contents(), "X\nX\n");
+    }
+}
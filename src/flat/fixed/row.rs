@@ -0,0 +1,293 @@
+use crate::{
+    flat::fixed::{Parser, Record},
+    HashMap,
+};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{iter::FromIterator, ops::Index, slice::Iter};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+type Values<'r> = core::iter::Map<Iter<'r, Option<String>>, fn(&Option<String>) -> Option<&str>>;
+
+/// Maps the names of the fields in a layout to their index in a [`RowRecord`].
+///
+/// Spacers are not part of the header, so the index of a field is its position among the named
+/// fields of the layout. When several fields share a name, the first one is used.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Header {
+    names: Vec<String>,
+    indices: HashMap<String, usize>,
+}
+
+#[allow(dead_code)]
+impl Header {
+    /// The index of the field with the given name.
+    pub fn index(&self, name: &str) -> Option<usize> {
+        self.indices.get(name).copied()
+    }
+
+    /// The name of the field at the given index.
+    pub fn name(&self, index: usize) -> Option<&str> {
+        self.names.get(index).map(|s| s.as_str())
+    }
+
+    /// The field names, in layout order.
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
+
+impl<'a> From<&Parser<'a>> for Header {
+    fn from(parser: &Parser<'a>) -> Self {
+        let names = parser
+            .fields()
+            .iter()
+            .filter_map(|f| f.name())
+            .map(String::from)
+            .collect::<Vec<String>>();
+        let mut indices = HashMap::with_capacity(names.len());
+        for (i, name) in names.iter().enumerate() {
+            indices.entry(name.clone()).or_insert(i);
+        }
+        Header { names, indices }
+    }
+}
+
+/// A record holding the values of the named fields in layout order, addressed by index.
+///
+/// Cheaper to build than a [`Record`] as no field names are hashed or cloned, use a [`Header`]
+/// to look values up by name. Null fields have no value.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct RowRecord {
+    values: Vec<Option<String>>,
+}
+
+#[allow(dead_code)]
+impl RowRecord {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The value at the given index, `None` if the field is null or out of bounds.
+    pub fn get(&self, index: usize) -> Option<&str> {
+        self.values.get(index).and_then(|s| s.as_deref())
+    }
+
+    /// Whether the field at the given index is null.
+    pub fn is_null(&self, index: usize) -> bool {
+        matches!(self.values.get(index), Some(None))
+    }
+
+    /// The value of the field with the given name, using the header of the layout.
+    pub fn get_by_name(&self, header: &Header, name: &str) -> Option<&str> {
+        header.index(name).and_then(|i| self.get(i))
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Iterates over the values, `None` for null fields.
+    pub fn iter(&self) -> Values<'_> {
+        self.values.iter().map(Option::as_deref)
+    }
+
+    /// Appends a value.
+    pub fn push<T: Into<String>>(&mut self, value: T) {
+        self.values.push(Some(value.into()));
+    }
+
+    /// Appends a null value.
+    pub fn push_null(&mut self) {
+        self.values.push(None);
+    }
+
+    pub fn clear(&mut self) {
+        self.values.clear();
+    }
+
+    /// Converts into a record keyed by field name, null fields are left out.
+    pub fn to_record(&self, header: &Header) -> Record {
+        header
+            .names()
+            .iter()
+            .zip(self.values.iter())
+            .filter_map(|(k, v)| v.as_ref().map(|v| (k.clone(), v.clone())))
+            .collect()
+    }
+
+    /// Sets the value at `index`, reusing the existing allocation, or appends it if the record
+    /// is not that long yet.
+    pub(crate) fn set(&mut self, index: usize, value: Option<&str>) {
+        let value = match value {
+            Some(value) => value,
+            None => {
+                match self.values.get_mut(index) {
+                    Some(v) => *v = None,
+                    None => self.values.push(None),
+                }
+                return;
+            }
+        };
+        match self.values.get_mut(index) {
+            Some(Some(v)) => {
+                v.clear();
+                v.push_str(value);
+            }
+            Some(v) => *v = Some(value.to_string()),
+            None => self.values.push(Some(value.to_string())),
+        }
+    }
+
+    pub(crate) fn truncate(&mut self, len: usize) {
+        self.values.truncate(len);
+    }
+}
+
+/// Panics if the index is out of bounds or the field is null.
+impl Index<usize> for RowRecord {
+    type Output = str;
+
+    fn index(&self, index: usize) -> &str {
+        self.values[index]
+            .as_deref()
+            .unwrap_or_else(|| panic!("Field {} is null", index))
+    }
+}
+
+impl<T: Into<String>> From<Vec<T>> for RowRecord {
+    fn from(values: Vec<T>) -> Self {
+        RowRecord {
+            values: values.into_iter().map(|v| Some(v.into())).collect(),
+        }
+    }
+}
+
+impl<T: Into<String>> FromIterator<T> for RowRecord {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        RowRecord {
+            values: iter.into_iter().map(|v| Some(v.into())).collect(),
+        }
+    }
+}
+
+impl<'r> IntoIterator for &'r RowRecord {
+    type Item = Option<&'r str>;
+    type IntoIter = Values<'r>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        builder::{Buildable, Builder},
+        flat::fixed::Null,
+    };
+
+    fn parser() -> Parser<'static> {
+        Parser::builder()
+            .field("first")
+            .width(4)
+            .append()
+            .spacer(4..5)
+            .field("second")
+            .width(4)
+            .align("right")
+            .padding('0')
+            .append()
+            .build()
+    }
+
+    #[test]
+    fn header_from_parser() {
+        let header = Header::from(&parser());
+
+        assert_eq!(header.len(), 2);
+        assert_eq!(header.index("first"), Some(0));
+        assert_eq!(header.index("second"), Some(1));
+        assert_eq!(header.index("third"), None);
+        assert_eq!(header.name(1), Some("second"));
+    }
+
+    #[test]
+    fn parse_row() {
+        let parser = parser();
+        let header = Header::from(&parser);
+        let row = parser.parse_row("AB  X0012").unwrap();
+
+        assert_eq!(row.len(), 2);
+        assert_eq!(&row[0], "AB");
+        assert_eq!(row.get(1), Some("12"));
+        assert_eq!(row.get_by_name(&header, "second"), Some("12"));
+        assert_eq!(row.to_record(&header), parser.parse("AB  X0012").unwrap());
+    }
+
+    #[test]
+    fn parse_row_into_reuses_values() {
+        let parser = parser();
+        let mut row = RowRecord::from(vec!["a", "b", "c"]);
+
+        parser.parse_row_into("ABCDX1234", &mut row).unwrap();
+        assert_eq!(row, RowRecord::from(vec!["ABCD", "1234"]));
+
+        assert!(parser.parse_row_into("AB", &mut row).is_err());
+    }
+
+    #[test]
+    fn format_row() {
+        let parser = parser();
+
+        assert_eq!(
+            parser.format_row(&RowRecord::from(vec!["AB", "12"])),
+            "AB   0012"
+        );
+        assert_eq!(parser.format_row(&RowRecord::from(vec!["AB"])), "AB   0000");
+    }
+
+    #[test]
+    fn null_values() {
+        let parser = Parser::builder()
+            .field("first")
+            .width(4)
+            .null_when(Null::Value("NULL"))
+            .append()
+            .field("second")
+            .width(4)
+            .append()
+            .build();
+        let header = Header::from(&parser);
+
+        let row = parser.parse_row("NULLAB  ").unwrap();
+        assert!(row.is_null(0));
+        assert_eq!(row.get(0), None);
+        assert_eq!(row.iter().collect::<Vec<_>>(), vec![None, Some("AB")]);
+        assert_eq!(row.to_record(&header).len(), 1);
+        assert_eq!(parser.format_row(&row), "NULLAB  ");
+
+        let mut row = RowRecord::new();
+        row.push_null();
+        row.push("CD");
+        assert_eq!(parser.format_row(&row), "NULLCD  ");
+    }
+}
@@ -0,0 +1,156 @@
+use crate::{
+    error::{Error, ParseErrorKind},
+    flat::fixed::{Parser, Record},
+};
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, Lines, Read},
+};
+
+/// A segment of a hybrid file: its type and the record parsed from the rest of the line.
+pub type Segment = (String, Record);
+
+/// Parses lines where the segment type is the first token, up to a delimiter, and the rest of
+/// the line is fixed width with a layout per segment type, as in some HL7 and X12 style feeds.
+#[derive(Debug)]
+pub struct SegmentParser<'a> {
+    delimiter: char,
+    segments: HashMap<&'a str, Parser<'a>>,
+}
+
+#[allow(dead_code)]
+impl<'a> SegmentParser<'a> {
+    pub fn new(delimiter: char) -> Self {
+        SegmentParser {
+            delimiter,
+            segments: HashMap::new(),
+        }
+    }
+
+    /// Adds the layout of the fixed width part of a segment type.
+    pub fn segment(mut self, segment: &'a str, parser: Parser<'a>) -> Self {
+        self.segments.insert(segment, parser);
+        self
+    }
+
+    /// The layout of a segment type.
+    pub fn parser(&self, segment: &str) -> Option<&Parser<'a>> {
+        self.segments.get(segment)
+    }
+
+    /// Splits the segment type from the line and parses the rest with its layout.
+    pub fn parse(&self, line: &str) -> Result<Segment, Error> {
+        let (segment, rest) = line.split_once(self.delimiter).unwrap_or((line, ""));
+        let parser = self.layout(segment)?;
+        Ok((segment.to_string(), parser.parse(rest)?))
+    }
+
+    /// Formats the record with the layout of the segment type, preceded by the type and the
+    /// delimiter.
+    pub fn format(&self, segment: &str, record: &Record) -> Result<String, Error> {
+        let parser = self.layout(segment)?;
+        let mut line = String::with_capacity(segment.len() + 1 + parser.width());
+        line.push_str(segment);
+        line.push(self.delimiter);
+        line.push_str(&parser.format(record));
+        Ok(line)
+    }
+
+    fn layout(&self, segment: &str) -> Result<&Parser<'a>, Error> {
+        self.segments
+            .get(segment)
+            .ok_or_else(|| Error::from(ParseErrorKind::UnknownRecordType(segment.to_string())))
+    }
+
+    /// Iterates over the segments of the lines of a reader, skipping blank lines.
+    pub fn segments<R: Read>(&self, reader: R) -> Segments<'_, 'a, R> {
+        Segments {
+            parser: self,
+            lines: BufReader::new(reader).lines(),
+            line: 0,
+        }
+    }
+}
+
+/// An iterator over the segments of a reader, see [`SegmentParser::segments`].
+pub struct Segments<'p, 'a, R> {
+    parser: &'p SegmentParser<'a>,
+    lines: Lines<BufReader<R>>,
+    line: u64,
+}
+
+impl<'p, 'a, R> Iterator for Segments<'p, 'a, R>
+where
+    R: Read,
+{
+    type Item = Result<Segment, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.line += 1;
+            let line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(e) => return Some(Err(Error::from(e))),
+            };
+            if !line.trim().is_empty() {
+                return Some(self.parser.parse(&line).map_err(|e| e.at_line(self.line)));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::builder::{Buildable, Builder};
+
+    fn parser() -> SegmentParser<'static> {
+        SegmentParser::new('|')
+            .segment(
+                "PID",
+                Parser::builder()
+                    .field("id")
+                    .width(4)
+                    .append()
+                    .field("name")
+                    .width(6)
+                    .append()
+                    .build(),
+            )
+            .segment(
+                "OBX",
+                Parser::builder()
+                    .field("value")
+                    .width(5)
+                    .align("right")
+                    .padding('0')
+                    .append()
+                    .build(),
+            )
+    }
+
+    #[test]
+    fn parse_and_format_segments() {
+        let parser = parser();
+
+        let (segment, record) = parser.parse("PID|0001SMITH ").unwrap();
+        assert_eq!(segment, "PID");
+        assert_eq!(record["name"], "SMITH");
+        assert_eq!(parser.format("PID", &record).unwrap(), "PID|0001SMITH ");
+        assert!(parser.parse("ZZZ|0001").is_err());
+        assert!(parser.format("ZZZ", &record).is_err());
+    }
+
+    #[test]
+    fn read_segments() {
+        let parser = parser();
+        let input = "PID|0001SMITH \n\nOBX|00042\nXYZ|1\n";
+
+        let segments = parser.segments(input.as_bytes()).collect::<Vec<_>>();
+        assert_eq!(segments.len(), 3);
+        assert_eq!(segments[1].as_ref().unwrap().1["value"], "42");
+        let error = segments[2].as_ref().unwrap_err();
+        assert_eq!(error.line(), Some(4));
+        assert_eq!(error.to_string(), "Line 4: Unknown record type XYZ");
+    }
+}
@@ -0,0 +1,280 @@
+use crate::{
+    error::Error,
+    flat::{
+        fixed::{write::Writer, Parser, Record},
+        terminator::Terminator,
+    },
+};
+use std::{
+    collections::HashMap,
+    fs::{File, OpenOptions},
+    path::{Path, PathBuf},
+};
+
+/// Chooses the output a record is written to.
+pub type Route<'a> = Box<dyn FnMut(&Record) -> String + 'a>;
+
+/// An output file of a [`SplitWriter`], the writer is closed while other outputs are in use.
+struct Output<'a> {
+    writer: Option<Writer<'a, File>>,
+    part: u32,
+    records: u64,
+    used: u64,
+}
+
+/// Writes records to one file per route, e.g. one file per branch code, in a directory.
+///
+/// Files are named after the route and the extension, `<route>.dat` by default, with characters
+/// other than letters, digits, `-` and `_` replaced by `_`. With a rollover the part number is
+/// added, `<route>-001.dat`, and a new part is started once a part has the maximum number of
+/// records.
+pub struct SplitWriter<'a> {
+    directory: PathBuf,
+    parser: &'a Parser<'a>,
+    route: Route<'a>,
+    terminator: Terminator,
+    extension: String,
+    rollover: Option<u64>,
+    max_open: Option<usize>,
+    outputs: HashMap<String, Output<'a>>,
+    paths: Vec<PathBuf>,
+    used: u64,
+}
+
+#[allow(dead_code)]
+impl<'a> SplitWriter<'a> {
+    /// Routes the records by the value of a field.
+    pub fn by_field<P: AsRef<Path>>(directory: P, parser: &'a Parser, field: &'a str) -> Self {
+        Self::by(directory, parser, move |record: &Record| {
+            record.get(field).cloned().unwrap_or_default()
+        })
+    }
+
+    /// Routes the records with a closure returning the name of the output.
+    pub fn by<P, F>(directory: P, parser: &'a Parser, route: F) -> Self
+    where
+        P: AsRef<Path>,
+        F: FnMut(&Record) -> String + 'a,
+    {
+        SplitWriter {
+            directory: directory.as_ref().to_path_buf(),
+            parser,
+            route: Box::new(route),
+            terminator: Terminator::default(),
+            extension: String::from("dat"),
+            rollover: None,
+            max_open: None,
+            outputs: HashMap::new(),
+            paths: Vec::new(),
+            used: 0,
+        }
+    }
+
+    /// Sets how records are separated, defaults to newlines.
+    pub fn terminator(mut self, terminator: Terminator) -> Self {
+        self.terminator = terminator;
+        self
+    }
+
+    /// Sets the extension of the files, defaults to `dat`.
+    pub fn extension(mut self, extension: &str) -> Self {
+        self.extension = extension.to_string();
+        self
+    }
+
+    /// Starts a new part once a file has the given number of records.
+    pub fn rollover(mut self, max_records: u64) -> Self {
+        self.rollover = Some(max_records.max(1));
+        self
+    }
+
+    /// Limits the number of files open at once, closing the least recently used file when
+    /// another one is needed. Closed files are reopened to append to them.
+    pub fn max_open(mut self, max_open: usize) -> Self {
+        self.max_open = Some(max_open.max(1));
+        self
+    }
+
+    /// The files created so far, in the order they were created.
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+
+    /// Formats the record and writes it to the file of its route.
+    pub fn write(&mut self, record: &Record) -> Result<(), Error> {
+        let route = (self.route)(record);
+        self.used += 1;
+        let used = self.used;
+        let rollover = self.rollover;
+        let part = match self.outputs.get_mut(&route) {
+            Some(output) if rollover.is_some_and(|max| output.records >= max) => {
+                if let Some(mut writer) = output.writer.take() {
+                    writer.flush()?;
+                }
+                output.part += 1;
+                output.records = 0;
+                Some(output.part)
+            }
+            Some(output) if output.writer.is_none() => None,
+            Some(output) => {
+                output.used = used;
+                output.records += 1;
+                return output.writer.as_mut().map_or(Ok(()), |w| w.write(record));
+            }
+            None => Some(1),
+        };
+        self.open(&route, part)?;
+        let output = self.outputs.get_mut(&route).expect("Output was opened");
+        output.used = used;
+        output.records += 1;
+        output.writer.as_mut().map_or(Ok(()), |w| w.write(record))
+    }
+
+    /// Flushes all the open files.
+    pub fn flush(&mut self) -> Result<(), Error> {
+        for writer in self.outputs.values_mut().filter_map(|o| o.writer.as_mut()) {
+            writer.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Flushes and closes all the files, returning their paths.
+    pub fn finish(mut self) -> Result<Vec<PathBuf>, Error> {
+        self.flush()?;
+        Ok(self.paths)
+    }
+
+    /// Opens the file of a route, creating a new part or reopening the current one to append.
+    fn open(&mut self, route: &str, part: Option<u32>) -> Result<(), Error> {
+        if let Some(max) = self.max_open {
+            let open = self.outputs.values().filter(|o| o.writer.is_some()).count();
+            if open >= max {
+                self.close_least_recently_used()?;
+            }
+        }
+        let number = part.unwrap_or_else(|| self.outputs.get(route).map_or(1, |o| o.part));
+        let path = self.path(route, number);
+        let file = match part {
+            Some(_) => {
+                self.paths.push(path.clone());
+                File::create(&path)?
+            }
+            None => OpenOptions::new().append(true).open(&path)?,
+        };
+        let writer = Writer::to_file(file, self.parser).terminator(self.terminator.clone());
+        let output = self
+            .outputs
+            .entry(route.to_string())
+            .or_insert_with(|| Output {
+                writer: None,
+                part: number,
+                records: 0,
+                used: 0,
+            });
+        output.writer = Some(writer);
+        Ok(())
+    }
+
+    fn close_least_recently_used(&mut self) -> Result<(), Error> {
+        let output = self
+            .outputs
+            .values_mut()
+            .filter(|o| o.writer.is_some())
+            .min_by_key(|o| o.used);
+        if let Some(mut writer) = output.and_then(|o| o.writer.take()) {
+            writer.flush()?;
+        }
+        Ok(())
+    }
+
+    fn path(&self, route: &str, part: u32) -> PathBuf {
+        let mut name = route
+            .chars()
+            .map(|c| match c {
+                c if c.is_alphanumeric() || c == '-' || c == '_' => c,
+                _ => '_',
+            })
+            .collect::<String>();
+        if name.is_empty() {
+            name.push('_');
+        }
+        if self.rollover.is_some() {
+            name = format!("{}-{:03}", name, part);
+        }
+        self.directory.join(format!("{}.{}", name, self.extension))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::builder::{Buildable, Builder};
+    use std::fs;
+
+    fn parser() -> Parser<'static> {
+        Parser::builder()
+            .field("branch")
+            .width(3)
+            .append()
+            .field("id")
+            .width(2)
+            .append()
+            .build()
+    }
+
+    fn record(branch: &str, id: &str) -> Record {
+        [("branch", branch), ("id", id)]
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    fn directory(name: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!("eta-parse-split-{}", name));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+        directory
+    }
+
+    #[test]
+    fn split_by_field() {
+        let parser = parser();
+        let directory = directory("by-field");
+        let mut wtr = SplitWriter::by_field(&directory, &parser, "branch").max_open(1);
+
+        for (branch, id) in [("A01", "1"), ("B/2", "2"), ("A01", "3")] {
+            wtr.write(&record(branch, id)).unwrap();
+        }
+        let paths = wtr.finish().unwrap();
+
+        assert_eq!(
+            paths,
+            vec![directory.join("A01.dat"), directory.join("B_2.dat")]
+        );
+        assert_eq!(fs::read_to_string(&paths[0]).unwrap(), "A011 \nA013 \n");
+        assert_eq!(fs::read_to_string(&paths[1]).unwrap(), "B/22 \n");
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn split_with_rollover() {
+        let parser = parser();
+        let directory = directory("rollover");
+        let mut wtr = SplitWriter::by(&directory, &parser, |r: &Record| r["branch"].to_lowercase())
+            .extension("txt")
+            .rollover(2);
+
+        for id in ["1", "2", "3"] {
+            wtr.write(&record("ABC", id)).unwrap();
+        }
+        let paths = wtr.finish().unwrap();
+
+        assert_eq!(
+            paths,
+            vec![directory.join("abc-001.txt"), directory.join("abc-002.txt")]
+        );
+        assert_eq!(fs::read_to_string(&paths[0]).unwrap(), "ABC1 \nABC2 \n");
+        assert_eq!(fs::read_to_string(&paths[1]).unwrap(), "ABC3 \n");
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,203 @@
+use crate::error::{Error, RecordError};
+use alloc::{
+    collections::BTreeMap,
+    string::{String, ToString},
+    vec::Vec,
+};
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+/// An invalid record kept as an example in a [`ValidationReport`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Example {
+    pub line: u64,
+    pub message: String,
+}
+
+/// Whether a file is accepted given the records that failed to parse or validate, e.g. to reject
+/// a file with more than 0.1% of bad records.
+///
+/// Built from the results of
+/// [`read_all_validated`](crate::flat::fixed::read::Reader::read_all_validated), or by adding
+/// records one at a time while streaming.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct ValidationReport {
+    /// Records read, valid or not.
+    pub records: u64,
+    /// Records that failed to parse or validate.
+    pub invalid: u64,
+    /// Records that could not be parsed.
+    pub parse_errors: u64,
+    /// Failures per field, a record failing several fields counts for each.
+    pub fields: BTreeMap<String, u64>,
+    /// Failures per rule, the message of a field check or the kind of parse error.
+    pub rules: BTreeMap<String, u64>,
+    /// The first invalid records, up to the example limit.
+    pub examples: Vec<Example>,
+    /// The most invalid records accepted.
+    pub max_invalid: Option<u64>,
+    /// The highest share of invalid records accepted, between 0 and 1.
+    pub max_invalid_rate: Option<f64>,
+    /// Whether the file passes the thresholds.
+    pub accepted: bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    example_limit: usize,
+}
+
+#[allow(dead_code)]
+impl ValidationReport {
+    /// A report keeping 10 examples, accepting any number of invalid records until thresholds
+    /// are set.
+    pub fn new() -> Self {
+        ValidationReport {
+            accepted: true,
+            example_limit: 10,
+            ..ValidationReport::default()
+        }
+    }
+
+    /// Sets how many invalid records are kept as examples.
+    pub fn examples(mut self, limit: usize) -> Self {
+        self.example_limit = limit;
+        self
+    }
+
+    /// Rejects the file when more than `n` records are invalid.
+    pub fn max_invalid(mut self, n: u64) -> Self {
+        self.max_invalid = Some(n);
+        self.evaluate();
+        self
+    }
+
+    /// Rejects the file when the share of invalid records is above the rate, e.g. `0.001` for
+    /// 0.1%.
+    pub fn max_invalid_rate(mut self, rate: f64) -> Self {
+        self.max_invalid_rate = Some(rate);
+        self.evaluate();
+        self
+    }
+
+    /// Adds the valid records and the failures returned by
+    /// [`read_all_validated`](crate::flat::fixed::read::Reader::read_all_validated).
+    pub fn with_results(mut self, valid: usize, errors: &[RecordError]) -> Self {
+        self.records += valid as u64;
+        for error in errors {
+            self.add_error(error);
+        }
+        self.evaluate();
+        self
+    }
+
+    /// Counts a valid record.
+    pub fn add_valid(&mut self) {
+        self.records += 1;
+        self.evaluate();
+    }
+
+    /// Counts an invalid record.
+    pub fn add_error(&mut self, error: &RecordError) {
+        self.records += 1;
+        self.invalid += 1;
+        if let Some(e) = &error.error {
+            self.parse_errors += 1;
+            let rule = match e {
+                Error::Parse { kind, .. } => kind.to_string(),
+                e => e.to_string(),
+            };
+            *self.rules.entry(rule).or_insert(0) += 1;
+            if let Some(field) = match e {
+                Error::Parse { field, .. } => field.as_ref(),
+                _ => None,
+            } {
+                *self.fields.entry(field.clone()).or_insert(0) += 1;
+            }
+        }
+        for field in &error.fields {
+            *self.fields.entry(field.field.clone()).or_insert(0) += 1;
+            *self.rules.entry(field.message.clone()).or_insert(0) += 1;
+        }
+        if self.examples.len() < self.example_limit {
+            self.examples.push(Example {
+                line: error.line,
+                message: error.to_string(),
+            });
+        }
+        self.evaluate();
+    }
+
+    /// The share of invalid records, between 0 and 1.
+    pub fn invalid_rate(&self) -> f64 {
+        match self.records {
+            0 => 0.0,
+            records => self.invalid as f64 / records as f64,
+        }
+    }
+
+    fn evaluate(&mut self) {
+        self.accepted = self.max_invalid.is_none_or(|max| self.invalid <= max)
+            && self
+                .max_invalid_rate
+                .is_none_or(|rate| self.invalid_rate() <= rate);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        builder::{Buildable, Builder},
+        flat::fixed::{read::Reader, Parser},
+    };
+
+    #[test]
+    fn validation_report() {
+        let parser = Parser::builder()
+            .field("name")
+            .width(4)
+            .append()
+            .field("amount")
+            .width(4)
+            .append()
+            .build();
+        let s = "AB  0012\nshort\n    0034\nCD  00X6\nEF  0056\nGH  0078";
+        let mut rdr = Reader::from_string(s, &parser)
+            .validate_field("name", |v| match v.is_empty() {
+                true => Err(String::from("is required")),
+                false => Ok(()),
+            })
+            .validate_field("amount", |v| match v.chars().all(|c| c.is_ascii_digit()) {
+                true => Ok(()),
+                false => Err(String::from("is not numeric")),
+            });
+        let (records, errors) = rdr.read_all_validated().unwrap();
+
+        let report = ValidationReport::new()
+            .examples(2)
+            .max_invalid_rate(0.5)
+            .with_results(records.len(), &errors);
+
+        assert_eq!((report.records, report.invalid), (6, 3));
+        assert_eq!(report.parse_errors, 1);
+        assert_eq!(report.fields["name"], 1);
+        assert_eq!(report.rules["is not numeric"], 1);
+        assert_eq!(report.examples.len(), 2);
+        assert_eq!(report.examples[1].message, "Line 3: name: is required");
+        assert!(report.accepted);
+        assert!(!report.max_invalid(2).accepted);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn validation_report_json() {
+        let mut report = ValidationReport::new().max_invalid(0);
+        report.add_valid();
+
+        let json = serde_json::to_string(&report).unwrap();
+
+        assert!(json.contains("\"records\":1"));
+        assert!(json.contains("\"accepted\":true"));
+        assert!(!json.contains("example_limit"));
+    }
+}
@@ -0,0 +1,905 @@
+use crate::{
+    error::{Error, FieldError},
+    flat::{
+        fixed::{
+            record::RecordSource,
+            redact::Redactor,
+            row::{Header, RowRecord},
+            Parser, Record, Sequence,
+        },
+        terminator::Terminator,
+    },
+    utilities::{
+        cancel::CancellationToken,
+        charset::{Charset, Unmappable},
+        flush::BackgroundFlush,
+        string::TruncateSide,
+        throttle::{Rate, Throttle},
+    },
+};
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+use std::path::Path;
+use std::{
+    borrow::{Borrow, Cow},
+    fs::File,
+    io::{self, BufWriter, Write},
+    time::Duration,
+};
+
+/// A transform applied to every record before it is formatted, see [`Writer::transform`].
+pub type RecordTransform<'a> = Box<dyn FnMut(&mut Record) + 'a>;
+
+/// The control totals accumulated by a [`Writer`]: the number of records and the hash totals,
+/// sums of numeric fields.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Totals {
+    records: u64,
+    sums: Vec<(String, i128)>,
+}
+
+#[allow(dead_code)]
+impl Totals {
+    /// The number of records written, not counting the trailer.
+    pub fn records(&self) -> u64 {
+        self.records
+    }
+
+    /// The hash total of a field, if it is accumulated.
+    pub fn sum(&self, field: &str) -> Option<i128> {
+        self.sums.iter().find(|(f, _)| f == field).map(|(_, s)| *s)
+    }
+
+    /// A trailer record with the record count under `record_count` and each hash total under
+    /// the name of its field.
+    pub fn to_record(&self) -> Record {
+        self.sums
+            .iter()
+            .map(|(f, s)| (f.clone(), s.to_string()))
+            .chain(std::iter::once((
+                String::from("record_count"),
+                self.records.to_string(),
+            )))
+            .collect()
+    }
+
+    /// The amounts of the next record for each hash total, read with `value`.
+    fn amounts<'r, F>(&self, value: F) -> Result<Vec<i128>, Error>
+    where
+        F: Fn(&str) -> Option<&'r str>,
+    {
+        self.sums
+            .iter()
+            .map(|(field, _)| match value(field).map_or("", str::trim) {
+                "" => Ok(0),
+                v => v.parse::<i128>().map_err(|_| Error::Validation {
+                    line: self.records + 1,
+                    fields: vec![FieldError {
+                        field: field.clone(),
+                        message: format!("{} is not numeric", v),
+                    }],
+                }),
+            })
+            .collect()
+    }
+
+    fn add(&mut self, amounts: Vec<i128>) {
+        for ((_, sum), amount) in self.sums.iter_mut().zip(amounts) {
+            *sum += amount;
+        }
+    }
+}
+
+#[allow(dead_code)]
+pub struct Writer<'a, W: Write> {
+    inner: BufWriter<W>,
+    parser: &'a Parser<'a>,
+    terminator: Terminator,
+    cancel: Option<CancellationToken>,
+    throttle: Option<Throttle>,
+    totals: Totals,
+    header: Option<Header>,
+    header_written: bool,
+    charset: Charset,
+    unmappable: Unmappable,
+    strict_ascii: bool,
+    redactor: Option<Redactor>,
+    /// The auto sequence fields of the layout, numbered by the count of records written.
+    sequences: Vec<(&'a str, Sequence)>,
+    transforms: Vec<RecordTransform<'a>>,
+    line: String,
+}
+
+#[allow(dead_code)]
+impl<'a, W> Writer<'a, W>
+where
+    W: Write,
+{
+    pub fn from_writer(writer: W, parser: &'a Parser) -> Self {
+        Writer {
+            inner: BufWriter::new(writer),
+            parser,
+            terminator: Terminator::default(),
+            cancel: None,
+            throttle: None,
+            totals: Totals::default(),
+            header: None,
+            header_written: false,
+            charset: Charset::default(),
+            unmappable: Unmappable::default(),
+            strict_ascii: false,
+            redactor: None,
+            sequences: parser
+                .fields()
+                .iter()
+                .filter_map(|f| Some((f.name()?, f.sequence()?)))
+                .collect(),
+            transforms: Vec::new(),
+            line: String::new(),
+        }
+    }
+
+    /// Sets the capacity of the buffer records are written to, 8 KiB by default. Larger buffers
+    /// mean fewer writes to the underlying writer, e.g. for network or compressed outputs.
+    /// Records already written stay buffered, in a larger buffer if they do not fit.
+    pub fn buffer_capacity(mut self, capacity: usize) -> Self {
+        let (writer, buffered) = self.inner.into_parts();
+        let buffered = buffered.unwrap_or_else(|e| e.into_inner());
+        if buffered.is_empty() {
+            self.inner = BufWriter::with_capacity(capacity, writer);
+            return self;
+        }
+        self.inner = BufWriter::with_capacity(capacity.max(buffered.len() + 1), writer);
+        // Copied into the buffer, as it has room for them, so this cannot fail.
+        self.inner
+            .write_all(&buffered)
+            .expect("Buffered bytes fit in the buffer");
+        self
+    }
+
+    /// Sets how records are separated, defaults to newlines.
+    pub fn terminator(mut self, terminator: Terminator) -> Self {
+        self.terminator = terminator;
+        self
+    }
+
+    /// Transcodes the records to a single-byte character set, UTF-8 by default. Widths are
+    /// counted before transcoding, so layouts counting characters keep their byte widths.
+    pub fn charset(mut self, charset: Charset) -> Self {
+        self.charset = charset;
+        self
+    }
+
+    /// Sets what happens to characters the character set cannot represent, failing the write by
+    /// default.
+    pub fn unmappable(mut self, unmappable: Unmappable) -> Self {
+        self.unmappable = unmappable;
+        self
+    }
+
+    /// Fails writing any record containing a character outside of ASCII with
+    /// `Error::NonAscii`, giving the column of its first byte. Nothing is written for the record.
+    pub fn strict_ascii(mut self) -> Self {
+        self.strict_ascii = true;
+        self
+    }
+
+    /// Redacts the fields of every record before formatting it, leaving the records passed in
+    /// untouched. Hash totals are accumulated on the values before redaction.
+    pub fn redact(mut self, redactor: Redactor) -> Self {
+        self.redactor = Some(redactor);
+        self
+    }
+
+    /// Appends a transform applied, in order, to every record before it is checked and
+    /// formatted, e.g. adding constant fields or renumbering a sequence, see
+    /// [`enrich`](super::enrich) for common ones. The records passed in are left untouched.
+    pub fn transform<F>(mut self, f: F) -> Self
+    where
+        F: FnMut(&mut Record) + 'a,
+    {
+        self.transforms.push(Box::new(f));
+        self
+    }
+
+    /// Stops writing further records once the token is cancelled.
+    pub fn cancel_on(mut self, token: CancellationToken) -> Self {
+        self.cancel = Some(token);
+        self
+    }
+
+    /// Limits the rate at which records are written.
+    pub fn throttle(mut self, rate: Rate) -> Self {
+        self.throttle = Some(Throttle::new(rate));
+        self
+    }
+
+    /// Accumulates the sum of an integer field, blank values count as zero and other values
+    /// fail the write with a validation error.
+    pub fn hash_total(mut self, field: &str) -> Self {
+        self.totals.sums.push((field.to_string(), 0));
+        self
+    }
+
+    /// The number of records written so far.
+    pub fn records(&self) -> u64 {
+        self.totals.records
+    }
+
+    /// The record count and hash totals so far.
+    pub fn totals(&self) -> &Totals {
+        &self.totals
+    }
+
+    /// Writes a header record, e.g. the file date, sequence number and source id, formatted with
+    /// the header layout. The header can only be written once, before any other record, and is
+    /// not counted in the totals.
+    pub fn write_header(&mut self, layout: &Parser, record: &Record) -> Result<(), Error> {
+        if self.header_written || self.totals.records > 0 {
+            return Err(Error::from(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "The header must be written once, before any record",
+            )));
+        }
+        let formatted = layout.format(record);
+        let bytes = self.encode(&formatted, None)?;
+        self.terminator.write_record(&mut self.inner, &bytes)?;
+        self.header_written = true;
+        Ok(())
+    }
+
+    /// Formats the record using the parser layout and writes it with the record terminator.
+    pub fn write(&mut self, record: &Record) -> Result<(), Error> {
+        if !self.transforms_records() || self.is_cancelled() {
+            return self.write_source(record);
+        }
+        let mut record = record.clone();
+        self.apply_transforms(&mut record);
+        self.write_source(&record)
+    }
+
+    /// Formats any container implementing [`RecordSource`] using the parser layout and writes
+    /// it with the record terminator. Transforms only see the fields of the layout.
+    pub fn write_from<S>(&mut self, record: &S) -> Result<(), Error>
+    where
+        S: RecordSource + ?Sized,
+    {
+        if !self.transforms_records() || self.is_cancelled() {
+            return self.write_source(record);
+        }
+        let mut record = to_record(self.parser, record);
+        self.apply_transforms(&mut record);
+        self.write_source(&record)
+    }
+
+    fn write_source<S>(&mut self, record: &S) -> Result<(), Error>
+    where
+        S: RecordSource + ?Sized,
+    {
+        if self.is_cancelled() {
+            return Err(Error::Cancelled);
+        }
+        let amounts = self.totals.amounts(|field| record.get(field))?;
+        check_widths(self.parser, self.totals.records + 1, |field| {
+            record.get(field)
+        })?;
+        let formatted = match &self.redactor {
+            Some(redactor) => {
+                let mut record = to_record(self.parser, record);
+                redactor.apply(&mut record);
+                self.parser.format(&record)
+            }
+            None => self.parser.format_from(record),
+        };
+        self.write_formatted(&formatted)?;
+        self.totals.add(amounts);
+        Ok(())
+    }
+
+    /// Formats a row using the parser layout and writes it with the record terminator.
+    pub fn write_row(&mut self, row: &RowRecord) -> Result<(), Error> {
+        if self.is_cancelled() {
+            return Err(Error::Cancelled);
+        }
+        let parser = self.parser;
+        let transformed = self.transforms_records();
+        let header = self.header.get_or_insert_with(|| Header::from(parser));
+        if transformed {
+            let mut record = row.to_record(header);
+            self.apply_transforms(&mut record);
+            return self.write_source(&record);
+        }
+        let amounts = self
+            .totals
+            .amounts(|field| row.get_by_name(header, field))?;
+        check_widths(self.parser, self.totals.records + 1, |field| {
+            row.get_by_name(header, field)
+        })?;
+        let formatted = match &self.redactor {
+            Some(redactor) => {
+                let mut record = row.to_record(header);
+                redactor.apply(&mut record);
+                self.parser.format(&record)
+            }
+            None => self.parser.format_row(row),
+        };
+        self.write_formatted(&formatted)?;
+        self.totals.add(amounts);
+        Ok(())
+    }
+
+    /// Writes a trailer record built from the totals and formatted with the trailer layout,
+    /// then flushes and returns the underlying writer. Use [`Totals::to_record`] for a trailer
+    /// layout with a `record_count` field and fields named after the hash totals.
+    pub fn finalize<F>(mut self, layout: &Parser, trailer: F) -> Result<W, Error>
+    where
+        F: FnOnce(&Totals) -> Record,
+    {
+        let formatted = layout.format(&trailer(&self.totals));
+        let bytes = self.encode(&formatted, None)?;
+        self.terminator.write_record(&mut self.inner, &bytes)?;
+        self.into_inner()
+    }
+
+    /// Writes every record from the iterator, stopping at the first error.
+    pub fn write_all<'r, I>(&mut self, records: I) -> Result<(), Error>
+    where
+        I: IntoIterator<Item = &'r Record>,
+    {
+        for record in records {
+            self.write(record)?;
+        }
+        Ok(())
+    }
+
+    /// Writes every record from the iterator, stopping at the first error. Each record is
+    /// formatted into a single line buffer reused across records, unless it is redacted or
+    /// transformed.
+    pub fn write_all_from_iter<I, T>(&mut self, records: I) -> Result<(), Error>
+    where
+        I: IntoIterator<Item = T>,
+        T: Borrow<Record>,
+    {
+        let mut line = std::mem::take(&mut self.line);
+        let result = records.into_iter().try_for_each(|record| {
+            let record = record.borrow();
+            if self.redactor.is_some() || self.transforms_records() {
+                return self.write(record);
+            }
+            if self.is_cancelled() {
+                return Err(Error::Cancelled);
+            }
+            let amounts = self
+                .totals
+                .amounts(|field| record.get(field).map(|v| v.as_str()))?;
+            check_widths(self.parser, self.totals.records + 1, |field| {
+                record.get(field).map(|v| v.as_str())
+            })?;
+            line.clear();
+            self.parser.format_into(record, &mut line);
+            self.write_formatted(&line)?;
+            self.totals.add(amounts);
+            Ok(())
+        });
+        self.line = line;
+        result
+    }
+
+    pub fn flush(&mut self) -> Result<(), Error> {
+        self.inner.flush()?;
+        Ok(())
+    }
+
+    /// Flushes any buffered data and returns the underlying writer.
+    pub fn into_inner(self) -> Result<W, Error> {
+        self.inner
+            .into_inner()
+            .map_err(|e| Error::from(e.into_error()))
+    }
+
+    fn transforms_records(&self) -> bool {
+        !self.sequences.is_empty() || !self.transforms.is_empty()
+    }
+
+    /// Numbers the auto sequence fields after the records written so far, so a record failing
+    /// to be written does not use up a number, then applies the transforms.
+    fn apply_transforms(&mut self, record: &mut Record) {
+        for (name, sequence) in &self.sequences {
+            let value = sequence
+                .step
+                .wrapping_mul(self.totals.records)
+                .wrapping_add(sequence.start);
+            record.insert(name.to_string(), value.to_string());
+        }
+        for transform in self.transforms.iter_mut() {
+            transform(record);
+        }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel.as_ref().is_some_and(|c| c.is_cancelled())
+    }
+
+    fn encode<'f>(&self, formatted: &'f str, line: Option<u64>) -> Result<Cow<'f, [u8]>, Error> {
+        if let Some(i) = formatted
+            .bytes()
+            .position(|b| self.strict_ascii && !b.is_ascii())
+        {
+            return Err(Error::NonAscii {
+                line,
+                column: i + 1,
+                byte: formatted.as_bytes()[i],
+            });
+        }
+        self.charset
+            .encode(formatted, self.unmappable)
+            .map_err(|character| Error::Unmappable {
+                line,
+                character,
+                charset: self.charset,
+            })
+    }
+
+    fn write_formatted(&mut self, formatted: &str) -> Result<(), Error> {
+        let bytes = self.encode(formatted, Some(self.totals.records + 1))?;
+        if let Some(throttle) = self.throttle.as_mut() {
+            throttle.wait(bytes.len());
+        }
+        self.terminator.write_record(&mut self.inner, &bytes)?;
+        self.totals.records += 1;
+        Ok(())
+    }
+}
+
+/// Copies the values of the named fields of the layout into a record.
+fn to_record<S>(parser: &Parser, source: &S) -> Record
+where
+    S: RecordSource + ?Sized,
+{
+    parser
+        .fields()
+        .iter()
+        .filter_map(|f| f.name())
+        .filter_map(|name| Some((name.to_string(), source.get(name)?.to_string())))
+        .collect()
+}
+
+/// Fails with a validation error if a value is too long for a field that does not allow
+/// truncation.
+fn check_widths<'r, F>(parser: &Parser, line: u64, value: F) -> Result<(), Error>
+where
+    F: Fn(&str) -> Option<&'r str>,
+{
+    let fields = parser
+        .fields()
+        .iter()
+        .filter(|f| f.truncate_side() == TruncateSide::Error)
+        .filter_map(|f| {
+            let name = f.name()?;
+            let v = value(name).filter(|v| f.overflows(v))?;
+            Some(FieldError {
+                field: name.to_string(),
+                message: format!("{} is longer than {} columns", v, f.width()),
+            })
+        })
+        .collect::<Vec<FieldError>>();
+    match fields.is_empty() {
+        true => Ok(()),
+        false => Err(Error::Validation { line, fields }),
+    }
+}
+
+#[allow(dead_code)]
+impl<'a> Writer<'a, File> {
+    /// Creates a new writer to a file.
+    pub fn to_file(file: File, parser: &'a Parser) -> Self {
+        Self::from_writer(file, parser)
+    }
+}
+
+#[allow(dead_code)]
+impl<'a, W> Writer<'a, BackgroundFlush<W>>
+where
+    W: Write + Send + 'static,
+{
+    /// Creates a new writer whose output is flushed by a background thread at the interval, so
+    /// that records written in bursts become visible to readers of the output without waiting
+    /// for the buffer to fill. Records are buffered by the [`BackgroundFlush`] itself.
+    pub fn with_background_flush(writer: W, parser: &'a Parser, interval: Duration) -> Self {
+        Self::from_writer(BackgroundFlush::new(writer, interval), parser).buffer_capacity(0)
+    }
+}
+
+#[cfg(feature = "gzip")]
+#[allow(dead_code)]
+impl<'a> Writer<'a, flate2::write::GzEncoder<File>> {
+    /// Creates a new writer compressing to a gzip file. The gzip trailer is written when the
+    /// writer is dropped, call `into_inner()?.finish()` to handle any error doing so.
+    pub fn to_gzip<P: AsRef<Path>>(path: P, parser: &'a Parser) -> Result<Self, Error> {
+        let file = File::create(path)?;
+        Ok(Self::from_writer(
+            flate2::write::GzEncoder::new(file, flate2::Compression::default()),
+            parser,
+        ))
+    }
+}
+
+#[cfg(feature = "zstd")]
+#[allow(dead_code)]
+impl<'a> Writer<'a, zstd::stream::write::AutoFinishEncoder<'static, File>> {
+    /// Creates a new writer compressing to a zstd file with the default compression level. The
+    /// zstd frame is finished when the writer is dropped.
+    pub fn to_zstd<P: AsRef<Path>>(path: P, parser: &'a Parser) -> Result<Self, Error> {
+        let file = File::create(path)?;
+        Ok(Self::from_writer(
+            zstd::stream::write::Encoder::new(file, zstd::DEFAULT_COMPRESSION_LEVEL)?.auto_finish(),
+            parser,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::builder::{Buildable, Builder};
+
+    fn record(pairs: &[(&str, &str)]) -> Record {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn write_records() {
+        let parser = Parser::builder()
+            .field("first")
+            .width(4)
+            .append()
+            .field("second")
+            .width(4)
+            .align("right")
+            .padding('0')
+            .append()
+            .build();
+        let mut wtr = Writer::from_writer(Vec::new(), &parser);
+
+        let rows = [
+            record(&[("first", "AB"), ("second", "12")]),
+            record(&[("first", "ABCD"), ("second", "1234")]),
+        ];
+        wtr.write_all(rows.iter()).unwrap();
+
+        assert_eq!(wtr.records(), 2);
+        let out = String::from_utf8(wtr.into_inner().unwrap()).unwrap();
+        assert_eq!(out, "AB  0012\nABCD1234\n");
+    }
+
+    #[test]
+    fn write_all_from_iter() {
+        let parser = Parser::builder()
+            .field("first")
+            .width(4)
+            .append()
+            .spacer(0..1)
+            .field("second")
+            .width(2)
+            .append()
+            .build();
+        let mut wtr = Writer::from_writer(Vec::new(), &parser)
+            .buffer_capacity(4)
+            .hash_total("second");
+
+        let rows = vec![
+            record(&[("first", "AB"), ("second", "12")]),
+            record(&[("first", "ABCD"), ("second", "3")]),
+        ];
+        wtr.write_all_from_iter(rows).unwrap();
+
+        assert_eq!(wtr.totals().sum("second"), Some(15));
+        let out = String::from_utf8(wtr.into_inner().unwrap()).unwrap();
+        assert_eq!(out, "AB   12\nABCD 3 \n");
+    }
+
+    #[test]
+    fn write_truncate_side() {
+        let parser = Parser::builder()
+            .field("amount")
+            .width(4)
+            .align("right")
+            .padding('0')
+            .truncate_side(TruncateSide::Right)
+            .append()
+            .field("code")
+            .width(2)
+            .truncate_side(TruncateSide::Error)
+            .append()
+            .build();
+        let mut wtr = Writer::from_writer(Vec::new(), &parser);
+
+        wtr.write(&record(&[("amount", "123456"), ("code", "AB")]))
+            .unwrap();
+        let result = wtr.write(&record(&[("amount", "1"), ("code", "ABC")]));
+
+        match result {
+            Err(Error::Validation { line, fields }) => {
+                assert_eq!(line, 2);
+                assert_eq!(fields[0].field, "code");
+            }
+            _ => panic!("Expected a validation error"),
+        }
+        let out = String::from_utf8(wtr.into_inner().unwrap()).unwrap();
+        assert_eq!(out, "3456AB\n");
+    }
+
+    #[test]
+    fn write_auto_sequence() {
+        let parser = Parser::builder()
+            .field("seq")
+            .auto_sequence(100, 10, 4)
+            .append()
+            .field("name")
+            .width(2)
+            .append()
+            .build();
+        let mut wtr = Writer::from_writer(Vec::new(), &parser);
+
+        wtr.write(&record(&[("name", "A"), ("seq", "1")])).unwrap();
+        wtr.write(&record(&[("name", "B")])).unwrap();
+
+        let out = String::from_utf8(wtr.into_inner().unwrap()).unwrap();
+        assert_eq!(out, "0100A \n0110B \n");
+
+        let parser = Parser::builder()
+            .field("seq")
+            .auto_sequence(1, 1, 3)
+            .append()
+            .field("name")
+            .width(2)
+            .append()
+            .build();
+        let mut wtr = Writer::from_writer(Vec::new(), &parser).strict_ascii();
+
+        assert!(wtr.write(&record(&[("name", "É")])).is_err());
+        wtr.write(&record(&[("name", "OK")])).unwrap();
+
+        let out = String::from_utf8(wtr.into_inner().unwrap()).unwrap();
+        assert_eq!(out, "001OK\n");
+    }
+
+    #[test]
+    fn write_cancelled() {
+        let parser = Parser::builder().field("first").width(4).append().build();
+        let token = CancellationToken::new();
+        let mut wtr = Writer::from_writer(Vec::new(), &parser).cancel_on(token.clone());
+
+        assert!(wtr.write(&record(&[("first", "A")])).is_ok());
+        token.cancel();
+        assert!(matches!(
+            wtr.write(&record(&[("first", "B")])),
+            Err(Error::Cancelled)
+        ));
+
+        assert_eq!(wtr.records(), 1);
+        let out = String::from_utf8(wtr.into_inner().unwrap()).unwrap();
+        assert_eq!(out, "A   \n");
+    }
+
+    #[test]
+    fn write_with_terminator() {
+        let parser = Parser::builder().field("first").width(4).append().build();
+        let mut wtr = Writer::from_writer(Vec::new(), &parser).terminator(Terminator::CrLf);
+
+        wtr.write(&record(&[("first", "A")])).unwrap();
+        wtr.write(&record(&[("first", "B")])).unwrap();
+
+        let out = String::from_utf8(wtr.into_inner().unwrap()).unwrap();
+        assert_eq!(out, "A   \r\nB   \r\n");
+    }
+
+    #[test]
+    fn write_rows() {
+        let parser = Parser::builder()
+            .field("first")
+            .width(4)
+            .append()
+            .field("second")
+            .width(4)
+            .align("right")
+            .padding('0')
+            .append()
+            .build();
+        let mut wtr = Writer::from_writer(Vec::new(), &parser);
+
+        wtr.write_row(&RowRecord::from(vec!["AB", "12"])).unwrap();
+
+        assert_eq!(wtr.records(), 1);
+        let out = String::from_utf8(wtr.into_inner().unwrap()).unwrap();
+        assert_eq!(out, "AB  0012\n");
+    }
+
+    #[test]
+    fn write_control_totals() {
+        let parser = Parser::builder()
+            .field("id")
+            .width(2)
+            .append()
+            .field("amount")
+            .width(6)
+            .align("right")
+            .padding('0')
+            .append()
+            .build();
+        let trailer = Parser::builder()
+            .field("type")
+            .width(2)
+            .append()
+            .field("record_count")
+            .width(2)
+            .align("right")
+            .padding('0')
+            .append()
+            .field("amount")
+            .width(4)
+            .align("right")
+            .padding('0')
+            .append()
+            .build();
+        let mut wtr = Writer::from_writer(Vec::new(), &parser).hash_total("amount");
+
+        wtr.write(&record(&[("id", "01"), ("amount", "150")]))
+            .unwrap();
+        wtr.write(&record(&[("id", "02")])).unwrap();
+        wtr.write_row(&RowRecord::from(vec!["03", "25"])).unwrap();
+        assert!(matches!(
+            wtr.write(&record(&[("id", "04"), ("amount", "1X")])),
+            Err(Error::Validation { line: 4, .. })
+        ));
+
+        assert_eq!(wtr.totals().records(), 3);
+        assert_eq!(wtr.totals().sum("amount"), Some(175));
+        let out = wtr
+            .finalize(&trailer, |totals| {
+                let mut record = totals.to_record();
+                record.insert(String::from("type"), String::from("TR"));
+                record
+            })
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "01000150\n02000000\n03000025\nTR030175\n"
+        );
+    }
+
+    #[test]
+    fn write_header_once() {
+        let parser = Parser::builder().field("first").width(4).append().build();
+        let header = Parser::builder()
+            .field("date")
+            .width(8)
+            .append()
+            .field("sequence")
+            .width(3)
+            .align("right")
+            .padding('0')
+            .append()
+            .build();
+        let mut wtr = Writer::from_writer(Vec::new(), &parser);
+
+        wtr.write_header(&header, &record(&[("date", "20240131"), ("sequence", "7")]))
+            .unwrap();
+        assert!(wtr.write_header(&header, &Record::new()).is_err());
+        wtr.write(&record(&[("first", "A")])).unwrap();
+
+        assert_eq!(wtr.records(), 1);
+        let out = String::from_utf8(wtr.into_inner().unwrap()).unwrap();
+        assert_eq!(out, "20240131007\nA   \n");
+
+        let mut wtr = Writer::from_writer(Vec::new(), &parser);
+        wtr.write_header(&header, &record(&[("date", "20240131"), ("sequence", "7")]))
+            .unwrap();
+        let mut wtr = wtr.buffer_capacity(4);
+        wtr.write(&record(&[("first", "A")])).unwrap();
+        let out = String::from_utf8(wtr.into_inner().unwrap()).unwrap();
+        assert_eq!(out, "20240131007\nA   \n");
+
+        let mut wtr = Writer::from_writer(Vec::new(), &parser);
+        wtr.write(&record(&[("first", "A")])).unwrap();
+        assert!(matches!(
+            wtr.write_header(&header, &Record::new()),
+            Err(Error::Io(_))
+        ));
+    }
+
+    #[test]
+    fn write_latin1() {
+        let parser = Parser::builder().field("name").width(6).append().build();
+        let mut wtr = Writer::from_writer(Vec::new(), &parser).charset(Charset::Latin1);
+
+        wtr.write(&record(&[("name", "Zoë")])).unwrap();
+        assert!(matches!(
+            wtr.write(&record(&[("name", "5€")])),
+            Err(Error::Unmappable {
+                line: Some(2),
+                character: '€',
+                ..
+            })
+        ));
+        assert_eq!(wtr.records(), 1);
+        assert_eq!(wtr.into_inner().unwrap(), b"Zo\xEB   \n");
+
+        let mut wtr = Writer::from_writer(Vec::new(), &parser)
+            .charset(Charset::Windows1252)
+            .unmappable(Unmappable::Replace(b'?'));
+        wtr.write(&record(&[("name", "5€ ✓")])).unwrap();
+        assert_eq!(wtr.into_inner().unwrap(), b"5\x80 ?  \n");
+    }
+
+    #[test]
+    fn write_strict_ascii() {
+        let parser = Parser::builder().field("name").width(5).append().build();
+        let mut wtr = Writer::from_writer(Vec::new(), &parser).strict_ascii();
+
+        wtr.write(&record(&[("name", "Alice")])).unwrap();
+        assert!(matches!(
+            wtr.write(&record(&[("name", "Zoë")])),
+            Err(Error::NonAscii {
+                line: Some(2),
+                column: 3,
+                ..
+            })
+        ));
+
+        assert_eq!(wtr.records(), 1);
+        assert_eq!(wtr.into_inner().unwrap(), b"Alice\n");
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn write_gzip() {
+        use crate::flat::fixed::read::Reader;
+
+        let parser = Parser::builder().field("first").width(4).append().build();
+        let path = std::env::temp_dir().join(format!("eta-parse-{}.txt.gz", std::process::id()));
+        let mut wtr = Writer::to_gzip(&path, &parser).unwrap();
+        wtr.write(&record(&[("first", "AB")])).unwrap();
+        wtr.write(&record(&[("first", "CD")])).unwrap();
+        wtr.into_inner().unwrap().finish().unwrap();
+
+        let rows = Reader::from_gzip(&path, &parser)
+            .unwrap()
+            .string_reader()
+            .collect::<Result<Vec<Record>, Error>>()
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            rows,
+            vec![record(&[("first", "AB")]), record(&[("first", "CD")])]
+        );
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn write_zstd() {
+        use crate::flat::fixed::read::Reader;
+
+        let parser = Parser::builder().field("first").width(4).append().build();
+        let path = std::env::temp_dir().join(format!("eta-parse-{}.txt.zst", std::process::id()));
+        let mut wtr = Writer::to_zstd(&path, &parser).unwrap();
+        wtr.write(&record(&[("first", "AB")])).unwrap();
+        wtr.write(&record(&[("first", "CD")])).unwrap();
+        drop(wtr);
+
+        let rows = Reader::from_zstd(&path, &parser)
+            .unwrap()
+            .string_reader()
+            .collect::<Result<Vec<Record>, Error>>()
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            rows,
+            vec![record(&[("first", "AB")]), record(&[("first", "CD")])]
+        );
+    }
+}
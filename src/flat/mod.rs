@@ -1 +1,5 @@
-mod fixed;
+#[cfg(feature = "std")]
+pub mod delimited;
+pub mod fixed;
+#[cfg(feature = "std")]
+pub mod terminator;
@@ -0,0 +1,358 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::{
+    convert::TryFrom,
+    io::{self, BufRead, Read, Write},
+};
+
+/// Marks the end of a record in a flat file.
+#[allow(dead_code)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Terminator {
+    /// Records end with `\n`, a preceding `\r` is also removed when reading.
+    #[default]
+    Newline,
+    /// Records end with `\r\n`, a lone `\n` is also accepted when reading.
+    #[cfg_attr(feature = "serde", serde(rename = "crlf"))]
+    CrLf,
+    /// Records end with the single NEL byte (0x85) produced by EBCDIC conversions into a single
+    /// byte encoding. Every 0x85 byte ends a record, so the data must not be UTF-8.
+    Nel,
+    /// Records end with NEL encoded in UTF-8 (`C2 85`), as produced by EBCDIC conversions into
+    /// UTF-8.
+    NelUtf8,
+    /// Records end with an arbitrary byte sequence.
+    Bytes(Vec<u8>),
+    /// Records are preceded by their length in bytes, written as the given number of ASCII
+    /// decimal digits.
+    LengthPrefixed(usize),
+    /// Records have no terminator and are exactly the width of the layout.
+    Fixed,
+    /// Records are preceded by an IBM record descriptor word, as in variable blocked datasets
+    /// transferred in binary: a 2 byte big endian length that includes the 4 bytes of the RDW,
+    /// followed by 2 reserved bytes which are ignored when reading and written as zeros.
+    Rdw,
+}
+
+impl Terminator {
+    /// Reads the next record without its terminator into `buf`, replacing its contents and
+    /// returning `false` at the end of the input.
+    pub(crate) fn read_record<R: BufRead>(
+        &self,
+        reader: &mut R,
+        width: usize,
+        buf: &mut Vec<u8>,
+    ) -> io::Result<bool> {
+        buf.clear();
+        match self {
+            Terminator::Newline | Terminator::CrLf => {
+                if reader.read_until(b'\n', buf)? == 0 {
+                    return Ok(false);
+                }
+                if buf.ends_with(b"\n") {
+                    buf.pop();
+                    if buf.ends_with(b"\r") {
+                        buf.pop();
+                    }
+                }
+            }
+            Terminator::Nel => return Self::read_until_seq(reader, &[0x85], buf),
+            Terminator::NelUtf8 => return Self::read_until_seq(reader, &[0xc2, 0x85], buf),
+            Terminator::Bytes(seq) if seq.is_empty() => {
+                return Self::read_fixed(reader, width, buf)
+            }
+            Terminator::Bytes(seq) => return Self::read_until_seq(reader, seq, buf),
+            Terminator::LengthPrefixed(digits) => {
+                if reader.take(*digits as u64).read_to_end(buf)? == 0 {
+                    return Ok(false);
+                }
+                let length = std::str::from_utf8(buf)
+                    .ok()
+                    .and_then(|s| s.trim().parse::<usize>().ok())
+                    .ok_or_else(|| {
+                        io::Error::new(io::ErrorKind::InvalidData, "Invalid record length prefix")
+                    })?;
+                buf.clear();
+                if reader.take(length as u64).read_to_end(buf)? != length {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "Record shorter than its length prefix",
+                    ));
+                }
+            }
+            Terminator::Fixed => return Self::read_fixed(reader, width, buf),
+            Terminator::Rdw => {
+                if reader.take(4).read_to_end(buf)? == 0 {
+                    return Ok(false);
+                }
+                let length = match buf[..] {
+                    [high, low, _, _] => u16::from_be_bytes([high, low]) as usize,
+                    _ => 0,
+                };
+                if length < 4 {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "Invalid record descriptor word",
+                    ));
+                }
+                buf.clear();
+                let length = length as u64 - 4;
+                if reader.take(length).read_to_end(buf)? as u64 != length {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "Record shorter than its record descriptor word",
+                    ));
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    /// Writes the record followed (or for length prefixes, preceded) by the terminator.
+    pub(crate) fn write_record<W: Write>(&self, writer: &mut W, record: &[u8]) -> io::Result<()> {
+        match self {
+            Terminator::Newline => {
+                writer.write_all(record)?;
+                writer.write_all(b"\n")
+            }
+            Terminator::CrLf => {
+                writer.write_all(record)?;
+                writer.write_all(b"\r\n")
+            }
+            Terminator::Nel => {
+                writer.write_all(record)?;
+                writer.write_all(&[0x85])
+            }
+            Terminator::NelUtf8 => {
+                writer.write_all(record)?;
+                writer.write_all(&[0xc2, 0x85])
+            }
+            Terminator::Bytes(seq) => {
+                writer.write_all(record)?;
+                writer.write_all(seq)
+            }
+            Terminator::LengthPrefixed(digits) => {
+                let prefix = format!("{:0width$}", record.len(), width = *digits);
+                if prefix.len() > *digits {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "Record too long for length prefix",
+                    ));
+                }
+                writer.write_all(prefix.as_bytes())?;
+                writer.write_all(record)
+            }
+            Terminator::Fixed => writer.write_all(record),
+            Terminator::Rdw => {
+                let length = u16::try_from(record.len() + 4).map_err(|_| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "Record too long for record descriptor word",
+                    )
+                })?;
+                writer.write_all(&length.to_be_bytes())?;
+                writer.write_all(&[0, 0])?;
+                writer.write_all(record)
+            }
+        }
+    }
+
+    fn read_until_seq<R: BufRead>(
+        reader: &mut R,
+        seq: &[u8],
+        buf: &mut Vec<u8>,
+    ) -> io::Result<bool> {
+        let last = seq[seq.len() - 1];
+        loop {
+            if reader.read_until(last, buf)? == 0 || buf.ends_with(seq) {
+                break;
+            }
+        }
+        if buf.is_empty() {
+            return Ok(false);
+        }
+        if buf.ends_with(seq) {
+            buf.truncate(buf.len() - seq.len());
+        }
+        Ok(true)
+    }
+
+    fn read_fixed<R: BufRead>(reader: &mut R, width: usize, buf: &mut Vec<u8>) -> io::Result<bool> {
+        Ok(reader.take(width as u64).read_to_end(buf)? > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn read_all(terminator: Terminator, input: &[u8], width: usize) -> Vec<Vec<u8>> {
+        let mut reader = Cursor::new(input);
+        let mut records = Vec::new();
+        let mut buf = Vec::new();
+        while terminator
+            .read_record(&mut reader, width, &mut buf)
+            .unwrap()
+        {
+            records.push(buf.clone());
+        }
+        records
+    }
+
+    fn write_all(terminator: Terminator, records: &[&[u8]]) -> Vec<u8> {
+        let mut out = Vec::new();
+        for record in records {
+            terminator.write_record(&mut out, record).unwrap();
+        }
+        out
+    }
+
+    #[test]
+    fn newline() {
+        assert_eq!(
+            read_all(Terminator::Newline, b"AB\r\nCD\nEF", 0),
+            vec![b"AB".to_vec(), b"CD".to_vec(), b"EF".to_vec()]
+        );
+        assert_eq!(write_all(Terminator::Newline, &[b"AB", b"CD"]), b"AB\nCD\n");
+    }
+
+    #[test]
+    fn crlf() {
+        assert_eq!(
+            read_all(Terminator::CrLf, b"AB\r\nCD\r\n", 0),
+            vec![b"AB".to_vec(), b"CD".to_vec()]
+        );
+        assert_eq!(
+            write_all(Terminator::CrLf, &[b"AB", b"CD"]),
+            b"AB\r\nCD\r\n"
+        );
+    }
+
+    #[test]
+    fn nel() {
+        assert_eq!(
+            read_all(Terminator::Nel, b"AB\x85CD\x85", 0),
+            vec![b"AB".to_vec(), b"CD".to_vec()]
+        );
+        assert_eq!(
+            read_all(Terminator::Nel, b"\xe9\x85\xc2\x85", 0),
+            vec![b"\xe9".to_vec(), b"\xc2".to_vec()]
+        );
+        assert_eq!(write_all(Terminator::Nel, &[b"AB"]), b"AB\x85");
+    }
+
+    #[test]
+    fn nel_utf8() {
+        assert_eq!(
+            read_all(
+                Terminator::NelUtf8,
+                b"A\xc5\x85B\xc2\x85\xc3\xa9\xc2\x85C",
+                0
+            ),
+            vec![b"A\xc5\x85B".to_vec(), b"\xc3\xa9".to_vec(), b"C".to_vec()]
+        );
+        assert_eq!(write_all(Terminator::NelUtf8, &[b"AB"]), b"AB\xc2\x85");
+    }
+
+    #[test]
+    fn length_prefixed_truncated() {
+        let mut reader = Cursor::new(b"004ABCD004AB".to_vec());
+        let mut buf = Vec::new();
+        let terminator = Terminator::LengthPrefixed(3);
+
+        assert!(terminator.read_record(&mut reader, 0, &mut buf).unwrap());
+        let error = terminator
+            .read_record(&mut reader, 0, &mut buf)
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn byte_sequence() {
+        let terminator = Terminator::Bytes(b"~|".to_vec());
+
+        assert_eq!(
+            read_all(terminator.clone(), b"A|B~|C~D~|E", 0),
+            vec![b"A|B".to_vec(), b"C~D".to_vec(), b"E".to_vec()]
+        );
+        assert_eq!(write_all(terminator, &[b"AB", b"CD"]), b"AB~|CD~|");
+    }
+
+    #[test]
+    fn length_prefixed() {
+        assert_eq!(
+            read_all(Terminator::LengthPrefixed(3), b"002AB004CDEF", 0),
+            vec![b"AB".to_vec(), b"CDEF".to_vec()]
+        );
+        assert_eq!(
+            write_all(Terminator::LengthPrefixed(3), &[b"AB", b"CDEF"]),
+            b"002AB004CDEF"
+        );
+    }
+
+    #[test]
+    fn length_prefixed_invalid() {
+        let mut reader = Cursor::new(b"0X2AB".to_vec());
+
+        assert!(Terminator::LengthPrefixed(3)
+            .read_record(&mut reader, 0, &mut Vec::new())
+            .is_err());
+        assert!(Terminator::LengthPrefixed(1)
+            .write_record(&mut Vec::new(), b"ABCDEFGHIJKL")
+            .is_err());
+    }
+
+    #[test]
+    fn rdw() {
+        let input = b"\x00\x06\x00\x00AB\x00\x08\x00\x00CDEF";
+
+        assert_eq!(
+            read_all(Terminator::Rdw, input, 0),
+            vec![b"AB".to_vec(), b"CDEF".to_vec()]
+        );
+        assert_eq!(write_all(Terminator::Rdw, &[b"AB", b"CDEF"]), input);
+    }
+
+    #[test]
+    fn rdw_invalid() {
+        for input in [&b"\x00\x02\x00\x00"[..], b"\x00\x08\x00\x00AB", b"\x00"] {
+            assert!(Terminator::Rdw
+                .read_record(&mut Cursor::new(input), 0, &mut Vec::new())
+                .is_err());
+        }
+        assert!(Terminator::Rdw
+            .write_record(&mut Vec::new(), &[b' '; 65532])
+            .is_err());
+    }
+
+    #[test]
+    fn fixed() {
+        assert_eq!(
+            read_all(Terminator::Fixed, b"ABCDE", 2),
+            vec![b"AB".to_vec(), b"CD".to_vec(), b"E".to_vec()]
+        );
+        assert_eq!(write_all(Terminator::Fixed, &[b"AB", b"CD"]), b"ABCD");
+    }
+
+    #[test]
+    fn read_reuses_buffer() {
+        let mut reader = Cursor::new(b"ABCD\nEF\n".to_vec());
+        let mut buf = Vec::new();
+
+        assert!(Terminator::Newline
+            .read_record(&mut reader, 0, &mut buf)
+            .unwrap());
+        assert_eq!(buf, b"ABCD");
+        assert!(Terminator::Newline
+            .read_record(&mut reader, 0, &mut buf)
+            .unwrap());
+        assert_eq!(buf, b"EF");
+        assert!(!Terminator::Newline
+            .read_record(&mut reader, 0, &mut buf)
+            .unwrap());
+        assert!(buf.is_empty());
+    }
+}
@@ -0,0 +1,2 @@
+//! Layouts and rules of standard fixed width file formats.
+pub mod nacha;
@@ -0,0 +1,623 @@
+//! NACHA (ACH) files: 94 character records of six types, grouped into batches of entries and
+//! padded with lines of `9`s to a multiple of the blocking factor.
+//!
+//! [`AchFile`] reads and writes whole files, computing the batch and file control records when
+//! writing and reconciling them when reading.
+use crate::{
+    builder::{Buildable, Builder},
+    error::{Error, FieldError},
+    flat::{
+        fixed::{Parser, Record},
+        terminator::Terminator,
+    },
+};
+use std::io::{BufReader, BufWriter, Read, Write};
+
+/// The width of every record.
+pub const RECORD_SIZE: usize = 94;
+/// The number of records per block.
+pub const BLOCKING_FACTOR: usize = 10;
+
+/// Whether a field is alphanumeric, left aligned with spaces, or numeric, right aligned with
+/// zeros.
+#[derive(Clone, Copy)]
+enum Kind {
+    Alpha,
+    Numeric,
+}
+
+use self::Kind::{Alpha, Numeric};
+
+const FILE_HEADER: &[(&str, usize, Kind)] = &[
+    ("priority_code", 2, Numeric),
+    ("immediate_destination", 10, Alpha),
+    ("immediate_origin", 10, Alpha),
+    ("file_creation_date", 6, Numeric),
+    ("file_creation_time", 4, Numeric),
+    ("file_id_modifier", 1, Alpha),
+    ("record_size", 3, Numeric),
+    ("blocking_factor", 2, Numeric),
+    ("format_code", 1, Numeric),
+    ("immediate_destination_name", 23, Alpha),
+    ("immediate_origin_name", 23, Alpha),
+    ("reference_code", 8, Alpha),
+];
+
+const BATCH_HEADER: &[(&str, usize, Kind)] = &[
+    ("service_class_code", 3, Numeric),
+    ("company_name", 16, Alpha),
+    ("company_discretionary_data", 20, Alpha),
+    ("company_identification", 10, Alpha),
+    ("standard_entry_class_code", 3, Alpha),
+    ("company_entry_description", 10, Alpha),
+    ("company_descriptive_date", 6, Alpha),
+    ("effective_entry_date", 6, Numeric),
+    ("settlement_date", 3, Alpha),
+    ("originator_status_code", 1, Alpha),
+    ("originating_dfi_identification", 8, Numeric),
+    ("batch_number", 7, Numeric),
+];
+
+const ENTRY_DETAIL: &[(&str, usize, Kind)] = &[
+    ("transaction_code", 2, Numeric),
+    ("receiving_dfi_identification", 8, Numeric),
+    ("check_digit", 1, Numeric),
+    ("dfi_account_number", 17, Alpha),
+    ("amount", 10, Numeric),
+    ("individual_identification_number", 15, Alpha),
+    ("individual_name", 22, Alpha),
+    ("discretionary_data", 2, Alpha),
+    ("addenda_record_indicator", 1, Numeric),
+    ("trace_number", 15, Numeric),
+];
+
+const ADDENDA: &[(&str, usize, Kind)] = &[
+    ("addenda_type_code", 2, Numeric),
+    ("payment_related_information", 80, Alpha),
+    ("addenda_sequence_number", 4, Numeric),
+    ("entry_detail_sequence_number", 7, Numeric),
+];
+
+const BATCH_CONTROL: &[(&str, usize, Kind)] = &[
+    ("service_class_code", 3, Numeric),
+    ("entry_addenda_count", 6, Numeric),
+    ("entry_hash", 10, Numeric),
+    ("total_debit_entry_dollar_amount", 12, Numeric),
+    ("total_credit_entry_dollar_amount", 12, Numeric),
+    ("company_identification", 10, Alpha),
+    ("message_authentication_code", 19, Alpha),
+    ("reserved", 6, Alpha),
+    ("originating_dfi_identification", 8, Numeric),
+    ("batch_number", 7, Numeric),
+];
+
+const FILE_CONTROL: &[(&str, usize, Kind)] = &[
+    ("batch_count", 6, Numeric),
+    ("block_count", 6, Numeric),
+    ("entry_addenda_count", 8, Numeric),
+    ("entry_hash", 10, Numeric),
+    ("total_debit_entry_dollar_amount", 12, Numeric),
+    ("total_credit_entry_dollar_amount", 12, Numeric),
+    ("reserved", 39, Alpha),
+];
+
+/// The type of a record, given by its first character.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordType {
+    FileHeader,
+    BatchHeader,
+    EntryDetail,
+    Addenda,
+    BatchControl,
+    FileControl,
+}
+
+#[allow(dead_code)]
+impl RecordType {
+    /// The type of the record on the line, `None` for unknown codes.
+    pub fn of(line: &str) -> Option<Self> {
+        match line.chars().next()? {
+            '1' => Some(RecordType::FileHeader),
+            '5' => Some(RecordType::BatchHeader),
+            '6' => Some(RecordType::EntryDetail),
+            '7' => Some(RecordType::Addenda),
+            '8' => Some(RecordType::BatchControl),
+            '9' => Some(RecordType::FileControl),
+            _ => None,
+        }
+    }
+
+    /// The record type code.
+    pub fn code(&self) -> &'static str {
+        match self {
+            RecordType::FileHeader => "1",
+            RecordType::BatchHeader => "5",
+            RecordType::EntryDetail => "6",
+            RecordType::Addenda => "7",
+            RecordType::BatchControl => "8",
+            RecordType::FileControl => "9",
+        }
+    }
+
+    /// The layout of the record type, the `record_type_code` field defaults to the code.
+    pub fn parser(&self) -> Parser<'static> {
+        let fields = match self {
+            RecordType::FileHeader => FILE_HEADER,
+            RecordType::BatchHeader => BATCH_HEADER,
+            RecordType::EntryDetail => ENTRY_DETAIL,
+            RecordType::Addenda => ADDENDA,
+            RecordType::BatchControl => BATCH_CONTROL,
+            RecordType::FileControl => FILE_CONTROL,
+        };
+        let builder = Parser::builder()
+            .field("record_type_code")
+            .width(1)
+            .default_value(self.code())
+            .append();
+        fields
+            .iter()
+            .fold(builder, |builder, (name, width, kind)| match kind {
+                Alpha => builder.field(name).width(*width).append(),
+                Numeric => builder
+                    .field(name)
+                    .width(*width)
+                    .align("right")
+                    .padding('0')
+                    .append(),
+            })
+            .build()
+    }
+}
+
+/// An entry detail record and its addenda records.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Entry {
+    pub detail: Record,
+    pub addenda: Vec<Record>,
+}
+
+/// A batch header record and its entries, the batch control record is computed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Batch {
+    pub header: Record,
+    pub entries: Vec<Entry>,
+}
+
+/// A file header record and its batches, the file control record is computed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AchFile {
+    pub header: Record,
+    pub batches: Vec<Batch>,
+}
+
+/// The counts and amounts of the control records.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Control {
+    entry_addenda_count: u64,
+    entry_hash: u64,
+    debit: u64,
+    credit: u64,
+}
+
+impl Control {
+    fn add(&mut self, other: Control) {
+        self.entry_addenda_count += other.entry_addenda_count;
+        self.entry_hash = (self.entry_hash + other.entry_hash) % 10_000_000_000;
+        self.debit += other.debit;
+        self.credit += other.credit;
+    }
+
+    fn insert(&self, record: &mut Record) {
+        let values = [
+            ("entry_addenda_count", self.entry_addenda_count),
+            ("entry_hash", self.entry_hash),
+            ("total_debit_entry_dollar_amount", self.debit),
+            ("total_credit_entry_dollar_amount", self.credit),
+        ];
+        for (name, value) in values {
+            record.insert(name.to_string(), value.to_string());
+        }
+    }
+
+    /// Checks the counts and amounts of a control record.
+    fn reconcile(&self, record: &Record) -> Result<(), Error> {
+        let mut expected = Record::new();
+        self.insert(&mut expected);
+        reconcile(expected, record)
+    }
+}
+
+/// Checks the numeric fields of a control record against the expected values.
+fn reconcile(expected: Record, record: &Record) -> Result<(), Error> {
+    for (name, value) in expected {
+        let actual = number(record, &name, 0)?;
+        if actual.to_string() != value {
+            return Err(Error::Reconciliation {
+                name,
+                expected: value,
+                actual: actual.to_string(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// The numeric value of a field, blank values are zero.
+fn number(record: &Record, field: &str, line: u64) -> Result<u64, Error> {
+    match record.get(field).map_or("", |v| v.trim()) {
+        "" => Ok(0),
+        value => value.parse().map_err(|_| Error::Validation {
+            line,
+            fields: vec![FieldError {
+                field: field.to_string(),
+                message: format!("{} is not numeric", value),
+            }],
+        }),
+    }
+}
+
+#[allow(dead_code)]
+impl Batch {
+    fn control(&self) -> Result<Control, Error> {
+        let mut control = Control::default();
+        for entry in &self.entries {
+            let detail = &entry.detail;
+            control.entry_addenda_count += 1 + entry.addenda.len() as u64;
+            control.entry_hash += number(detail, "receiving_dfi_identification", 0)?;
+            let amount = number(detail, "amount", 0)?;
+            // Transaction codes ending in 1 to 4 are credits, 5 to 9 debits.
+            match number(detail, "transaction_code", 0)? % 10 {
+                1..=4 => control.credit += amount,
+                5..=9 => control.debit += amount,
+                _ => (),
+            }
+        }
+        control.entry_hash %= 10_000_000_000;
+        Ok(control)
+    }
+
+    /// The batch control record, with the counts and amounts of the entries and the service
+    /// class, company and batch number of the header.
+    pub fn control_record(&self) -> Result<Record, Error> {
+        let mut record = Record::new();
+        for name in [
+            "service_class_code",
+            "company_identification",
+            "originating_dfi_identification",
+            "batch_number",
+        ] {
+            if let Some(value) = self.header.get(name) {
+                record.insert(name.to_string(), value.clone());
+            }
+        }
+        self.control()?.insert(&mut record);
+        Ok(record)
+    }
+}
+
+#[allow(dead_code)]
+impl AchFile {
+    fn control(&self) -> Result<Control, Error> {
+        let mut control = Control::default();
+        for batch in &self.batches {
+            control.add(batch.control()?);
+        }
+        Ok(control)
+    }
+
+    /// The number of records in the file, without the blocking padding.
+    fn record_count(&self) -> usize {
+        2 + self
+            .batches
+            .iter()
+            .map(|b| 2 + b.entries.iter().map(|e| 1 + e.addenda.len()).sum::<usize>())
+            .sum::<usize>()
+    }
+
+    /// The file control record, with the batch and block counts and the totals of all batches.
+    pub fn control_record(&self) -> Result<Record, Error> {
+        let mut record = Record::new();
+        let blocks = self.record_count().div_ceil(BLOCKING_FACTOR);
+        record.insert(String::from("batch_count"), self.batches.len().to_string());
+        record.insert(String::from("block_count"), blocks.to_string());
+        self.control()?.insert(&mut record);
+        Ok(record)
+    }
+
+    /// Writes the file, numbering the batches and addenda records and setting the addenda
+    /// indicators when they are missing, followed by the control records and the blocking
+    /// padding.
+    pub fn write<W: Write>(&self, writer: W) -> Result<W, Error> {
+        let mut writer = BufWriter::new(writer);
+        let mut line = |parser: &Parser, record: &Record| -> Result<(), Error> {
+            Terminator::Newline.write_record(&mut writer, parser.format(record).as_bytes())?;
+            Ok(())
+        };
+        let batch_header = RecordType::BatchHeader.parser();
+        let entry_detail = RecordType::EntryDetail.parser();
+        let addenda = RecordType::Addenda.parser();
+        let batch_control = RecordType::BatchControl.parser();
+
+        let mut header = self.header.clone();
+        for (name, value) in [
+            ("priority_code", "01"),
+            ("record_size", "094"),
+            ("blocking_factor", "10"),
+            ("format_code", "1"),
+        ] {
+            header
+                .entry(name.to_string())
+                .or_insert_with(|| value.to_string());
+        }
+        line(&RecordType::FileHeader.parser(), &header)?;
+        for (number, batch) in self.batches.iter().enumerate() {
+            let mut batch = batch.clone();
+            batch
+                .header
+                .entry(String::from("batch_number"))
+                .or_insert_with(|| (number + 1).to_string());
+            line(&batch_header, &batch.header)?;
+            for entry in &batch.entries {
+                let mut detail = entry.detail.clone();
+                detail
+                    .entry(String::from("addenda_record_indicator"))
+                    .or_insert_with(|| u8::from(!entry.addenda.is_empty()).to_string());
+                line(&entry_detail, &detail)?;
+                let sequence = detail
+                    .get("trace_number")
+                    .and_then(|t| t.get(t.len().saturating_sub(7)..))
+                    .unwrap_or_default()
+                    .to_string();
+                for (n, record) in entry.addenda.iter().enumerate() {
+                    let mut record = record.clone();
+                    record
+                        .entry(String::from("addenda_sequence_number"))
+                        .or_insert_with(|| (n + 1).to_string());
+                    record
+                        .entry(String::from("entry_detail_sequence_number"))
+                        .or_insert_with(|| sequence.clone());
+                    line(&addenda, &record)?;
+                }
+            }
+            line(&batch_control, &batch.control_record()?)?;
+        }
+        line(&RecordType::FileControl.parser(), &self.control_record()?)?;
+        let padding = "9".repeat(RECORD_SIZE);
+        let count = self.record_count();
+        for _ in count..count.div_ceil(BLOCKING_FACTOR) * BLOCKING_FACTOR {
+            Terminator::Newline.write_record(&mut writer, padding.as_bytes())?;
+        }
+        writer.into_inner().map_err(|e| Error::from(e.into_error()))
+    }
+
+    /// Reads a file, checking the order of the records and reconciling the control records with
+    /// the entries, batches and blocks.
+    pub fn read<R: Read>(reader: R) -> Result<Self, Error> {
+        let parsers = [
+            RecordType::FileHeader,
+            RecordType::BatchHeader,
+            RecordType::EntryDetail,
+            RecordType::Addenda,
+            RecordType::BatchControl,
+            RecordType::FileControl,
+        ]
+        .map(|t| (t, t.parser()));
+        let mut file: Option<AchFile> = None;
+        let mut batch: Option<Batch> = None;
+        let mut finished = false;
+        let mut reader = BufReader::new(reader);
+        let mut bytes = Vec::new();
+        let mut n = 0;
+        while Terminator::Newline.read_record(&mut reader, RECORD_SIZE, &mut bytes)? {
+            n += 1;
+            let text = std::str::from_utf8(&bytes).map_err(|e| Error::Encoding {
+                line: Some(n),
+                source: e,
+            })?;
+            if text.trim().is_empty() || (finished && text.chars().all(|c| c == '9')) {
+                continue;
+            }
+            let unexpected = |message: &str| Error::Validation {
+                line: n,
+                fields: vec![FieldError {
+                    field: String::from("record_type_code"),
+                    message: message.to_string(),
+                }],
+            };
+            let record_type = RecordType::of(text).ok_or_else(|| unexpected("Unknown"))?;
+            let parser = &parsers
+                .iter()
+                .find(|(t, _)| *t == record_type)
+                .expect("All types")
+                .1;
+            let record = parser.parse(text).map_err(|e| e.at_line(n))?;
+            match (record_type, file.as_mut(), batch.as_mut(), finished) {
+                (RecordType::FileHeader, None, _, false) => {
+                    file = Some(AchFile {
+                        header: record,
+                        batches: Vec::new(),
+                    })
+                }
+                (RecordType::BatchHeader, Some(_), None, false) => {
+                    batch = Some(Batch {
+                        header: record,
+                        entries: Vec::new(),
+                    })
+                }
+                (RecordType::EntryDetail, Some(_), Some(batch), false) => {
+                    batch.entries.push(Entry {
+                        detail: record,
+                        addenda: Vec::new(),
+                    })
+                }
+                (RecordType::Addenda, Some(_), Some(batch), false) => batch
+                    .entries
+                    .last_mut()
+                    .ok_or_else(|| unexpected("Addenda without an entry"))?
+                    .addenda
+                    .push(record),
+                (RecordType::BatchControl, Some(file), Some(_), false) => {
+                    let batch = batch.take().expect("Batch is open");
+                    batch.control()?.reconcile(&record)?;
+                    file.batches.push(batch);
+                }
+                (RecordType::FileControl, Some(file), None, false) => {
+                    reconcile(file.control_record()?, &record)?;
+                    finished = true;
+                }
+                _ => return Err(unexpected("Out of order")),
+            }
+        }
+        match (file, finished) {
+            (Some(file), true) => Ok(file),
+            _ => Err(Error::Layout(String::from(
+                "Missing file header or control",
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(pairs: &[(&str, &str)]) -> Record {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    fn file() -> AchFile {
+        AchFile {
+            header: record(&[
+                ("immediate_destination", " 091000019"),
+                ("immediate_origin", "1234567890"),
+                ("file_creation_date", "240131"),
+                ("file_creation_time", "1200"),
+                ("file_id_modifier", "A"),
+                ("immediate_destination_name", "DEST BANK"),
+                ("immediate_origin_name", "ORIGIN CO"),
+            ]),
+            batches: vec![Batch {
+                header: record(&[
+                    ("service_class_code", "200"),
+                    ("company_name", "ORIGIN CO"),
+                    ("company_identification", "1234567890"),
+                    ("standard_entry_class_code", "PPD"),
+                    ("company_entry_description", "PAYROLL"),
+                    ("effective_entry_date", "240201"),
+                    ("originator_status_code", "1"),
+                    ("originating_dfi_identification", "09100001"),
+                ]),
+                entries: vec![
+                    Entry {
+                        detail: record(&[
+                            ("transaction_code", "22"),
+                            ("receiving_dfi_identification", "12345678"),
+                            ("check_digit", "0"),
+                            ("dfi_account_number", "111"),
+                            ("amount", "150000"),
+                            ("individual_name", "JANE DOE"),
+                            ("trace_number", "091000010000001"),
+                        ]),
+                        addenda: vec![record(&[
+                            ("addenda_type_code", "05"),
+                            ("payment_related_information", "BONUS"),
+                        ])],
+                    },
+                    Entry {
+                        detail: record(&[
+                            ("transaction_code", "27"),
+                            ("receiving_dfi_identification", "87654321"),
+                            ("check_digit", "0"),
+                            ("dfi_account_number", "222"),
+                            ("amount", "2500"),
+                            ("individual_name", "JOHN DOE"),
+                            ("trace_number", "091000010000002"),
+                        ]),
+                        addenda: Vec::new(),
+                    },
+                ],
+            }],
+        }
+    }
+
+    #[test]
+    fn layouts_are_record_size() {
+        for record_type in [
+            RecordType::FileHeader,
+            RecordType::BatchHeader,
+            RecordType::EntryDetail,
+            RecordType::Addenda,
+            RecordType::BatchControl,
+            RecordType::FileControl,
+        ] {
+            assert_eq!(record_type.parser().width(), RECORD_SIZE);
+        }
+    }
+
+    #[test]
+    fn write_and_read() {
+        let out = String::from_utf8(file().write(Vec::new()).unwrap()).unwrap();
+        let lines = out.lines().collect::<Vec<&str>>();
+
+        assert_eq!(lines.len(), 10);
+        assert!(lines.iter().all(|l| l.len() == RECORD_SIZE));
+        assert!(lines[0].starts_with("101 091000019123456789024013112"));
+        assert_eq!(&lines[2][78..79], "1");
+        assert_eq!(&lines[4][78..79], "0");
+        assert!(lines[3].ends_with("00010000001"));
+        assert!(lines[5].starts_with("82000000030099999999000000002500000000150000"));
+        assert!(lines[6].starts_with("9000001000001000000030099999999"));
+        assert_eq!(lines[7], "9".repeat(RECORD_SIZE));
+
+        let read = AchFile::read(out.as_bytes()).unwrap();
+        assert_eq!(read.batches.len(), 1);
+        assert_eq!(read.batches[0].entries.len(), 2);
+        assert_eq!(read.batches[0].entries[0].addenda.len(), 1);
+        assert_eq!(
+            read.batches[0].entries[1].detail["individual_name"],
+            "JOHN DOE"
+        );
+        assert_eq!(read.batches[0].header["batch_number"], "1");
+    }
+
+    #[test]
+    fn read_fails_reconciliation() {
+        let out = String::from_utf8(file().write(Vec::new()).unwrap()).unwrap();
+        let out = out.replacen("0000150000", "0000150001", 1);
+
+        assert!(matches!(
+            AchFile::read(out.as_bytes()),
+            Err(Error::Reconciliation { .. })
+        ));
+    }
+
+    #[test]
+    fn read_fails_batch_and_block_counts() {
+        let out = String::from_utf8(file().write(Vec::new()).unwrap()).unwrap();
+        for (from, to, name) in [
+            ("9000001000001", "9000002000001", "batch_count"),
+            ("9000001000001", "9000001000002", "block_count"),
+        ] {
+            let changed = out.replacen(from, to, 1);
+
+            match AchFile::read(changed.as_bytes()) {
+                Err(Error::Reconciliation { name: actual, .. }) => assert_eq!(actual, name),
+                other => panic!("{:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn read_out_of_order() {
+        let out = String::from_utf8(file().write(Vec::new()).unwrap()).unwrap();
+        let lines = out.lines().skip(1).collect::<Vec<&str>>().join("\n");
+
+        assert!(matches!(
+            AchFile::read(lines.as_bytes()),
+            Err(Error::Validation { line: 1, .. })
+        ));
+    }
+}
@@ -0,0 +1,251 @@
+use crate::flat::fixed::{Field, Parser, Record};
+use std::collections::HashMap;
+
+const ALPHA: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const ALPHANUMERIC: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+const DAYS: [u32; 12] = [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+
+/// The kind of values generated for a field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Kind {
+    /// Upper case letters, between one character and the width of the field.
+    Alpha,
+    /// Upper case letters and digits, between one character and the width of the field.
+    Alphanumeric,
+    /// A number without leading zeros that fits the field.
+    Digits,
+    /// A number with `scale` decimals and no decimal point, e.g. an amount in cents.
+    Decimal {
+        scale: usize,
+    },
+    /// A date between 1970 and 2049 in a pattern of `YYYY`, `MM` and `DD`, e.g. `YYYY-MM-DD`.
+    Date(String),
+    /// One of the values, e.g. the codes accepted by a validator.
+    Choice(Vec<String>),
+    Constant(String),
+    /// Consecutive numbers from the start, e.g. record ids.
+    Sequence(u64),
+}
+
+impl Kind {
+    /// Guesses the kind of a field from its layout: numbers when it is padded with zeros,
+    /// letters and digits otherwise.
+    fn infer(field: &Field) -> Self {
+        match field.padding() {
+            '0' => Kind::Digits,
+            _ => Kind::Alphanumeric,
+        }
+    }
+}
+
+/// The kinds of values generated for the fields of a layout, fields without a kind have it
+/// inferred from their layout.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Spec {
+    fields: HashMap<String, Kind>,
+    nulls: HashMap<String, f64>,
+    seed: u64,
+}
+
+#[allow(dead_code)]
+impl Spec {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn field<T: Into<String>>(mut self, field: T, kind: Kind) -> Self {
+        self.fields.insert(field.into(), kind);
+        self
+    }
+
+    /// Leaves a field out of about `rate` of the records, between 0 and 1.
+    pub fn null_rate<T: Into<String>>(mut self, field: T, rate: f64) -> Self {
+        self.nulls.insert(field.into(), rate);
+        self
+    }
+
+    /// Sets the seed of the random values, the same seed always generates the same records.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+}
+
+/// Generates random records for a layout, see [`records`].
+pub struct Records<'p> {
+    fields: Vec<(&'p str, usize, Kind, f64)>,
+    rng: Rng,
+    index: u64,
+    count: u64,
+}
+
+/// Generates `n` random records fitting the layout, with values of the kinds set by the spec.
+/// Formatted with the parser, they make test files of a layout without any production data.
+pub fn records<'p>(parser: &'p Parser, spec: &Spec, n: u64) -> Records<'p> {
+    Records {
+        fields: parser
+            .fields()
+            .iter()
+            .filter_map(|f| {
+                let name = f.name()?;
+                let kind = spec
+                    .fields
+                    .get(name)
+                    .cloned()
+                    .unwrap_or_else(|| Kind::infer(f));
+                let nulls = spec.nulls.get(name).copied().unwrap_or(0.0);
+                Some((name, f.width(), kind, nulls))
+            })
+            .collect(),
+        rng: Rng(spec.seed),
+        index: 0,
+        count: n,
+    }
+}
+
+impl<'p> Records<'p> {
+    fn value(rng: &mut Rng, width: usize, kind: &Kind, index: u64) -> String {
+        match kind {
+            Kind::Alpha => rng.chars(ALPHA, width),
+            Kind::Alphanumeric => rng.chars(ALPHANUMERIC, width),
+            Kind::Digits => rng.number(width),
+            Kind::Decimal { scale } => {
+                let mut value = rng.number(width);
+                while value.len() <= *scale && value.len() < width {
+                    value.insert(0, '0');
+                }
+                value
+            }
+            Kind::Date(pattern) => {
+                let year = 1970 + rng.below(80) as u32;
+                let month = 1 + rng.below(12) as u32;
+                let day = 1 + rng.below(DAYS[month as usize - 1] as u64) as u32;
+                pattern
+                    .replace("YYYY", &format!("{:04}", year))
+                    .replace("MM", &format!("{:02}", month))
+                    .replace("DD", &format!("{:02}", day))
+            }
+            Kind::Choice(values) if values.is_empty() => String::new(),
+            Kind::Choice(values) => values[rng.below(values.len() as u64) as usize].clone(),
+            Kind::Constant(value) => value.clone(),
+            Kind::Sequence(start) => (start + index).to_string(),
+        }
+    }
+}
+
+impl<'p> Iterator for Records<'p> {
+    type Item = Record;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index == self.count {
+            return None;
+        }
+        let mut record = Record::with_capacity(self.fields.len());
+        for (name, width, kind, nulls) in &self.fields {
+            if *nulls > 0.0 && self.rng.fraction() < *nulls {
+                continue;
+            }
+            let value = Self::value(&mut self.rng, *width, kind, self.index);
+            record.insert(name.to_string(), value);
+        }
+        self.index += 1;
+        Some(record)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = (self.count - self.index) as usize;
+        (remaining, Some(remaining))
+    }
+}
+
+/// SplitMix64, good enough for test data and without dependencies.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n.max(1)
+    }
+
+    fn fraction(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn chars(&mut self, alphabet: &[u8], width: usize) -> String {
+        let length = 1 + self.below(width as u64) as usize;
+        (0..length)
+            .map(|_| alphabet[self.below(alphabet.len() as u64) as usize] as char)
+            .collect()
+    }
+
+    fn number(&mut self, width: usize) -> String {
+        let digits = 1 + self.below(width.min(18) as u64) as usize;
+        let mut number = (1 + self.below(9)).to_string();
+        number.extend((1..digits).map(|_| char::from(b'0' + self.below(10) as u8)));
+        number
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::{Buildable, Builder};
+
+    #[test]
+    fn generate_records() {
+        let parser = Parser::builder()
+            .field("id")
+            .width(4)
+            .append()
+            .field("amount")
+            .width(6)
+            .align("right")
+            .padding('0')
+            .append()
+            .field("date")
+            .width(10)
+            .append()
+            .field("status")
+            .width(1)
+            .append()
+            .field("note")
+            .width(5)
+            .append()
+            .build();
+        let spec = Spec::new()
+            .field("id", Kind::Sequence(1))
+            .field("date", Kind::Date(String::from("YYYY-MM-DD")))
+            .field(
+                "status",
+                Kind::Choice(vec![String::from("A"), String::from("C")]),
+            )
+            .null_rate("note", 1.0)
+            .seed(7);
+
+        let records = records(&parser, &spec, 50).collect::<Vec<Record>>();
+
+        assert_eq!(records.len(), 50);
+        assert_eq!(records[0]["id"], "1");
+        assert_eq!(records[49]["id"], "50");
+        for record in &records {
+            assert!(record["amount"].len() <= 6);
+            assert!(record["amount"].chars().all(|c| c.is_ascii_digit()));
+            assert_eq!(record["date"].len(), 10);
+            assert!(["A", "C"].contains(&record["status"].as_str()));
+            assert!(!record.contains_key("note"));
+            let formatted = parser.format(record);
+            assert_eq!(parser.parse(formatted).unwrap()["status"], record["status"]);
+        }
+        assert_eq!(
+            super::records(&parser, &spec, 50).collect::<Vec<_>>(),
+            records
+        );
+    }
+}
@@ -0,0 +1,302 @@
+use crate::{
+    error::Error,
+    flat::{
+        fixed::{progress::CountingReader, Parser, Record},
+        terminator::Terminator,
+    },
+};
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+const BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// The byte offsets of the records of a flat file by the values of one or more key fields.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Index {
+    offsets: HashMap<String, HashMap<String, Vec<u64>>>,
+}
+
+#[allow(dead_code)]
+impl Index {
+    /// Scans the records once, recording the offset of each under the values of the key fields.
+    pub fn build<R: Read>(
+        reader: R,
+        parser: &Parser,
+        terminator: &Terminator,
+        fields: &[&str],
+    ) -> Result<Self, Error> {
+        let mut index = Index {
+            offsets: fields
+                .iter()
+                .map(|f| (f.to_string(), HashMap::new()))
+                .collect(),
+        };
+        let mut reader = CountingReader::new(BufReader::new(reader));
+        let mut buf = Vec::new();
+        let mut record = Record::new();
+        let mut line = 0;
+        loop {
+            let offset = reader.count();
+            if !terminator.read_record(&mut reader, parser.width(), &mut buf)? {
+                break;
+            }
+            line += 1;
+            let s = decode(&buf, offset, line)?;
+            if s.trim().is_empty() {
+                continue;
+            }
+            parser
+                .parse_into(s, &mut record)
+                .map_err(|e| e.at_line(line))?;
+            for (field, keys) in index.offsets.iter_mut() {
+                if let Some(key) = record.get(field) {
+                    keys.entry(key.clone()).or_default().push(offset);
+                }
+            }
+        }
+        Ok(index)
+    }
+
+    /// The offsets of the records whose field has the given value.
+    pub fn offsets(&self, field: &str, key: &str) -> &[u64] {
+        self.offsets
+            .get(field)
+            .and_then(|keys| keys.get(key))
+            .map_or(&[], |offsets| offsets.as_slice())
+    }
+
+    /// The indexed key fields.
+    pub fn fields(&self) -> impl Iterator<Item = &str> {
+        self.offsets.keys().map(|f| f.as_str())
+    }
+
+    /// Writes the index as one tab separated `field`, `key` and `offset` entry per line.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        for (field, keys) in &self.offsets {
+            writeln!(writer, "{}", escape(field))?;
+            for (key, offsets) in keys {
+                for offset in offsets {
+                    writeln!(writer, "{}\t{}\t{}", escape(field), escape(key), offset)?;
+                }
+            }
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Reads an index written by [`Index::save`].
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let mut index = Index::default();
+        for (n, line) in BufReader::new(File::open(path)?).lines().enumerate() {
+            let line = line?;
+            let mut parts = line.split('\t');
+            let field = unescape(parts.next().unwrap_or_default());
+            let keys = index.offsets.entry(field).or_default();
+            match (parts.next(), parts.next().map(str::parse::<u64>)) {
+                (Some(key), Some(Ok(offset))) => {
+                    keys.entry(unescape(key)).or_default().push(offset)
+                }
+                (None, None) => (),
+                _ => {
+                    return Err(Error::Config(format!(
+                        "Invalid index entry on line {}",
+                        n + 1
+                    )))
+                }
+            }
+        }
+        Ok(index)
+    }
+}
+
+/// A flat file with an index, reading the matching records on lookup without rescanning.
+pub struct IndexedFile<'a, R> {
+    inner: BufReader<R>,
+    parser: &'a Parser<'a>,
+    terminator: Terminator,
+    index: Index,
+    buf: Vec<u8>,
+}
+
+#[allow(dead_code)]
+impl<'a, R> IndexedFile<'a, R>
+where
+    R: Read + Seek,
+{
+    /// Uses an existing index, built with the same terminator.
+    pub fn new(reader: R, parser: &'a Parser<'a>, index: Index) -> Self {
+        IndexedFile {
+            inner: BufReader::new(reader),
+            parser,
+            terminator: Terminator::default(),
+            index,
+            buf: Vec::new(),
+        }
+    }
+
+    /// Scans the records to build the index of the key fields.
+    pub fn build(
+        mut reader: R,
+        parser: &'a Parser<'a>,
+        terminator: Terminator,
+        fields: &[&str],
+    ) -> Result<Self, Error> {
+        let index = Index::build(&mut reader, parser, &terminator, fields)?;
+        Ok(Self::new(reader, parser, index).terminator(terminator))
+    }
+
+    pub fn terminator(mut self, terminator: Terminator) -> Self {
+        self.terminator = terminator;
+        self
+    }
+
+    pub fn index(&self) -> &Index {
+        &self.index
+    }
+
+    /// Reads the records whose field has the given value.
+    pub fn lookup(&mut self, field: &str, key: &str) -> Result<Vec<Record>, Error> {
+        let offsets = self.index.offsets(field, key).to_vec();
+        offsets
+            .into_iter()
+            .map(|offset| self.read_at(offset))
+            .collect()
+    }
+
+    /// Reads the record starting at the offset.
+    pub fn read_at(&mut self, offset: u64) -> Result<Record, Error> {
+        self.inner.seek(SeekFrom::Start(offset))?;
+        if !self
+            .terminator
+            .read_record(&mut self.inner, self.parser.width(), &mut self.buf)?
+        {
+            return Err(Error::from(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("No record at offset {}", offset),
+            )));
+        }
+        self.parser.parse(decode(&self.buf, offset, 0)?)
+    }
+}
+
+#[allow(dead_code)]
+impl<'a> IndexedFile<'a, File> {
+    /// Opens a newline terminated file and indexes the key fields.
+    pub fn open<P: AsRef<Path>>(
+        path: P,
+        parser: &'a Parser<'a>,
+        fields: &[&str],
+    ) -> Result<Self, Error> {
+        Self::build(File::open(path)?, parser, Terminator::default(), fields)
+    }
+}
+
+/// Decodes a record, skipping the byte order mark at the start of the file.
+fn decode(buf: &[u8], offset: u64, line: u64) -> Result<&str, Error> {
+    let buf = match offset {
+        0 => buf.strip_prefix(BOM).unwrap_or(buf),
+        _ => buf,
+    };
+    std::str::from_utf8(buf).map_err(|source| Error::Encoding {
+        line: Some(line).filter(|l| *l > 0),
+        source,
+    })
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+}
+
+fn unescape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => out.push('\t'),
+            Some('n') => out.push('\n'),
+            Some(c) => out.push(c),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::{Buildable, Builder};
+    use std::io::Cursor;
+
+    fn parser() -> Parser<'static> {
+        Parser::builder()
+            .field("id")
+            .width(4)
+            .append()
+            .field("name")
+            .width(6)
+            .append()
+            .build()
+    }
+
+    const DATA: &str = "\u{feff}0001ALICE \n0002BOB   \n\n0001AL\tX  \n";
+
+    #[test]
+    fn lookup_records() {
+        let parser = parser();
+        let mut file =
+            IndexedFile::build(Cursor::new(DATA), &parser, Terminator::Newline, &["id"]).unwrap();
+
+        let records = file.lookup("id", "0001").unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].get("name").unwrap(), "ALICE");
+        assert_eq!(records[1].get("name").unwrap(), "AL\tX");
+        assert_eq!(file.lookup("id", "0002").unwrap()[0]["name"], "BOB");
+        assert!(file.lookup("id", "0003").unwrap().is_empty());
+        assert!(file.lookup("name", "BOB").unwrap().is_empty());
+    }
+
+    #[test]
+    fn save_and_load() {
+        let parser = parser();
+        let index = Index::build(
+            Cursor::new(DATA),
+            &parser,
+            &Terminator::Newline,
+            &["id", "name"],
+        )
+        .unwrap();
+        let path = std::env::temp_dir().join("eta-parse-index-save-and-load.idx");
+
+        index.save(&path).unwrap();
+        let loaded = Index::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, index);
+        assert_eq!(loaded.offsets("name", "AL\tX"), &[26]);
+
+        let mut file = IndexedFile::new(Cursor::new(DATA), &parser, loaded);
+        assert_eq!(file.lookup("name", "BOB").unwrap()[0]["id"], "0002");
+    }
+
+    #[test]
+    fn build_invalid_record() {
+        let result = Index::build(
+            Cursor::new("0001ALICE \n0002\n"),
+            &parser(),
+            &Terminator::Newline,
+            &["id"],
+        );
+
+        assert_eq!(result.unwrap_err().line(), Some(2));
+    }
+}
@@ -0,0 +1,298 @@
+use crate::error::{Error, IntakeError};
+use std::{
+    collections::HashSet,
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Read, Write},
+    path::{Path, PathBuf},
+};
+
+const FNV_OFFSET: u128 = 0x6c62_272e_07bb_0142_62b8_2175_6295_c58d;
+const FNV_PRIME: u128 = 0x0000_0000_0100_0000_0000_0000_0000_013b;
+
+/// A stable 128-bit FNV-1a hasher, used so fingerprints remain comparable across runs.
+#[derive(Debug, Clone, Copy)]
+struct Fnv(u128);
+
+impl Fnv {
+    fn new() -> Self {
+        Fnv(FNV_OFFSET)
+    }
+
+    fn update(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 ^= u128::from(*b);
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+    }
+}
+
+/// Identifies the content of an incoming file.
+///
+/// The content is identified by its length and 128-bit FNV-1a hash, which is not
+/// cryptographic: a different file of the same length colliding with an admitted one, by
+/// accident or on purpose, is refused as a duplicate. The header lines are kept as they are,
+/// so header matches never collide.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fingerprint {
+    content: u128,
+    length: u64,
+    header: Option<Vec<u8>>,
+}
+
+impl Fingerprint {
+    /// Key identifying the full content of the file.
+    pub fn content_key(&self) -> String {
+        format!("content:{:032x}:{}", self.content, self.length)
+    }
+
+    /// Key holding the header lines of the file in hexadecimal, if header matching is enabled.
+    pub fn header_key(&self) -> Option<String> {
+        self.header.as_ref().map(|header| {
+            let hex = header
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect::<String>();
+            format!("header:{}", hex)
+        })
+    }
+}
+
+/// Persists the fingerprints of files that have already been processed.
+pub trait StateStore {
+    fn contains(&self, key: &str) -> bool;
+
+    fn insert(&mut self, key: String) -> Result<(), Error>;
+}
+
+/// A state store that only remembers files for the lifetime of the process.
+#[allow(dead_code)]
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    keys: HashSet<String>,
+}
+
+#[allow(dead_code)]
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl StateStore for MemoryStore {
+    fn contains(&self, key: &str) -> bool {
+        self.keys.contains(key)
+    }
+
+    fn insert(&mut self, key: String) -> Result<(), Error> {
+        self.keys.insert(key);
+        Ok(())
+    }
+}
+
+/// A state store backed by a plain text file containing one key per line.
+#[allow(dead_code)]
+#[derive(Debug)]
+pub struct FileStore {
+    path: PathBuf,
+    keys: HashSet<String>,
+}
+
+#[allow(dead_code)]
+impl FileStore {
+    /// Opens the store at the given path, creating it on first insert if it does not exist.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref().to_path_buf();
+        let mut keys = HashSet::new();
+        if path.exists() {
+            for line in BufReader::new(File::open(&path)?).lines() {
+                let line = line?;
+                if !line.trim().is_empty() {
+                    keys.insert(line.trim().to_string());
+                }
+            }
+        }
+        Ok(FileStore { path, keys })
+    }
+}
+
+impl StateStore for FileStore {
+    fn contains(&self, key: &str) -> bool {
+        self.keys.contains(key)
+    }
+
+    fn insert(&mut self, key: String) -> Result<(), Error> {
+        if !self.keys.contains(&key) {
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)?;
+            writeln!(file, "{}", key)?;
+            self.keys.insert(key);
+        }
+        Ok(())
+    }
+}
+
+/// Refuses files whose content (or header lines) have already been admitted.
+#[derive(Debug)]
+pub struct IntakeGuard<S> {
+    store: S,
+    header_lines: usize,
+}
+
+#[allow(dead_code)]
+impl<S> IntakeGuard<S>
+where
+    S: StateStore,
+{
+    pub fn new(store: S) -> Self {
+        IntakeGuard {
+            store,
+            header_lines: 0,
+        }
+    }
+
+    /// Also treat files as duplicates when their first `lines` lines match a previous file.
+    pub fn header_lines(mut self, lines: usize) -> Self {
+        self.header_lines = lines;
+        self
+    }
+
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// Hashes the content of the reader without consulting the store.
+    pub fn fingerprint<R: Read>(&self, reader: R) -> Result<Fingerprint, Error> {
+        let mut reader = BufReader::new(reader);
+        let mut content = Fnv::new();
+        let mut header = Vec::new();
+        let mut length = 0;
+        let mut lines = 0;
+        let mut buf = Vec::new();
+
+        loop {
+            buf.clear();
+            let read = reader.read_until(b'\n', &mut buf)?;
+            if read == 0 {
+                break;
+            }
+            content.update(&buf);
+            length += read as u64;
+            if lines < self.header_lines {
+                header.extend_from_slice(&buf);
+                lines += 1;
+            }
+        }
+
+        Ok(Fingerprint {
+            content: content.0,
+            length,
+            header: if self.header_lines > 0 && lines > 0 {
+                Some(header)
+            } else {
+                None
+            },
+        })
+    }
+
+    /// Returns an error if the fingerprint matches a file that was already admitted.
+    pub fn check(&self, fingerprint: &Fingerprint) -> Result<(), Error> {
+        let key = fingerprint.content_key();
+        if self.store.contains(&key) {
+            return Err(Error::from(IntakeError::DuplicateContent(key)));
+        }
+        if let Some(key) = fingerprint.header_key() {
+            if self.store.contains(&key) {
+                return Err(Error::from(IntakeError::DuplicateHeader(key)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Fingerprints the reader and records it in the store, unless it is a duplicate.
+    pub fn admit<R: Read>(&mut self, reader: R) -> Result<Fingerprint, Error> {
+        let fingerprint = self.fingerprint(reader)?;
+        self.check(&fingerprint)?;
+        self.store.insert(fingerprint.content_key())?;
+        if let Some(key) = fingerprint.header_key() {
+            self.store.insert(key)?;
+        }
+        Ok(fingerprint)
+    }
+
+    /// Admits the file at the given path, see [`IntakeGuard::admit`].
+    pub fn admit_file<P: AsRef<Path>>(&mut self, path: P) -> Result<Fingerprint, Error> {
+        self.admit(File::open(path)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, fs};
+
+    #[test]
+    fn fingerprint_is_stable() {
+        let guard = IntakeGuard::new(MemoryStore::new());
+        let first = guard.fingerprint("1111\n2222\n".as_bytes()).unwrap();
+        let second = guard.fingerprint("1111\n2222\n".as_bytes()).unwrap();
+        let other = guard.fingerprint("1111\n2223\n".as_bytes()).unwrap();
+
+        assert_eq!(first, second);
+        assert_ne!(first, other);
+        assert_eq!(first.header_key(), None);
+    }
+
+    #[test]
+    fn admit_refuses_duplicate_content() {
+        let mut guard = IntakeGuard::new(MemoryStore::new());
+
+        assert!(guard.admit("1111\n2222\n".as_bytes()).is_ok());
+        assert!(guard.admit("1111\n3333\n".as_bytes()).is_ok());
+        let error = guard.admit("1111\n2222\n".as_bytes()).unwrap_err();
+
+        assert!(matches!(
+            error,
+            Error::Intake(IntakeError::DuplicateContent(_))
+        ));
+    }
+
+    #[test]
+    fn admit_refuses_duplicate_header() {
+        let mut guard = IntakeGuard::new(MemoryStore::new()).header_lines(1);
+
+        assert!(guard.admit("HDR0001\n2222\n".as_bytes()).is_ok());
+        assert!(guard.admit("HDR0002\n2222\n".as_bytes()).is_ok());
+        let error = guard.admit("HDR0001\n3333\n".as_bytes()).unwrap_err();
+
+        assert!(matches!(
+            error,
+            Error::Intake(IntakeError::DuplicateHeader(_))
+        ));
+    }
+
+    #[test]
+    fn header_key_holds_the_lines() {
+        let guard = IntakeGuard::new(MemoryStore::new()).header_lines(1);
+        let fingerprint = guard.fingerprint("AB\nCD\n".as_bytes()).unwrap();
+
+        assert_eq!(fingerprint.header_key().unwrap(), "header:41420a");
+    }
+
+    #[test]
+    fn file_store_persists() {
+        let mut path = env::temp_dir();
+        path.push(format!("eta-parse-intake-{}.txt", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let mut guard = IntakeGuard::new(FileStore::open(&path).unwrap());
+        assert!(guard.admit("1111\n".as_bytes()).is_ok());
+
+        let mut guard = IntakeGuard::new(FileStore::open(&path).unwrap());
+        assert!(guard.admit("1111\n".as_bytes()).is_err());
+        assert!(guard.admit("2222\n".as_bytes()).is_ok());
+
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,463 @@
+#[cfg(feature = "expr")]
+use crate::transform::expr::Expression;
+use crate::{
+    error::Error,
+    flat::{
+        fixed::{read::Reader, write::Writer, Record},
+        terminator::Terminator,
+    },
+    pipeline::{summary::RunSummary, Pipeline},
+    schema::Schema,
+};
+use serde::Deserialize;
+use std::{
+    fs::{self, File},
+    path::{Path, PathBuf},
+};
+
+/// A declarative end-to-end job: where to read from, the layouts, and what to do with each
+/// record before writing it to the sink.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Job {
+    pub source: Source,
+    pub layout: Layout,
+    #[serde(default)]
+    pub validations: Vec<ValidationDef>,
+    #[serde(default)]
+    pub transforms: Vec<TransformDef>,
+    pub sink: Sink,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Source {
+    pub path: PathBuf,
+    #[serde(default)]
+    pub terminator: Terminator,
+    #[serde(default)]
+    pub skip_blank_lines: bool,
+    #[serde(default)]
+    pub comments: Vec<String>,
+    /// Skip records that fail to parse instead of aborting the job.
+    #[serde(default)]
+    pub skip_errors: bool,
+    /// Abort the job once more than this many records have been rejected.
+    #[serde(default)]
+    pub max_errors: Option<u64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Sink {
+    pub path: PathBuf,
+    #[serde(default)]
+    pub terminator: Terminator,
+    /// The output layout, defaults to the source layout.
+    #[serde(default)]
+    pub layout: Option<Layout>,
+}
+
+/// A layout, either a path to a schema file or an inline schema.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum Layout {
+    File { path: PathBuf },
+    Inline(Schema),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "rule", rename_all = "snake_case")]
+pub enum ValidationDef {
+    /// The field must not be empty.
+    Required { field: String },
+    /// The field must only contain ASCII digits, when present.
+    Numeric { field: String },
+    /// The field must be one of the values.
+    OneOf { field: String, values: Vec<String> },
+    /// The expression must be true, see [`Expression`].
+    #[cfg(feature = "expr")]
+    Expression {
+        expression: Expression,
+        #[serde(default)]
+        message: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TransformDef {
+    /// Sets a field to a constant value.
+    Set {
+        field: String,
+        value: String,
+    },
+    Rename {
+        from: String,
+        to: String,
+    },
+    Remove {
+        field: String,
+    },
+    Uppercase {
+        field: String,
+    },
+    Lowercase {
+        field: String,
+    },
+    /// Only keeps records where the field is one of the values.
+    Filter {
+        field: String,
+        values: Vec<String>,
+    },
+    /// Sets a field to the value of an expression, or removes it when the expression fails.
+    #[cfg(feature = "expr")]
+    Compute {
+        field: String,
+        expression: Expression,
+    },
+    /// Only keeps records where the expression is true, e.g. of one record type.
+    #[cfg(feature = "expr")]
+    Where {
+        expression: Expression,
+    },
+}
+
+impl Layout {
+    fn load(&self, base: &Path) -> Result<Schema, Error> {
+        match self {
+            Layout::File { path } => Schema::from_file(base.join(path)),
+            Layout::Inline(schema) => Ok(schema.clone()),
+        }
+    }
+}
+
+impl ValidationDef {
+    fn check(&self, record: &Record) -> Result<(), String> {
+        match self {
+            ValidationDef::Required { field } => match record.get(field) {
+                Some(value) if !value.is_empty() => Ok(()),
+                _ => Err(format!("{} is required", field)),
+            },
+            ValidationDef::Numeric { field } => match record.get(field) {
+                Some(value) if !value.chars().all(|c| c.is_ascii_digit()) => {
+                    Err(format!("{} is not numeric", field))
+                }
+                _ => Ok(()),
+            },
+            ValidationDef::OneOf { field, values } => match record.get(field) {
+                Some(value) if values.contains(value) => Ok(()),
+                _ => Err(format!("{} is not one of {}", field, values.join(", "))),
+            },
+            #[cfg(feature = "expr")]
+            ValidationDef::Expression {
+                expression,
+                message,
+            } => match expression.eval_bool(record) {
+                Ok(true) => Ok(()),
+                Ok(false) => Err(message
+                    .clone()
+                    .unwrap_or_else(|| format!("{} is false", expression))),
+                Err(e) => Err(e.to_string()),
+            },
+        }
+    }
+}
+
+impl TransformDef {
+    fn apply(&self, mut record: Record) -> Option<Record> {
+        match self {
+            TransformDef::Set { field, value } => {
+                record.insert(field.clone(), value.clone());
+            }
+            TransformDef::Rename { from, to } => {
+                if let Some(value) = record.remove(from) {
+                    record.insert(to.clone(), value);
+                }
+            }
+            TransformDef::Remove { field } => {
+                record.remove(field);
+            }
+            TransformDef::Uppercase { field } => {
+                if let Some(value) = record.get_mut(field) {
+                    *value = value.to_uppercase();
+                }
+            }
+            TransformDef::Lowercase { field } => {
+                if let Some(value) = record.get_mut(field) {
+                    *value = value.to_lowercase();
+                }
+            }
+            TransformDef::Filter { field, values } => match record.get(field) {
+                Some(value) if values.contains(value) => (),
+                _ => return None,
+            },
+            #[cfg(feature = "expr")]
+            TransformDef::Compute { field, expression } => match expression.eval_string(&record) {
+                Ok(value) => {
+                    record.insert(field.clone(), value);
+                }
+                Err(_) => {
+                    record.remove(field);
+                }
+            },
+            #[cfg(feature = "expr")]
+            TransformDef::Where { expression } => {
+                if !expression.eval_bool(&record).unwrap_or(false) {
+                    return None;
+                }
+            }
+        }
+        Some(record)
+    }
+}
+
+#[allow(dead_code)]
+impl Job {
+    /// Parses a job from a TOML document.
+    pub fn from_toml(s: &str) -> Result<Self, Error> {
+        toml::from_str(s).map_err(|e| Error::Config(e.to_string()))
+    }
+
+    /// Loads a job from a TOML file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Self::from_toml(&fs::read_to_string(path)?)
+    }
+
+    /// Runs the job, resolving relative paths against `base`.
+    pub fn run<P: AsRef<Path>>(&self, base: P) -> Result<RunSummary, Error> {
+        let base = base.as_ref();
+        let input = self.layout.load(base)?;
+        let output = match &self.sink.layout {
+            Some(layout) => layout.load(base)?,
+            None => input.clone(),
+        };
+        let input = input.parser()?;
+        let output = output.parser()?;
+
+        let mut reader = Reader::from_file(File::open(base.join(&self.source.path))?, &input)
+            .terminator(self.source.terminator.clone());
+        if self.source.skip_blank_lines {
+            reader = reader.skip_blank_lines();
+        }
+        for prefix in &self.source.comments {
+            reader = reader.skip_comments(prefix.as_str());
+        }
+        let writer = Writer::to_file(File::create(base.join(&self.sink.path))?, &output)
+            .terminator(self.sink.terminator.clone());
+
+        let mut pipeline = Pipeline::new(reader, writer);
+        if self.source.skip_errors {
+            pipeline = pipeline.skip_errors();
+        }
+        if let Some(max) = self.source.max_errors {
+            pipeline = pipeline.max_errors(max);
+        }
+        for validation in &self.validations {
+            pipeline = pipeline.validate(move |r| validation.check(r));
+        }
+        for transform in &self.transforms {
+            pipeline = pipeline.transform(move |r| transform.apply(r));
+        }
+        pipeline.run()
+    }
+}
+
+/// Loads the job definition at `path` and runs it, relative paths in the job are resolved
+/// against the directory containing the job file.
+#[allow(dead_code)]
+pub fn run_job<P: AsRef<Path>>(path: P) -> Result<RunSummary, Error> {
+    let path = path.as_ref();
+    let job = Job::from_file(path)?;
+    job.run(path.parent().unwrap_or_else(|| Path::new(".")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let mut dir = env::temp_dir();
+        dir.push(format!("eta-parse-job-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn run_job_from_file() {
+        let dir = temp_dir("file");
+        fs::write(
+            dir.join("layout.toml"),
+            r#"
+            [[fields]]
+            name = "type"
+            width = 1
+
+            [[fields]]
+            name = "name"
+            width = 6
+
+            [[fields]]
+            name = "amount"
+            width = 5
+            align = "right"
+            padding = "0"
+            "#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("input.txt"),
+            "# banner\nDalice 00012\nHheader00000\nDbob   000AB\nDcarol 00300\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("job.toml"),
+            r##"
+            [source]
+            path = "input.txt"
+            comments = ["#"]
+
+            [layout]
+            path = "layout.toml"
+
+            [[validations]]
+            rule = "numeric"
+            field = "amount"
+
+            [[transforms]]
+            type = "filter"
+            field = "type"
+            values = ["D"]
+
+            [[transforms]]
+            type = "uppercase"
+            field = "name"
+
+            [sink]
+            path = "output.txt"
+            terminator = "crlf"
+
+            [sink.layout]
+            fields = [
+                { name = "name", width = 6 },
+                { name = "amount", width = 8, align = "right", padding = "0" },
+            ]
+            "##,
+        )
+        .unwrap();
+
+        let summary = run_job(dir.join("job.toml")).unwrap();
+
+        assert_eq!(summary.records_in, 4);
+        assert_eq!(summary.records_out, 2);
+        assert_eq!(summary.rejected, 1);
+        assert_eq!(
+            fs::read_to_string(dir.join("output.txt")).unwrap(),
+            "ALICE 00000012\r\nCAROL 00000300\r\n"
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn job_invalid() {
+        assert!(matches!(
+            Job::from_toml("[source]\npath = 1"),
+            Err(Error::Config(_))
+        ));
+    }
+
+    #[test]
+    fn validations() {
+        let record: Record = [(String::from("a"), String::from("12"))]
+            .iter()
+            .cloned()
+            .collect();
+
+        assert!(ValidationDef::Required {
+            field: String::from("a")
+        }
+        .check(&record)
+        .is_ok());
+        assert!(ValidationDef::Required {
+            field: String::from("b")
+        }
+        .check(&record)
+        .is_err());
+        assert!(ValidationDef::OneOf {
+            field: String::from("a"),
+            values: vec![String::from("13")]
+        }
+        .check(&record)
+        .is_err());
+    }
+
+    #[cfg(feature = "expr")]
+    #[test]
+    fn expressions() {
+        let job = Job::from_toml(
+            r#"
+            source = { path = "input.txt" }
+            layout = { fields = [{ name = "amount", width = 4 }] }
+            sink = { path = "output.txt" }
+
+            [[validations]]
+            rule = "expression"
+            expression = "int(amount) < 100"
+            message = "amount is too large"
+
+            [[transforms]]
+            type = "where"
+            expression = "int(amount) > 0"
+
+            [[transforms]]
+            type = "compute"
+            field = "cents"
+            expression = "int(amount) * 100"
+            "#,
+        )
+        .unwrap();
+        let record: Record = [(String::from("amount"), String::from("12"))]
+            .iter()
+            .cloned()
+            .collect();
+
+        assert!(job.validations[0].check(&record).is_ok());
+        let record = job.transforms[0].apply(record).unwrap();
+        let record = job.transforms[1].apply(record).unwrap();
+        assert_eq!(record["cents"], "1200");
+        assert!(Job::from_toml(
+            "source = { path = \"a\" }\nlayout = { fields = [] }\nsink = { path = \"b\" }\n\
+             [[transforms]]\ntype = \"where\"\nexpression = \"int(\""
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn transforms() {
+        let record: Record = [(String::from("a"), String::from("x"))]
+            .iter()
+            .cloned()
+            .collect();
+
+        let record = TransformDef::Rename {
+            from: String::from("a"),
+            to: String::from("b"),
+        }
+        .apply(record)
+        .unwrap();
+        assert_eq!(record.get("b"), Some(&String::from("x")));
+
+        let record = TransformDef::Set {
+            field: String::from("c"),
+            value: String::from("y"),
+        }
+        .apply(record)
+        .unwrap();
+        let record = TransformDef::Remove {
+            field: String::from("b"),
+        }
+        .apply(record)
+        .unwrap();
+        assert_eq!(record.len(), 1);
+        assert_eq!(record.get("c"), Some(&String::from("y")));
+    }
+}
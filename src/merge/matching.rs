@@ -0,0 +1,214 @@
+use super::key;
+use crate::{
+    error::Error,
+    flat::fixed::{Record, ResultRecord},
+};
+use std::{cmp::Ordering, iter::Peekable};
+
+/// The outcome of matching a master and a detail file on a key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Match {
+    /// A detail record and the master record with the same key.
+    Matched(Record, Record),
+    /// A master record without detail records.
+    UnmatchedMaster(Record),
+    /// A detail record without a master record.
+    UnmatchedDetail(Record),
+}
+
+/// Matches master and detail records sorted by a key field, see [`match_files`].
+pub struct MatchFiles<'k, M, D>
+where
+    M: Iterator<Item = ResultRecord>,
+    D: Iterator<Item = ResultRecord>,
+{
+    masters: Sequenced<'k, M>,
+    details: Sequenced<'k, D>,
+    master: Option<Record>,
+    matched: bool,
+}
+
+/// Matches master and detail records, both sorted by the key field, with full outer join
+/// semantics: every detail is matched to the master with the same key, masters without details
+/// and details without a master are yielded on their own. Several details may match a master,
+/// a key repeated in the master file only matches the first of those masters.
+///
+/// Keys are compared as strings, a key lower than the previous one of the same file is an
+/// [`Error::Sequence`] as the files would no longer match correctly.
+pub fn match_files<'k, M, D>(masters: M, details: D, field: &'k str) -> MatchFiles<'k, M, D>
+where
+    M: Iterator<Item = ResultRecord>,
+    D: Iterator<Item = ResultRecord>,
+{
+    MatchFiles {
+        masters: Sequenced::new(masters, field),
+        details: Sequenced::new(details, field),
+        master: None,
+        matched: false,
+    }
+}
+
+impl<'k, M, D> Iterator for MatchFiles<'k, M, D>
+where
+    M: Iterator<Item = ResultRecord>,
+    D: Iterator<Item = ResultRecord>,
+{
+    type Item = Result<Match, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.master.is_none() {
+                match self.masters.next() {
+                    Some(Ok(master)) => {
+                        self.master = Some(master);
+                        self.matched = false;
+                    }
+                    Some(Err(e)) => return Some(Err(e)),
+                    None => (),
+                }
+            }
+            let field = self.details.field;
+            let order = match (&self.master, self.details.peek_key()) {
+                (_, Some(Err(e))) => return Some(Err(e)),
+                (Some(master), Some(Ok(detail))) => key(master, field).cmp(detail),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(Ok(_))) => Ordering::Greater,
+                (None, None) => return None,
+            };
+            match order {
+                Ordering::Less => {
+                    let master = self.master.take()?;
+                    if !self.matched {
+                        return Some(Ok(Match::UnmatchedMaster(master)));
+                    }
+                }
+                Ordering::Equal => {
+                    let master = self.master.clone()?;
+                    self.matched = true;
+                    return Some(self.details.next()?.map(|d| Match::Matched(master, d)));
+                }
+                Ordering::Greater => {
+                    return Some(self.details.next()?.map(Match::UnmatchedDetail));
+                }
+            }
+        }
+    }
+}
+
+/// Yields the records of a file, checking they are sorted by the key field.
+struct Sequenced<'k, I: Iterator<Item = ResultRecord>> {
+    inner: Peekable<I>,
+    field: &'k str,
+    previous: Option<String>,
+}
+
+impl<'k, I> Sequenced<'k, I>
+where
+    I: Iterator<Item = ResultRecord>,
+{
+    fn new(inner: I, field: &'k str) -> Self {
+        Sequenced {
+            inner: inner.peekable(),
+            field,
+            previous: None,
+        }
+    }
+
+    /// The key of the next record, or the error reading it which is then consumed.
+    fn peek_key(&mut self) -> Option<Result<&str, Error>> {
+        if let Some(Err(_)) = self.inner.peek() {
+            return self.next().map(|r| r.map(|_| ""));
+        }
+        let field = self.field;
+        self.inner
+            .peek()
+            .map(|r| Ok(key(r.as_ref().expect("Errors are consumed"), field)))
+    }
+}
+
+impl<'k, I> Iterator for Sequenced<'k, I>
+where
+    I: Iterator<Item = ResultRecord>,
+{
+    type Item = ResultRecord;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let record = match self.inner.next()? {
+            Ok(record) => record,
+            Err(e) => return Some(Err(e)),
+        };
+        let current = key(&record, self.field);
+        match &mut self.previous {
+            Some(previous) if current < previous.as_str() => {
+                return Some(Err(Error::Sequence {
+                    key: current.to_string(),
+                    previous: previous.clone(),
+                }))
+            }
+            Some(previous) => {
+                previous.clear();
+                previous.push_str(current);
+            }
+            None => self.previous = Some(current.to_string()),
+        }
+        Some(Ok(record))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        builder::{Buildable, Builder},
+        flat::fixed::{read::Reader, Parser},
+    };
+
+    fn parser() -> Parser<'static> {
+        Parser::builder()
+            .field("key")
+            .width(2)
+            .append()
+            .field("value")
+            .width(2)
+            .append()
+            .build()
+    }
+
+    fn summary(result: Result<Match, Error>) -> String {
+        match result {
+            Ok(Match::Matched(m, d)) => format!("{}={}", m["value"], d["value"]),
+            Ok(Match::UnmatchedMaster(m)) => format!("{}-", m["value"]),
+            Ok(Match::UnmatchedDetail(d)) => format!("-{}", d["value"]),
+            Err(e) => e.to_string(),
+        }
+    }
+
+    #[test]
+    fn match_master_detail() {
+        let parser = parser();
+        let mut masters = Reader::from_string("01M1\n02M2\n04M4\n05M5\n", &parser);
+        let mut details = Reader::from_string("01D1\n01D2\n03D3\n04D4\n06D6\n", &parser);
+
+        let results = match_files(masters.string_reader(), details.string_reader(), "key")
+            .map(summary)
+            .collect::<Vec<String>>();
+
+        assert_eq!(
+            results,
+            vec!["M1=D1", "M1=D2", "M2-", "-D3", "M4=D4", "M5-", "-D6"]
+        );
+    }
+
+    #[test]
+    fn match_out_of_sequence() {
+        let parser = parser();
+        let mut masters = Reader::from_string("02M2\n01M1\n", &parser);
+        let mut details = Reader::from_string("", &parser);
+
+        let results = match_files(masters.string_reader(), details.string_reader(), "key")
+            .map(summary)
+            .collect::<Vec<String>>();
+
+        assert_eq!(results, vec!["M2-", "Key 01 is out of sequence after 02"]);
+    }
+}
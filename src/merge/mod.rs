@@ -0,0 +1,153 @@
+use crate::flat::fixed::{Record, ResultRecord};
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::BinaryHeap,
+};
+
+pub mod matching;
+pub mod sort;
+
+pub use self::{
+    matching::{match_files, Match, MatchFiles},
+    sort::{Compare, DateOrder, Sorter},
+};
+
+/// The value of the key field, missing keys sort first.
+fn key<'r>(record: &'r Record, field: &str) -> &'r str {
+    record.get(field).map_or("", |k| k.as_str())
+}
+
+/// The next record of a source, ordered by key then by source so equal keys keep the order of
+/// the sources.
+struct Head<K = String, R = Record> {
+    key: K,
+    source: usize,
+    record: R,
+}
+
+impl<K: Ord, R> PartialEq for Head<K, R> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<K: Ord, R> Eq for Head<K, R> {}
+
+impl<K: Ord, R> PartialOrd for Head<K, R> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K: Ord, R> Ord for Head<K, R> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (&self.key, self.source).cmp(&(&other.key, other.source))
+    }
+}
+
+/// Merges record streams that are each sorted by a key field into a single stream sorted by
+/// that field, see [`merge`].
+pub struct Merge<'k, I> {
+    sources: Vec<I>,
+    field: &'k str,
+    heads: BinaryHeap<Reverse<Head>>,
+    refill: Vec<usize>,
+}
+
+/// Merges readers, or any record iterators, each sorted by the key field. Keys are compared as
+/// strings, records with equal keys are yielded in the order of their sources. Errors are
+/// yielded as they are read and the merge carries on with the next record of that source.
+pub fn merge<I>(sources: Vec<I>, field: &str) -> Merge<'_, I>
+where
+    I: Iterator<Item = ResultRecord>,
+{
+    let refill = (0..sources.len()).rev().collect();
+    Merge {
+        sources,
+        field,
+        heads: BinaryHeap::new(),
+        refill,
+    }
+}
+
+impl<'k, I> Iterator for Merge<'k, I>
+where
+    I: Iterator<Item = ResultRecord>,
+{
+    type Item = ResultRecord;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(source) = self.refill.pop() {
+            match self.sources[source].next() {
+                Some(Ok(record)) => self.heads.push(Reverse(Head {
+                    key: key(&record, self.field).to_string(),
+                    source,
+                    record,
+                })),
+                Some(Err(e)) => {
+                    self.refill.push(source);
+                    return Some(Err(e));
+                }
+                None => (),
+            }
+        }
+        let Reverse(head) = self.heads.pop()?;
+        self.refill.push(head.source);
+        Some(Ok(head.record))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        builder::{Buildable, Builder},
+        flat::fixed::{read::Reader, Parser},
+    };
+
+    fn parser() -> Parser<'static> {
+        Parser::builder()
+            .field("key")
+            .width(2)
+            .append()
+            .field("value")
+            .width(2)
+            .append()
+            .build()
+    }
+
+    #[test]
+    fn merge_sorted_readers() {
+        let parser = parser();
+        let mut first = Reader::from_string("01A1\n03A3\n05A5\n", &parser);
+        let mut second = Reader::from_string("02B2\n03B3\n", &parser);
+        let mut third = Reader::from_string("", &parser);
+
+        let values = merge(
+            vec![
+                first.string_reader(),
+                second.string_reader(),
+                third.string_reader(),
+            ],
+            "key",
+        )
+        .map(|r| r.unwrap()["value"].clone())
+        .collect::<Vec<String>>();
+
+        assert_eq!(values, vec!["A1", "B2", "A3", "B3", "A5"]);
+    }
+
+    #[test]
+    fn merge_yields_errors() {
+        let parser = parser();
+        let mut first = Reader::from_string("01A1\n0\n03A3\n", &parser);
+        let mut second = Reader::from_string("02B2\n", &parser);
+
+        let results = merge(vec![first.string_reader(), second.string_reader()], "key")
+            .collect::<Vec<ResultRecord>>();
+
+        assert_eq!(results.len(), 4);
+        assert!(results[1].is_err());
+        assert_eq!(results[3].as_ref().unwrap()["value"], "A3");
+    }
+}
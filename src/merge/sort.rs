@@ -0,0 +1,471 @@
+use super::Head;
+use crate::{
+    error::Error,
+    flat::{
+        fixed::{numeric, read::Reader, Parser, Record},
+        terminator::Terminator,
+    },
+};
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::BinaryHeap,
+    fs::{self, File},
+    io::{BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering as AtomicOrdering},
+};
+
+/// Runs are written with length prefixes, so any record survives the round trip.
+const RUN_TERMINATOR: Terminator = Terminator::LengthPrefixed(10);
+
+/// A record read back from a run, with the values of its sort keys.
+type RunRecord = (Vec<String>, Vec<u8>);
+
+/// Distinguishes the runs of sorts running at the same time in a process.
+static SORTS: AtomicUsize = AtomicUsize::new(0);
+
+/// How the values of a sort key are compared.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compare {
+    /// As strings.
+    Text,
+    /// As numbers written with any sign convention, thousands separators or currency symbols,
+    /// see [`Sign`](crate::flat::fixed::numeric::Sign). Values that are not numbers sort first.
+    Numeric,
+    /// As dates of 8 digits in the given order, separators ignored. Values that are not dates
+    /// sort first.
+    Date(DateOrder),
+}
+
+/// The order of the year, month and day in a date.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateOrder {
+    /// `YYYYMMDD`.
+    Ymd,
+    /// `DDMMYYYY`.
+    Dmy,
+    /// `MMDDYYYY`.
+    Mdy,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct SortKey<'a> {
+    field: &'a str,
+    compare: Compare,
+    descending: bool,
+}
+
+/// The key values of a record, ordered by the sort keys.
+struct KeyValues<'s, 'a> {
+    keys: &'s [SortKey<'a>],
+    values: Vec<String>,
+}
+
+impl PartialEq for KeyValues<'_, '_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for KeyValues<'_, '_> {}
+
+impl PartialOrd for KeyValues<'_, '_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for KeyValues<'_, '_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        compare(self.keys, &self.values, &other.values)
+    }
+}
+
+/// Sorts a file of fixed width records by one or more fields with an external merge sort.
+///
+/// Records are sorted in memory by chunks, each written to a temporary file, and the sorted
+/// runs are merged into the output, so inputs larger than memory can be sorted. The sort is
+/// stable and records are written as they were read, only their terminator may change.
+pub struct Sorter<'a> {
+    parser: &'a Parser<'a>,
+    keys: Vec<SortKey<'a>>,
+    chunk: usize,
+    fan_in: usize,
+    temp_dir: PathBuf,
+    terminator: Terminator,
+}
+
+#[allow(dead_code)]
+impl<'a> Sorter<'a> {
+    pub fn new(parser: &'a Parser) -> Self {
+        Sorter {
+            parser,
+            keys: Vec::new(),
+            chunk: 100_000,
+            fan_in: 64,
+            temp_dir: std::env::temp_dir(),
+            terminator: Terminator::default(),
+        }
+    }
+
+    /// Adds a sort key, compared after the keys added before it.
+    pub fn by(mut self, field: &'a str, compare: Compare) -> Self {
+        self.keys.push(SortKey {
+            field,
+            compare,
+            descending: false,
+        });
+        self
+    }
+
+    /// Sorts the last key added in descending order.
+    pub fn descending(mut self) -> Self {
+        if let Some(key) = self.keys.last_mut() {
+            key.descending = true;
+        }
+        self
+    }
+
+    /// Sets the number of records sorted in memory before they are written to a temporary
+    /// file, 100,000 by default.
+    pub fn chunk_records(mut self, records: usize) -> Self {
+        self.chunk = records.max(1);
+        self
+    }
+
+    /// Sets the number of runs merged at once, 64 by default. More runs are merged in several
+    /// passes, keeping the number of open files under the limit of the system.
+    pub fn fan_in(mut self, runs: usize) -> Self {
+        self.fan_in = runs.max(2);
+        self
+    }
+
+    /// Sets the directory of the temporary files, the system one by default.
+    pub fn temp_dir<P: AsRef<Path>>(mut self, directory: P) -> Self {
+        self.temp_dir = directory.as_ref().to_path_buf();
+        self
+    }
+
+    /// Sets how the records of the output are separated, defaults to newlines.
+    pub fn terminator(mut self, terminator: Terminator) -> Self {
+        self.terminator = terminator;
+        self
+    }
+
+    /// Sorts a file into another, returning the number of records. The input is read with a
+    /// default [`Reader`], use [`sort`](Self::sort) for other reader options.
+    pub fn sort_file<P, Q>(&self, input: P, output: Q) -> Result<u64, Error>
+    where
+        P: AsRef<Path>,
+        Q: AsRef<Path>,
+    {
+        let mut reader = Reader::from_file(File::open(input)?, self.parser);
+        self.sort(&mut reader, File::create(output)?)
+    }
+
+    /// Sorts the records of a reader into the output, returning the number of records. Fails
+    /// on the first record that cannot be read.
+    pub fn sort<R: Read, W: Write>(
+        &self,
+        reader: &mut Reader<'_, R>,
+        output: W,
+    ) -> Result<u64, Error> {
+        let mut runs = Runs::new(&self.temp_dir);
+        let mut chunk = Vec::new();
+        let mut count = 0;
+        for (raw, record) in reader.with_raw() {
+            chunk.push((self.key(&record?), raw));
+            count += 1;
+            if chunk.len() >= self.chunk {
+                self.spill(&mut chunk, &mut runs)?;
+            }
+        }
+        let mut output = BufWriter::new(output);
+        if runs.paths.is_empty() {
+            self.sort_chunk(&mut chunk);
+            for (_, raw) in &chunk {
+                self.terminator.write_record(&mut output, raw.as_bytes())?;
+            }
+        } else {
+            if !chunk.is_empty() {
+                self.spill(&mut chunk, &mut runs)?;
+            }
+            self.merge(&mut runs, &mut output)?;
+        }
+        output.flush()?;
+        Ok(count)
+    }
+
+    /// The values of the sort keys, normalized for comparison.
+    fn key(&self, record: &Record) -> Vec<String> {
+        self.keys
+            .iter()
+            .map(|key| {
+                let value = record.get(key.field).map_or("", |v| v.as_str());
+                match key.compare {
+                    Compare::Text => value.to_string(),
+                    Compare::Numeric => numeric::number(value).unwrap_or_default(),
+                    Compare::Date(order) => date(value, order).unwrap_or_default(),
+                }
+            })
+            .collect()
+    }
+
+    fn sort_chunk(&self, chunk: &mut [(Vec<String>, String)]) {
+        chunk.sort_by(|(a, _), (b, _)| compare(&self.keys, a, b));
+    }
+
+    /// Sorts the chunk and writes it to a new run.
+    fn spill(&self, chunk: &mut Vec<(Vec<String>, String)>, runs: &mut Runs) -> Result<(), Error> {
+        self.sort_chunk(chunk);
+        let mut run = BufWriter::new(File::create(runs.next())?);
+        for (key, raw) in chunk.drain(..) {
+            write_run(&mut run, &key, raw.as_bytes())?;
+        }
+        run.flush()?;
+        Ok(())
+    }
+
+    /// Merges the runs into the output. While there are more runs than the fan in, groups of
+    /// consecutive runs are merged into new runs, which keeps the sort stable.
+    fn merge<W: Write>(&self, runs: &mut Runs, output: &mut W) -> Result<(), Error> {
+        let mut pending = runs.paths.clone();
+        while pending.len() > self.fan_in {
+            let mut merged = Vec::new();
+            for group in pending.chunks(self.fan_in) {
+                if let [path] = group {
+                    merged.push(path.clone());
+                    continue;
+                }
+                let path = runs.next().to_path_buf();
+                let mut run = BufWriter::new(File::create(&path)?);
+                self.merge_runs(group, |key, raw| write_run(&mut run, key, raw))?;
+                run.flush()?;
+                for path in group {
+                    let _ = fs::remove_file(path);
+                }
+                merged.push(path);
+            }
+            pending = merged;
+        }
+        self.merge_runs(&pending, |_, raw| {
+            self.terminator.write_record(output, raw)?;
+            Ok(())
+        })
+    }
+
+    /// Merges runs, passing each record with its key values to `emit`. Equal keys are taken
+    /// from the first run so the merge is stable.
+    fn merge_runs<F>(&self, paths: &[PathBuf], mut emit: F) -> Result<(), Error>
+    where
+        F: FnMut(&[String], &[u8]) -> Result<(), Error>,
+    {
+        let mut sources = paths
+            .iter()
+            .map(|path| File::open(path).map(BufReader::new))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut heads = BinaryHeap::with_capacity(sources.len());
+        for source in 0..sources.len() {
+            self.refill(&mut sources, source, &mut heads)?;
+        }
+        while let Some(Reverse(head)) = heads.pop() {
+            emit(&head.key.values, &head.record)?;
+            self.refill(&mut sources, head.source, &mut heads)?;
+        }
+        Ok(())
+    }
+
+    /// Pushes the next record of a run, if any, to the heads of the merge.
+    fn refill<'s>(
+        &'s self,
+        sources: &mut [BufReader<File>],
+        source: usize,
+        heads: &mut BinaryHeap<Reverse<Head<KeyValues<'s, 'a>, Vec<u8>>>>,
+    ) -> Result<(), Error> {
+        if let Some((values, record)) = self.read_run(&mut sources[source])? {
+            heads.push(Reverse(Head {
+                key: KeyValues {
+                    keys: &self.keys,
+                    values,
+                },
+                source,
+                record,
+            }));
+        }
+        Ok(())
+    }
+
+    /// The next record of a run with its key values.
+    fn read_run(&self, run: &mut BufReader<File>) -> Result<Option<RunRecord>, Error> {
+        let mut buf = Vec::new();
+        let mut key = Vec::with_capacity(self.keys.len());
+        for _ in &self.keys {
+            if !RUN_TERMINATOR.read_record(run, 0, &mut buf)? {
+                return Ok(None);
+            }
+            key.push(String::from_utf8_lossy(&buf).into_owned());
+        }
+        match RUN_TERMINATOR.read_record(run, 0, &mut buf)? {
+            true => Ok(Some((key, buf))),
+            false => Ok(None),
+        }
+    }
+}
+
+/// Compares key values by the sort keys.
+fn compare(keys: &[SortKey], a: &[String], b: &[String]) -> Ordering {
+    for ((key, a), b) in keys.iter().zip(a).zip(b) {
+        let ordering = match key.compare {
+            Compare::Numeric => numeric::compare(a, b),
+            Compare::Text | Compare::Date(_) => a.cmp(b),
+        };
+        let ordering = match key.descending {
+            true => ordering.reverse(),
+            false => ordering,
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    Ordering::Equal
+}
+
+/// Writes a record to a run, preceded by its key values.
+fn write_run<W: Write>(run: &mut W, key: &[String], raw: &[u8]) -> Result<(), Error> {
+    for value in key {
+        RUN_TERMINATOR.write_record(run, value.as_bytes())?;
+    }
+    RUN_TERMINATOR.write_record(run, raw)?;
+    Ok(())
+}
+
+/// The temporary files of the sorted runs, removed when the sort ends.
+struct Runs {
+    directory: PathBuf,
+    sort: usize,
+    paths: Vec<PathBuf>,
+}
+
+impl Runs {
+    fn new(directory: &Path) -> Self {
+        Runs {
+            directory: directory.to_path_buf(),
+            sort: SORTS.fetch_add(1, AtomicOrdering::Relaxed),
+            paths: Vec::new(),
+        }
+    }
+
+    fn next(&mut self) -> &Path {
+        let name = format!(
+            "eta-parse-sort-{}-{}-{}.run",
+            std::process::id(),
+            self.sort,
+            self.paths.len()
+        );
+        self.paths.push(self.directory.join(name));
+        &self.paths[self.paths.len() - 1]
+    }
+}
+
+impl Drop for Runs {
+    fn drop(&mut self) {
+        for path in &self.paths {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// The date as `YYYYMMDD`, `None` if it does not have 8 digits.
+fn date(value: &str, order: DateOrder) -> Option<String> {
+    let digits = value
+        .chars()
+        .filter(char::is_ascii_digit)
+        .collect::<String>();
+    if digits.len() != 8 {
+        return None;
+    }
+    Some(match order {
+        DateOrder::Ymd => digits,
+        DateOrder::Dmy => format!("{}{}{}", &digits[4..], &digits[2..4], &digits[..2]),
+        DateOrder::Mdy => format!("{}{}{}", &digits[4..], &digits[..2], &digits[2..4]),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::{Buildable, Builder};
+
+    fn parser() -> Parser<'static> {
+        Parser::builder()
+            .field("amount")
+            .width(6)
+            .append()
+            .field("date")
+            .width(8)
+            .append()
+            .field("id")
+            .width(2)
+            .append()
+            .build()
+    }
+
+    fn ids(output: &[u8]) -> Vec<&str> {
+        std::str::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| &line[14..])
+            .collect()
+    }
+
+    #[test]
+    fn sort_in_memory_and_with_runs() {
+        let parser = parser();
+        let input = "  -10001012024a1\n   2.513012023b2\n 1,00001012023a3\n  2.5001012024a4\n   N/A01012020a5\n";
+
+        for chunk in [100, 2] {
+            let mut reader = Reader::from_string(input, &parser);
+            let mut output = Vec::new();
+            let sorter = Sorter::new(&parser)
+                .by("amount", Compare::Numeric)
+                .by("date", Compare::Date(DateOrder::Dmy))
+                .descending()
+                .chunk_records(chunk);
+
+            assert_eq!(sorter.sort(&mut reader, &mut output).unwrap(), 5);
+            assert_eq!(ids(&output), vec!["a5", "a1", "a4", "b2", "a3"]);
+        }
+    }
+
+    #[test]
+    fn merge_in_passes() {
+        let parser = parser();
+        let input = (0..20)
+            .map(|i| format!("{:>6}01012024{:02}\n", (i * 7) % 5, i))
+            .collect::<String>();
+        let mut reader = Reader::from_string(&input, &parser);
+        let mut output = Vec::new();
+        let sorter = Sorter::new(&parser)
+            .by("amount", Compare::Numeric)
+            .chunk_records(2)
+            .fan_in(3);
+
+        assert_eq!(sorter.sort(&mut reader, &mut output).unwrap(), 20);
+        let mut expected = (0..20).collect::<Vec<_>>();
+        expected.sort_by_key(|i| (i * 7) % 5);
+        let expected = expected
+            .iter()
+            .map(|i| format!("{:02}", i))
+            .collect::<Vec<_>>();
+        assert_eq!(ids(&output), expected);
+    }
+
+    #[test]
+    fn normalize_dates() {
+        assert_eq!(
+            date("31/12/2023", DateOrder::Dmy).as_deref(),
+            Some("20231231")
+        );
+        assert_eq!(date("2023-12", DateOrder::Ymd), None);
+    }
+}
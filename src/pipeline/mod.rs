@@ -0,0 +1,353 @@
+use crate::{
+    error::Error,
+    flat::fixed::{read::Reader, write::Writer, Record},
+    utilities::{cancel::CancellationToken, throttle::Rate},
+};
+use std::{
+    io::{Read, Write},
+    time::Instant,
+};
+
+pub mod summary;
+
+use summary::RunSummary;
+
+/// A transformation applied to every record, returning `None` drops the record.
+pub type Transform<'a> = Box<dyn FnMut(Record) -> Option<Record> + 'a>;
+
+/// A check applied to every parsed record, an error rejects the record with the message.
+pub type Validation<'a> = Box<dyn FnMut(&Record) -> Result<(), String> + 'a>;
+
+/// Reads records, applies the transforms in order and writes the results.
+pub struct Pipeline<'a, R, W: Write> {
+    reader: Reader<'a, R>,
+    writer: Writer<'a, W>,
+    transforms: Vec<Transform<'a>>,
+    validations: Vec<Validation<'a>>,
+    cancel: Option<CancellationToken>,
+    skip_errors: bool,
+    max_errors: Option<u64>,
+    type_field: Option<&'a str>,
+}
+
+#[allow(dead_code)]
+impl<'a, R, W> Pipeline<'a, R, W>
+where
+    R: Read,
+    W: Write,
+{
+    pub fn new(reader: Reader<'a, R>, writer: Writer<'a, W>) -> Self {
+        Pipeline {
+            reader,
+            writer,
+            transforms: Vec::new(),
+            validations: Vec::new(),
+            cancel: None,
+            skip_errors: false,
+            max_errors: None,
+            type_field: None,
+        }
+    }
+
+    /// Appends a transform, see [`Transform`].
+    pub fn transform<F>(mut self, f: F) -> Self
+    where
+        F: FnMut(Record) -> Option<Record> + 'a,
+    {
+        self.transforms.push(Box::new(f));
+        self
+    }
+
+    /// Appends a validation, applied before the transforms. Records failing a validation are
+    /// counted as rejected and skipped.
+    pub fn validate<F>(mut self, f: F) -> Self
+    where
+        F: FnMut(&Record) -> Result<(), String> + 'a,
+    {
+        self.validations.push(Box::new(f));
+        self
+    }
+
+    /// Limits the rate at which the pipeline writes records.
+    pub fn throttle(self, rate: Rate) -> Self {
+        Pipeline {
+            writer: self.writer.throttle(rate),
+            ..self
+        }
+    }
+
+    /// Skips records that fail to parse, counting them as rejected, instead of stopping.
+    pub fn skip_errors(mut self) -> Self {
+        self.skip_errors = true;
+        self
+    }
+
+    /// Aborts the run with `Error::TooManyErrors` once more than `n` records have been rejected,
+    /// whether they failed to parse or failed a validation.
+    pub fn max_errors(mut self, n: u64) -> Self {
+        self.max_errors = Some(n);
+        self
+    }
+
+    /// Counts the records read per value of the given field in the run summary.
+    pub fn count_by(mut self, field: &'a str) -> Self {
+        self.type_field = Some(field);
+        self
+    }
+
+    /// Stops the pipeline between records once the token is cancelled.
+    pub fn cancel_on(mut self, token: CancellationToken) -> Self {
+        self.cancel = Some(token);
+        self
+    }
+
+    /// Runs the pipeline to completion, returning a summary of the run.
+    ///
+    /// The writer is flushed before returning, including when the pipeline was cancelled, in
+    /// which case `Error::Cancelled` is returned.
+    pub fn run(&mut self) -> Result<RunSummary, Error> {
+        let start = Instant::now();
+        let mut summary = RunSummary::default();
+        let result = self.process(&mut summary);
+        self.writer.flush()?;
+        result?;
+        summary.finish(start.elapsed());
+        Ok(summary)
+    }
+
+    pub fn into_writer(self) -> Writer<'a, W> {
+        self.writer
+    }
+
+    fn cancelled(&self) -> bool {
+        self.cancel.as_ref().is_some_and(|c| c.is_cancelled()) || self.reader.is_cancelled()
+    }
+
+    fn process(&mut self, summary: &mut RunSummary) -> Result<(), Error> {
+        let mut records = self.reader.enumerated_records();
+        loop {
+            if self.cancel.as_ref().is_some_and(|c| c.is_cancelled()) {
+                return Err(Error::Cancelled);
+            }
+            let (line, mut record) = match records.next() {
+                Some((line, Ok(record))) => (line, Some(record)),
+                Some((line, Err(e)))
+                    if self.skip_errors && !matches!(e, Error::TooManyErrors { .. }) =>
+                {
+                    summary.records_in += 1;
+                    summary.rejected += 1;
+                    summary.warnings.push(e.to_string());
+                    check_errors(self.max_errors, summary, line)?;
+                    continue;
+                }
+                Some((_, Err(e))) => return Err(e),
+                None => break,
+            };
+            summary.records_in += 1;
+            if let (Some(field), Some(r)) = (self.type_field, record.as_ref()) {
+                let key = r.get(field).cloned().unwrap_or_default();
+                *summary.per_type_counts.entry(key).or_insert(0) += 1;
+            }
+            if let Some(r) = record.as_ref() {
+                if let Some(message) = self.validations.iter_mut().find_map(|v| v(r).err()) {
+                    summary.rejected += 1;
+                    summary.warnings.push(format!("Line {}: {}", line, message));
+                    check_errors(self.max_errors, summary, line)?;
+                    continue;
+                }
+            }
+            for transform in self.transforms.iter_mut() {
+                record = match record {
+                    Some(r) => transform(r),
+                    None => break,
+                };
+            }
+            if let Some(record) = record {
+                self.writer.write(&record)?;
+                summary.records_out += 1;
+            }
+        }
+        // The reader also ends early when cancelled, possibly since the check above.
+        match self.cancelled() {
+            true => Err(Error::Cancelled),
+            false => Ok(()),
+        }
+    }
+}
+
+/// Fails once more records have been rejected than allowed.
+fn check_errors(max_errors: Option<u64>, summary: &RunSummary, line: u64) -> Result<(), Error> {
+    match max_errors {
+        Some(max) if summary.rejected > max => Err(Error::TooManyErrors {
+            errors: summary.rejected,
+            line,
+        }),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        builder::{Buildable, Builder},
+        flat::fixed::Parser,
+    };
+
+    #[test]
+    fn run_pipeline() {
+        let parser = Parser::builder()
+            .field("first")
+            .width(4)
+            .append()
+            .field("second")
+            .width(4)
+            .append()
+            .build();
+        let reader = Reader::from_string("AAAA1111\nBBBB2222\nCCCC3333", &parser);
+        let writer = Writer::from_writer(Vec::new(), &parser);
+
+        let mut pipeline = Pipeline::new(reader, writer)
+            .transform(|r| match r.get("first").map(String::as_str) {
+                Some("BBBB") => None,
+                _ => Some(r),
+            })
+            .transform(|mut r| {
+                r.insert(String::from("second"), String::from("9"));
+                Some(r)
+            });
+
+        let summary = pipeline.run().unwrap();
+        assert_eq!(summary.records_in, 3);
+        assert_eq!(summary.records_out, 2);
+        let out = pipeline.into_writer().into_inner().unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "AAAA9   \nCCCC9   \n");
+    }
+
+    #[test]
+    fn run_pipeline_summary() {
+        let parser = Parser::builder()
+            .field("type")
+            .width(1)
+            .append()
+            .field("value")
+            .width(3)
+            .append()
+            .build();
+        let reader = Reader::from_string("A111\nB222\nA33\nA444", &parser);
+        let writer = Writer::from_writer(Vec::new(), &parser);
+
+        let mut pipeline = Pipeline::new(reader, writer).skip_errors().count_by("type");
+        let summary = pipeline.run().unwrap();
+
+        assert_eq!(summary.records_in, 4);
+        assert_eq!(summary.records_out, 3);
+        assert_eq!(summary.rejected, 1);
+        assert_eq!(summary.warnings.len(), 1);
+        assert!(summary.warnings[0].starts_with("Line 3: "));
+        assert_eq!(summary.per_type_counts.get("A"), Some(&2));
+        assert_eq!(summary.per_type_counts.get("B"), Some(&1));
+    }
+
+    #[test]
+    fn run_pipeline_validations() {
+        let parser = Parser::builder().field("first").width(4).append().build();
+        let reader = Reader::from_string("AAAA\nBBBB\n1234", &parser);
+        let writer = Writer::from_writer(Vec::new(), &parser);
+
+        let mut pipeline = Pipeline::new(reader, writer).validate(|r| {
+            match r.get("first").map(|v| v.starts_with('B')) {
+                Some(true) => Err(String::from("starts with B")),
+                _ => Ok(()),
+            }
+        });
+        let summary = pipeline.run().unwrap();
+
+        assert_eq!(summary.records_in, 3);
+        assert_eq!(summary.records_out, 2);
+        assert_eq!(summary.rejected, 1);
+        assert_eq!(
+            summary.warnings,
+            vec![String::from("Line 2: starts with B")]
+        );
+    }
+
+    #[test]
+    fn run_pipeline_stops_on_error() {
+        let parser = Parser::builder().field("first").width(4).append().build();
+        let reader = Reader::from_string("AAAA\nBB\nCCCC", &parser);
+        let writer = Writer::from_writer(Vec::new(), &parser);
+
+        let mut pipeline = Pipeline::new(reader, writer);
+
+        assert!(matches!(pipeline.run(), Err(Error::Parse { .. })));
+    }
+
+    #[test]
+    fn run_pipeline_max_errors() {
+        let parser = Parser::builder().field("first").width(4).append().build();
+        let reader = Reader::from_string("AAAA\nBB\nBBBB\nCCCC\nDD", &parser);
+        let writer = Writer::from_writer(Vec::new(), &parser);
+
+        let mut pipeline = Pipeline::new(reader, writer)
+            .skip_errors()
+            .max_errors(2)
+            .validate(|r| match r.get("first").map(|v| v.starts_with('B')) {
+                Some(true) => Err(String::from("starts with B")),
+                _ => Ok(()),
+            });
+
+        assert!(matches!(
+            pipeline.run(),
+            Err(Error::TooManyErrors { errors: 3, line: 5 })
+        ));
+        let out = pipeline.into_writer().into_inner().unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "AAAA\nCCCC\n");
+    }
+
+    #[test]
+    fn run_pipeline_throttled() {
+        let parser = Parser::builder().field("first").width(4).append().build();
+        let reader = Reader::from_string("AAAA\nBBBB\nCCCC\nDDDD\nEEEE", &parser);
+        let writer = Writer::from_writer(Vec::new(), &parser);
+        let start = std::time::Instant::now();
+
+        let mut pipeline = Pipeline::new(reader, writer).throttle(Rate::RecordsPerSecond(50));
+
+        assert_eq!(pipeline.run().unwrap().records_out, 5);
+        assert!(start.elapsed() >= std::time::Duration::from_millis(80));
+    }
+
+    #[test]
+    fn run_pipeline_cancelled() {
+        let parser = Parser::builder().field("first").width(4).append().build();
+        let reader = Reader::from_string("AAAA\nBBBB\nCCCC", &parser);
+        let writer = Writer::from_writer(Vec::new(), &parser);
+        let token = CancellationToken::new();
+        let cancel = token.clone();
+
+        let mut pipeline = Pipeline::new(reader, writer)
+            .cancel_on(token)
+            .transform(move |r| {
+                cancel.cancel();
+                Some(r)
+            });
+
+        assert!(matches!(pipeline.run(), Err(Error::Cancelled)));
+        let out = pipeline.into_writer().into_inner().unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "AAAA\n");
+    }
+
+    #[test]
+    fn run_pipeline_reader_cancelled() {
+        let parser = Parser::builder().field("first").width(4).append().build();
+        let token = CancellationToken::new();
+        let reader = Reader::from_string("AAAA\nBBBB", &parser).cancel_on(token.clone());
+        let writer = Writer::from_writer(Vec::new(), &parser);
+        token.cancel();
+
+        let mut pipeline = Pipeline::new(reader, writer);
+
+        assert!(matches!(pipeline.run(), Err(Error::Cancelled)));
+    }
+}
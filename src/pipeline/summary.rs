@@ -0,0 +1,77 @@
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use std::{collections::HashMap, time::Duration};
+
+/// The outcome of a pipeline run.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct RunSummary {
+    /// Records read from the input, including rejected records.
+    pub records_in: u64,
+    /// Records written to the output.
+    pub records_out: u64,
+    /// Records that could not be parsed and were skipped.
+    pub rejected: u64,
+    /// Messages describing recoverable problems, such as the reason a record was rejected.
+    pub warnings: Vec<String>,
+    /// Wall clock time of the run.
+    pub duration: Duration,
+    /// Records read per second.
+    pub throughput: f64,
+    /// Records read, keyed by the value of the record type field.
+    pub per_type_counts: HashMap<String, u64>,
+}
+
+impl RunSummary {
+    pub(crate) fn finish(&mut self, duration: Duration) {
+        self.duration = duration;
+        let secs = duration.as_secs_f64();
+        self.throughput = if secs > 0.0 {
+            self.records_in as f64 / secs
+        } else {
+            0.0
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finish_computes_throughput() {
+        let mut summary = RunSummary {
+            records_in: 50,
+            ..RunSummary::default()
+        };
+        summary.finish(Duration::from_millis(500));
+
+        assert_eq!(summary.duration, Duration::from_millis(500));
+        assert_eq!(summary.throughput, 100.0);
+    }
+
+    #[test]
+    fn finish_zero_duration() {
+        let mut summary = RunSummary::default();
+        summary.finish(Duration::from_secs(0));
+
+        assert_eq!(summary.throughput, 0.0);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serialize_summary() {
+        let summary = RunSummary {
+            records_in: 2,
+            records_out: 1,
+            rejected: 1,
+            ..RunSummary::default()
+        };
+        let json = serde_json::to_value(&summary).unwrap();
+
+        assert_eq!(json["records_in"], 2);
+        assert_eq!(json["records_out"], 1);
+        assert_eq!(json["rejected"], 1);
+        assert_eq!(json["duration"]["secs"], 0);
+    }
+}
@@ -0,0 +1,277 @@
+use crate::flat::fixed::{Parser, Record, ResultRecord};
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+/// Date patterns recognised by the profiler, in order of preference.
+const DATE_PATTERNS: &[&str] = &[
+    "YYYYMMDD",
+    "YYYY-MM-DD",
+    "YYYY/MM/DD",
+    "MM/DD/YYYY",
+    "DD/MM/YYYY",
+    "DD.MM.YYYY",
+];
+
+/// Statistics of the values of a single field.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct FieldProfile {
+    pub name: String,
+    /// Values seen, blank ones included.
+    pub count: u64,
+    /// Values that are empty after trimming, or null.
+    pub blank: u64,
+    /// Shortest non-blank value after trimming, in characters.
+    pub min_length: Option<usize>,
+    /// Longest non-blank value after trimming, in characters.
+    pub max_length: Option<usize>,
+    /// The first distinct non-blank values, up to the limit of the profiler.
+    pub distinct: Vec<String>,
+    /// Whether there are more distinct values than those sampled.
+    pub more_distinct: bool,
+    /// Smallest value when every non-blank value is a number.
+    pub numeric_min: Option<f64>,
+    /// Largest value when every non-blank value is a number.
+    pub numeric_max: Option<f64>,
+    /// The date pattern every non-blank value matches, e.g. `YYYY-MM-DD`.
+    pub date_pattern: Option<&'static str>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    numeric: bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    dates: Vec<&'static str>,
+}
+
+impl FieldProfile {
+    fn new(name: &str) -> Self {
+        FieldProfile {
+            name: name.to_string(),
+            count: 0,
+            blank: 0,
+            min_length: None,
+            max_length: None,
+            distinct: Vec::new(),
+            more_distinct: false,
+            numeric_min: None,
+            numeric_max: None,
+            date_pattern: None,
+            numeric: true,
+            dates: DATE_PATTERNS.to_vec(),
+        }
+    }
+
+    /// The share of blank values, between 0 and 1.
+    pub fn blank_rate(&self) -> f64 {
+        match self.count {
+            0 => 0.0,
+            count => self.blank as f64 / count as f64,
+        }
+    }
+
+    fn add(&mut self, value: Option<&str>, limit: usize) {
+        self.count += 1;
+        let value = value.map_or("", |v| v.trim());
+        if value.is_empty() {
+            self.blank += 1;
+            return;
+        }
+        let length = value.chars().count();
+        self.min_length = Some(self.min_length.map_or(length, |l| l.min(length)));
+        self.max_length = Some(self.max_length.map_or(length, |l| l.max(length)));
+        if !self.distinct.iter().any(|d| d == value) {
+            match self.distinct.len() < limit {
+                true => self.distinct.push(value.to_string()),
+                false => self.more_distinct = true,
+            }
+        }
+        self.numeric = self.numeric
+            && match number(value) {
+                Some(n) => {
+                    self.numeric_min = Some(self.numeric_min.map_or(n, |m| m.min(n)));
+                    self.numeric_max = Some(self.numeric_max.map_or(n, |m| m.max(n)));
+                    true
+                }
+                None => false,
+            };
+        if !self.numeric {
+            self.numeric_min = None;
+            self.numeric_max = None;
+        }
+        self.dates.retain(|pattern| is_date(value, pattern));
+        self.date_pattern = self.dates.first().copied();
+    }
+}
+
+/// The statistics of a file, see [`Profiler`].
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Profile {
+    /// Records profiled.
+    pub records: u64,
+    /// Records that could not be parsed.
+    pub errors: u64,
+    /// The statistics of each named field, in the order of the layout.
+    pub fields: Vec<FieldProfile>,
+}
+
+/// Computes per field statistics while streaming records, as a first look at a new feed.
+#[derive(Debug)]
+pub struct Profiler {
+    profile: Profile,
+    limit: usize,
+}
+
+#[allow(dead_code)]
+impl Profiler {
+    pub fn new(parser: &Parser) -> Self {
+        Profiler {
+            profile: Profile {
+                fields: parser
+                    .fields()
+                    .iter()
+                    .filter_map(|f| f.name())
+                    .map(FieldProfile::new)
+                    .collect(),
+                ..Profile::default()
+            },
+            limit: 20,
+        }
+    }
+
+    /// Sets how many distinct values are sampled per field, 20 by default.
+    pub fn distinct_limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Adds a record to the statistics.
+    pub fn add(&mut self, record: &Record) {
+        self.profile.records += 1;
+        for field in self.profile.fields.iter_mut() {
+            field.add(record.get(&field.name).map(|v| v.as_str()), self.limit);
+        }
+    }
+
+    /// Adds a parsed record, or counts the error.
+    pub fn add_result(&mut self, result: &ResultRecord) {
+        match result {
+            Ok(record) => self.add(record),
+            Err(_) => self.profile.errors += 1,
+        }
+    }
+
+    /// The statistics so far.
+    pub fn profile(&self) -> &Profile {
+        &self.profile
+    }
+
+    pub fn finish(self) -> Profile {
+        self.profile
+    }
+}
+
+/// Profiles all the records of a reader, or any record iterator.
+pub fn profile<I>(parser: &Parser, records: I) -> Profile
+where
+    I: IntoIterator<Item = ResultRecord>,
+{
+    let mut profiler = Profiler::new(parser);
+    for result in records {
+        profiler.add_result(&result);
+    }
+    profiler.finish()
+}
+
+/// Parses a plain decimal number, rejecting forms such as `inf` or `1e5` that are unlikely to
+/// be numbers in a flat file.
+fn number(value: &str) -> Option<f64> {
+    let digits = value.strip_prefix(['-', '+']).unwrap_or(value);
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit() || c == '.') {
+        return None;
+    }
+    value.parse().ok()
+}
+
+fn is_date(value: &str, pattern: &str) -> bool {
+    if value.len() != pattern.len() {
+        return false;
+    }
+    let mut month = 0;
+    let mut day = 0;
+    for (c, p) in value.chars().zip(pattern.chars()) {
+        match p {
+            'Y' | 'M' | 'D' => {
+                let digit = match c.to_digit(10) {
+                    Some(digit) => digit,
+                    None => return false,
+                };
+                match p {
+                    'M' => month = month * 10 + digit,
+                    'D' => day = day * 10 + digit,
+                    _ => (),
+                }
+            }
+            separator if c != separator => return false,
+            _ => (),
+        }
+    }
+    (1..=12).contains(&month) && (1..=31).contains(&day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        builder::{Buildable, Builder},
+        flat::fixed::read::Reader,
+    };
+
+    #[test]
+    fn profile_fields() {
+        let parser = Parser::builder()
+            .field("code")
+            .width(3)
+            .append()
+            .field("amount")
+            .width(6)
+            .append()
+            .field("date")
+            .width(10)
+            .append()
+            .build();
+        let input = "A  -12.5 2024-01-31\nBB 7     2024-02-01\nA  x               \nshort\n";
+        let mut rdr = Reader::from_string(input, &parser);
+
+        let profile = profile(&parser, rdr.string_reader());
+
+        assert_eq!(profile.records, 3);
+        assert_eq!(profile.errors, 1);
+        let code = &profile.fields[0];
+        assert_eq!((code.min_length, code.max_length), (Some(1), Some(2)));
+        assert_eq!(code.distinct, vec!["A", "BB"]);
+        assert_eq!(code.numeric_min, None);
+        let amount = &profile.fields[1];
+        assert_eq!(amount.numeric_min, None);
+        let date = &profile.fields[2];
+        assert_eq!(date.blank, 1);
+        assert!((date.blank_rate() - 1.0 / 3.0).abs() < 1e-9);
+        assert_eq!(date.date_pattern, Some("YYYY-MM-DD"));
+    }
+
+    #[test]
+    fn profile_numbers_and_dates() {
+        let parser = Parser::builder().field("value").width(8).append().build();
+        let mut profiler = Profiler::new(&parser).distinct_limit(2);
+        for value in ["20240131", "-5", "20231201"] {
+            profiler.add(&parser.parse(format!("{:8}", value)).unwrap());
+        }
+
+        let field = &profiler.profile().fields[0];
+        assert_eq!(field.numeric_min, Some(-5.0));
+        assert_eq!(field.numeric_max, Some(20240131.0));
+        assert_eq!(field.date_pattern, None);
+        assert!(field.more_distinct);
+        assert!(is_date("12/31/2024", "MM/DD/YYYY"));
+        assert!(!is_date("31/12/2024", "MM/DD/YYYY"));
+        assert_eq!(number("1e5"), None);
+    }
+}
@@ -0,0 +1,183 @@
+use crate::{error::Error, flat::fixed::Parser};
+use std::{collections::HashMap, ops::Range};
+
+/// One version of a layout, with a tag and the dates it is effective between.
+///
+/// Dates are compared on their digits only, so `2024-01-31` and `20240131` are the same date
+/// as long as they are written year first.
+#[derive(Debug)]
+pub struct LayoutVersion<'a> {
+    tag: &'a str,
+    from: Option<String>,
+    until: Option<String>,
+    parser: Parser<'a>,
+}
+
+#[allow(dead_code)]
+impl<'a> LayoutVersion<'a> {
+    pub fn new(tag: &'a str, parser: Parser<'a>) -> Self {
+        LayoutVersion {
+            tag,
+            from: None,
+            until: None,
+            parser,
+        }
+    }
+
+    /// Sets the first date the version is effective, inclusive.
+    pub fn effective_from(mut self, date: &str) -> Self {
+        self.from = Some(digits(date));
+        self
+    }
+
+    /// Sets the date the version is no longer effective, exclusive.
+    pub fn effective_until(mut self, date: &str) -> Self {
+        self.until = Some(digits(date));
+        self
+    }
+
+    pub fn tag(&self) -> &'a str {
+        self.tag
+    }
+
+    pub fn parser(&self) -> &Parser<'a> {
+        &self.parser
+    }
+
+    /// Whether the version is effective on the date.
+    pub fn is_effective(&self, date: &str) -> bool {
+        let date = digits(date);
+        self.from.as_ref().is_none_or(|from| &date >= from)
+            && self.until.as_ref().is_none_or(|until| &date < until)
+    }
+}
+
+/// Keeps the versions of several layouts, e.g. the layout a vendor used last year and the one
+/// they use this year, and selects the version for a file by tag or by date.
+#[derive(Debug, Default)]
+pub struct LayoutRegistry<'a> {
+    layouts: HashMap<&'a str, Vec<LayoutVersion<'a>>>,
+}
+
+#[allow(dead_code)]
+impl<'a> LayoutRegistry<'a> {
+    pub fn new() -> Self {
+        LayoutRegistry::default()
+    }
+
+    /// Adds a version of a layout.
+    pub fn register(mut self, layout: &'a str, version: LayoutVersion<'a>) -> Self {
+        self.layouts.entry(layout).or_default().push(version);
+        self
+    }
+
+    /// The versions of a layout, in the order they were registered.
+    pub fn versions(&self, layout: &str) -> &[LayoutVersion<'a>] {
+        self.layouts.get(layout).map_or(&[], |v| v.as_slice())
+    }
+
+    /// The version of a layout with the tag.
+    pub fn version(&self, layout: &str, tag: &str) -> Result<&LayoutVersion<'a>, Error> {
+        self.versions(layout)
+            .iter()
+            .find(|v| v.tag == tag)
+            .ok_or_else(|| Error::Layout(format!("No version {} of layout {}", tag, layout)))
+    }
+
+    /// The version of a layout effective on the date. When several versions are effective the
+    /// one that became effective last is selected.
+    pub fn effective(&self, layout: &str, date: &str) -> Result<&LayoutVersion<'a>, Error> {
+        self.versions(layout)
+            .iter()
+            .filter(|v| v.is_effective(date))
+            .max_by(|a, b| a.from.cmp(&b.from))
+            .ok_or_else(|| {
+                Error::Layout(format!(
+                    "No version of layout {} effective on {}",
+                    layout, date
+                ))
+            })
+    }
+
+    /// The version of a layout effective on the date found in a header line, at the given
+    /// character positions.
+    pub fn for_header(
+        &self,
+        layout: &str,
+        header: &str,
+        date: Range<usize>,
+    ) -> Result<&LayoutVersion<'a>, Error> {
+        let date = header
+            .chars()
+            .skip(date.start)
+            .take(date.end.saturating_sub(date.start))
+            .collect::<String>();
+        if date.trim().is_empty() {
+            return Err(Error::Layout(format!(
+                "No date in the header of layout {}",
+                layout
+            )));
+        }
+        self.effective(layout, &date)
+    }
+}
+
+fn digits(date: &str) -> String {
+    date.chars().filter(|c| c.is_ascii_digit()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::{Buildable, Builder};
+
+    fn parser(width: usize) -> Parser<'static> {
+        Parser::builder().field("id").width(width).append().build()
+    }
+
+    fn registry() -> LayoutRegistry<'static> {
+        LayoutRegistry::new()
+            .register(
+                "claims",
+                LayoutVersion::new("v1", parser(4)).effective_until("2024-07-01"),
+            )
+            .register(
+                "claims",
+                LayoutVersion::new("v2", parser(6)).effective_from("2024-06-01"),
+            )
+    }
+
+    #[test]
+    fn select_by_tag() {
+        let registry = registry();
+
+        assert_eq!(
+            registry.version("claims", "v1").unwrap().parser().width(),
+            4
+        );
+        assert!(registry.version("claims", "v3").is_err());
+        assert!(registry.version("payments", "v1").is_err());
+    }
+
+    #[test]
+    fn select_by_date() {
+        let registry = registry();
+
+        assert_eq!(
+            registry.effective("claims", "2023-12-31").unwrap().tag(),
+            "v1"
+        );
+        assert_eq!(
+            registry.effective("claims", "20240615").unwrap().tag(),
+            "v2"
+        );
+        assert_eq!(
+            registry.effective("claims", "2024-07-01").unwrap().tag(),
+            "v2"
+        );
+        let version = registry.for_header("claims", "HDR20240101", 3..11).unwrap();
+        assert_eq!(version.tag(), "v1");
+        assert!(registry.for_header("claims", "HDR", 3..11).is_err());
+        assert!(registry.effective("payments", "2024-01-01").is_err());
+    }
+}
@@ -0,0 +1,498 @@
+use super::{
+    parse_align, parse_case, parse_check_digit, parse_sign, parse_strip, parse_trim,
+    parse_truncate, width, FieldDef, FieldSet, NullDef, Schema,
+};
+use crate::{
+    error::Error,
+    utilities::string::{Case, WidthMode},
+};
+use std::{convert::TryFrom, fmt::Write};
+#[cfg(feature = "toml")]
+use std::{fs, path::Path};
+
+/// Keywords and reserved words, written as raw identifiers.
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let",
+    "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return",
+    "static", "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use",
+    "virtual", "where", "while", "yield",
+];
+
+/// Keywords that cannot be raw identifiers, followed by `_` instead.
+const PATH_KEYWORDS: &[&str] = &["crate", "self", "super"];
+
+/// The types fields can have in generated code, parsed from and formatted to their values.
+const TYPES: &[&str] = &[
+    "String", "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128",
+    "usize", "f32", "f64",
+];
+
+/// A named field of the generated struct.
+struct Member<'s> {
+    field: &'s str,
+    identifier: String,
+    rust_type: &'s str,
+    nullable: bool,
+}
+
+/// Generates a Rust struct named `name` with a field per named field of the schema, and the
+/// code building its parser and converting it from and to a record.
+///
+/// Fields have the type set by [`FieldDef::rust_type`], numeric fields are `i64` and other
+/// fields `String` by default, and fields that can be null are wrapped in an `Option`.
+/// Converting a record fails on values that do not parse as the type of their field, blank
+/// values take the default of the type. Of fields with the same name only the first one is a
+/// member, and distinct names making the same identifier, such as `a-b` and `a_b`, fail with
+/// `Error::Layout`. The generated code refers to this crate as `eta_parse`.
+pub fn generate(schema: &Schema, name: &str) -> Result<String, Error> {
+    generate_with(schema, &FieldSet::new(), name)
+}
+
+/// Generates the code of a schema, resolving its field references from a [`FieldSet`].
+pub fn generate_with(schema: &Schema, fields: &FieldSet, name: &str) -> Result<String, Error> {
+    let mut members = Vec::new();
+    let mut tables = Vec::new();
+    let mut builder = String::from("::eta_parse::flat::fixed::Parser::builder()");
+    if let Some(align) = &schema.align {
+        write!(
+            builder,
+            "\n            .default_align(::eta_parse::utilities::string::Align::{:?})",
+            parse_align(align)?
+        )
+        .ok();
+    }
+    if let Some(padding) = schema.padding {
+        write!(builder, "\n            .default_padding({:?})", padding).ok();
+    }
+    if let Some(width_mode) = &schema.width_mode {
+        let width_mode = WidthMode::try_from(width_mode.as_str())
+            .map_err(|_| Error::Layout(format!("Unknown width mode {}", width_mode)))?;
+        write!(
+            builder,
+            "\n            .width_mode(::eta_parse::utilities::string::WidthMode::{:?})",
+            width_mode
+        )
+        .ok();
+    }
+    if let Some(record_width) = schema.record_width {
+        write!(builder, "\n            .record_width({})", record_width).ok();
+    }
+    for field in &schema.fields {
+        let definitions = fields.resolve(field)?;
+        let width = width(&definitions)?;
+        let name = match &field.name {
+            Some(name) => name,
+            None => {
+                write!(builder, "\n            .spacer(0..{})", width).ok();
+                continue;
+            }
+        };
+        write!(
+            builder,
+            "\n            .field({:?})\n            .width({})",
+            name, width
+        )
+        .ok();
+        for call in calls(&definitions)? {
+            write!(builder, "\n            {}", call).ok();
+        }
+        if let Some(codes) = first(&definitions, |d| d.codes.as_ref()) {
+            let table = format!("CODES_{}", tables.len());
+            write!(builder, "\n            .codes(&{})", table).ok();
+            tables.push((table, codes));
+        }
+        builder.push_str("\n            .append()");
+        if members.iter().any(|m: &Member| m.field == name) {
+            continue;
+        }
+        let identifier = identifier(name);
+        if let Some(other) = members.iter().find(|m| m.identifier == identifier) {
+            return Err(Error::Layout(format!(
+                "Fields {} and {} both generate the member {}",
+                other.field, name, identifier
+            )));
+        }
+        let rust_type = match first(&definitions, |d| d.rust_type.as_deref()) {
+            Some(rust_type) => TYPES.iter().find(|t| **t == rust_type).ok_or_else(|| {
+                Error::Layout(format!("Unknown Rust type {} of field {}", rust_type, name))
+            })?,
+            None if first(&definitions, |d| d.sign.as_ref()).is_some() => "i64",
+            None => "String",
+        };
+        members.push(Member {
+            field: name,
+            identifier,
+            rust_type,
+            nullable: first(&definitions, |d| d.null_when.as_ref()).is_some(),
+        });
+    }
+    builder.push_str("\n            .build()");
+
+    let mut code = String::new();
+    writeln!(code, "/// Generated from a schema, do not edit.").ok();
+    let float = members.iter().any(|m| m.rust_type.starts_with('f'));
+    writeln!(
+        code,
+        "#[derive(Debug, Clone, Default, PartialEq{})]",
+        if float { "" } else { ", Eq" }
+    )
+    .ok();
+    writeln!(code, "pub struct {} {{", name).ok();
+    for member in &members {
+        match member.nullable {
+            true => writeln!(
+                code,
+                "    pub {}: Option<{}>,",
+                member.identifier, member.rust_type
+            ),
+            false => writeln!(code, "    pub {}: {},", member.identifier, member.rust_type),
+        }
+        .ok();
+    }
+    writeln!(code, "}}\n").ok();
+    writeln!(code, "impl {} {{", name).ok();
+    writeln!(code, "    /// The parser of the layout.").ok();
+    writeln!(
+        code,
+        "    pub fn parser() -> ::eta_parse::flat::fixed::Parser<'static> {{"
+    )
+    .ok();
+    writeln!(
+        code,
+        "        use ::eta_parse::builder::{{Buildable, Builder}};"
+    )
+    .ok();
+    for (table, codes) in &tables {
+        writeln!(
+            code,
+            "        static {}: ::std::sync::LazyLock<::eta_parse::flat::fixed::codes::CodeTable> =",
+            table
+        )
+        .ok();
+        writeln!(code, "            ::std::sync::LazyLock::new(|| {{").ok();
+        write!(
+            code,
+            "                ::eta_parse::flat::fixed::codes::CodeTable::new()"
+        )
+        .ok();
+        for (value, description) in codes.iter() {
+            write!(
+                code,
+                "\n                    .code({:?}, {:?})",
+                value, description
+            )
+            .ok();
+        }
+        writeln!(code).ok();
+        writeln!(code, "            }});").ok();
+    }
+    writeln!(code, "        {}", builder).ok();
+    writeln!(code, "    }}\n").ok();
+    writeln!(
+        code,
+        "    pub fn from_record(\n        record: &::eta_parse::flat::fixed::Record,\n    ) -> Result<Self, ::eta_parse::error::Error> {{"
+    )
+    .ok();
+    writeln!(code, "        Ok({} {{", name).ok();
+    for member in &members {
+        let value = match member.rust_type {
+            "String" => format!("record.get({:?}).cloned()", member.field),
+            _ => format!(
+                "record\n                .get({:?})\n                .map(|v| Self::parse_value({:?}, v))\n                .transpose()?",
+                member.field, member.field
+            ),
+        };
+        match member.nullable {
+            true => writeln!(code, "            {}: {},", member.identifier, value),
+            false if member.rust_type == "String" => writeln!(
+                code,
+                "            {}: {}.unwrap_or_default(),",
+                member.identifier, value
+            ),
+            false => writeln!(
+                code,
+                "            {}: {}\n                .unwrap_or_default(),",
+                member.identifier, value
+            ),
+        }
+        .ok();
+    }
+    writeln!(code, "        }})\n    }}\n").ok();
+    writeln!(
+        code,
+        "    pub fn to_record(&self) -> ::eta_parse::flat::fixed::Record {{"
+    )
+    .ok();
+    writeln!(
+        code,
+        "        let mut record = ::eta_parse::flat::fixed::Record::new();"
+    )
+    .ok();
+    for member in &members {
+        let convert = match member.rust_type {
+            "String" => "clone",
+            _ => "to_string",
+        };
+        match member.nullable {
+            true => writeln!(
+                code,
+                "        if let Some(value) = &self.{} {{\n            record.insert(String::from({:?}), value.{}());\n        }}",
+                member.identifier, member.field, convert
+            ),
+            false => writeln!(
+                code,
+                "        record.insert(String::from({:?}), self.{}.{}());",
+                member.field, member.identifier, convert
+            ),
+        }
+        .ok();
+    }
+    write!(code, "        record\n    }}\n").ok();
+    if members.iter().any(|m| m.rust_type != "String") {
+        writeln!(
+            code,
+            "
+    fn parse_value<T: ::std::str::FromStr + Default>(
+        field: &str,
+        value: &str,
+    ) -> Result<T, ::eta_parse::error::Error> {{
+        match value.trim() {{
+            \"\" => Ok(T::default()),
+            trimmed => trimmed.parse().map_err(|_| ::eta_parse::error::Error::Parse {{
+                line: None,
+                field: Some(String::from(field)),
+                kind: ::eta_parse::error::ParseErrorKind::NotNumeric(value.to_string()),
+            }}),
+        }}
+    }}"
+        )
+        .ok();
+    }
+    writeln!(code, "}}").ok();
+    Ok(code)
+}
+
+/// Generates the code of a schema file into a file, e.g. from a build script into `OUT_DIR`.
+#[cfg(feature = "toml")]
+pub fn generate_file<P, Q>(schema: P, name: &str, output: Q) -> Result<(), Error>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    let code = generate(&Schema::from_file(schema)?, name)?;
+    fs::write(output, code)?;
+    Ok(())
+}
+
+/// The first attribute set by a field or the definitions it refers to.
+fn first<'s, T, F>(definitions: &[&'s FieldDef], attribute: F) -> Option<T>
+where
+    F: Fn(&'s FieldDef) -> Option<T>,
+{
+    definitions.iter().find_map(|d| attribute(d))
+}
+
+/// The builder calls setting the attributes of a field other than its name and width.
+fn calls(definitions: &[&FieldDef]) -> Result<Vec<String>, Error> {
+    let mut calls = Vec::new();
+    if let Some(align) = first(definitions, |d| d.align.as_deref()) {
+        calls.push(format!(
+            ".align(::eta_parse::utilities::string::Align::{:?})",
+            parse_align(align)?
+        ));
+    }
+    if let Some(padding) = first(definitions, |d| d.padding) {
+        calls.push(format!(".padding({:?})", padding));
+    }
+    if let Some(fill) = first(definitions, |d| d.fill.as_deref()) {
+        calls.push(format!(".fill({:?})", fill));
+    }
+    if let Some(value) = first(definitions, |d| d.default_value.as_deref()) {
+        calls.push(format!(".default_value({:?})", value));
+    }
+    if let Some(null) = first(definitions, |d| d.null_when.as_ref()) {
+        let null = match null {
+            NullDef::Blank => String::from("Blank"),
+            NullDef::Filled(c) => format!("Filled({:?})", c),
+            NullDef::Value(v) => format!("Value({:?})", v),
+        };
+        calls.push(format!(
+            ".null_when(::eta_parse::flat::fixed::Null::{})",
+            null
+        ));
+    }
+    if let Some(strip) = first(definitions, |d| d.strip.as_deref()) {
+        calls.push(format!(
+            ".strip(::eta_parse::utilities::string::StripMode::{:?})",
+            parse_strip(strip)?
+        ));
+    }
+    if let Some(trim) = first(definitions, |d| d.trim.as_deref()) {
+        calls.push(format!(
+            ".trim(::eta_parse::utilities::string::Trim::{:?})",
+            parse_trim(trim)?
+        ));
+    }
+    if let Some(truncate) = first(definitions, |d| d.truncate.as_deref()) {
+        calls.push(format!(
+            ".truncate_side(::eta_parse::utilities::string::TruncateSide::{:?})",
+            parse_truncate(truncate)?
+        ));
+    }
+    if let Some(sign) = first(definitions, |d| d.sign.as_deref()) {
+        calls.push(format!(
+            ".numeric(::eta_parse::flat::fixed::numeric::Sign::{:?})",
+            parse_sign(sign)?
+        ));
+    }
+    if let Some(check_digit) = first(definitions, |d| d.check_digit.as_deref()) {
+        calls.push(format!(
+            ".check_digit(&::eta_parse::flat::fixed::check::{})",
+            parse_check_digit(check_digit)?.1
+        ));
+    }
+    if let Some(name) = first(definitions, |d| d.description.as_deref()) {
+        calls.push(format!(".description_field({:?})", name));
+    }
+    match first(definitions, |d| d.case.as_deref())
+        .map(parse_case)
+        .transpose()?
+    {
+        Some(Case::Upper) => calls.push(String::from(".uppercase()")),
+        Some(Case::Lower) => calls.push(String::from(".lowercase()")),
+        None => (),
+    }
+    Ok(calls)
+}
+
+/// A snake case Rust identifier for a field name.
+fn identifier(name: &str) -> String {
+    let mut identifier = String::new();
+    for (i, c) in name.char_indices() {
+        match c {
+            c if c.is_ascii_alphanumeric() => {
+                if c.is_ascii_uppercase()
+                    && i > 0
+                    && !identifier.ends_with('_')
+                    && !name[..i].ends_with(|p: char| p.is_ascii_uppercase())
+                {
+                    identifier.push('_');
+                }
+                identifier.push(c.to_ascii_lowercase());
+            }
+            _ if !identifier.ends_with('_') => identifier.push('_'),
+            _ => (),
+        }
+    }
+    match identifier.chars().next() {
+        _ if identifier.trim_matches('_').is_empty() => format!("field{}", identifier),
+        Some(c) if c.is_ascii_digit() => format!("_{}", identifier),
+        _ if KEYWORDS.contains(&identifier.as_str()) => format!("r#{}", identifier),
+        _ if PATH_KEYWORDS.contains(&identifier.as_str()) => format!("{}_", identifier),
+        _ => identifier,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flat::fixed::codes::CodeTable;
+
+    /// The schema of `tests/data/codegen/account.rs`, compiled by the `codegen` test.
+    fn account() -> Schema {
+        Schema {
+            padding: Some('0'),
+            fields: vec![
+                FieldDef {
+                    name: Some(String::from("accountId")),
+                    width: Some(6),
+                    align: Some(String::from("right")),
+                    check_digit: Some(String::from("luhn")),
+                    ..FieldDef::default()
+                },
+                FieldDef {
+                    width: Some(2),
+                    ..FieldDef::default()
+                },
+                FieldDef {
+                    name: Some(String::from("type")),
+                    width: Some(4),
+                    null_when: Some(NullDef::Value(String::from("NULL"))),
+                    case: Some(String::from("upper")),
+                    codes: Some(CodeTable::new().code("CHK", "Checking")),
+                    description: Some(String::from("type_name")),
+                    ..FieldDef::default()
+                },
+                FieldDef {
+                    name: Some(String::from("count")),
+                    width: Some(3),
+                    align: Some(String::from("right")),
+                    sign: Some(String::from("leading")),
+                    ..FieldDef::default()
+                },
+                FieldDef {
+                    name: Some(String::from("rate")),
+                    width: Some(6),
+                    align: Some(String::from("right")),
+                    padding: Some(' '),
+                    null_when: Some(NullDef::Blank),
+                    rust_type: Some(String::from("f64")),
+                    ..FieldDef::default()
+                },
+            ],
+            ..Schema::default()
+        }
+    }
+
+    #[test]
+    fn generate_struct() {
+        let code = generate(&account(), "Account").unwrap();
+
+        assert_eq!(code, include_str!("../../tests/data/codegen/account.rs"));
+    }
+
+    #[test]
+    fn unknown_type() {
+        let mut schema = account();
+        schema.fields[0].rust_type = Some(String::from("Vec<u8>"));
+
+        assert!(matches!(
+            generate(&schema, "Account"),
+            Err(Error::Layout(_))
+        ));
+    }
+
+    #[test]
+    fn identifiers() {
+        assert_eq!(identifier("AccountID"), "account_id");
+        assert_eq!(identifier("first-name"), "first_name");
+        assert_eq!(identifier("2nd"), "_2nd");
+        assert_eq!(identifier("match"), "r#match");
+        assert_eq!(identifier("abstract"), "r#abstract");
+        assert_eq!(identifier("crate"), "crate_");
+        assert_eq!(identifier("Self"), "self_");
+        assert_eq!(identifier("-"), "field_");
+    }
+
+    #[test]
+    fn identifier_collisions() {
+        let schema = Schema {
+            fields: vec![
+                FieldDef {
+                    name: Some(String::from("accountId")),
+                    width: Some(2),
+                    ..FieldDef::default()
+                },
+                FieldDef {
+                    name: Some(String::from("account_id")),
+                    width: Some(2),
+                    ..FieldDef::default()
+                },
+            ],
+            ..Schema::default()
+        };
+
+        assert!(matches!(generate(&schema, "A"), Err(Error::Layout(_))));
+    }
+}
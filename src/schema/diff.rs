@@ -0,0 +1,187 @@
+use crate::flat::fixed::Parser;
+use std::fmt::{Display, Formatter, Result};
+
+/// The position of a named field within a layout.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldSpan {
+    pub name: String,
+    /// Offset of the field from the start of the record, 0-based.
+    pub offset: usize,
+    pub width: usize,
+}
+
+/// The differences between two layouts, comparing named fields only.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LayoutDiff {
+    /// Fields only present in the new layout.
+    pub added: Vec<FieldSpan>,
+    /// Fields only present in the old layout.
+    pub removed: Vec<FieldSpan>,
+    /// Fields present in both layouts with a different width, as `(old, new)`.
+    pub resized: Vec<(FieldSpan, FieldSpan)>,
+    /// Fields present in both layouts at a different offset, as `(old, new)`.
+    pub moved: Vec<(FieldSpan, FieldSpan)>,
+}
+
+impl LayoutDiff {
+    /// Whether both layouts place the same named fields at the same positions.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.resized.is_empty()
+            && self.moved.is_empty()
+    }
+}
+
+impl Display for FieldSpan {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(
+            f,
+            "{} [{}..{}]",
+            self.name,
+            self.offset,
+            self.offset + self.width
+        )
+    }
+}
+
+impl Display for LayoutDiff {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        if self.is_empty() {
+            return write!(f, "Layouts are identical");
+        }
+        let mut lines = Vec::new();
+        lines.extend(self.added.iter().map(|s| format!("+ {}", s)));
+        lines.extend(self.removed.iter().map(|s| format!("- {}", s)));
+        lines.extend(
+            self.resized
+                .iter()
+                .map(|(old, new)| format!("~ {} resized {} -> {}", old.name, old.width, new.width)),
+        );
+        lines.extend(
+            self.moved
+                .iter()
+                .map(|(old, new)| format!("> {} moved {} -> {}", old.name, old.offset, new.offset)),
+        );
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+fn spans(parser: &Parser) -> Vec<FieldSpan> {
+    let mut offset = 0;
+    let mut spans = Vec::new();
+    for field in parser.fields() {
+        if let Some(name) = field.name() {
+            spans.push(FieldSpan {
+                name: name.to_string(),
+                offset,
+                width: field.width(),
+            });
+        }
+        offset += field.width();
+    }
+    spans
+}
+
+/// Compares the named fields of two layouts, reporting what changed from `old` to `new`.
+pub fn diff(old: &Parser, new: &Parser) -> LayoutDiff {
+    let old = spans(old);
+    let new = spans(new);
+    let mut diff = LayoutDiff::default();
+
+    for o in &old {
+        match new.iter().find(|n| n.name == o.name) {
+            Some(n) => {
+                if n.width != o.width {
+                    diff.resized.push((o.clone(), n.clone()));
+                }
+                if n.offset != o.offset {
+                    diff.moved.push((o.clone(), n.clone()));
+                }
+            }
+            None => diff.removed.push(o.clone()),
+        }
+    }
+    diff.added = new
+        .into_iter()
+        .filter(|n| !old.iter().any(|o| o.name == n.name))
+        .collect();
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::{Buildable, Builder};
+
+    #[test]
+    fn diff_identical() {
+        let parser = Parser::builder().field("a").width(4).append().build();
+        let diff = diff(&parser, &parser);
+
+        assert!(diff.is_empty());
+        assert_eq!(diff.to_string(), "Layouts are identical");
+    }
+
+    #[test]
+    fn diff_changes() {
+        let old = Parser::builder()
+            .field("id")
+            .width(4)
+            .append()
+            .field("name")
+            .width(10)
+            .append()
+            .field("legacy")
+            .width(2)
+            .append()
+            .field("amount")
+            .width(8)
+            .append()
+            .build();
+        let new = Parser::builder()
+            .field("id")
+            .width(6)
+            .append()
+            .field("name")
+            .width(10)
+            .append()
+            .spacer(0..2)
+            .field("amount")
+            .width(8)
+            .append()
+            .field("currency")
+            .width(3)
+            .append()
+            .build();
+
+        let diff = diff(&old, &new);
+
+        assert!(!diff.is_empty());
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].name, "currency");
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].name, "legacy");
+        assert_eq!(diff.resized.len(), 1);
+        assert_eq!(diff.resized[0].0.width, 4);
+        assert_eq!(diff.resized[0].1.width, 6);
+        assert_eq!(
+            diff.moved
+                .iter()
+                .map(|(o, _)| o.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["name", "amount"]
+        );
+        assert_eq!(
+            diff.to_string(),
+            [
+                "+ currency [26..29]",
+                "- legacy [14..16]",
+                "~ id resized 4 -> 6",
+                "> name moved 4 -> 6",
+                "> amount moved 16 -> 18",
+            ]
+            .join("\n")
+        );
+    }
+}
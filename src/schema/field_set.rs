@@ -0,0 +1,240 @@
+use super::{width, FieldDef};
+use crate::{
+    error::Error,
+    flat::fixed::builder::{FieldBuilder, ParserBuilder},
+};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+#[cfg(feature = "toml")]
+use std::{fs, path::Path};
+
+/// A library of named field definitions shared by many layouts, e.g. `amount-13.2-right-zero`
+/// or `date-yyyymmdd`.
+///
+/// Fields of a [`Schema`](super::Schema) refer to a definition with `$ref`, and definitions may
+/// themselves refer to other definitions. In TOML a field set is a table of definitions:
+///
+/// ```toml
+/// [amount-13_2-right-zero]
+/// width = 13
+/// align = "right"
+/// padding = "0"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct FieldSet {
+    definitions: HashMap<String, FieldDef>,
+}
+
+#[allow(dead_code)]
+impl FieldSet {
+    pub fn new() -> Self {
+        FieldSet::default()
+    }
+
+    /// Adds a definition, replacing any definition with the same name.
+    pub fn define<T: Into<String>>(mut self, name: T, definition: FieldDef) -> Self {
+        self.insert(name, definition);
+        self
+    }
+
+    /// Adds a definition, returning the definition it replaced.
+    pub fn insert<T: Into<String>>(&mut self, name: T, definition: FieldDef) -> Option<FieldDef> {
+        self.definitions.insert(name.into(), definition)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&FieldDef> {
+        self.definitions.get(name)
+    }
+
+    /// The names of the definitions, in no particular order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.definitions.keys().map(|k| k.as_str())
+    }
+
+    /// Adds a field based on a definition to a parser built in code. The returned builder can
+    /// override the attributes of the definition before it is appended.
+    pub fn field<'s>(
+        &'s self,
+        builder: ParserBuilder<'s>,
+        name: &'s str,
+        reference: &str,
+    ) -> Result<FieldBuilder<'s>, Error> {
+        let definition = self.definition(reference)?;
+        let definitions = self.resolve(definition)?;
+        let width = width(&definitions)?;
+        let mut field_builder = builder.field(name).width(width);
+        for definition in definitions.iter().rev() {
+            field_builder = definition.apply(field_builder)?;
+        }
+        Ok(field_builder)
+    }
+
+    /// The field followed by the definitions it refers to, directly or through other
+    /// definitions.
+    pub(crate) fn resolve<'s>(&'s self, field: &'s FieldDef) -> Result<Vec<&'s FieldDef>, Error> {
+        let mut definitions = vec![field];
+        let mut reference = field.reference.as_deref();
+        while let Some(name) = reference {
+            let definition = self.definition(name)?;
+            if definitions.iter().any(|d| std::ptr::eq(*d, definition)) {
+                return Err(Error::Layout(format!("Circular field reference {}", name)));
+            }
+            definitions.push(definition);
+            reference = definition.reference.as_deref();
+        }
+        Ok(definitions)
+    }
+
+    fn definition(&self, name: &str) -> Result<&FieldDef, Error> {
+        self.get(name)
+            .ok_or_else(|| Error::Layout(format!("Unknown field reference {}", name)))
+    }
+
+    /// Parses a field set from a TOML document.
+    #[cfg(feature = "toml")]
+    pub fn from_toml(s: &str) -> Result<Self, Error> {
+        toml::from_str(s).map_err(|e| Error::Layout(e.to_string()))
+    }
+
+    /// Loads a field set from a TOML file.
+    #[cfg(feature = "toml")]
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Self::from_toml(&fs::read_to_string(path)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        builder::{Buildable, Builder},
+        flat::fixed::Parser,
+        schema::Schema,
+    };
+
+    fn fields() -> FieldSet {
+        FieldSet::new()
+            .define(
+                "amount",
+                FieldDef {
+                    width: Some(6),
+                    align: Some(String::from("right")),
+                    padding: Some('0'),
+                    ..FieldDef::default()
+                },
+            )
+            .define(
+                "amount-default",
+                FieldDef {
+                    reference: Some(String::from("amount")),
+                    default_value: Some(String::from("0")),
+                    ..FieldDef::default()
+                },
+            )
+    }
+
+    fn field(name: &str, reference: &str) -> FieldDef {
+        FieldDef {
+            name: Some(String::from(name)),
+            reference: Some(String::from(reference)),
+            ..FieldDef::default()
+        }
+    }
+
+    #[test]
+    fn schema_with_references() {
+        let fields = fields();
+        let schema = Schema {
+            fields: vec![
+                field("debit", "amount-default"),
+                FieldDef {
+                    width: Some(4),
+                    ..field("credit", "amount")
+                },
+            ],
+            ..Schema::default()
+        };
+        let parser = schema.parser_with(&fields).unwrap();
+
+        assert_eq!(parser.width(), 10);
+        let record = parser.parse("0001230000").unwrap();
+        assert_eq!(record["debit"], "123");
+        assert_eq!(record["credit"], "");
+        let record = parser.parse("0000000045").unwrap();
+        assert_eq!(record["debit"], "0");
+        assert_eq!(record["credit"], "45");
+    }
+
+    #[test]
+    fn unknown_and_circular_references() {
+        let fields = fields().define("loop", field("loop", "loop"));
+
+        let schema = Schema {
+            fields: vec![field("debit", "missing")],
+            ..Schema::default()
+        };
+        assert!(matches!(schema.parser_with(&fields), Err(Error::Layout(_))));
+        assert!(matches!(schema.parser(), Err(Error::Layout(_))));
+
+        let schema = Schema {
+            fields: vec![field("debit", "loop")],
+            ..Schema::default()
+        };
+        assert!(matches!(schema.parser_with(&fields), Err(Error::Layout(_))));
+    }
+
+    #[test]
+    fn parser_with_references() {
+        let fields = fields();
+        let builder = fields
+            .field(Parser::builder(), "debit", "amount")
+            .unwrap()
+            .append();
+        let parser = fields
+            .field(builder, "credit", "amount")
+            .unwrap()
+            .width(3)
+            .append()
+            .build();
+
+        assert_eq!(
+            parser.format(&parser.parse("000012034").unwrap()),
+            "000012034"
+        );
+        assert_eq!(parser.parse("000012034").unwrap()["credit"], "34");
+        assert!(fields.field(Parser::builder(), "x", "missing").is_err());
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn field_set_from_toml() {
+        let fields = FieldSet::from_toml(
+            r#"
+            [date-yyyymmdd]
+            width = 8
+            null_when = { filled = "0" }
+            "#,
+        )
+        .unwrap();
+        let schema = Schema::from_toml(
+            r#"
+            [[fields]]
+            name = "opened"
+            "$ref" = "date-yyyymmdd"
+
+            [[fields]]
+            name = "closed"
+            ref = "date-yyyymmdd"
+            "#,
+        )
+        .unwrap();
+
+        let parser = schema.parser_with(&fields).unwrap();
+        let record = parser.parse("2024010100000000").unwrap();
+        assert_eq!(record["opened"], "20240101");
+        assert_eq!(record.get("closed"), None);
+    }
+}
@@ -0,0 +1,594 @@
+use crate::{
+    builder::{Buildable, Builder},
+    error::Error,
+    flat::fixed::{
+        builder::FieldBuilder,
+        check::{CheckDigit, Luhn, Mod10, Mod11},
+        codes::CodeTable,
+        numeric::Sign,
+        Null, Parser,
+    },
+    utilities::string::{Align, Case, StripMode, Trim, TruncateSide, WidthMode},
+};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+#[cfg(feature = "toml")]
+use std::{fs, path::Path};
+
+pub mod codegen;
+pub mod diff;
+pub mod field_set;
+
+pub use self::diff::{diff, FieldSpan, LayoutDiff};
+pub use self::field_set::FieldSet;
+
+/// An owned description of a fixed width layout, typically loaded from a file.
+///
+/// A `Schema` owns the field names, the [`Parser`] built from it borrows them.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Schema {
+    /// Default alignment of the fields, `left` or `right`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub align: Option<String>,
+    /// Default padding character of the fields.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub padding: Option<char>,
+    /// How field widths are counted, `chars`, `bytes`, `display_width` or `graphemes`
+    /// with the `unicode-segmentation` feature.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub width_mode: Option<String>,
+    /// Total width of a record, the fields are followed by a filler up to it.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub record_width: Option<usize>,
+    pub fields: Vec<FieldDef>,
+}
+
+/// The definition of a single field, fields without a name are spacers.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FieldDef {
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub name: Option<String>,
+    /// Name of a definition of a [`FieldSet`] this field is based on, written `$ref` or `ref`.
+    /// Attributes set on the field override those of the definition.
+    #[cfg_attr(feature = "serde", serde(default, rename = "$ref", alias = "ref"))]
+    pub reference: Option<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub width: Option<usize>,
+    /// Start of the field, 0-based, used with `end` instead of `width`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub start: Option<usize>,
+    /// End of the field, exclusive.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub end: Option<usize>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub align: Option<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub padding: Option<char>,
+    /// Padding string repeated instead of the padding character, e.g. `**`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub fill: Option<String>,
+    /// Value used when the field is blank on parse or missing on format.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub default_value: Option<String>,
+    /// When the field is null, e.g. `"blank"`, `{ filled = "9" }` or `{ value = "NULL" }`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub null_when: Option<NullDef>,
+    /// Sides the padding is stripped from, `aligned_side`, `both` or `none`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub strip: Option<String>,
+    /// Whitespace trimmed after stripping the padding, `none`, `left`, `right` or `both`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub trim: Option<String>,
+    /// Case the value is converted to on parse, `upper` or `lower`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub case: Option<String>,
+    /// Characters kept when a value is too long on format, `left`, `right` or `error`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub truncate: Option<String>,
+    /// Makes the field numeric with the sign written `leading`, `leading_plus`, `trailing` or
+    /// `cr_db`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub sign: Option<String>,
+    /// Check digit following the value, `luhn`, `aba`, `mod11` or `isbn`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub check_digit: Option<String>,
+    /// The codes the field may hold and their descriptions, e.g. `{ A = "Active" }`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub codes: Option<CodeTable>,
+    /// Name under which the description of the code is added to parsed records.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub description: Option<String>,
+    /// Type of the field in generated code, see [`codegen`]: an integer type, `f32`, `f64` or
+    /// `String`. Numeric fields default to `i64`, other fields to `String`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub rust_type: Option<String>,
+}
+
+/// An owned [`Null`] representation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum NullDef {
+    Blank,
+    Filled(char),
+    Value(String),
+}
+
+impl NullDef {
+    fn null(&self) -> Null<'_> {
+        match self {
+            NullDef::Blank => Null::Blank,
+            NullDef::Filled(c) => Null::Filled(*c),
+            NullDef::Value(v) => Null::Value(v),
+        }
+    }
+}
+
+impl FieldDef {
+    fn label(&self) -> &str {
+        self.name.as_deref().unwrap_or("<spacer>")
+    }
+
+    fn width(&self) -> Result<usize, Error> {
+        match (self.width, self.start, self.end) {
+            (Some(width), _, _) => Ok(width),
+            (None, Some(start), Some(end)) if end >= start => Ok(end - start),
+            _ => Err(Error::Layout(format!(
+                "Field {} requires a width or a start and end",
+                self.label()
+            ))),
+        }
+    }
+
+    /// Sets the attributes of the field, other than its name and width, on the builder.
+    fn apply<'s>(&'s self, mut builder: FieldBuilder<'s>) -> Result<FieldBuilder<'s>, Error> {
+        if let Some(align) = &self.align {
+            builder = builder.align(parse_align(align)?);
+        }
+        if let Some(padding) = self.padding {
+            builder = builder.padding(padding);
+        }
+        if let Some(fill) = &self.fill {
+            builder = builder.fill(fill);
+        }
+        if let Some(value) = &self.default_value {
+            builder = builder.default_value(value);
+        }
+        if let Some(null) = &self.null_when {
+            builder = builder.null_when(null.null());
+        }
+        if let Some(strip) = &self.strip {
+            builder = builder.strip(parse_strip(strip)?);
+        }
+        if let Some(trim) = &self.trim {
+            builder = builder.trim(parse_trim(trim)?);
+        }
+        if let Some(truncate) = &self.truncate {
+            builder = builder.truncate_side(parse_truncate(truncate)?);
+        }
+        if let Some(sign) = &self.sign {
+            builder = builder.numeric(parse_sign(sign)?);
+        }
+        if let Some(check_digit) = &self.check_digit {
+            builder = builder.check_digit(parse_check_digit(check_digit)?.0);
+        }
+        if let Some(codes) = &self.codes {
+            builder = builder.codes(codes);
+        }
+        if let Some(description) = &self.description {
+            builder = builder.description_field(description);
+        }
+        Ok(match self.case.as_deref().map(parse_case).transpose()? {
+            Some(Case::Upper) => builder.uppercase(),
+            Some(Case::Lower) => builder.lowercase(),
+            None => builder,
+        })
+    }
+}
+
+/// The width of a field resolved to its definitions, from the first that sets it.
+fn width(definitions: &[&FieldDef]) -> Result<usize, Error> {
+    definitions
+        .iter()
+        .find(|d| d.width.is_some() || d.start.is_some() || d.end.is_some())
+        .unwrap_or(&definitions[0])
+        .width()
+}
+
+/// Builds a schema from rows describing its fields, e.g. loaded from a metadata table, as
+/// `(name, start, end, align, padding, type)` tuples. Positions are 0-based with an exclusive
+/// end, the rows may be in any order and gaps between them become spacers.
+///
+/// The type is `string` or `char` for text, `numeric`, `number`, `integer` or `decimal` for a
+/// number aligned right and padded with zeros unless the row sets them, and `filler` for a
+/// spacer. The [`Parser`] is built from the returned schema, which owns the field names.
+pub fn from_rows<I, S>(rows: I) -> Result<Schema, Error>
+where
+    I: IntoIterator<Item = (S, usize, usize, Option<S>, Option<char>, Option<S>)>,
+    S: AsRef<str>,
+{
+    let mut rows = rows.into_iter().collect::<Vec<_>>();
+    rows.sort_by_key(|row| row.1);
+    let mut fields = Vec::with_capacity(rows.len());
+    let mut position = 0;
+    for (name, start, end, align, padding, kind) in rows {
+        let name = name.as_ref();
+        if start < position || end < start {
+            return Err(Error::Layout(format!(
+                "Field {} at {}..{} overlaps the previous field",
+                name, start, end
+            )));
+        }
+        if start > position {
+            fields.push(FieldDef {
+                width: Some(start - position),
+                ..FieldDef::default()
+            });
+        }
+        position = end;
+        let mut field = FieldDef {
+            name: Some(name.to_string()),
+            width: Some(end - start),
+            align: align.map(|a| a.as_ref().to_string()),
+            padding,
+            ..FieldDef::default()
+        };
+        match kind.as_ref().map(|k| k.as_ref().to_lowercase()).as_deref() {
+            None | Some("string") | Some("char") => (),
+            Some("numeric") | Some("number") | Some("integer") | Some("decimal") => {
+                field.align.get_or_insert_with(|| String::from("right"));
+                field.padding.get_or_insert('0');
+            }
+            Some("filler") => field.name = None,
+            Some(kind) => {
+                return Err(Error::Layout(format!(
+                    "Unknown type {} of field {}",
+                    kind, name
+                )))
+            }
+        }
+        if let Some(align) = &field.align {
+            parse_align(align)?;
+        }
+        fields.push(field);
+    }
+    Ok(Schema {
+        fields,
+        ..Schema::default()
+    })
+}
+
+fn parse_align(align: &str) -> Result<Align, Error> {
+    Align::try_from(align).map_err(|_| Error::Layout(format!("Unknown align {}", align)))
+}
+
+fn parse_strip(strip: &str) -> Result<StripMode, Error> {
+    StripMode::try_from(strip).map_err(|_| Error::Layout(format!("Unknown strip {}", strip)))
+}
+
+fn parse_trim(trim: &str) -> Result<Trim, Error> {
+    Trim::try_from(trim).map_err(|_| Error::Layout(format!("Unknown trim {}", trim)))
+}
+
+fn parse_truncate(truncate: &str) -> Result<TruncateSide, Error> {
+    TruncateSide::try_from(truncate)
+        .map_err(|_| Error::Layout(format!("Unknown truncate side {}", truncate)))
+}
+
+fn parse_sign(sign: &str) -> Result<Sign, Error> {
+    Sign::try_from(sign).map_err(|_| Error::Layout(format!("Unknown sign {}", sign)))
+}
+
+/// The built in check digits by name, with their paths in the `check` module.
+const CHECK_DIGITS: [(&str, &dyn CheckDigit, &str); 4] = [
+    ("luhn", &Luhn, "Luhn"),
+    ("aba", &Mod10::ABA, "Mod10::ABA"),
+    ("mod11", &Mod11::STANDARD, "Mod11::STANDARD"),
+    ("isbn", &Mod11::ISBN, "Mod11::ISBN"),
+];
+
+fn parse_check_digit(name: &str) -> Result<(&'static dyn CheckDigit, &'static str), Error> {
+    CHECK_DIGITS
+        .iter()
+        .find(|(n, _, _)| *n == name)
+        .map(|(_, check_digit, path)| (*check_digit, *path))
+        .ok_or_else(|| Error::Layout(format!("Unknown check digit {}", name)))
+}
+
+fn parse_case(case: &str) -> Result<Case, Error> {
+    Case::try_from(case).map_err(|_| Error::Layout(format!("Unknown case {}", case)))
+}
+
+#[allow(dead_code)]
+impl Schema {
+    /// Builds a parser for the layout, borrowing the field names from the schema.
+    pub fn parser(&self) -> Result<Parser<'_>, Error> {
+        self.build(None)
+    }
+
+    /// Appends the fields of a reusable template, such as an address or audit block, with
+    /// their names prefixed, e.g. `bill_` and `ship_` for two addresses. The fields keep the
+    /// alignment and padding defaults of the template.
+    pub fn include(&mut self, template: &Schema, prefix: &str) {
+        self.fields.extend(template.fields.iter().map(|field| {
+            FieldDef {
+                name: field
+                    .name
+                    .as_ref()
+                    .map(|name| format!("{}{}", prefix, name)),
+                align: field.align.clone().or_else(|| template.align.clone()),
+                padding: field.padding.or(template.padding),
+                ..field.clone()
+            }
+        }));
+    }
+
+    /// Builds a parser for the layout, resolving the field references from a [`FieldSet`].
+    pub fn parser_with<'s>(&'s self, fields: &'s FieldSet) -> Result<Parser<'s>, Error> {
+        self.build(Some(fields))
+    }
+
+    fn build<'s>(&'s self, fields: Option<&'s FieldSet>) -> Result<Parser<'s>, Error> {
+        let mut builder = Parser::builder();
+        if let Some(align) = &self.align {
+            builder = builder.default_align(parse_align(align)?);
+        }
+        if let Some(padding) = self.padding {
+            builder = builder.default_padding(padding);
+        }
+        if let Some(width_mode) = &self.width_mode {
+            builder =
+                builder
+                    .width_mode(WidthMode::try_from(width_mode.as_str()).map_err(|_| {
+                        Error::Layout(format!("Unknown width mode {}", width_mode))
+                    })?);
+        }
+        for field in &self.fields {
+            let definitions = match (fields, &field.reference) {
+                (Some(fields), _) => fields.resolve(field)?,
+                (None, Some(reference)) => {
+                    return Err(Error::Layout(format!(
+                        "Unknown field reference {}",
+                        reference
+                    )))
+                }
+                (None, None) => vec![field],
+            };
+            let width = width(&definitions)?;
+            builder = match &field.name {
+                Some(name) => {
+                    let mut field_builder = builder.field(name).width(width);
+                    for definition in definitions.iter().rev() {
+                        field_builder = definition.apply(field_builder)?;
+                    }
+                    field_builder.append()
+                }
+                None => builder.spacer(0..width),
+            };
+        }
+        if let Some(record_width) = self.record_width {
+            let width = builder.width();
+            if width > record_width {
+                return Err(Error::Layout(format!(
+                    "The fields are {} wide, more than the record width of {}",
+                    width, record_width
+                )));
+            }
+            builder = builder.record_width(record_width);
+        }
+        Ok(builder.build())
+    }
+
+    /// Parses a schema from a TOML document.
+    #[cfg(feature = "toml")]
+    pub fn from_toml(s: &str) -> Result<Self, Error> {
+        toml::from_str(s).map_err(|e| Error::Layout(e.to_string()))
+    }
+
+    /// Loads a schema from a TOML file.
+    #[cfg(feature = "toml")]
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Self::from_toml(&fs::read_to_string(path)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(name: Option<&str>, width: usize) -> FieldDef {
+        FieldDef {
+            name: name.map(String::from),
+            width: Some(width),
+            ..FieldDef::default()
+        }
+    }
+
+    #[test]
+    fn schema_parser() {
+        let schema = Schema {
+            align: Some(String::from("right")),
+            padding: Some('0'),
+            width_mode: None,
+            record_width: None,
+            fields: vec![
+                field(Some("first"), 4),
+                field(None, 2),
+                FieldDef {
+                    name: Some(String::from("second")),
+                    start: Some(6),
+                    end: Some(10),
+                    align: Some(String::from("left")),
+                    padding: Some(' '),
+                    default_value: Some(String::from("NONE")),
+                    case: Some(String::from("lower")),
+                    ..FieldDef::default()
+                },
+            ],
+        };
+        let parser = schema.parser().unwrap();
+
+        assert_eq!(parser.width(), 10);
+        let record = parser.parse("0012XXAB  ").unwrap();
+        assert_eq!(record.get("first"), Some(&String::from("12")));
+        assert_eq!(record.get("second"), Some(&String::from("ab")));
+        let record = parser.parse("0012XX    ").unwrap();
+        assert_eq!(record.get("second"), Some(&String::from("NONE")));
+    }
+
+    #[test]
+    fn schema_from_rows() {
+        let rows = vec![
+            ("name", 6, 10, None, None, Some("string")),
+            ("id", 0, 4, None, None, Some("NUMERIC")),
+            ("end", 12, 14, Some("right"), Some('*'), None),
+            ("reserved", 10, 12, None, None, Some("filler")),
+        ];
+        let schema = from_rows(rows).unwrap();
+        let parser = schema.parser().unwrap();
+
+        assert_eq!(schema.fields.len(), 5);
+        assert_eq!(schema.fields[1].name, None);
+        assert_eq!(parser.width(), 14);
+        let record = parser.parse("0042--Ann XX*1").unwrap();
+        assert_eq!(record.get("id"), Some(&String::from("42")));
+        assert_eq!(record.get("name"), Some(&String::from("Ann")));
+        assert_eq!(record.get("end"), Some(&String::from("1")));
+        assert_eq!(record.len(), 3);
+
+        let overlapping = vec![("a", 0, 4, None, None, None), ("b", 3, 6, None, None, None)];
+        assert!(matches!(from_rows(overlapping), Err(Error::Layout(_))));
+        let unknown = vec![("a", 0, 4, None, None, Some("blob"))];
+        assert!(matches!(from_rows(unknown), Err(Error::Layout(_))));
+    }
+
+    #[test]
+    fn schema_include() {
+        let address = Schema {
+            padding: Some('_'),
+            fields: vec![
+                field(Some("street"), 4),
+                field(None, 1),
+                field(Some("city"), 3),
+            ],
+            ..Schema::default()
+        };
+        let mut schema = Schema {
+            fields: vec![field(Some("id"), 2)],
+            ..Schema::default()
+        };
+        schema.include(&address, "bill_");
+        schema.include(&address, "ship_");
+        let parser = schema.parser().unwrap();
+
+        assert_eq!(parser.width(), 18);
+        let record = parser.parse("01Main Rio1st_ Osl").unwrap();
+        assert_eq!(record["bill_city"], "Rio");
+        assert_eq!(record["ship_street"], "1st");
+        assert_eq!(record.len(), 5);
+    }
+
+    #[test]
+    fn schema_record_width() {
+        let mut schema = Schema {
+            record_width: Some(6),
+            fields: vec![field(Some("id"), 3)],
+            ..Schema::default()
+        };
+        let parser = schema.parser().unwrap();
+
+        assert_eq!(parser.width(), 6);
+        assert_eq!(parser.format(&parser.parse("001").unwrap()), "001   ");
+
+        schema.record_width = Some(2);
+        assert!(matches!(schema.parser(), Err(Error::Layout(_))));
+    }
+
+    #[test]
+    fn schema_unknown_trim() {
+        let schema = Schema {
+            fields: vec![FieldDef {
+                trim: Some(String::from("middle")),
+                ..field(Some("first"), 4)
+            }],
+            ..Schema::default()
+        };
+
+        assert!(matches!(schema.parser(), Err(Error::Layout(_))));
+    }
+
+    #[test]
+    fn schema_missing_width() {
+        let schema = Schema {
+            fields: vec![FieldDef {
+                name: Some(String::from("first")),
+                ..FieldDef::default()
+            }],
+            ..Schema::default()
+        };
+
+        assert!(matches!(schema.parser(), Err(Error::Layout(_))));
+    }
+
+    #[test]
+    fn schema_unknown_align() {
+        let schema = Schema {
+            align: Some(String::from("banana")),
+            fields: vec![field(Some("first"), 4)],
+            ..Schema::default()
+        };
+
+        assert!(matches!(schema.parser(), Err(Error::Layout(_))));
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn schema_from_toml() {
+        let schema = Schema::from_toml(
+            r#"
+            padding = "0"
+
+            [[fields]]
+            name = "id"
+            width = 4
+            align = "right"
+
+            [[fields]]
+            width = 2
+
+            [[fields]]
+            name = "name"
+            start = 6
+            end = 12
+            fill = "<>"
+            null_when = { value = "NULL" }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(schema.fields.len(), 3);
+        assert_eq!(schema.padding, Some('0'));
+        assert_eq!(
+            schema.fields[2].null_when,
+            Some(NullDef::Value(String::from("NULL")))
+        );
+        let parser = schema.parser().unwrap();
+        assert_eq!(parser.width(), 12);
+        let record = parser.parse("0012  NULL<>").unwrap();
+        assert_eq!(record.get("name"), None);
+        let record = parser.parse("0012  ABC<><").unwrap();
+        assert_eq!(record.get("name"), Some(&String::from("ABC")));
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn schema_from_toml_invalid() {
+        assert!(matches!(
+            Schema::from_toml("fields = 1"),
+            Err(Error::Layout(_))
+        ));
+    }
+}
@@ -0,0 +1,139 @@
+//! Helpers to check that layouts round-trip, for use in downstream `proptest` suites.
+//!
+//! ```ignore
+//! proptest! {
+//!     #[test]
+//!     fn layout_round_trips(record in testing::records(&parser())) {
+//!         testing::check_round_trip(&parser(), &record).map_err(TestCaseError::fail)?;
+//!     }
+//! }
+//! ```
+use crate::{
+    flat::fixed::{Field, Null, Parser, Record},
+    utilities::string::{Case, StripMode},
+};
+use proptest::{collection::vec, prelude::*, sample::select};
+
+/// Generates values that survive formatting and parsing with the field, made of ASCII letters and
+/// digits other than the padding and respecting the case, null and strip options of the field.
+pub fn values(field: &Field) -> BoxedStrategy<String> {
+    let alphabet = ('A'..='Z')
+        .chain('a'..='z')
+        .chain('0'..='9')
+        .filter(|c| *c != field.padding())
+        .filter(|c| !field.fill().is_some_and(|f| f.contains(*c)))
+        .filter(|c| match field.case() {
+            Some(Case::Upper) => !c.is_ascii_lowercase(),
+            Some(Case::Lower) => !c.is_ascii_uppercase(),
+            None => true,
+        })
+        .collect::<Vec<char>>();
+    let width = field.width();
+    let min = match field.strip() {
+        StripMode::None => width,
+        _ => width.min(1),
+    };
+    let null = field.null_when().map(|n| match n {
+        Null::Blank => None,
+        Null::Filled(c) => Some(c.to_string().repeat(width)),
+        Null::Value(v) => Some(v.to_string()),
+    });
+    vec(select(alphabet), min..=width)
+        .prop_map(|chars| chars.into_iter().collect::<String>())
+        .prop_filter("Value is null", move |s| {
+            null.as_ref()
+                .is_none_or(|n| n.as_deref() != Some(s.as_str()))
+        })
+        .boxed()
+}
+
+/// Generates records with a value for every named field of the parser.
+pub fn records(parser: &Parser) -> BoxedStrategy<Record> {
+    let mut names = Vec::new();
+    let mut strategies = Vec::new();
+    for field in parser.fields() {
+        if let Some(name) = field.name() {
+            // The first field with a name wins when parsing.
+            if !names.iter().any(|n| n == name) {
+                names.push(name.to_string());
+                strategies.push(values(field));
+            }
+        }
+    }
+    strategies
+        .prop_map(move |values| names.iter().cloned().zip(values).collect())
+        .boxed()
+}
+
+/// Formats and parses the record, describing the first difference if the result is not equal.
+pub fn check_round_trip(parser: &Parser, record: &Record) -> Result<(), String> {
+    let line = parser.format(record);
+    let parsed = parser
+        .parse(line.as_str())
+        .map_err(|e| format!("Unable to parse {:?}: {}", line, e))?;
+    let mut names = record.keys().chain(parsed.keys()).collect::<Vec<&String>>();
+    names.sort();
+    names.dedup();
+    match names.into_iter().find(|n| record.get(*n) != parsed.get(*n)) {
+        Some(name) => Err(format!(
+            "Field {} was {:?} but parsed as {:?} from {:?}",
+            name,
+            record.get(name),
+            parsed.get(name),
+            line
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Asserts that the record is unchanged by formatting and parsing it.
+pub fn assert_round_trip(parser: &Parser, record: &Record) {
+    if let Err(e) = check_round_trip(parser, record) {
+        panic!("{}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::{Buildable, Builder};
+
+    fn parser() -> Parser<'static> {
+        Parser::builder()
+            .field("first")
+            .width(4)
+            .uppercase()
+            .append()
+            .spacer(4..5)
+            .field("second")
+            .width(6)
+            .align("right")
+            .padding('0')
+            .null_when(Null::Value("NONE"))
+            .append()
+            .field("third")
+            .width(3)
+            .fill("*-")
+            .append()
+            .build()
+    }
+
+    proptest! {
+        #[test]
+        fn generated_records_round_trip(record in records(&parser())) {
+            prop_assert_eq!(record.len(), 3);
+            check_round_trip(&parser(), &record).map_err(TestCaseError::fail)?;
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Field first was Some(\"ABCDE\")")]
+    fn assert_round_trip_lossy() {
+        let record = [(String::from("first"), String::from("ABCDE"))]
+            .iter()
+            .cloned()
+            .collect();
+
+        assert_round_trip(&parser(), &record);
+    }
+}
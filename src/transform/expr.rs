@@ -0,0 +1,161 @@
+use crate::{error::Error, flat::fixed::Record};
+use evalexpr::{
+    build_operator_tree, ContextWithMutableFunctions, ContextWithMutableVariables, EvalexprError,
+    Function, HashMapContext, Node,
+};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::{
+    convert::TryFrom,
+    fmt::{Display, Formatter, Result as FmtResult},
+};
+
+pub use evalexpr::Value;
+
+/// An expression over the fields of a record, e.g. `int(amount) * 100` or `status == "A"`, for
+/// computed fields, validation rules and filters kept in spec files.
+///
+/// The fields of the record are variables holding their values as strings, `int` and `float`
+/// convert them to numbers. Referencing a field missing from the record fails.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "String", into = "String"))]
+pub struct Expression {
+    source: String,
+    node: Node,
+}
+
+#[allow(dead_code)]
+impl Expression {
+    pub fn parse(source: &str) -> Result<Self, Error> {
+        let node = build_operator_tree(source).map_err(|e| expression_error(source, e))?;
+        Ok(Expression {
+            source: source.to_string(),
+            node,
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    pub fn eval(&self, record: &Record) -> Result<Value, Error> {
+        let mut context = HashMapContext::new();
+        for (name, value) in record {
+            context
+                .set_value(name.clone(), Value::String(value.clone()))
+                .map_err(|e| expression_error(&self.source, e))?;
+        }
+        for (name, function) in functions() {
+            context
+                .set_function(name.to_string(), function)
+                .map_err(|e| expression_error(&self.source, e))?;
+        }
+        self.node
+            .eval_with_context(&context)
+            .map_err(|e| expression_error(&self.source, e))
+    }
+
+    /// Evaluates the expression to a field value, strings as they are and other values
+    /// formatted.
+    pub fn eval_string(&self, record: &Record) -> Result<String, Error> {
+        Ok(match self.eval(record)? {
+            Value::String(s) => s,
+            Value::Empty => String::new(),
+            value => value.to_string(),
+        })
+    }
+
+    /// Evaluates the expression to a condition, failing when it is not a boolean.
+    pub fn eval_bool(&self, record: &Record) -> Result<bool, Error> {
+        self.eval(record)?
+            .as_boolean()
+            .map_err(|e| expression_error(&self.source, e))
+    }
+}
+
+impl Display for Expression {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        f.write_str(&self.source)
+    }
+}
+
+impl TryFrom<String> for Expression {
+    type Error = Error;
+
+    fn try_from(source: String) -> Result<Self, Error> {
+        Expression::parse(&source)
+    }
+}
+
+impl From<Expression> for String {
+    fn from(expression: Expression) -> Self {
+        expression.source
+    }
+}
+
+fn expression_error(source: &str, error: EvalexprError) -> Error {
+    Error::Expression(format!("{} in {}", error, source))
+}
+
+/// The functions available to expressions besides the builtins of `evalexpr`.
+fn functions() -> [(&'static str, Function); 2] {
+    [
+        (
+            "int",
+            Function::new(|value| match value {
+                Value::String(s) => s.trim().parse().map(Value::Int).map_err(|_| {
+                    EvalexprError::CustomMessage(format!("{:?} is not an integer", s))
+                }),
+                value => Ok(Value::Int(value.as_int()?)),
+            }),
+        ),
+        (
+            "float",
+            Function::new(|value| match value {
+                Value::String(s) => {
+                    s.trim().parse().map(Value::Float).map_err(|_| {
+                        EvalexprError::CustomMessage(format!("{:?} is not a number", s))
+                    })
+                }
+                value => Ok(Value::Float(value.as_number()?)),
+            }),
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(values: &[(&str, &str)]) -> Record {
+        values
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn evaluate() {
+        let record = record(&[("amount", "0012"), ("status", "A")]);
+
+        let cents = Expression::parse("int(amount) * 100").unwrap();
+        assert_eq!(cents.eval_string(&record).unwrap(), "1200");
+        let active = Expression::parse("status == \"A\" && int(amount) > 10").unwrap();
+        assert!(active.eval_bool(&record).unwrap());
+        let label = Expression::parse("status + \"-\" + amount").unwrap();
+        assert_eq!(label.eval_string(&record).unwrap(), "A-0012");
+    }
+
+    #[test]
+    fn errors() {
+        assert!(matches!(
+            Expression::parse("int(amount"),
+            Err(Error::Expression(_))
+        ));
+        let missing = Expression::parse("int(missing) > 1").unwrap();
+        assert!(missing.eval_bool(&Record::new()).is_err());
+        let not_bool = Expression::parse("1 + 1").unwrap();
+        assert!(not_bool.eval_bool(&Record::new()).is_err());
+    }
+}
@@ -0,0 +1,398 @@
+//! Control break summaries: records sorted by key are grouped as they stream by, and each group
+//! is summarized in a single record, as in the totals of a batch report.
+
+use crate::{
+    error::{Error, ParseErrorKind},
+    flat::fixed::{numeric, write::Writer, Record, ResultRecord},
+};
+use std::{cmp::Ordering, io::Write};
+
+/// How the records of a group are summarized in a field of the summary record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Aggregate {
+    /// The number of records.
+    Count,
+    /// The exact total of a numeric field, blank and missing values are skipped.
+    Sum(String),
+    /// The smallest value of a field, compared as numbers when both values are numbers.
+    Min(String),
+    /// The largest value of a field, compared as numbers when both values are numbers.
+    Max(String),
+}
+
+/// The running value of an aggregate.
+enum Total {
+    Count(u64),
+    /// A decimal number as units of `10^-scale`.
+    Sum {
+        units: i128,
+        scale: u32,
+    },
+    Extreme(Option<String>),
+}
+
+struct Group {
+    key: Vec<String>,
+    totals: Vec<Total>,
+}
+
+/// Groups records with the same values of the key fields and yields a summary record for each
+/// group, holding the key fields and the aggregates under their target names. The summaries
+/// are usually written in a target layout, see [`write_all`](GroupBy::write_all).
+///
+/// The records of a key must be adjacent, as in input sorted by the key fields. Errors from the
+/// records iterator, and values failing to sum, are yielded as they are met and the group
+/// carries on without the record.
+pub struct GroupBy<'k, I> {
+    records: I,
+    keys: &'k [&'k str],
+    aggregates: Vec<(String, Aggregate)>,
+    group: Option<Group>,
+}
+
+#[allow(dead_code)]
+impl<'k, I> GroupBy<'k, I>
+where
+    I: Iterator<Item = ResultRecord>,
+{
+    pub fn new(records: I, keys: &'k [&'k str]) -> Self {
+        GroupBy {
+            records,
+            keys,
+            aggregates: Vec::new(),
+            group: None,
+        }
+    }
+
+    /// Adds an aggregate written to the target field of the summaries.
+    pub fn aggregate<T: Into<String>>(mut self, target: T, aggregate: Aggregate) -> Self {
+        self.aggregates.push((target.into(), aggregate));
+        self
+    }
+
+    /// Counts the records of each group.
+    pub fn count<T: Into<String>>(self, target: T) -> Self {
+        self.aggregate(target, Aggregate::Count)
+    }
+
+    /// Sums a numeric field over each group.
+    pub fn sum<T: Into<String>, F: Into<String>>(self, target: T, field: F) -> Self {
+        self.aggregate(target, Aggregate::Sum(field.into()))
+    }
+
+    /// Keeps the smallest value of a field in each group.
+    pub fn min<T: Into<String>, F: Into<String>>(self, target: T, field: F) -> Self {
+        self.aggregate(target, Aggregate::Min(field.into()))
+    }
+
+    /// Keeps the largest value of a field in each group.
+    pub fn max<T: Into<String>, F: Into<String>>(self, target: T, field: F) -> Self {
+        self.aggregate(target, Aggregate::Max(field.into()))
+    }
+
+    /// Writes every summary in the layout of the writer, returning the number of summaries.
+    /// Stops at the first error.
+    pub fn write_all<W: Write>(self, writer: &mut Writer<'_, W>) -> Result<u64, Error> {
+        let mut written = 0;
+        for summary in self {
+            writer.write(&summary?)?;
+            written += 1;
+        }
+        writer.flush()?;
+        Ok(written)
+    }
+
+    fn start(&self, key: Vec<String>) -> Group {
+        let totals = self
+            .aggregates
+            .iter()
+            .map(|(_, aggregate)| match aggregate {
+                Aggregate::Count => Total::Count(0),
+                Aggregate::Sum(_) => Total::Sum { units: 0, scale: 0 },
+                Aggregate::Min(_) | Aggregate::Max(_) => Total::Extreme(None),
+            })
+            .collect();
+        Group { key, totals }
+    }
+
+    fn summary(&self, group: Group) -> Record {
+        let mut record = Record::with_capacity(self.keys.len() + self.aggregates.len());
+        for (field, value) in self.keys.iter().zip(group.key) {
+            record.insert(field.to_string(), value);
+        }
+        for ((target, _), total) in self.aggregates.iter().zip(group.totals) {
+            let value = match total {
+                Total::Count(count) => count.to_string(),
+                Total::Sum { units, scale } => decimal(units, scale),
+                Total::Extreme(Some(value)) => value,
+                Total::Extreme(None) => continue,
+            };
+            record.insert(target.clone(), value);
+        }
+        record
+    }
+}
+
+impl<'k, I> Iterator for GroupBy<'k, I>
+where
+    I: Iterator<Item = ResultRecord>,
+{
+    type Item = ResultRecord;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let record = match self.records.next() {
+                Some(Ok(record)) => record,
+                Some(Err(e)) => return Some(Err(e)),
+                None => {
+                    let group = self.group.take()?;
+                    return Some(Ok(self.summary(group)));
+                }
+            };
+            let key = self
+                .keys
+                .iter()
+                .map(|field| record.get(*field).cloned().unwrap_or_default())
+                .collect::<Vec<_>>();
+            if let Some(group) = self.group.as_mut().filter(|g| g.key == key) {
+                if let Err(e) = add(&self.aggregates, group, &record) {
+                    return Some(Err(e));
+                }
+                continue;
+            }
+            // A record failing to add neither closes the current group nor opens its own.
+            let mut group = self.start(key);
+            if let Err(e) = add(&self.aggregates, &mut group, &record) {
+                return Some(Err(e));
+            }
+            if let Some(finished) = self.group.replace(group) {
+                return Some(Ok(self.summary(finished)));
+            }
+        }
+    }
+}
+
+/// Adds the record to the totals of the group. Sums are computed before any total changes, so
+/// a value failing to sum leaves the record out of every aggregate.
+fn add(
+    aggregates: &[(String, Aggregate)],
+    group: &mut Group,
+    record: &Record,
+) -> Result<(), Error> {
+    let sums = aggregates
+        .iter()
+        .zip(group.totals.iter())
+        .map(|((_, aggregate), total)| match (aggregate, total) {
+            (Aggregate::Sum(field), Total::Sum { units, scale }) => {
+                sum(field, record, *units, *scale)
+            }
+            _ => Ok(None),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    for (((_, aggregate), total), sum) in aggregates.iter().zip(group.totals.iter_mut()).zip(sums) {
+        match (aggregate, total) {
+            (Aggregate::Count, Total::Count(count)) => *count += 1,
+            (Aggregate::Sum(_), Total::Sum { units, scale }) => {
+                if let Some((sum_units, sum_scale)) = sum {
+                    *units = sum_units;
+                    *scale = sum_scale;
+                }
+            }
+            (Aggregate::Min(field) | Aggregate::Max(field), Total::Extreme(extreme)) => {
+                let value = match record.get(field).map(|v| v.trim()) {
+                    Some(value) if !value.is_empty() => value,
+                    _ => continue,
+                };
+                let wanted = match aggregate {
+                    Aggregate::Min(_) => Ordering::Less,
+                    _ => Ordering::Greater,
+                };
+                if extreme
+                    .as_deref()
+                    .is_none_or(|current| compare(value, current) == wanted)
+                {
+                    *extreme = Some(value.to_string());
+                }
+            }
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+/// The sum of a total and the value of the field, `None` if the value is blank.
+fn sum(
+    field: &str,
+    record: &Record,
+    units: i128,
+    scale: u32,
+) -> Result<Option<(i128, u32)>, Error> {
+    let value = record.get(field).map_or("", |v| v.trim());
+    if value.is_empty() {
+        return Ok(None);
+    }
+    let not_numeric = || Error::Parse {
+        line: None,
+        field: Some(field.to_string()),
+        kind: ParseErrorKind::NotNumeric(value.to_string()),
+    };
+    let (value_units, value_scale) = numeric::number(value)
+        .and_then(|n| parse_decimal(&n))
+        .ok_or_else(not_numeric)?;
+    let common = scale.max(value_scale);
+    let units = rescale(units, common - scale)
+        .zip(rescale(value_units, common - value_scale))
+        .and_then(|(a, b)| a.checked_add(b))
+        .ok_or_else(not_numeric)?;
+    Ok(Some((units, common)))
+}
+
+/// Compares values as numbers when both are, as strings otherwise.
+fn compare(a: &str, b: &str) -> Ordering {
+    match (numeric::number(a), numeric::number(b)) {
+        (Some(a), Some(b)) => numeric::compare(&a, &b),
+        _ => a.cmp(b),
+    }
+}
+
+/// A plain number with a leading `-` as units of `10^-scale`.
+fn parse_decimal(number: &str) -> Option<(i128, u32)> {
+    let (integer, fraction) = number.split_once('.').unwrap_or((number, ""));
+    let units = format!("{}{}", integer, fraction).parse::<i128>().ok()?;
+    Some((units, fraction.len() as u32))
+}
+
+fn rescale(units: i128, by: u32) -> Option<i128> {
+    10i128
+        .checked_pow(by)
+        .and_then(|factor| units.checked_mul(factor))
+}
+
+fn decimal(units: i128, scale: u32) -> String {
+    let digits = format!(
+        "{:0>width$}",
+        units.unsigned_abs(),
+        width = scale as usize + 1
+    );
+    let (integer, fraction) = digits.split_at(digits.len() - scale as usize);
+    let sign = if units < 0 { "-" } else { "" };
+    match fraction {
+        "" => format!("{}{}", sign, integer),
+        fraction => format!("{}{}.{}", sign, integer, fraction),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        builder::{Buildable, Builder},
+        flat::fixed::{read::Reader, Parser},
+    };
+
+    #[test]
+    fn control_break_summaries() {
+        let source = Parser::builder()
+            .field("branch")
+            .width(2)
+            .append()
+            .field("amount")
+            .width(7)
+            .append()
+            .field("date")
+            .width(8)
+            .append()
+            .build();
+        let target = Parser::builder()
+            .field("branch")
+            .width(2)
+            .append()
+            .field("records")
+            .width(3)
+            .align("right")
+            .padding('0')
+            .append()
+            .field("total")
+            .width(8)
+            .align("right")
+            .append()
+            .field("last")
+            .width(8)
+            .append()
+            .build();
+        let mut reader = Reader::from_string(
+            "01  10.5020240105\n01  -0.2520240301\n01       20240201\n02  1,00020231231\n",
+            &source,
+        );
+        let mut writer = Writer::from_writer(Vec::new(), &target);
+
+        let written = GroupBy::new(reader.string_reader(), &["branch"])
+            .count("records")
+            .sum("total", "amount")
+            .max("last", "date")
+            .write_all(&mut writer)
+            .unwrap();
+
+        assert_eq!(written, 2);
+        let out = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(out, "01003   10.2520240301\n02001    100020231231\n");
+    }
+
+    #[test]
+    fn sum_errors() {
+        let parser = Parser::builder().field("n").width(3).append().build();
+        let mut reader = Reader::from_string("001\nABC\n-02\n", &parser);
+
+        let results = GroupBy::new(reader.string_reader(), &[])
+            .count("records")
+            .max("high", "n")
+            .sum("total", "n")
+            .min("low", "n")
+            .collect::<Vec<_>>();
+
+        assert_eq!(results.len(), 2);
+        assert!(matches!(
+            results[0],
+            Err(Error::Parse {
+                kind: ParseErrorKind::NotNumeric(_),
+                ..
+            })
+        ));
+        let summary = results[1].as_ref().unwrap();
+        assert_eq!(summary["records"], "2");
+        assert_eq!(summary["high"], "001");
+        assert_eq!(summary["total"], "-1");
+        assert_eq!(summary["low"], "-02");
+    }
+
+    #[test]
+    fn sum_error_on_key_change() {
+        let parser = Parser::builder()
+            .field("k")
+            .width(1)
+            .append()
+            .field("v")
+            .width(1)
+            .append()
+            .build();
+        let mut reader = Reader::from_string("A1\nA2\nBx\nC3\n", &parser);
+
+        let results = GroupBy::new(reader.string_reader(), &["k"])
+            .count("n")
+            .sum("s", "v")
+            .collect::<Vec<_>>();
+
+        assert_eq!(results.len(), 3);
+        assert!(results[0].is_err());
+        let a = results[1].as_ref().unwrap();
+        assert_eq!(
+            (a["k"].as_str(), a["n"].as_str(), a["s"].as_str()),
+            ("A", "2", "3")
+        );
+        let c = results[2].as_ref().unwrap();
+        assert_eq!(
+            (c["k"].as_str(), c["n"].as_str(), c["s"].as_str()),
+            ("C", "1", "3")
+        );
+    }
+}
@@ -0,0 +1,360 @@
+use crate::{
+    error::Error,
+    flat::fixed::{read::Reader, write::Writer, Parser, Record},
+};
+use std::{
+    collections::HashMap,
+    io::{Read, Write},
+    ops::Range,
+};
+
+#[cfg(feature = "expr")]
+pub mod expr;
+pub mod group;
+
+/// How the value of a target field is taken from a source record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mapping {
+    /// The value of the source field with the given name.
+    Field(String),
+    /// The same value for every record.
+    Constant(String),
+    /// The values of the source fields joined with the separator, missing fields are empty.
+    Concat {
+        fields: Vec<String>,
+        separator: String,
+    },
+    /// The characters of a source field in the range, e.g. the branch code in an account number.
+    Substring { field: String, range: Range<usize> },
+    /// The value of the source field looked up in the table, e.g. vendor codes to ours. Values
+    /// not in the table take the default, or are missing without one.
+    Lookup {
+        field: String,
+        table: HashMap<String, String>,
+        default: Option<String>,
+    },
+}
+
+#[allow(dead_code)]
+impl Mapping {
+    /// The value of the target field, `None` when the source fields are missing.
+    pub fn value(&self, source: &Record) -> Option<String> {
+        match self {
+            Mapping::Field(field) => source.get(field).cloned(),
+            Mapping::Constant(value) => Some(value.clone()),
+            Mapping::Concat { fields, separator } => {
+                if fields.iter().all(|f| !source.contains_key(f)) {
+                    return None;
+                }
+                let values: Vec<&str> = fields
+                    .iter()
+                    .map(|f| source.get(f).map_or("", |v| v.as_str()))
+                    .collect();
+                Some(values.join(separator))
+            }
+            Mapping::Substring { field, range } => source.get(field).map(|v| {
+                v.chars()
+                    .skip(range.start)
+                    .take(range.end.saturating_sub(range.start))
+                    .collect()
+            }),
+            Mapping::Lookup {
+                field,
+                table,
+                default,
+            } => source
+                .get(field)
+                .and_then(|v| table.get(v).or(default.as_ref()))
+                .cloned(),
+        }
+    }
+}
+
+/// A value computed from the source record, `None` leaves the target field missing.
+pub type Compute<'a> = Box<dyn Fn(&Record) -> Option<String> + 'a>;
+
+enum Rule<'a> {
+    Mapping(Mapping),
+    Computed(Compute<'a>),
+}
+
+impl Rule<'_> {
+    fn value(&self, source: &Record) -> Option<String> {
+        match self {
+            Rule::Mapping(mapping) => mapping.value(source),
+            Rule::Computed(compute) => compute(source),
+        }
+    }
+}
+
+/// Converts records from a source layout to a target layout. Fields of the target with a
+/// mapping take its value, the others take the value of the source field with the same name.
+pub struct Mapper<'a> {
+    source: &'a Parser<'a>,
+    target: &'a Parser<'a>,
+    rules: Vec<(String, Rule<'a>)>,
+}
+
+#[allow(dead_code)]
+impl<'a> Mapper<'a> {
+    pub fn new(source: &'a Parser<'a>, target: &'a Parser<'a>) -> Self {
+        Mapper {
+            source,
+            target,
+            rules: Vec::new(),
+        }
+    }
+
+    fn rule(mut self, target: String, rule: Rule<'a>) -> Self {
+        self.rules.retain(|(t, _)| *t != target);
+        self.rules.push((target, rule));
+        self
+    }
+
+    /// Maps a target field, replacing any earlier mapping of the field.
+    pub fn map<T: Into<String>>(self, target: T, mapping: Mapping) -> Self {
+        self.rule(target.into(), Rule::Mapping(mapping))
+    }
+
+    /// Maps a target field to a source field with a different name.
+    pub fn rename<T: Into<String>, F: Into<String>>(self, target: T, field: F) -> Self {
+        self.map(target, Mapping::Field(field.into()))
+    }
+
+    /// Sets a target field to the same value in every record.
+    pub fn constant<T: Into<String>, V: Into<String>>(self, target: T, value: V) -> Self {
+        self.map(target, Mapping::Constant(value.into()))
+    }
+
+    /// Maps a target field to the value of a source field looked up in the table, see
+    /// [`Mapping::Lookup`].
+    pub fn lookup<T, F>(self, target: T, field: F, table: HashMap<String, String>) -> Self
+    where
+        T: Into<String>,
+        F: Into<String>,
+    {
+        let field = field.into();
+        self.map(
+            target,
+            Mapping::Lookup {
+                field,
+                table,
+                default: None,
+            },
+        )
+    }
+
+    /// Maps a target field to a value computed from the source record.
+    pub fn computed<T, F>(self, target: T, compute: F) -> Self
+    where
+        T: Into<String>,
+        F: Fn(&Record) -> Option<String> + 'a,
+    {
+        self.rule(target.into(), Rule::Computed(Box::new(compute)))
+    }
+
+    /// Maps a target field to the value of an expression over the source record. The field is
+    /// missing from records the expression fails to evaluate for.
+    #[cfg(feature = "expr")]
+    pub fn expression<T: Into<String>>(self, target: T, expression: expr::Expression) -> Self {
+        self.computed(target, move |r| expression.eval_string(r).ok())
+    }
+
+    /// Maps a target field to the values of the source fields joined with the separator.
+    pub fn concat<T, I, S>(self, target: T, fields: I, separator: &str) -> Self
+    where
+        T: Into<String>,
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let fields = fields.into_iter().map(Into::into).collect();
+        self.map(
+            target,
+            Mapping::Concat {
+                fields,
+                separator: separator.to_string(),
+            },
+        )
+    }
+
+    /// Maps a target field to the characters of a source field in the range.
+    pub fn substring<T, F>(self, target: T, field: F, range: Range<usize>) -> Self
+    where
+        T: Into<String>,
+        F: Into<String>,
+    {
+        let field = field.into();
+        self.map(target, Mapping::Substring { field, range })
+    }
+
+    /// Splits a source field into target fields, each taking the characters in its range.
+    pub fn split<F: AsRef<str>>(self, field: F, targets: &[(&str, Range<usize>)]) -> Self {
+        targets.iter().fold(self, |mapper, (target, range)| {
+            mapper.substring(*target, field.as_ref(), range.clone())
+        })
+    }
+
+    pub fn source(&self) -> &'a Parser<'a> {
+        self.source
+    }
+
+    pub fn target(&self) -> &'a Parser<'a> {
+        self.target
+    }
+
+    /// Maps a record of the source layout to a record of the target layout.
+    pub fn map_record(&self, source: &Record) -> Record {
+        let mut record = Record::with_capacity(self.target.fields().len());
+        for name in self.target.fields().iter().filter_map(|f| f.name()) {
+            let value = match self.rules.iter().find(|(t, _)| t == name) {
+                Some((_, rule)) => rule.value(source),
+                None => source.get(name).cloned(),
+            };
+            if let Some(value) = value {
+                record.insert(name.to_string(), value);
+            }
+        }
+        record
+    }
+
+    /// Parses a line of the source layout and formats it in the target layout.
+    pub fn convert(&self, line: &str) -> Result<String, Error> {
+        let record = self.source.parse(line)?;
+        Ok(self.target.format(&self.map_record(&record)))
+    }
+
+    /// Reads every record of the source layout, writing it in the target layout, and returns
+    /// the number of records converted. The reader and writer should use the source and target
+    /// layouts of the mapper.
+    pub fn convert_all<R, W>(
+        &self,
+        reader: &mut Reader<'_, R>,
+        writer: &mut Writer<'_, W>,
+    ) -> Result<u64, Error>
+    where
+        R: Read,
+        W: Write,
+    {
+        let mut record = Record::new();
+        let mut converted = 0;
+        while reader.read_into(&mut record)? {
+            writer.write(&self.map_record(&record))?;
+            converted += 1;
+        }
+        writer.flush()?;
+        Ok(converted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::{Buildable, Builder};
+
+    #[test]
+    fn concat_and_split() {
+        let source = Parser::builder()
+            .field("first")
+            .width(5)
+            .append()
+            .field("last")
+            .width(5)
+            .append()
+            .field("account")
+            .width(8)
+            .append()
+            .build();
+        let target = Parser::builder()
+            .field("name")
+            .width(11)
+            .append()
+            .field("branch")
+            .width(3)
+            .append()
+            .field("number")
+            .width(5)
+            .append()
+            .field("last")
+            .width(5)
+            .append()
+            .build();
+        let mapper = Mapper::new(&source, &target)
+            .concat("name", ["first", "last"], " ")
+            .split("account", &[("branch", 0..3), ("number", 3..8)]);
+
+        assert_eq!(
+            mapper.convert("Ada  Byron12345678").unwrap(),
+            "Ada Byron  12345678Byron"
+        );
+    }
+
+    #[test]
+    fn convert_all() {
+        let source = Parser::builder()
+            .field("id")
+            .width(3)
+            .append()
+            .field("status")
+            .width(1)
+            .append()
+            .field("amount")
+            .width(4)
+            .append()
+            .build();
+        let target = Parser::builder()
+            .field("source")
+            .width(2)
+            .append()
+            .field("account")
+            .width(3)
+            .append()
+            .field("state")
+            .width(6)
+            .append()
+            .field("cents")
+            .width(6)
+            .align("right")
+            .padding('0')
+            .append()
+            .build();
+        let states = [("A", "ACTIVE"), ("C", "CLOSED")]
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        let mapper = Mapper::new(&source, &target)
+            .constant("source", "VA")
+            .rename("account", "id")
+            .lookup("state", "status", states)
+            .computed("cents", |r| {
+                r.get("amount")
+                    .map(|a| (a.trim().parse::<u32>().unwrap_or(0) * 100).to_string())
+            });
+        let mut reader = Reader::from_string("001A  12\n002C1500\n003X   7", &source);
+        let mut writer = Writer::from_writer(Vec::new(), &target);
+
+        assert_eq!(mapper.convert_all(&mut reader, &mut writer).unwrap(), 3);
+
+        let out = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(
+            out,
+            "VA001ACTIVE001200\nVA002CLOSED150000\nVA003      000700\n"
+        );
+    }
+
+    #[test]
+    fn mapping_values() {
+        let mut record = Record::new();
+        record.insert(String::from("a"), String::from("xyz"));
+
+        let concat = Mapping::Concat {
+            fields: vec![String::from("a"), String::from("b")],
+            separator: String::from("-"),
+        };
+        assert_eq!(concat.value(&record).as_deref(), Some("xyz-"));
+        let substring = Mapping::Substring {
+            field: String::from("b"),
+            range: 0..2,
+        };
+        assert_eq!(substring.value(&record), None);
+    }
+}
@@ -0,0 +1,271 @@
+use std::io::{self, Read};
+
+const UTF8_BOM: &[u8] = &[0xEF, 0xBB, 0xBF];
+const UTF16LE_BOM: &[u8] = &[0xFF, 0xFE];
+const UTF16BE_BOM: &[u8] = &[0xFE, 0xFF];
+
+/// The encoding announced by a byte order mark.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+}
+
+/// How a byte order mark at the start of the input is handled.
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Bom {
+    /// Leave the input untouched.
+    Ignore,
+    /// Remove the byte order mark, without changing the encoding of the remaining input.
+    #[default]
+    Strip,
+    /// Remove the byte order mark and transcode UTF-16 input to UTF-8.
+    Transcode,
+}
+
+/// A reader that detects, and depending on the policy removes, a leading byte order mark.
+#[derive(Debug)]
+pub struct BomReader<R> {
+    inner: R,
+    policy: Bom,
+    encoding: Option<Encoding>,
+    skipped: usize,
+    detected: bool,
+    eof: bool,
+    pending: Vec<u8>,
+    out: Vec<u8>,
+    pos: usize,
+}
+
+#[allow(dead_code)]
+impl<R> BomReader<R>
+where
+    R: Read,
+{
+    pub fn new(inner: R) -> Self {
+        BomReader {
+            inner,
+            policy: Bom::default(),
+            encoding: None,
+            skipped: 0,
+            detected: false,
+            eof: false,
+            pending: Vec::new(),
+            out: Vec::new(),
+            pos: 0,
+        }
+    }
+
+    /// Changes the policy, only effective before the first read.
+    pub fn set_policy(&mut self, policy: Bom) {
+        self.policy = policy;
+    }
+
+    /// The encoding announced by the byte order mark, `None` if there was none or nothing has
+    /// been read yet.
+    pub fn encoding(&self) -> Option<Encoding> {
+        self.encoding
+    }
+
+    /// The number of bytes of byte order mark removed from the input.
+    pub fn skipped(&self) -> usize {
+        self.skipped
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    fn detect(&mut self) -> io::Result<()> {
+        self.detected = true;
+        let mut prefix = [0; 3];
+        let mut len = 0;
+        while len < prefix.len() {
+            match self.inner.read(&mut prefix[len..])? {
+                0 => break,
+                n => len += n,
+            }
+        }
+        let prefix = &prefix[..len];
+
+        let (encoding, bom) = if prefix.starts_with(UTF8_BOM) {
+            (Some(Encoding::Utf8), UTF8_BOM.len())
+        } else if prefix.starts_with(UTF16LE_BOM) {
+            (Some(Encoding::Utf16Le), UTF16LE_BOM.len())
+        } else if prefix.starts_with(UTF16BE_BOM) {
+            (Some(Encoding::Utf16Be), UTF16BE_BOM.len())
+        } else {
+            (None, 0)
+        };
+        self.encoding = encoding;
+
+        let skip = match self.policy {
+            Bom::Ignore => 0,
+            _ => bom,
+        };
+        self.skipped = skip;
+        self.out.extend_from_slice(&prefix[skip..]);
+        if self.is_transcoding() {
+            self.pending = std::mem::take(&mut self.out);
+            self.transcode()?;
+        }
+        Ok(())
+    }
+
+    /// Whether UTF-16 input is transcoded to UTF-8.
+    pub fn is_transcoding(&self) -> bool {
+        self.policy == Bom::Transcode
+            && matches!(self.encoding, Some(Encoding::Utf16Le | Encoding::Utf16Be))
+    }
+
+    /// Decodes the pending UTF-16 bytes into the output buffer, reading more input if needed.
+    fn transcode(&mut self) -> io::Result<()> {
+        let mut chunk = [0; 4096];
+        while self.out.len() == self.pos && !(self.eof && self.pending.is_empty()) {
+            if !self.eof {
+                match self.inner.read(&mut chunk)? {
+                    0 => self.eof = true,
+                    n => self.pending.extend_from_slice(&chunk[..n]),
+                }
+            }
+
+            let mut units = self
+                .pending
+                .chunks_exact(2)
+                .map(|b| match self.encoding {
+                    Some(Encoding::Utf16Be) => u16::from_be_bytes([b[0], b[1]]),
+                    _ => u16::from_le_bytes([b[0], b[1]]),
+                })
+                .collect::<Vec<u16>>();
+            let mut consumed = units.len() * 2;
+            if !self.eof {
+                // Keep a trailing high surrogate until its pair has been read.
+                if let Some(0xD800..=0xDBFF) = units.last() {
+                    units.pop();
+                    consumed -= 2;
+                }
+            } else if self.pending.len() % 2 == 1 {
+                // A dangling byte at the end of the input cannot be decoded.
+                consumed += 1;
+                units.push(0xFFFD);
+            }
+            self.pending.drain(..consumed);
+
+            self.out.drain(..self.pos);
+            self.pos = 0;
+            let mut buf = [0; 4];
+            for c in std::char::decode_utf16(units) {
+                let c = c.unwrap_or(std::char::REPLACEMENT_CHARACTER);
+                self.out
+                    .extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<R> Read for BomReader<R>
+where
+    R: Read,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if !self.detected {
+            self.detect()?;
+        }
+        if self.is_transcoding() {
+            self.transcode()?;
+        }
+        if self.pos < self.out.len() {
+            let n = buf.len().min(self.out.len() - self.pos);
+            buf[..n].copy_from_slice(&self.out[self.pos..self.pos + n]);
+            self.pos += n;
+            if self.pos == self.out.len() {
+                self.out.clear();
+                self.pos = 0;
+            }
+            return Ok(n);
+        }
+        if self.is_transcoding() {
+            return Ok(0);
+        }
+        self.inner.read(buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(input: Vec<u8>, policy: Bom) -> (Vec<u8>, Option<Encoding>) {
+        let mut reader = BomReader::new(input.as_slice());
+        reader.set_policy(policy);
+        let mut out = Vec::new();
+        reader.read_to_end(&mut out).unwrap();
+        (out, reader.encoding())
+    }
+
+    fn utf16(s: &str, bom: &[u8], be: bool) -> Vec<u8> {
+        let mut bytes = bom.to_vec();
+        for unit in s.encode_utf16() {
+            if be {
+                bytes.extend_from_slice(&unit.to_be_bytes());
+            } else {
+                bytes.extend_from_slice(&unit.to_le_bytes());
+            }
+        }
+        bytes
+    }
+
+    #[test]
+    fn no_bom() {
+        assert_eq!(read(b"AB".to_vec(), Bom::Strip), (b"AB".to_vec(), None));
+        assert_eq!(read(Vec::new(), Bom::Strip), (Vec::new(), None));
+    }
+
+    #[test]
+    fn utf8_bom_strip() {
+        assert_eq!(
+            read(b"\xEF\xBB\xBFABCD".to_vec(), Bom::Strip),
+            (b"ABCD".to_vec(), Some(Encoding::Utf8))
+        );
+    }
+
+    #[test]
+    fn utf8_bom_ignore() {
+        assert_eq!(
+            read(b"\xEF\xBB\xBFABCD".to_vec(), Bom::Ignore),
+            (b"\xEF\xBB\xBFABCD".to_vec(), Some(Encoding::Utf8))
+        );
+    }
+
+    #[test]
+    fn utf16le_transcode() {
+        let (out, encoding) = read(utf16("AB\nCD\n", UTF16LE_BOM, false), Bom::Transcode);
+
+        assert_eq!(String::from_utf8(out).unwrap(), "AB\nCD\n");
+        assert_eq!(encoding, Some(Encoding::Utf16Le));
+    }
+
+    #[test]
+    fn utf16be_transcode_surrogates() {
+        let s = "会げク😀\n".repeat(2000);
+        let (out, encoding) = read(utf16(&s, UTF16BE_BOM, true), Bom::Transcode);
+
+        assert_eq!(String::from_utf8(out).unwrap(), s);
+        assert_eq!(encoding, Some(Encoding::Utf16Be));
+    }
+
+    #[test]
+    fn utf16_strip_only() {
+        let (out, encoding) = read(utf16("A", UTF16LE_BOM, false), Bom::Strip);
+
+        assert_eq!(out, vec![b'A', 0]);
+        assert_eq!(encoding, Some(Encoding::Utf16Le));
+    }
+}
@@ -0,0 +1,123 @@
+use alloc::{borrow::Cow, vec::Vec};
+
+/// The characters Windows-1252 maps to bytes `0x80` to `0x9F`, `None` for the unused bytes.
+const WINDOWS_1252: [Option<char>; 32] = [
+    Some('\u{20AC}'),
+    None,
+    Some('\u{201A}'),
+    Some('\u{0192}'),
+    Some('\u{201E}'),
+    Some('\u{2026}'),
+    Some('\u{2020}'),
+    Some('\u{2021}'),
+    Some('\u{02C6}'),
+    Some('\u{2030}'),
+    Some('\u{0160}'),
+    Some('\u{2039}'),
+    Some('\u{0152}'),
+    None,
+    Some('\u{017D}'),
+    None,
+    None,
+    Some('\u{2018}'),
+    Some('\u{2019}'),
+    Some('\u{201C}'),
+    Some('\u{201D}'),
+    Some('\u{2022}'),
+    Some('\u{2013}'),
+    Some('\u{2014}'),
+    Some('\u{02DC}'),
+    Some('\u{2122}'),
+    Some('\u{0161}'),
+    Some('\u{203A}'),
+    Some('\u{0153}'),
+    None,
+    Some('\u{017E}'),
+    Some('\u{0178}'),
+];
+
+/// The character set records are written in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Charset {
+    #[default]
+    Utf8,
+    /// ISO-8859-1, the first 256 Unicode code points.
+    Latin1,
+    /// Latin-1 with printable characters, such as `€`, in place of the C1 controls.
+    Windows1252,
+}
+
+/// What happens to a character the character set cannot represent.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Unmappable {
+    /// Fail the write.
+    #[default]
+    Error,
+    /// Write the byte instead, e.g. `b'?'`.
+    Replace(u8),
+}
+
+impl Charset {
+    /// Encodes a string, borrowing it when it is unchanged. Fails with the first unmappable
+    /// character unless they are replaced.
+    pub fn encode<'s>(&self, s: &'s str, unmappable: Unmappable) -> Result<Cow<'s, [u8]>, char> {
+        if *self == Charset::Utf8 || s.is_ascii() {
+            return Ok(Cow::Borrowed(s.as_bytes()));
+        }
+        s.chars()
+            .map(|c| match (self.byte(c), unmappable) {
+                (Some(b), _) => Ok(b),
+                (None, Unmappable::Replace(b)) => Ok(b),
+                (None, Unmappable::Error) => Err(c),
+            })
+            .collect::<Result<Vec<u8>, char>>()
+            .map(Cow::Owned)
+    }
+
+    fn byte(&self, c: char) -> Option<u8> {
+        match (self, c as u32) {
+            (_, code) if code < 0x80 => Some(code as u8),
+            (Charset::Latin1, code) if code < 0x100 => Some(code as u8),
+            (Charset::Windows1252, code) if (0xA0..0x100).contains(&code) => Some(code as u8),
+            (Charset::Windows1252, _) => WINDOWS_1252
+                .iter()
+                .position(|m| *m == Some(c))
+                .map(|i| 0x80 + i as u8),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_single_byte() {
+        assert_eq!(
+            Charset::Latin1.encode("café", Unmappable::Error).unwrap(),
+            &b"caf\xE9"[..]
+        );
+        assert_eq!(Charset::Latin1.encode("5€", Unmappable::Error), Err('€'));
+        assert_eq!(
+            Charset::Latin1
+                .encode("5€", Unmappable::Replace(b'?'))
+                .unwrap(),
+            &b"5?"[..]
+        );
+        assert_eq!(
+            Charset::Windows1252
+                .encode("5€ “é”", Unmappable::Error)
+                .unwrap(),
+            &b"5\x80 \x93\xE9\x94"[..]
+        );
+        assert_eq!(
+            Charset::Windows1252.encode("\u{81}", Unmappable::Error),
+            Err('\u{81}')
+        );
+        assert_eq!(
+            Charset::Utf8.encode("é", Unmappable::Error).unwrap(),
+            "é".as_bytes()
+        );
+    }
+}
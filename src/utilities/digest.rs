@@ -0,0 +1,136 @@
+use std::io::{self, Read};
+
+/// A checksum or hash algorithm, each one requires the feature of the same name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Algorithm {
+    /// CRC-32 (IEEE), as used by zip and gzip.
+    #[cfg(feature = "crc32")]
+    Crc32,
+    /// SHA-256.
+    #[cfg(feature = "sha2")]
+    Sha256,
+}
+
+#[allow(dead_code)]
+impl Algorithm {
+    /// The digest of the bytes as lower case hexadecimal.
+    pub fn digest(&self, _bytes: &[u8]) -> String {
+        match *self {
+            #[cfg(feature = "crc32")]
+            Algorithm::Crc32 => format!("{:08x}", crc32fast::hash(_bytes)),
+            #[cfg(feature = "sha2")]
+            Algorithm::Sha256 => hex(&<sha2::Sha256 as sha2::Digest>::digest(_bytes)),
+        }
+    }
+
+    pub(crate) fn hasher(&self) -> Hasher {
+        match *self {
+            #[cfg(feature = "crc32")]
+            Algorithm::Crc32 => Hasher::Crc32(crc32fast::Hasher::new()),
+            #[cfg(feature = "sha2")]
+            Algorithm::Sha256 => Hasher::Sha256(<sha2::Sha256 as sha2::Digest>::new()),
+        }
+    }
+}
+
+/// The running state of a digest.
+#[derive(Clone)]
+pub(crate) enum Hasher {
+    #[cfg(feature = "crc32")]
+    Crc32(crc32fast::Hasher),
+    #[cfg(feature = "sha2")]
+    Sha256(sha2::Sha256),
+}
+
+#[allow(dead_code)]
+impl Hasher {
+    pub(crate) fn update(&mut self, _bytes: &[u8]) {
+        match *self {
+            #[cfg(feature = "crc32")]
+            Hasher::Crc32(ref mut hasher) => hasher.update(_bytes),
+            #[cfg(feature = "sha2")]
+            Hasher::Sha256(ref mut hasher) => sha2::Digest::update(hasher, _bytes),
+        }
+    }
+
+    /// The digest of the bytes so far, the hasher can still be updated afterwards.
+    pub(crate) fn finish(&self) -> String {
+        match *self {
+            #[cfg(feature = "crc32")]
+            Hasher::Crc32(ref hasher) => format!("{:08x}", hasher.clone().finalize()),
+            #[cfg(feature = "sha2")]
+            Hasher::Sha256(ref hasher) => hex(&sha2::Digest::finalize(hasher.clone())),
+        }
+    }
+}
+
+#[cfg(feature = "sha2")]
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// A reader computing the digest of the bytes read through it, once an algorithm is set.
+pub(crate) struct DigestReader<R> {
+    inner: R,
+    hasher: Option<Hasher>,
+}
+
+impl<R> DigestReader<R> {
+    pub(crate) fn new(inner: R) -> Self {
+        DigestReader {
+            inner,
+            hasher: None,
+        }
+    }
+
+    /// Starts computing the digest, only complete if set before the first read.
+    pub(crate) fn set_algorithm(&mut self, algorithm: Algorithm) {
+        self.hasher = Some(algorithm).as_ref().map(Algorithm::hasher);
+    }
+
+    pub(crate) fn digest(&self) -> Option<String> {
+        self.hasher.as_ref().map(|h| h.finish())
+    }
+}
+
+impl<R: Read> Read for DigestReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        if let Some(hasher) = self.hasher.as_mut() {
+            hasher.update(&buf[..n]);
+        }
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[cfg(feature = "crc32")]
+    #[test]
+    fn crc32() {
+        assert_eq!(Algorithm::Crc32.digest(b"123456789"), "cbf43926");
+    }
+
+    #[cfg(feature = "sha2")]
+    #[test]
+    fn sha256() {
+        assert_eq!(
+            Algorithm::Sha256.digest(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[cfg(feature = "sha2")]
+    #[test]
+    fn digest_reader() {
+        let mut reader = DigestReader::new(&b"abc"[..]);
+        reader.set_algorithm(Algorithm::Sha256);
+        let mut out = Vec::new();
+        reader.read_to_end(&mut out).unwrap();
+
+        assert_eq!(reader.digest(), Some(Algorithm::Sha256.digest(b"abc")));
+    }
+}
@@ -0,0 +1,171 @@
+use std::{
+    io::{self, BufWriter, Write},
+    sync::{Arc, Condvar, Mutex, MutexGuard},
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+struct Shared<W: Write> {
+    writer: Mutex<Option<BufWriter<W>>>,
+    error: Mutex<Option<io::Error>>,
+    stopped: Mutex<bool>,
+    wake: Condvar,
+}
+
+/// A buffered writer flushed by a background thread at a fixed interval, so that output written
+/// in bursts reaches its destination without waiting for the buffer to fill, e.g. for a
+/// consumer tailing the file.
+///
+/// An error flushing in the background is returned by the next write or flush.
+pub struct BackgroundFlush<W: Write + Send + 'static> {
+    shared: Arc<Shared<W>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+#[allow(dead_code)]
+impl<W> BackgroundFlush<W>
+where
+    W: Write + Send + 'static,
+{
+    pub fn new(inner: W, interval: Duration) -> Self {
+        Self::with_capacity(8 * 1024, inner, interval)
+    }
+
+    pub fn with_capacity(capacity: usize, inner: W, interval: Duration) -> Self {
+        let shared = Arc::new(Shared {
+            writer: Mutex::new(Some(BufWriter::with_capacity(capacity, inner))),
+            error: Mutex::new(None),
+            stopped: Mutex::new(false),
+            wake: Condvar::new(),
+        });
+        let thread = {
+            let shared = Arc::clone(&shared);
+            thread::spawn(move || {
+                let mut stopped = lock(&shared.stopped);
+                while !*stopped {
+                    stopped = match shared.wake.wait_timeout(stopped, interval) {
+                        Ok((stopped, _)) => stopped,
+                        Err(poisoned) => poisoned.into_inner().0,
+                    };
+                    if let Some(Err(e)) = lock(&shared.writer).as_mut().map(|w| w.flush()) {
+                        lock(&shared.error).get_or_insert(e);
+                    }
+                }
+            })
+        };
+        BackgroundFlush {
+            shared,
+            thread: Some(thread),
+        }
+    }
+
+    /// Stops the background thread, flushes any buffered data and returns the underlying
+    /// writer.
+    pub fn into_inner(mut self) -> io::Result<W> {
+        self.stop();
+        self.take_error()?;
+        match lock(&self.shared.writer).take() {
+            Some(writer) => writer.into_inner().map_err(|e| e.into_error()),
+            None => Err(io::Error::other("The writer was already taken")),
+        }
+    }
+
+    fn stop(&mut self) {
+        *lock(&self.shared.stopped) = true;
+        self.shared.wake.notify_all();
+        if let Some(thread) = self.thread.take() {
+            thread.join().ok();
+        }
+    }
+
+    fn take_error(&self) -> io::Result<()> {
+        match lock(&self.shared.error).take() {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    fn with_writer<T, F>(&self, f: F) -> io::Result<T>
+    where
+        F: FnOnce(&mut BufWriter<W>) -> io::Result<T>,
+    {
+        self.take_error()?;
+        match lock(&self.shared.writer).as_mut() {
+            Some(writer) => f(writer),
+            None => Err(io::Error::other("The writer was already taken")),
+        }
+    }
+}
+
+impl<W> Write for BackgroundFlush<W>
+where
+    W: Write + Send + 'static,
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.with_writer(|w| w.write(buf))
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.with_writer(|w| w.write_all(buf))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.with_writer(|w| w.flush())
+    }
+}
+
+impl<W> Drop for BackgroundFlush<W>
+where
+    W: Write + Send + 'static,
+{
+    fn drop(&mut self) {
+        self.stop();
+        if let Some(writer) = lock(&self.shared.writer).as_mut() {
+            writer.flush().ok();
+        }
+    }
+}
+
+/// Locks a mutex, ignoring poisoning as the data stays consistent between writes.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    #[derive(Clone, Default)]
+    struct Output(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Output {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            lock(&self.0).extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn flushed_in_background() {
+        let output = Output::default();
+        let mut wtr = BackgroundFlush::new(output.clone(), Duration::from_millis(5));
+
+        wtr.write_all(b"AAAA\n").unwrap();
+        let started = Instant::now();
+        while lock(&output.0).is_empty() && started.elapsed() < Duration::from_secs(5) {
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        assert_eq!(*lock(&output.0), b"AAAA\n");
+        wtr.write_all(b"BBBB\n").unwrap();
+        wtr.into_inner().unwrap();
+        assert_eq!(*lock(&output.0), b"AAAA\nBBBB\n");
+    }
+}
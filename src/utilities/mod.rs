@@ -1 +1,14 @@
+#[cfg(feature = "std")]
+pub mod bom;
+#[cfg(feature = "std")]
+pub mod cancel;
+pub mod charset;
+#[cfg(feature = "std")]
+pub mod digest;
+#[cfg(feature = "std")]
+pub mod flush;
 pub mod string;
+#[cfg(feature = "std")]
+pub mod tail;
+#[cfg(feature = "std")]
+pub mod throttle;
@@ -1,8 +1,14 @@
-use std::{
+use alloc::{
     borrow::Cow,
+    string::{String, ToString},
+};
+use core::{
     cmp::Ordering,
     convert::{From, TryFrom},
 };
+#[cfg(feature = "unicode-segmentation")]
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Align {
@@ -30,51 +36,297 @@ impl TryFrom<String> for Align {
     }
 }
 
+/// The whitespace trimmed from a value, independently of its padding.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Trim {
+    #[default]
+    None,
+    Left,
+    Right,
+    Both,
+}
+
+impl TryFrom<&str> for Trim {
+    type Error = String;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        match s.to_lowercase().trim() {
+            "none" => Ok(Trim::None),
+            "left" => Ok(Trim::Left),
+            "right" => Ok(Trim::Right),
+            "both" => Ok(Trim::Both),
+            _ => Err(String::from("Unknown trim argument")),
+        }
+    }
+}
+
+/// Which sides of a value the padding is stripped from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StripMode {
+    /// Strips the padding from the side implied by the alignment.
+    #[default]
+    AlignedSide,
+    /// Also strips whitespace from the other side, for values padded by their alignment and
+    /// followed or preceded by record padding, e.g. `00123  `.
+    Both,
+    /// Keeps the value as is.
+    None,
+}
+
+impl TryFrom<&str> for StripMode {
+    type Error = String;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        match s.to_lowercase().trim() {
+            "aligned_side" => Ok(StripMode::AlignedSide),
+            "both" => Ok(StripMode::Both),
+            "none" => Ok(StripMode::None),
+            _ => Err(String::from("Unknown strip argument")),
+        }
+    }
+}
+
+/// Which characters are kept when a value is longer than its field on format.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TruncateSide {
+    /// Keeps the leftmost characters.
+    #[default]
+    Left,
+    /// Keeps the rightmost characters, e.g. the least significant digits of a number.
+    Right,
+    /// Fails the write with a validation error. Formatting outside of a writer keeps the
+    /// leftmost characters.
+    Error,
+}
+
+impl TryFrom<&str> for TruncateSide {
+    type Error = String;
+
+    fn try_from(s: &str) -> Result<Self, String> {
+        match s.to_lowercase().trim() {
+            "left" => Ok(TruncateSide::Left),
+            "right" => Ok(TruncateSide::Right),
+            "error" => Ok(TruncateSide::Error),
+            _ => Err(String::from("Unknown truncate side argument")),
+        }
+    }
+}
+
+/// How the width of a field is counted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WidthMode {
+    /// One column per char.
+    #[default]
+    Chars,
+    /// One column per UTF-8 byte, a char is never split and moves to the next field instead.
+    Bytes,
+    /// Columns as displayed by a terminal, wide characters such as CJK take two columns.
+    DisplayWidth,
+    /// One column per extended grapheme cluster, so combining characters and emoji sequences
+    /// are never split.
+    #[cfg(feature = "unicode-segmentation")]
+    Graphemes,
+}
+
+impl TryFrom<&str> for WidthMode {
+    type Error = String;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        match s.to_lowercase().trim() {
+            "chars" => Ok(WidthMode::Chars),
+            "bytes" => Ok(WidthMode::Bytes),
+            "display_width" => Ok(WidthMode::DisplayWidth),
+            #[cfg(feature = "unicode-segmentation")]
+            "graphemes" => Ok(WidthMode::Graphemes),
+            _ => Err(String::from("Unknown width mode argument")),
+        }
+    }
+}
+
+impl WidthMode {
+    /// The number of columns taken by the value.
+    pub fn measure(&self, s: &str) -> usize {
+        match self {
+            WidthMode::Chars => s.chars().count(),
+            WidthMode::Bytes => s.len(),
+            WidthMode::DisplayWidth => s.width(),
+            #[cfg(feature = "unicode-segmentation")]
+            WidthMode::Graphemes => s.graphemes(true).count(),
+        }
+    }
+
+    /// Splits the value after the longest prefix that fits in the number of columns.
+    pub fn split<'s>(&self, s: &'s str, width: usize) -> (&'s str, &'s str) {
+        let end = match self {
+            WidthMode::Chars => s.char_indices().nth(width).map_or(s.len(), |(i, _)| i),
+            WidthMode::Bytes => {
+                let mut end = width.min(s.len());
+                while !s.is_char_boundary(end) {
+                    end -= 1;
+                }
+                end
+            }
+            WidthMode::DisplayWidth => {
+                let mut columns = 0;
+                // Control characters take a column, as when measuring the value on format.
+                s.char_indices()
+                    .find(|(_, c)| {
+                        columns += c.width().unwrap_or(1);
+                        columns > width
+                    })
+                    .map_or(s.len(), |(i, _)| i)
+            }
+            #[cfg(feature = "unicode-segmentation")]
+            WidthMode::Graphemes => s
+                .grapheme_indices(true)
+                .nth(width)
+                .map_or(s.len(), |(i, _)| i),
+        };
+        s.split_at(end)
+    }
+}
+
+/// The case a value is converted to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Case {
+    Upper,
+    Lower,
+}
+
+impl TryFrom<&str> for Case {
+    type Error = String;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        match s.to_lowercase().trim() {
+            "upper" => Ok(Case::Upper),
+            "lower" => Ok(Case::Lower),
+            _ => Err(String::from("Unknown case argument")),
+        }
+    }
+}
+
+/// Trims whitespace from the given side(s) of a value.
+pub fn trim(s: &str, trim: Trim) -> &str {
+    match trim {
+        Trim::None => s,
+        Trim::Left => s.trim_start(),
+        Trim::Right => s.trim_end(),
+        Trim::Both => s.trim(),
+    }
+}
+
+/// Converts a value to the given case, borrowing it if it is already in that case.
+pub fn convert_case(s: &str, case: Case) -> Cow<'_, str> {
+    match case {
+        Case::Upper if s.chars().any(char::is_lowercase) => s.to_uppercase().into(),
+        Case::Lower if s.chars().any(char::is_uppercase) => s.to_lowercase().into(),
+        _ => s.into(),
+    }
+}
+
 #[allow(dead_code)]
-pub fn truncate(s: &str, width: usize) -> Cow<str> {
+pub fn truncate(s: &str, width: usize) -> Cow<'_, str> {
     _truncate(s, width, s.chars().count())
 }
 
-pub fn _truncate(s: &str, width: usize, len: usize) -> Cow<str> {
+pub fn _truncate(s: &str, width: usize, len: usize) -> Cow<'_, str> {
     if len > width {
-        s[..width].into()
+        WidthMode::Chars.split(s, width).0.into()
     } else {
         s.into()
     }
 }
 
 #[allow(dead_code)]
-pub fn pad(s: &str, width: usize, align: Align, padding: char) -> Cow<str> {
+pub fn pad(s: &str, width: usize, align: Align, padding: char) -> Cow<'_, str> {
     _pad(s, width, align, padding, s.chars().count())
 }
 
 #[allow(dead_code)]
-pub fn _pad(s: &str, width: usize, align: Align, padding: char, len: usize) -> Cow<str> {
+pub fn _pad(s: &str, width: usize, align: Align, padding: char, len: usize) -> Cow<'_, str> {
     if len < width {
-        let mut buf = String::with_capacity(width);
-        buf.push_str(s);
+        let fill = width - len;
+        let mut buf = String::with_capacity(s.len() + fill * padding.len_utf8());
         match align {
             Align::Left => {
-                for _ in len..width {
-                    buf.push(padding);
-                }
-                buf.into()
+                buf.push_str(s);
+                buf.extend(core::iter::repeat_n(padding, fill));
             }
             Align::Right => {
-                for _ in len..width {
-                    buf.insert(0, padding);
-                }
-                buf.into()
+                buf.extend(core::iter::repeat_n(padding, fill));
+                buf.push_str(s);
             }
         }
+        buf.into()
     } else {
         s.into()
     }
 }
 
-#[allow(dead_code)]
-pub fn fixed_width(s: &str, width: usize, align: Align, padding: char) -> Cow<str> {
+/// Pads to the width by repeating a padding string, the last repetition is cut short when the
+/// string does not fit exactly.
+pub fn pad_with<'s>(s: &'s str, width: usize, align: Align, fill: &str) -> Cow<'s, str> {
     let len = s.chars().count();
+    if len >= width || fill.is_empty() {
+        return s.into();
+    }
+    let filler = fill.chars().cycle().take(width - len);
+    let mut buf = String::with_capacity(s.len() + (width - len) * 4);
+    match align {
+        Align::Left => {
+            buf.push_str(s);
+            buf.extend(filler);
+        }
+        Align::Right => {
+            buf.extend(filler);
+            buf.push_str(s);
+        }
+    }
+    buf.into()
+}
+
+/// Truncates or pads to the width, appending the result to `buf` without allocating.
+pub fn fixed_width_into(s: &str, width: usize, align: Align, padding: char, buf: &mut String) {
+    let len = if s.is_ascii() {
+        s.len()
+    } else {
+        s.chars().count()
+    };
+    if len >= width {
+        buf.push_str(&_truncate(s, width, len));
+        return;
+    }
+    let fill = core::iter::repeat_n(padding, width - len);
+    match align {
+        Align::Left => {
+            buf.push_str(s);
+            buf.extend(fill);
+        }
+        Align::Right => {
+            buf.extend(fill);
+            buf.push_str(s);
+        }
+    }
+}
+
+/// Truncates or pads to the width, padding with a repeated string.
+pub fn fixed_width_with<'s>(s: &'s str, width: usize, align: Align, fill: &str) -> Cow<'s, str> {
+    let len = s.chars().count();
+    match width.cmp(&len) {
+        Ordering::Less => _truncate(s, width, len),
+        Ordering::Greater => pad_with(s, width, align, fill),
+        _ => s.into(),
+    }
+}
+
+#[allow(dead_code)]
+pub fn fixed_width(s: &str, width: usize, align: Align, padding: char) -> Cow<'_, str> {
+    // ASCII input has one char per byte, which avoids decoding the string to count them.
+    let len = if s.is_ascii() {
+        s.len()
+    } else {
+        s.chars().count()
+    };
     match width.cmp(&len) {
         Ordering::Less => _truncate(s, width, len),
         Ordering::Greater => _pad(s, width, align, padding, len),
@@ -83,7 +335,10 @@ pub fn fixed_width(s: &str, width: usize, align: Align, padding: char) -> Cow<st
 }
 
 #[allow(dead_code)]
-pub fn strip_padding(s: &str, align: Align, padding: char) -> Cow<str> {
+pub fn strip_padding(s: &str, align: Align, padding: char) -> Cow<'_, str> {
+    if padding.is_ascii() {
+        return strip_ascii_padding(s, align, padding as u8).into();
+    }
     match align {
         Align::Left => {
             if s.ends_with(padding) {
@@ -100,20 +355,90 @@ pub fn strip_padding(s: &str, align: Align, padding: char) -> Cow<str> {
                 s.into()
             }
         }
+        Align::Right => match s.char_indices().find(|(_, c)| *c != padding) {
+            Some((start, _)) => s[start..].into(),
+            None => "".into(),
+        },
+    }
+}
+
+/// Strips the repetitions of a padding string from the side implied by the alignment, including
+/// a repetition cut short by [`pad_with`].
+pub fn strip_padding_with<'s>(s: &'s str, align: Align, fill: &str) -> Cow<'s, str> {
+    if fill.is_empty() {
+        return s.into();
+    }
+    let mut s = s;
+    match align {
+        Align::Left => {
+            s = &s[..s.len() - partial_fill(fill, |p| s.ends_with(p))];
+            while let Some(rest) = s.strip_suffix(fill) {
+                s = rest;
+            }
+        }
         Align::Right => {
-            if s.starts_with(padding) {
-                s.chars()
-                    .skip_while(|c| *c == padding)
-                    .collect::<String>()
-                    .into()
-            } else {
-                s.into()
+            while let Some(rest) = s.strip_prefix(fill) {
+                s = rest;
             }
+            s = &s[partial_fill(fill, |p| s.starts_with(p))..];
         }
     }
+    s.into()
+}
+
+/// The length of the longest proper prefix of the padding string that matches.
+fn partial_fill<F: Fn(&str) -> bool>(fill: &str, matches: F) -> usize {
+    fill.char_indices()
+        .rev()
+        .map(|(i, _)| i)
+        .find(|i| *i > 0 && matches(&fill[..*i]))
+        .unwrap_or(0)
+}
+
+/// Strips the padding from a value according to the mode.
+pub fn strip(s: &str, align: Align, padding: char, mode: StripMode) -> Cow<'_, str> {
+    match mode {
+        StripMode::AlignedSide => strip_padding(s, align, padding),
+        StripMode::Both => strip_padding(trim_unaligned(s, align), align, padding),
+        StripMode::None => s.into(),
+    }
+}
+
+/// Strips a repeated padding string from a value according to the mode.
+pub fn strip_with<'s>(s: &'s str, align: Align, fill: &str, mode: StripMode) -> Cow<'s, str> {
+    match mode {
+        StripMode::AlignedSide => strip_padding_with(s, align, fill),
+        StripMode::Both => strip_padding_with(trim_unaligned(s, align), align, fill),
+        StripMode::None => s.into(),
+    }
+}
+
+fn trim_unaligned(s: &str, align: Align) -> &str {
+    match align {
+        Align::Left => s.trim_start(),
+        Align::Right => s.trim_end(),
+    }
+}
+
+/// Strips an ASCII padding byte by scanning the bytes directly, an ASCII byte never occurs
+/// inside a multi-byte UTF-8 sequence so the result is always on a char boundary.
+#[inline]
+fn strip_ascii_padding(s: &str, align: Align, padding: u8) -> &str {
+    let bytes = s.as_bytes();
+    match align {
+        Align::Left => match bytes.iter().rposition(|b| *b != padding) {
+            Some(end) => &s[..=end],
+            None => "",
+        },
+        Align::Right => match bytes.iter().position(|b| *b != padding) {
+            Some(start) => &s[start..],
+            None => "",
+        },
+    }
 }
 
 #[cfg(test)]
+#[allow(clippy::redundant_pattern_matching)]
 mod tests {
     use super::*;
 
@@ -131,6 +456,88 @@ mod tests {
         assert!(matches!(Align::try_from("Banana".to_string()), Err(_)));
     }
 
+    #[test]
+    fn trim_sides() {
+        assert_eq!(trim("  A B  ", Trim::None), "  A B  ");
+        assert_eq!(trim("  A B  ", Trim::Left), "A B  ");
+        assert_eq!(trim("  A B  ", Trim::Right), "  A B");
+        assert_eq!(trim("  A B  ", Trim::Both), "A B");
+        assert_eq!(Trim::try_from("BOTH"), Ok(Trim::Both));
+        assert!(Trim::try_from("Banana").is_err());
+    }
+
+    #[test]
+    fn strip_modes() {
+        assert_eq!(
+            strip("00123  ", Align::Right, '0', StripMode::AlignedSide),
+            "123  "
+        );
+        assert_eq!(strip("00123  ", Align::Right, '0', StripMode::Both), "123");
+        assert_eq!(strip("  AB  ", Align::Left, ' ', StripMode::Both), "AB");
+        assert_eq!(
+            strip("00123  ", Align::Right, '0', StripMode::None),
+            "00123  "
+        );
+        assert_eq!(
+            StripMode::try_from("Aligned_Side"),
+            Ok(StripMode::AlignedSide)
+        );
+        assert!(StripMode::try_from("Banana").is_err());
+    }
+
+    #[test]
+    fn pad_with_string() {
+        assert_eq!(pad_with("AB", 5, Align::Left, "**"), "AB***");
+        assert_eq!(pad_with("AB", 5, Align::Right, "-="), "-=-AB");
+        assert_eq!(pad_with("ABCDE", 5, Align::Right, "-="), "ABCDE");
+        assert_eq!(fixed_width_with("ABCDEF", 4, Align::Left, "-="), "ABCD");
+        assert_eq!(fixed_width_with("AB", 6, Align::Left, "<>"), "AB<><>");
+    }
+
+    #[test]
+    fn fixed_width_into_buffer() {
+        let mut buf = String::from(">");
+        fixed_width_into("AB", 4, Align::Right, '0', &mut buf);
+        fixed_width_into("CDEF", 2, Align::Left, ' ', &mut buf);
+        fixed_width_into("日本", 3, Align::Left, ' ', &mut buf);
+        assert_eq!(buf, ">00ABCD日本 ");
+    }
+
+    #[test]
+    fn strip_padding_with_string() {
+        assert_eq!(strip_padding_with("AB***", Align::Left, "**"), "AB");
+        assert_eq!(strip_padding_with("AB-=-", Align::Left, "-="), "AB");
+        assert_eq!(strip_padding_with("-=-AB", Align::Right, "-="), "AB");
+        assert_eq!(strip_padding_with("A-B", Align::Left, "-="), "A-B");
+        assert_eq!(strip_padding_with("-=-=", Align::Right, "-="), "");
+    }
+
+    #[test]
+    fn width_mode_split() {
+        let s = "日本AB";
+
+        assert_eq!(WidthMode::Chars.measure(s), 4);
+        assert_eq!(WidthMode::Bytes.measure(s), 8);
+        assert_eq!(WidthMode::DisplayWidth.measure(s), 6);
+        assert_eq!(WidthMode::Chars.split(s, 3), ("日本A", "B"));
+        assert_eq!(WidthMode::Bytes.split(s, 4), ("日", "本AB"));
+        assert_eq!(WidthMode::DisplayWidth.split(s, 3), ("日", "本AB"));
+        assert_eq!(WidthMode::DisplayWidth.split(s, 5), ("日本A", "B"));
+        assert_eq!(WidthMode::DisplayWidth.split(s, 10), (s, ""));
+        assert_eq!(WidthMode::DisplayWidth.split("\t\nAB", 2), ("\t\n", "AB"));
+    }
+
+    #[test]
+    fn convert_case_borrows() {
+        assert!(matches!(
+            convert_case("ABC", Case::Upper),
+            Cow::Borrowed("ABC")
+        ));
+        assert_eq!(convert_case("aBc", Case::Upper), "ABC");
+        assert_eq!(convert_case("aBc", Case::Lower), "abc");
+        assert_eq!(Case::try_from("lower"), Ok(Case::Lower));
+    }
+
     #[test]
     fn truncate_shorter() {
         assert_eq!(truncate("1234567890", 5), "12345".to_string())
@@ -146,6 +553,26 @@ mod tests {
         assert_eq!(truncate("1234567890", 15), "1234567890".to_string())
     }
 
+    #[test]
+    fn truncate_multi_byte() {
+        assert_eq!(truncate("日本語", 2), "日本".to_string());
+        assert_eq!(fixed_width("äöü", 1, Align::Left, ' '), "ä".to_string());
+    }
+
+    #[cfg(feature = "unicode-segmentation")]
+    #[test]
+    fn width_mode_graphemes() {
+        let s = "e\u{301}a\u{1F44D}\u{1F3FD}b";
+
+        assert_eq!(WidthMode::Graphemes.measure(s), 4);
+        assert_eq!(
+            WidthMode::Graphemes.split(s, 1),
+            ("e\u{301}", "a\u{1F44D}\u{1F3FD}b")
+        );
+        assert_eq!(WidthMode::Graphemes.split(s, 3).1, "b");
+        assert_eq!(WidthMode::Chars.split(s, 1).0, "e");
+    }
+
     #[test]
     fn pad_left_shorter() {
         assert_eq!(
@@ -256,4 +683,35 @@ mod tests {
             "ABCX0987XXX"
         );
     }
+
+    #[test]
+    fn strip_padding_all_padding() {
+        assert_eq!(strip_padding("XXXX", Align::Left, 'X'), "");
+        assert_eq!(strip_padding("0000", Align::Right, '0'), "");
+        assert_eq!(strip_padding("", Align::Left, ' '), "");
+    }
+
+    #[test]
+    fn strip_padding_ascii_borrows() {
+        assert!(matches!(
+            strip_padding("00012", Align::Right, '0'),
+            Cow::Borrowed("12")
+        ));
+        assert!(matches!(
+            strip_padding("会げ  ", Align::Left, ' '),
+            Cow::Borrowed("会げ")
+        ));
+    }
+
+    #[test]
+    fn strip_padding_non_ascii() {
+        assert_eq!(strip_padding("ABC会会", Align::Left, '会'), "ABC");
+        assert_eq!(strip_padding("ククABC", Align::Right, 'ク'), "ABC");
+    }
+
+    #[test]
+    fn fixed_width_non_ascii() {
+        assert_eq!(fixed_width("会げ", 4, Align::Right, '*'), "**会げ");
+        assert_eq!(fixed_width("AB", 4, Align::Left, 'ク'), "ABクク");
+    }
 }
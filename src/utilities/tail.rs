@@ -0,0 +1,145 @@
+use crate::utilities::cancel::CancellationToken;
+use std::{
+    io::{self, Read},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// A reader following a growing input, such as a spool file still being written, like
+/// `tail -f`. At the end of the input it waits for more data instead of ending, until it is
+/// cancelled or no data arrived for the idle timeout.
+///
+/// Wrapped in a [`Reader`](crate::flat::fixed::read::Reader), records are yielded as they are
+/// appended and a line being written is only parsed once its terminator arrives, or the tail
+/// stops.
+#[derive(Debug)]
+pub struct Tail<R> {
+    inner: R,
+    poll: Duration,
+    idle_timeout: Option<Duration>,
+    cancel: Option<CancellationToken>,
+    last_data: Option<Instant>,
+}
+
+#[allow(dead_code)]
+impl<R> Tail<R>
+where
+    R: Read,
+{
+    /// Follows the input, checking for more data every 250 milliseconds.
+    pub fn new(inner: R) -> Self {
+        Tail {
+            inner,
+            poll: Duration::from_millis(250),
+            idle_timeout: None,
+            cancel: None,
+            last_data: None,
+        }
+    }
+
+    /// Sets how long to wait before checking for more data.
+    pub fn poll(mut self, poll: Duration) -> Self {
+        self.poll = poll;
+        self
+    }
+
+    /// Ends the input once no data arrived for the timeout, by default it is followed forever.
+    pub fn idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Ends the input once the token is cancelled.
+    pub fn cancel_on(mut self, token: CancellationToken) -> Self {
+        self.cancel = Some(token);
+        self
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    fn is_stopped(&self) -> bool {
+        if self.cancel.as_ref().is_some_and(|c| c.is_cancelled()) {
+            return true;
+        }
+        match (self.idle_timeout, self.last_data) {
+            (Some(timeout), Some(last)) => last.elapsed() >= timeout,
+            _ => false,
+        }
+    }
+}
+
+impl<R> Read for Tail<R>
+where
+    R: Read,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        // The idle timeout counts from the first read.
+        self.last_data.get_or_insert_with(Instant::now);
+        loop {
+            let n = self.inner.read(buf)?;
+            if n > 0 {
+                self.last_data = Some(Instant::now());
+                return Ok(n);
+            }
+            if self.is_stopped() {
+                return Ok(0);
+            }
+            thread::sleep(self.poll);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        builder::{Buildable, Builder},
+        flat::fixed::{read::Reader, Parser},
+    };
+    use std::{fs, io::Write};
+
+    #[test]
+    fn tail_growing_file() {
+        let path = std::env::temp_dir().join(format!("eta-parse-tail-{}.txt", std::process::id()));
+        fs::write(&path, "1111\n22").unwrap();
+        let appender = {
+            let path = path.clone();
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(50));
+                let mut file = fs::OpenOptions::new().append(true).open(path).unwrap();
+                file.write_all(b"22\n3333\n").unwrap();
+            })
+        };
+        let parser = Parser::builder().field("test").width(4).append().build();
+        let tail = Tail::new(fs::File::open(&path).unwrap())
+            .poll(Duration::from_millis(5))
+            .idle_timeout(Duration::from_millis(300));
+
+        let values = Reader::from_reader(tail, &parser)
+            .string_reader()
+            .map(|r| r.unwrap()["test"].clone())
+            .collect::<Vec<String>>();
+        appender.join().unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(values, ["1111", "2222", "3333"]);
+    }
+
+    #[test]
+    fn tail_cancelled() {
+        let token = CancellationToken::new();
+        token.cancel();
+        let mut tail = Tail::new(io::empty()).cancel_on(token);
+
+        assert_eq!(tail.read(&mut [0; 4]).unwrap(), 0);
+    }
+}
@@ -0,0 +1,103 @@
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+/// The maximum sustained output rate of a throttle.
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rate {
+    RecordsPerSecond(u64),
+    BytesPerSecond(u64),
+}
+
+/// Paces output so that, averaged since the first record, it never exceeds the rate.
+#[derive(Debug, Clone)]
+pub struct Throttle {
+    rate: Rate,
+    start: Option<Instant>,
+    records: u64,
+    bytes: u64,
+}
+
+#[allow(dead_code)]
+impl Throttle {
+    pub fn new(rate: Rate) -> Self {
+        Throttle {
+            rate,
+            start: None,
+            records: 0,
+            bytes: 0,
+        }
+    }
+
+    pub fn rate(&self) -> Rate {
+        self.rate
+    }
+
+    /// Blocks until a record of `bytes` length may be sent, then accounts for it.
+    pub fn wait(&mut self, bytes: usize) {
+        let start = *self.start.get_or_insert_with(Instant::now);
+        let delay = self.delay(start.elapsed());
+        if delay > Duration::from_secs(0) {
+            thread::sleep(delay);
+        }
+        self.records += 1;
+        self.bytes += bytes as u64;
+    }
+
+    /// How long to wait before sending the next record, given the time elapsed since the first.
+    fn delay(&self, elapsed: Duration) -> Duration {
+        let (sent, per_second) = match self.rate {
+            Rate::RecordsPerSecond(rate) => (self.records, rate),
+            Rate::BytesPerSecond(rate) => (self.bytes, rate),
+        };
+        if per_second == 0 {
+            return Duration::from_secs(0);
+        }
+        let target = Duration::from_secs_f64(sent as f64 / per_second as f64);
+        target.checked_sub(elapsed).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delay_records() {
+        let mut throttle = Throttle::new(Rate::RecordsPerSecond(10));
+        throttle.records = 5;
+
+        assert_eq!(
+            throttle.delay(Duration::from_millis(200)),
+            Duration::from_millis(300)
+        );
+        assert_eq!(
+            throttle.delay(Duration::from_secs(1)),
+            Duration::from_secs(0)
+        );
+    }
+
+    #[test]
+    fn delay_bytes() {
+        let mut throttle = Throttle::new(Rate::BytesPerSecond(1000));
+        throttle.bytes = 500;
+
+        assert_eq!(
+            throttle.delay(Duration::from_millis(100)),
+            Duration::from_millis(400)
+        );
+    }
+
+    #[test]
+    fn wait_paces_records() {
+        let mut throttle = Throttle::new(Rate::RecordsPerSecond(100));
+        let start = Instant::now();
+        for _ in 0..11 {
+            throttle.wait(10);
+        }
+
+        assert!(start.elapsed() >= Duration::from_millis(100));
+    }
+}
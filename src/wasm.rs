@@ -0,0 +1,121 @@
+//! JavaScript bindings through `wasm-bindgen`, so browser tools such as file previewers and
+//! layout editors parse and format with the same engine.
+
+use crate::{
+    flat::fixed::{Parser, Record},
+    schema::Schema,
+};
+use serde::Serialize;
+use serde_wasm_bindgen::Serializer;
+use std::{mem::ManuallyDrop, ptr::NonNull};
+use wasm_bindgen::prelude::*;
+
+/// A layout loaded from a schema, exposed to JavaScript as `Layout`. The parser is built once
+/// when the layout is loaded.
+#[wasm_bindgen]
+pub struct Layout {
+    // Borrows from `schema`, so it is dropped first.
+    parser: ManuallyDrop<Parser<'static>>,
+    schema: NonNull<Schema>,
+}
+
+#[wasm_bindgen]
+impl Layout {
+    /// Loads a layout from a TOML schema.
+    #[wasm_bindgen(js_name = fromToml)]
+    pub fn from_toml(schema: &str) -> Result<Layout, JsError> {
+        Layout::new(Schema::from_toml(schema)?)
+    }
+
+    /// Loads a layout from a schema given as a JavaScript object, with the fields of a TOML
+    /// schema.
+    #[wasm_bindgen(js_name = fromObject)]
+    pub fn from_object(schema: JsValue) -> Result<Layout, JsError> {
+        Layout::new(serde_wasm_bindgen::from_value(schema)?)
+    }
+
+    fn new(schema: Schema) -> Result<Layout, JsError> {
+        let schema = NonNull::from(Box::leak(Box::new(schema)));
+        // The schema is only freed on drop, after the parser.
+        let parser = match unsafe { schema.as_ref() }.parser() {
+            Ok(parser) => parser,
+            Err(e) => {
+                drop(unsafe { Box::from_raw(schema.as_ptr()) });
+                return Err(e.into());
+            }
+        };
+        Ok(Layout {
+            parser: ManuallyDrop::new(parser),
+            schema,
+        })
+    }
+
+    /// The total width of a record, in characters.
+    pub fn width(&self) -> Result<usize, JsError> {
+        Ok(self.parser.width())
+    }
+
+    /// The names of the fields, in layout order.
+    pub fn names(&self) -> Result<Vec<String>, JsError> {
+        Ok(self
+            .parser
+            .fields()
+            .iter()
+            .filter_map(|f| f.name().map(String::from))
+            .collect())
+    }
+
+    /// A table of the fields with their positions, see `Parser::describe`.
+    pub fn describe(&self) -> Result<String, JsError> {
+        Ok(self.parser.describe())
+    }
+
+    /// Parses a line into an object of field names to values.
+    pub fn parse(&self, line: &str) -> Result<JsValue, JsError> {
+        let record = self.parser.parse(line)?;
+        let serializer = Serializer::new().serialize_maps_as_objects(true);
+        Ok(record.serialize(&serializer)?)
+    }
+
+    /// Formats an object of field names to values as a line.
+    pub fn format(&self, record: JsValue) -> Result<String, JsError> {
+        let record: Record = serde_wasm_bindgen::from_value(record)?;
+        Ok(self.parser.format(&record))
+    }
+}
+
+impl Drop for Layout {
+    fn drop(&mut self) {
+        unsafe {
+            ManuallyDrop::drop(&mut self.parser);
+            drop(Box::from_raw(self.schema.as_ptr()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layout_from_toml() {
+        let layout = Layout::from_toml(
+            r#"
+            [[fields]]
+            name = "id"
+            width = 3
+
+            [[fields]]
+            width = 2
+
+            [[fields]]
+            name = "name"
+            width = 5
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(layout.width().unwrap(), 10);
+        assert_eq!(layout.names().unwrap(), vec!["id", "name"]);
+    }
+}
@@ -0,0 +1,143 @@
+#![cfg(feature = "cli")]
+
+use std::{
+    io::Write,
+    process::{Command, Output, Stdio},
+};
+
+const LAYOUT: &str = "./tests/data/flat/fixed/layout-001.toml";
+const FILE: &str = "./tests/data/flat/fixed/file-001.txt";
+
+fn eta(args: &[&str], stdin: Option<&str>) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_eta"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Unable to run eta");
+    if let Some(stdin) = stdin {
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(stdin.as_bytes())
+            .unwrap();
+    }
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn parse_to_csv() {
+    let out = eta(&["parse", "--schema", LAYOUT, FILE], None);
+
+    assert!(out.status.success());
+    assert_eq!(
+        String::from_utf8(out.stdout).unwrap(),
+        "first,second,fourth\n1111,2222,44445555\n1111,2222,44445555\n1111,2222,44445555\n"
+    );
+}
+
+#[test]
+fn parse_to_json() {
+    let out = eta(
+        &["parse", "--schema", LAYOUT, "--to", "json"],
+        Some("AAAABBBBCCCCDDDDEEEE\n"),
+    );
+
+    assert!(out.status.success());
+    let line = String::from_utf8(out.stdout).unwrap();
+    let value: serde_json::Value = serde_json::from_str(line.trim()).unwrap();
+    assert_eq!(value["first"], "AAAA");
+    assert_eq!(value["fourth"], "DDDDEEEE");
+}
+
+#[test]
+fn format_from_csv() {
+    let out = eta(&["format", "--schema", LAYOUT], Some("first,fourth\nA,B\n"));
+
+    assert!(out.status.success());
+    assert_eq!(
+        String::from_utf8(out.stdout).unwrap(),
+        "A           B       \n"
+    );
+}
+
+#[test]
+fn format_from_json() {
+    let out = eta(
+        &["format", "--schema", LAYOUT, "--from", "json"],
+        Some("{\"second\": \"X\", \"fourth\": 12}\n"),
+    );
+
+    assert!(out.status.success());
+    assert_eq!(
+        String::from_utf8(out.stdout).unwrap(),
+        "    X       12      \n"
+    );
+}
+
+#[test]
+fn validate_reports_errors() {
+    let out = eta(
+        &["validate", "--schema", LAYOUT],
+        Some("11112222333344445555\nshort\n"),
+    );
+
+    assert_eq!(out.status.code(), Some(1));
+    let stdout = String::from_utf8(out.stdout).unwrap();
+    assert!(stdout.contains("Line 2: Insufficient buffer size"));
+    assert!(stdout.ends_with("2 records, 1 errors\n"));
+}
+
+#[test]
+fn validate_max_errors() {
+    let out = eta(
+        &["validate", "--schema", LAYOUT, "--max-errors", "1"],
+        Some("a\nb\nc\nd\n"),
+    );
+
+    assert_eq!(out.status.code(), Some(1));
+    let stdout = String::from_utf8(out.stdout).unwrap();
+    assert!(stdout.contains("\nAborted after 2 errors at line 2\n"));
+    assert!(stdout.ends_with("2 records, 2 errors\n"));
+}
+
+#[test]
+fn describe_with_sample() {
+    let out = eta(&["describe", "--schema", LAYOUT, FILE], None);
+
+    assert!(out.status.success());
+    let stdout = String::from_utf8(out.stdout).unwrap();
+    assert!(stdout.contains("Total width: 20"));
+    assert!(stdout.ends_with("[fi][se]    [fourth]\n11112222333344445555\n"));
+}
+
+#[test]
+fn gen_struct() {
+    let out = eta(&["gen", "--schema", LAYOUT, "--name", "Layout"], None);
+
+    assert!(out.status.success());
+    let stdout = String::from_utf8(out.stdout).unwrap();
+    assert!(stdout.contains("pub struct Layout {\n    pub first: String,\n"));
+    assert!(stdout.contains("pub fn parser() -> ::eta_parse::flat::fixed::Parser<'static>"));
+}
+
+#[test]
+fn gen_data() {
+    let out = eta(&["gen-data", "--schema", LAYOUT, "-n", "3"], None);
+
+    assert!(out.status.success());
+    let stdout = String::from_utf8(out.stdout).unwrap();
+    assert_eq!(stdout.lines().count(), 3);
+    let validated = eta(&["validate", "--schema", LAYOUT], Some(&stdout));
+    assert!(validated.status.success());
+}
+
+#[test]
+fn missing_schema() {
+    let out = eta(&["validate", "--schema", "./does-not-exist.toml"], Some(""));
+
+    assert_eq!(out.status.code(), Some(2));
+    assert!(String::from_utf8(out.stderr).unwrap().starts_with("eta: "));
+}
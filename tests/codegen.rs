@@ -0,0 +1,41 @@
+#![cfg(feature = "std")]
+
+include!("data/codegen/account.rs");
+
+#[test]
+fn generated_struct() {
+    let parser = Account::parser();
+
+    let record = parser.parse("123455  CHK0-12  1.50").unwrap();
+    let account = Account::from_record(&record).unwrap();
+
+    assert_eq!(
+        account,
+        Account {
+            account_id: String::from("12345"),
+            r#type: Some(String::from("CHK")),
+            count: -12,
+            rate: Some(1.5),
+        }
+    );
+    assert_eq!(parser.format(&account.to_record()), "12345500CHK0-12   1.5");
+}
+
+#[test]
+fn generated_struct_null() {
+    let record = Account::parser().parse("123455  NULL000      ").unwrap();
+
+    let account = Account::from_record(&record).unwrap();
+
+    assert_eq!(account.r#type, None);
+    assert_eq!(account.count, 0);
+    assert_eq!(account.rate, None);
+}
+
+#[test]
+fn generated_struct_invalid() {
+    let mut record = Account::default().to_record();
+    record.insert(String::from("count"), String::from("1.5"));
+
+    assert!(Account::from_record(&record).is_err());
+}
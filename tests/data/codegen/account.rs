@@ -0,0 +1,92 @@
+/// Generated from a schema, do not edit.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Account {
+    pub account_id: String,
+    pub r#type: Option<String>,
+    pub count: i64,
+    pub rate: Option<f64>,
+}
+
+impl Account {
+    /// The parser of the layout.
+    pub fn parser() -> ::eta_parse::flat::fixed::Parser<'static> {
+        use ::eta_parse::builder::{Buildable, Builder};
+        static CODES_0: ::std::sync::LazyLock<::eta_parse::flat::fixed::codes::CodeTable> =
+            ::std::sync::LazyLock::new(|| {
+                ::eta_parse::flat::fixed::codes::CodeTable::new()
+                    .code("CHK", "Checking")
+            });
+        ::eta_parse::flat::fixed::Parser::builder()
+            .default_padding('0')
+            .field("accountId")
+            .width(6)
+            .align(::eta_parse::utilities::string::Align::Right)
+            .check_digit(&::eta_parse::flat::fixed::check::Luhn)
+            .append()
+            .spacer(0..2)
+            .field("type")
+            .width(4)
+            .null_when(::eta_parse::flat::fixed::Null::Value("NULL"))
+            .description_field("type_name")
+            .uppercase()
+            .codes(&CODES_0)
+            .append()
+            .field("count")
+            .width(3)
+            .align(::eta_parse::utilities::string::Align::Right)
+            .numeric(::eta_parse::flat::fixed::numeric::Sign::Leading)
+            .append()
+            .field("rate")
+            .width(6)
+            .align(::eta_parse::utilities::string::Align::Right)
+            .padding(' ')
+            .null_when(::eta_parse::flat::fixed::Null::Blank)
+            .append()
+            .build()
+    }
+
+    pub fn from_record(
+        record: &::eta_parse::flat::fixed::Record,
+    ) -> Result<Self, ::eta_parse::error::Error> {
+        Ok(Account {
+            account_id: record.get("accountId").cloned().unwrap_or_default(),
+            r#type: record.get("type").cloned(),
+            count: record
+                .get("count")
+                .map(|v| Self::parse_value("count", v))
+                .transpose()?
+                .unwrap_or_default(),
+            rate: record
+                .get("rate")
+                .map(|v| Self::parse_value("rate", v))
+                .transpose()?,
+        })
+    }
+
+    pub fn to_record(&self) -> ::eta_parse::flat::fixed::Record {
+        let mut record = ::eta_parse::flat::fixed::Record::new();
+        record.insert(String::from("accountId"), self.account_id.clone());
+        if let Some(value) = &self.r#type {
+            record.insert(String::from("type"), value.clone());
+        }
+        record.insert(String::from("count"), self.count.to_string());
+        if let Some(value) = &self.rate {
+            record.insert(String::from("rate"), value.to_string());
+        }
+        record
+    }
+
+    fn parse_value<T: ::std::str::FromStr + Default>(
+        field: &str,
+        value: &str,
+    ) -> Result<T, ::eta_parse::error::Error> {
+        match value.trim() {
+            "" => Ok(T::default()),
+            trimmed => trimmed.parse().map_err(|_| ::eta_parse::error::Error::Parse {
+                line: None,
+                field: Some(String::from(field)),
+                kind: ::eta_parse::error::ParseErrorKind::NotNumeric(value.to_string()),
+            }),
+        }
+    }
+}